@@ -7,18 +7,29 @@ use std::{
     vec::IntoIter,
 };
 
-use crate::app::{models::*, SongsSource};
+use crate::app::{markup, models::*, SongsSource};
 
 #[derive(Serialize)]
 pub struct PlaylistDetails {
     pub name: String,
 }
 
+#[derive(Serialize)]
+pub struct PlaylistVisibility {
+    pub public: bool,
+}
+
 #[derive(Serialize)]
 pub struct Uris {
     pub uris: Vec<String>,
 }
 
+#[derive(Serialize)]
+pub struct PositionedUris {
+    pub uris: Vec<String>,
+    pub position: usize,
+}
+
 #[derive(Serialize)]
 pub struct PlayOffset {
     pub position: u32,
@@ -190,6 +201,7 @@ pub struct Playlist {
     pub images: Option<Vec<Image>>,
     pub tracks: Page<PlaylistTrack>,
     pub owner: PlaylistOwner,
+    pub public: Option<bool>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -220,6 +232,12 @@ pub struct SavedTrack {
     pub track: TrackItem,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct SavedEpisode {
+    pub added_at: String,
+    pub episode: Episode,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct SavedAlbum {
     pub album: Album,
@@ -239,8 +257,11 @@ pub struct Album {
     pub tracks: Option<Page<AlbumTrackItem>>,
     pub artists: Vec<Artist>,
     pub release_date: Option<String>,
+    pub release_date_precision: Option<String>,
     pub name: String,
     pub images: Vec<Image>,
+    // Only there for full albums, not in lists of albums
+    pub popularity: Option<u32>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -263,6 +284,53 @@ impl WithImages for Album {
     }
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct Show {
+    pub id: String,
+    pub name: String,
+    pub publisher: String,
+    pub description: String,
+    pub html_description: Option<String>,
+    pub images: Vec<Image>,
+    // Some episodes might be missing (null)
+    pub episodes: Option<Page<Option<Episode>>>,
+}
+
+impl WithImages for Show {
+    fn images(&self) -> &[Image] {
+        &self.images[..]
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Episode {
+    pub id: String,
+    pub uri: String,
+    pub name: String,
+    pub description: String,
+    pub html_description: Option<String>,
+    pub release_date: Option<String>,
+    pub duration_ms: i64,
+    pub images: Vec<Image>,
+    // Only there if the user allowed us to read it
+    pub resume_point: Option<ResumePoint>,
+    // Only there when the episode isn't fetched from its show, e.g. among saved episodes
+    #[serde(default)]
+    pub show: Option<Box<Show>>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ResumePoint {
+    pub fully_played: bool,
+    pub resume_position_ms: i64,
+}
+
+impl WithImages for Episode {
+    fn images(&self) -> &[Image] {
+        &self.images[..]
+    }
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct Image {
     pub url: String,
@@ -292,6 +360,23 @@ impl WithImages for Artist {
 pub struct User {
     pub id: String,
     pub display_name: String,
+    pub followers: Option<Followers>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Followers {
+    pub total: u32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct FollowedArtists {
+    pub artists: FollowedArtistsPage,
+}
+
+// A cursor-based page, of which we only need the total
+#[derive(Deserialize, Debug, Clone)]
+pub struct FollowedArtistsPage {
+    pub total: u32,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -316,6 +401,34 @@ pub struct PlayerQueue {
     pub queue: Vec<TrackItem>,
 }
 
+// What followed users are listening to, from the desktop client's buddy list
+#[derive(Deserialize, Debug, Clone)]
+pub struct BuddyList {
+    pub friends: Vec<Buddy>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Buddy {
+    // Milliseconds since the epoch
+    pub timestamp: u64,
+    pub user: BuddyItem,
+    pub track: BuddyTrack,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BuddyTrack {
+    pub uri: String,
+    pub name: String,
+    pub artist: BuddyItem,
+    pub context: Option<BuddyItem>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BuddyItem {
+    pub uri: String,
+    pub name: String,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct PlayerContext {
     #[serde(alias = "type")]
@@ -374,6 +487,18 @@ pub struct TopTracks {
     pub tracks: Vec<TrackItem>,
 }
 
+// Several full albums at once; unknown ids come back as null
+#[derive(Deserialize, Debug, Clone)]
+pub struct Albums {
+    pub albums: Vec<Option<Album>>,
+}
+
+// Tracks similar to a few seed artists and tracks, which is what radios are made of
+#[derive(Deserialize, Debug, Clone)]
+pub struct Recommendations {
+    pub tracks: Vec<TrackItem>,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct AlbumTrackItem {
     pub id: String,
@@ -382,6 +507,35 @@ pub struct AlbumTrackItem {
     pub name: String,
     pub duration_ms: i64,
     pub artists: Vec<Artist>,
+    // Only there when a market is given
+    #[serde(default)]
+    pub is_playable: Option<bool>,
+    #[serde(default)]
+    pub restrictions: Option<TrackRestrictions>,
+    // The track that was asked for, when it had to be swapped for one available in the market
+    #[serde(default)]
+    pub linked_from: Option<LinkedTrack>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LinkedTrack {
+    pub id: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TrackRestrictions {
+    pub reason: String,
+}
+
+impl From<TrackRestrictions> for Restriction {
+    fn from(restrictions: TrackRestrictions) -> Self {
+        match restrictions.reason.as_str() {
+            "market" => Self::Market,
+            "product" => Self::Product,
+            "explicit" => Self::Explicit,
+            _ => Self::Other,
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -389,6 +543,9 @@ pub struct TrackItem {
     #[serde(flatten)]
     pub track: AlbumTrackItem,
     pub album: Album,
+    // Only known for saved tracks, filled in from the SavedTrack wrapping it
+    #[serde(skip)]
+    pub added_at: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -433,8 +590,11 @@ impl TryFrom<PlaylistTrack> for TrackItem {
 }
 
 impl From<SavedTrack> for TrackItem {
-    fn from(track: SavedTrack) -> Self {
-        track.track
+    fn from(SavedTrack { added_at, track }: SavedTrack) -> Self {
+        Self {
+            added_at: Some(added_at),
+            ..track
+        }
     }
 }
 
@@ -462,12 +622,24 @@ impl From<PlayerQueue> for Vec<SongDescription> {
     }
 }
 
+impl From<TrackItem> for SongBatch {
+    fn from(track: TrackItem) -> Self {
+        Page::new(vec![track]).into()
+    }
+}
+
 impl From<TopTracks> for Vec<SongDescription> {
     fn from(top_tracks: TopTracks) -> Self {
         Page::new(top_tracks.tracks).into()
     }
 }
 
+impl From<Recommendations> for Vec<SongDescription> {
+    fn from(recommendations: Recommendations) -> Self {
+        Page::new(recommendations.tracks).into()
+    }
+}
+
 impl<T> From<Page<T>> for Vec<SongDescription>
 where
     T: TryInto<TrackItem>,
@@ -483,6 +655,7 @@ impl From<(Page<AlbumTrackItem>, &Album)> for SongBatch {
         Self::from(page.map(|track| TrackItem {
             track,
             album: album.clone(),
+            added_at: None,
         }))
     }
 }
@@ -500,7 +673,11 @@ where
         let songs = page
             .into_iter()
             .filter_map(|t| {
-                let TrackItem { track, album } = t.try_into().ok()?;
+                let TrackItem {
+                    track,
+                    album,
+                    added_at,
+                } = t.try_into().ok()?;
                 let AlbumTrackItem {
                     artists,
                     id,
@@ -508,16 +685,19 @@ where
                     name,
                     duration_ms,
                     track_number,
+                    is_playable,
+                    restrictions,
+                    linked_from,
                 } = track;
                 let artists = artists
                     .into_iter()
                     .map(|a| ArtistRef {
-                        id: a.id,
-                        name: a.name,
+                        id: intern(&a.id),
+                        name: intern(&a.name),
                     })
                     .collect::<Vec<ArtistRef>>();
 
-                let art = album.best_image_for_width(200).map(|i| &i.url).cloned();
+                let art = album.best_image_for_width(200).map(|i| intern(&i.url));
                 let Album {
                     id: album_id,
                     name: album_name,
@@ -525,8 +705,8 @@ where
                 } = album;
 
                 let album_ref = AlbumRef {
-                    id: album_id,
-                    name: album_name,
+                    id: intern(&album_id),
+                    name: intern(&album_name),
                 };
 
                 Some(SongDescription {
@@ -538,6 +718,13 @@ where
                     album: album_ref,
                     duration: duration_ms as u32,
                     art,
+                    restriction: match (is_playable, restrictions) {
+                        (_, Some(restrictions)) => Some(restrictions.into()),
+                        (Some(false), None) => Some(Restriction::Other),
+                        _ => None,
+                    },
+                    linked_from: linked_from.map(|l| l.id),
+                    added_at,
                 })
             })
             .collect();
@@ -545,6 +732,33 @@ where
     }
 }
 
+impl From<Page<SavedEpisode>> for SongBatch {
+    fn from(page: Page<SavedEpisode>) -> Self {
+        let batch = Batch {
+            offset: page.offset(),
+            batch_size: page.limit(),
+            total: page.total(),
+        };
+        let songs = page
+            .into_iter()
+            .filter_map(
+                |SavedEpisode {
+                     added_at,
+                     mut episode,
+                 }| {
+                    let show = ShowDescription::from(*episode.show.take()?);
+                    let song = show.episode_song(&episode.into());
+                    Some(SongDescription {
+                        added_at: Some(added_at),
+                        ..song
+                    })
+                },
+            )
+            .collect();
+        SongBatch { songs, batch }
+    }
+}
+
 impl TryFrom<Album> for SongBatch {
     type Error = ();
 
@@ -571,8 +785,8 @@ impl From<Album> for AlbumDescription {
             .artists
             .iter()
             .map(|a| ArtistRef {
-                id: a.id.clone(),
-                name: a.name.clone(),
+                id: intern(&a.id),
+                name: intern(&a.name),
             })
             .collect::<Vec<ArtistRef>>();
         let songs = album
@@ -580,12 +794,18 @@ impl From<Album> for AlbumDescription {
             .try_into()
             .unwrap_or_else(|_| SongBatch::empty());
         let art = album.best_image_for_width(200).map(|i| i.url.clone());
+        let precision = album.release_date_precision.as_deref();
+        let release_date = album
+            .release_date
+            .as_deref()
+            .and_then(|date| ReleaseDate::parse(date, precision));
 
         Self {
             id: album.id,
             title: album.name,
             artists,
-            release_date: album.release_date,
+            release_date,
+            popularity: album.popularity,
             art,
             songs,
             is_liked: false,
@@ -623,6 +843,7 @@ impl From<Playlist> for PlaylistDescription {
             name,
             tracks,
             owner,
+            public,
             ..
         } = playlist;
         let PlaylistOwner {
@@ -639,6 +860,97 @@ impl From<Playlist> for PlaylistDescription {
                 id: owner_id,
                 display_name,
             },
+            public,
+        }
+    }
+}
+
+// Plain descriptions are only there in case the HTML one is missing
+fn description_markup(description: &str, html_description: Option<&str>) -> String {
+    html_description
+        .map(markup::html_to_markup)
+        .unwrap_or_else(|| markup::escape(description))
+}
+
+impl From<Episode> for EpisodeDescription {
+    fn from(episode: Episode) -> Self {
+        let art = episode.best_image_for_width(200).map(|i| i.url.clone());
+        Self {
+            id: episode.id,
+            uri: episode.uri,
+            title: episode.name,
+            description: description_markup(
+                &episode.description,
+                episode.html_description.as_deref(),
+            ),
+            release_date: episode.release_date,
+            duration: episode.duration_ms as u32,
+            art,
+            resume_position: episode
+                .resume_point
+                .map(|point| point.resume_position_ms as u32),
+        }
+    }
+}
+
+impl From<Page<Option<Episode>>> for EpisodeBatch {
+    fn from(page: Page<Option<Episode>>) -> Self {
+        let batch = Batch {
+            offset: page.offset(),
+            batch_size: page.limit(),
+            total: page.total(),
+        };
+        let episodes = page.into_iter().flatten().map(|e| e.into()).collect();
+        Self { episodes, batch }
+    }
+}
+
+impl From<Show> for ShowDescription {
+    fn from(show: Show) -> Self {
+        let art = show.best_image_for_width(200).map(|i| i.url.clone());
+        let Show {
+            id,
+            name,
+            publisher,
+            description,
+            html_description,
+            episodes,
+            ..
+        } = show;
+        let episodes = episodes.map(EpisodeBatch::from);
+        Self {
+            id,
+            title: name,
+            publisher,
+            description: description_markup(&description, html_description.as_deref()),
+            art,
+            episodes_batch: episodes.as_ref().map(|page| page.batch),
+            episodes: episodes.map(|page| page.episodes).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<Buddy> for FriendActivity {
+    fn from(
+        Buddy {
+            timestamp,
+            user,
+            track,
+        }: Buddy,
+    ) -> Self {
+        let (context_uri, context_name) = track
+            .context
+            .map(|c| (Some(c.uri), Some(c.name)))
+            .unwrap_or_default();
+        Self {
+            user_name: user.name,
+            track_uri: track.uri,
+            track_title: track.name,
+            artist_name: track.artist.name,
+            // Empty names come up for contexts that can't be opened, like the radio
+            context_name: context_name.filter(|name| !name.is_empty()),
+            context_uri,
+            timestamp,
         }
     }
 }
@@ -684,6 +996,53 @@ mod tests {
         assert!(track_item.is_none());
     }
 
+    #[test]
+    fn test_show_episodes_null() {
+        let page = r#"{"items":[null,{"id":"1","uri":"spotify:episode:1","name":"Episode","description":"","release_date":"2023-01-01","duration_ms":60000,"images":[]}],"offset":0,"limit":50,"total":2}"#;
+        let deserialized: Page<Option<Episode>> = serde_json::from_str(page).unwrap();
+        let batch: EpisodeBatch = deserialized.into();
+        assert_eq!(batch.episodes.len(), 1);
+        assert_eq!(batch.episodes[0].uri, "spotify:episode:1");
+        // The missing episode still counts
+        assert_eq!(batch.batch.next().map(|b| b.offset), None);
+    }
+
+    #[test]
+    fn test_episode_resume_point() {
+        let episode = r#"{"id":"1","uri":"spotify:episode:1","name":"Episode","description":"","release_date":null,"duration_ms":60000,"images":[],"resume_point":{"fully_played":false,"resume_position_ms":1234}}"#;
+        let deserialized: Episode = serde_json::from_str(episode).unwrap();
+        let episode: EpisodeDescription = deserialized.into();
+        assert_eq!(episode.resume_position, Some(1234));
+    }
+
+    #[test]
+    fn test_saved_episodes() {
+        let page = r#"{"items":[{"added_at":"2023-01-02T00:00:00Z","episode":{"id":"1","uri":"spotify:episode:1","name":"Episode","description":"","release_date":null,"duration_ms":60000,"images":[],"show":{"id":"2","name":"Show","publisher":"Publisher","description":"","images":[]}}}],"offset":0,"limit":50,"total":1}"#;
+        let deserialized: Page<SavedEpisode> = serde_json::from_str(page).unwrap();
+        let batch: SongBatch = deserialized.into();
+        assert_eq!(batch.songs.len(), 1);
+        assert_eq!(&*batch.songs[0].album.name, "Show");
+        assert_eq!(
+            batch.songs[0].added_at.as_deref(),
+            Some("2023-01-02T00:00:00Z")
+        );
+        assert!(batch.songs[0].is_episode());
+    }
+
+    #[test]
+    fn test_buddy_list() {
+        let list = r#"{"friends":[{"timestamp":1690000000000,"user":{"uri":"spotify:user:1","name":"Friend","imageUrl":""},"track":{"uri":"spotify:track:1","name":"Track","imageUrl":"","album":{"uri":"spotify:album:1","name":"Album"},"artist":{"uri":"spotify:artist:1","name":"Artist"},"context":{"uri":"spotify:station:1","name":"","index":0}}}]}"#;
+        let deserialized: BuddyList = serde_json::from_str(list).unwrap();
+        let activity: Vec<FriendActivity> = deserialized
+            .friends
+            .into_iter()
+            .map(FriendActivity::from)
+            .collect();
+        assert_eq!(activity.len(), 1);
+        assert_eq!(activity[0].track_uri, "spotify:track:1");
+        assert_eq!(activity[0].context_name, None);
+    }
+
     #[test]
     fn test_playlist_track_ok() {
         let track = r#"{"is_local":false,"track":{"album":{"artists":[{"external_urls":{"spotify":""},"href":"","id":"","name":"","type":"artist","uri":""}],"id":"","images":[{"height":64,"url":"","width":64}],"name":""},"artists":[{"id":"","name":""}],"duration_ms":1,"id":"","name":"","uri":""}}"#;
@@ -691,4 +1050,30 @@ mod tests {
         let track_item: Option<TrackItem> = deserialized.try_into().ok();
         assert!(track_item.is_some());
     }
+
+    #[test]
+    fn test_track_restrictions() {
+        let track = r#"{"album":{"artists":[],"id":"","images":[],"name":""},"artists":[],"duration_ms":1,"id":"","name":"","uri":"","is_playable":false,"restrictions":{"reason":"market"}}"#;
+        let deserialized: TrackItem = serde_json::from_str(track).unwrap();
+        let songs = SongBatch::from(deserialized).songs;
+        assert_eq!(songs[0].restriction, Some(Restriction::Market));
+    }
+
+    #[test]
+    fn test_track_linked_from() {
+        let track = r#"{"album":{"artists":[],"id":"","images":[],"name":""},"artists":[],"duration_ms":1,"id":"relinked","name":"","uri":"","is_playable":true,"linked_from":{"id":"original","uri":"spotify:track:original"}}"#;
+        let deserialized: TrackItem = serde_json::from_str(track).unwrap();
+        let song = &SongBatch::from(deserialized).songs[0];
+        assert!(song.has_id("original"));
+        assert_eq!(song.original_id(), "original");
+        assert_eq!(song.id, "relinked");
+    }
+
+    #[test]
+    fn test_saved_track_added_at() {
+        let saved = r#"{"added_at":"2023-06-01T10:00:00Z","track":{"album":{"artists":[],"id":"","images":[],"name":""},"artists":[],"duration_ms":1,"id":"","name":"","uri":""}}"#;
+        let deserialized: SavedTrack = serde_json::from_str(saved).unwrap();
+        let songs = SongBatch::from(Page::new(vec![deserialized])).songs;
+        assert_eq!(songs[0].added_at.as_deref(), Some("2023-06-01T10:00:00Z"));
+    }
 }
@@ -8,11 +8,94 @@ use futures::join;
 use regex::Regex;
 use std::convert::From;
 use std::future::Future;
+use std::sync::{Mutex, RwLock};
 use std::time::{Duration, SystemTime};
 use thiserror::Error;
 
 const EXPIRY_FILE_EXT: &str = ".expiry";
 
+lazy_static! {
+    // Where all caches live; set once at startup from the preferences
+    static ref CACHE_ROOT: RwLock<std::path::PathBuf> = RwLock::new(glib::user_cache_dir());
+    // The account logged in, see CacheManager::for_account_dir
+    static ref CACHE_ACCOUNT: RwLock<Option<String>> = RwLock::new(None);
+    // See set_size_limit
+    static ref SIZE_LIMIT: Mutex<SizeLimit> = Default::default();
+}
+
+#[derive(Default)]
+struct SizeLimit {
+    max_size: Option<u64>,
+    caches: Vec<CacheManager>,
+    // As of the last eviction plus what was written since, unknown until the first write
+    usage: Option<u64>,
+    evicting: bool,
+}
+
+pub fn set_cache_root(root: std::path::PathBuf) {
+    *CACHE_ROOT.write().unwrap() = root;
+}
+
+pub fn cache_root() -> std::path::PathBuf {
+    CACHE_ROOT.read().unwrap().clone()
+}
+
+pub fn set_cache_account(username: Option<&str>) {
+    *CACHE_ACCOUNT.write().unwrap() = username.map(|u| u.to_string());
+}
+
+// Once the caches together grow past max_size bytes, the least recently used files are evicted
+pub fn set_size_limit(max_size: Option<u64>, caches: Vec<CacheManager>) {
+    let mut limit = SIZE_LIMIT.lock().unwrap();
+    limit.max_size = max_size;
+    limit.caches = caches;
+}
+
+// The first write scans the caches, later ones only do if the limit is exceeded
+fn note_written(size: u64) {
+    let mut limit = SIZE_LIMIT.lock().unwrap();
+    let Some(max_size) = limit.max_size else {
+        return;
+    };
+    limit.usage = limit.usage.map(|usage| usage + size);
+    if limit.evicting || limit.usage.map(|usage| usage <= max_size).unwrap_or(false) {
+        return;
+    }
+    limit.evicting = true;
+    let caches = limit.caches.clone();
+    // Not awaited, so that whatever was being cached isn't held up
+    glib::MainContext::default().spawn(async move {
+        let usage = CacheManager::evict_lru(&caches, max_size).await;
+        let mut limit = SIZE_LIMIT.lock().unwrap();
+        limit.evicting = false;
+        limit.usage = usage.ok();
+    });
+}
+
+// librespot manages this one itself (credentials, volume and audio files)
+pub fn librespot_cache_root() -> std::path::PathBuf {
+    cache_root().join("spot").join("librespot")
+}
+
+// Total size of the files in a directory and its subdirectories
+pub async fn dir_size(dir: &Path) -> u64 {
+    let mut size = 0;
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(mut entries) = fs::read_dir(&dir).await else {
+            continue;
+        };
+        while let Some(Ok(entry)) = entries.next().await {
+            match entry.metadata().await {
+                Ok(m) if m.is_dir() => dirs.push(entry.path()),
+                Ok(m) => size += m.len(),
+                _ => {}
+            }
+        }
+    }
+    size
+}
+
 #[derive(Error, Debug)]
 pub enum CacheError {
     #[error("No content available")]
@@ -80,26 +163,62 @@ impl CacheExpiry {
 #[derive(Clone)]
 pub struct CacheManager {
     root: PathBuf,
+    // Files are kept in a separate directory for each account
+    per_account: bool,
 }
 
 impl CacheManager {
     pub fn for_dir(dir: &str) -> Option<Self> {
-        let root: PathBuf = glib::user_cache_dir().into();
+        let root: PathBuf = cache_root().into();
         let root = root.join(dir);
         let mask = 0o744;
 
         glib::mkdir_with_parents(&root, mask);
 
-        Some(Self { root })
+        Some(Self {
+            root,
+            per_account: false,
+        })
+    }
+
+    // For what depends on who's logged in (saved tracks, playlists...), so that switching accounts
+    // neither mixes them up nor needs to throw them away
+    pub fn for_account_dir(dir: &str) -> Option<Self> {
+        Some(Self {
+            per_account: true,
+            ..Self::for_dir(dir)?
+        })
+    }
+
+    // Without an account (logged out), the shared directory is used
+    fn root(&self) -> PathBuf {
+        match CACHE_ACCOUNT.read().unwrap().as_deref() {
+            Some(account) if self.per_account => self.root.join("accounts").join(account),
+            _ => self.root.clone(),
+        }
     }
 
-    fn cache_path(&self, resource: &str) -> PathBuf {
-        self.root.join(resource)
+    pub fn cache_path(&self, resource: &str) -> PathBuf {
+        self.root().join(resource)
     }
 
     fn cache_meta_path(&self, resource: &str) -> PathBuf {
         let full = resource.to_string() + EXPIRY_FILE_EXT;
-        self.root.join(full)
+        self.root().join(full)
+    }
+
+    // Where cached files are, for all accounts
+    async fn all_roots(&self) -> Vec<PathBuf> {
+        let mut roots = vec![self.root.clone()];
+        if !self.per_account {
+            return roots;
+        }
+        if let Ok(mut accounts) = fs::read_dir(self.root.join("accounts")).await {
+            while let Some(Ok(account)) = accounts.next().await {
+                roots.push(account.path());
+            }
+        }
+        roots
     }
 }
 
@@ -181,7 +300,7 @@ impl CacheManager {
     }
 
     pub async fn clear_cache_pattern(&self, regex: &Regex) -> Result<(), CacheError> {
-        let mut entries = fs::read_dir(&self.root)
+        let mut entries = fs::read_dir(&self.root())
             .await
             .map_err(CacheError::ReadError)?;
 
@@ -210,7 +329,7 @@ impl CacheManager {
     }
 
     pub async fn set_expired_pattern(&self, regex: &Regex) -> Result<(), CacheError> {
-        let mut entries = fs::read_dir(&self.root)
+        let mut entries = fs::read_dir(&self.root())
             .await
             .map_err(CacheError::ReadError)?;
 
@@ -236,6 +355,11 @@ impl CacheManager {
         content: &[u8],
         expiry: CacheExpiry,
     ) -> Result<(), CacheError> {
+        if self.per_account {
+            fs::create_dir_all(self.root())
+                .await
+                .map_err(CacheError::WriteError)?;
+        }
         let file = self.cache_path(resource);
         let meta = self.cache_meta_path(resource);
         let (r1, r2) = join!(
@@ -244,6 +368,7 @@ impl CacheManager {
         );
         r1.map_err(CacheError::WriteError)?;
         r2?;
+        note_written(content.len() as u64);
         Ok(())
     }
 
@@ -283,6 +408,94 @@ impl CacheManager {
     }
 }
 
+// A cached file (along with its expiry file), as far as eviction is concerned
+struct CacheEntry {
+    path: PathBuf,
+    size: u64,
+    last_used: SystemTime,
+}
+
+impl CacheManager {
+    async fn entries(&self) -> Result<Vec<CacheEntry>, CacheError> {
+        let mut result = vec![];
+        for root in self.all_roots().await {
+            let mut entries = fs::read_dir(&root).await.map_err(CacheError::ReadError)?;
+            while let Some(Ok(entry)) = entries.next().await {
+                let is_expiry_file = entry
+                    .file_name()
+                    .to_str()
+                    .map(|s| s.ends_with(EXPIRY_FILE_EXT))
+                    .unwrap_or(false);
+                if is_expiry_file {
+                    continue;
+                }
+                let Ok(metadata) = entry.metadata().await else {
+                    continue;
+                };
+                if !metadata.is_file() {
+                    continue;
+                }
+                // Access times might not be tracked (noatime), in which case we fall back to the last write
+                let last_used = metadata
+                    .accessed()
+                    .or_else(|_| metadata.modified())
+                    .unwrap_or(SystemTime::UNIX_EPOCH);
+                let expiry_size = fs::metadata(expiry_path_for(&entry.path()))
+                    .await
+                    .map(|m| m.len())
+                    .unwrap_or(0);
+                result.push(CacheEntry {
+                    path: entry.path(),
+                    size: metadata.len() + expiry_size,
+                    last_used,
+                });
+            }
+        }
+        Ok(result)
+    }
+
+    pub async fn disk_usage(&self) -> Result<u64, CacheError> {
+        Ok(self.entries().await?.iter().map(|e| e.size).sum())
+    }
+
+    // Removes the least recently used files until all the given caches fit in max_size bytes, returns what they use
+    pub async fn evict_lru(caches: &[CacheManager], max_size: u64) -> Result<u64, CacheError> {
+        let mut entries = vec![];
+        for cache in caches {
+            entries.append(&mut cache.entries().await?);
+        }
+
+        let mut total_size: u64 = entries.iter().map(|e| e.size).sum();
+        if total_size <= max_size {
+            return Ok(total_size);
+        }
+
+        entries.sort_by_key(|e| e.last_used);
+        for entry in entries {
+            if total_size <= max_size {
+                break;
+            }
+            debug!("Evicting {:?} from cache", entry.path);
+            match fs::remove_file(&entry.path).await {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                    return Err(CacheError::RemoveError(e))
+                }
+                _ => {}
+            }
+            let _ = fs::remove_file(expiry_path_for(&entry.path)).await;
+            total_size = total_size.saturating_sub(entry.size);
+        }
+
+        Ok(total_size)
+    }
+}
+
+fn expiry_path_for(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_os_string();
+    path.push(EXPIRY_FILE_EXT);
+    path.into()
+}
+
 pub enum FetchResult {
     NotModified(CacheExpiry),
     Modified(Vec<u8>, CacheExpiry),
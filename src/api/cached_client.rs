@@ -24,8 +24,21 @@ pub trait SpotifyApiClient {
         limit: usize,
     ) -> BoxFuture<SpotifyResult<SongBatch>>;
 
+    fn get_track(&self, id: &str) -> BoxFuture<SpotifyResult<SongDescription>>;
+
     fn get_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<PlaylistDescription>>;
 
+    fn get_show(&self, id: &str) -> BoxFuture<SpotifyResult<ShowDescription>>;
+
+    fn get_show_episodes(
+        &self,
+        id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<EpisodeBatch>>;
+
+    fn get_episode(&self, id: &str) -> BoxFuture<SpotifyResult<EpisodeDescription>>;
+
     fn get_playlist_tracks(
         &self,
         id: &str,
@@ -41,6 +54,12 @@ pub trait SpotifyApiClient {
 
     fn get_saved_tracks(&self, offset: usize, limit: usize) -> BoxFuture<SpotifyResult<SongBatch>>;
 
+    fn get_saved_episodes(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<SongBatch>>;
+
     fn save_album(&self, id: &str) -> BoxFuture<SpotifyResult<AlbumDescription>>;
 
     fn save_tracks(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<()>>;
@@ -57,6 +76,13 @@ pub trait SpotifyApiClient {
 
     fn add_to_playlist(&self, id: &str, uris: Vec<String>) -> BoxFuture<SpotifyResult<()>>;
 
+    fn insert_into_playlist(
+        &self,
+        id: &str,
+        uris: Vec<String>,
+        position: usize,
+    ) -> BoxFuture<SpotifyResult<()>>;
+
     fn create_new_playlist(
         &self,
         name: &str,
@@ -67,6 +93,8 @@ pub trait SpotifyApiClient {
 
     fn update_playlist_details(&self, id: &str, name: String) -> BoxFuture<SpotifyResult<()>>;
 
+    fn set_playlist_public(&self, id: &str, public: bool) -> BoxFuture<SpotifyResult<()>>;
+
     fn search(
         &self,
         query: &str,
@@ -83,6 +111,8 @@ pub trait SpotifyApiClient {
 
     fn get_user(&self, id: &str) -> BoxFuture<SpotifyResult<UserDescription>>;
 
+    fn get_followed_artists_count(&self) -> BoxFuture<SpotifyResult<u32>>;
+
     fn get_user_playlists(
         &self,
         id: &str,
@@ -94,8 +124,24 @@ pub trait SpotifyApiClient {
 
     fn get_player_queue(&self) -> BoxFuture<SpotifyResult<Vec<SongDescription>>>;
 
+    // Not cached either, a radio is expected to be different every time
+    fn get_recommendations(
+        &self,
+        seed_artists: Vec<String>,
+        seed_tracks: Vec<String>,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>>;
+
+    // Not cached, it's only interesting while it's fresh
+    fn get_friend_activity(&self) -> BoxFuture<SpotifyResult<Vec<FriendActivity>>>;
+
     fn update_token(&self, token: String);
 
+    // In offline mode, only cached content is returned
+    fn set_offline(&self, offline: bool);
+
+    // Without a network, cached content is preferred even if stale, the rest is still attempted
+    fn set_connected(&self, connected: bool);
+
     fn player_pause(&self, device_id: String) -> BoxFuture<SpotifyResult<()>>;
 
     fn player_resume(&self, device_id: String) -> BoxFuture<SpotifyResult<()>>;
@@ -130,6 +176,7 @@ pub trait SpotifyApiClient {
 enum SpotCacheKey<'a> {
     SavedAlbums(usize, usize),
     SavedTracks(usize, usize),
+    SavedEpisodes(usize, usize),
     SavedPlaylists(usize, usize),
     Album(&'a str),
     AlbumLiked(&'a str),
@@ -137,7 +184,12 @@ enum SpotCacheKey<'a> {
     Playlist(&'a str),
     PlaylistTracks(&'a str, usize, usize),
     ArtistAlbums(&'a str, usize, usize),
+    ArtistAlbumsPopularity(&'a str, usize, usize),
     Artist(&'a str),
+    Track(&'a str),
+    Show(&'a str),
+    Episode(&'a str),
+    ShowEpisodes(&'a str, usize, usize),
     ArtistTopTracks(&'a str),
     User(&'a str),
     UserPlaylists(&'a str, usize, usize),
@@ -148,6 +200,7 @@ impl<'a> SpotCacheKey<'a> {
         match self {
             Self::SavedAlbums(offset, limit) => format!("me_albums_{offset}_{limit}.json"),
             Self::SavedTracks(offset, limit) => format!("me_tracks_{offset}_{limit}.json"),
+            Self::SavedEpisodes(offset, limit) => format!("me_episodes_{offset}_{limit}.json"),
             Self::SavedPlaylists(offset, limit) => format!("me_playlists_{offset}_{limit}.json"),
             Self::Album(id) => format!("album_{id}.json"),
             Self::AlbumTracks(id, offset, limit) => {
@@ -161,7 +214,16 @@ impl<'a> SpotCacheKey<'a> {
             Self::ArtistAlbums(id, offset, limit) => {
                 format!("artist_albums_{id}_{offset}_{limit}.json")
             }
+            Self::ArtistAlbumsPopularity(id, offset, limit) => {
+                format!("artist_albums_popularity_{id}_{offset}_{limit}.json")
+            }
             Self::Artist(id) => format!("artist_{id}.json"),
+            Self::Track(id) => format!("track_{id}.json"),
+            Self::Show(id) => format!("show_{id}.json"),
+            Self::Episode(id) => format!("episode_{id}.json"),
+            Self::ShowEpisodes(id, offset, limit) => {
+                format!("show_episodes_{id}_{offset}_{limit}.json")
+            }
             Self::ArtistTopTracks(id) => format!("artist_top_tracks_{id}.json"),
             Self::User(id) => format!("user_{id}.json"),
             Self::UserPlaylists(id, offset, limit) => {
@@ -176,8 +238,14 @@ lazy_static! {
     pub static ref ME_ALBUMS_CACHE: Regex = Regex::new(r"^me_albums_\w+_\w+\.json$").unwrap();
     pub static ref USER_CACHE: Regex =
         Regex::new(r"^me_(albums|playlists|tracks)_\w+_\w+\.json$").unwrap();
+    // Profiles list the public playlists of their user
+    static ref USER_PLAYLISTS_CACHE: Regex =
+        Regex::new(r"^user_playlists_.+_\w+_\w+\.json$").unwrap();
 }
 
+// The saved tracks endpoints take at most 50 ids per request
+pub const SAVED_TRACKS_MAX_IDS: usize = 50;
+
 fn playlist_cache_key(id: &str) -> Regex {
     Regex::new(&format!(r"^playlist(_{id}|item_{id}_\w+_\w+)\.json$")).unwrap()
 }
@@ -191,12 +259,12 @@ impl CachedSpotifyClient {
     pub fn new() -> CachedSpotifyClient {
         CachedSpotifyClient {
             client: SpotifyClient::new(),
-            cache: CacheManager::for_dir("spot/net").unwrap(),
+            cache: CacheManager::for_account_dir("spot/net").unwrap(),
         }
     }
 
     fn default_cache_policy(&self) -> CachePolicy {
-        if self.client.has_token() {
+        if self.client.has_token() && !self.client.is_offline() {
             CachePolicy::Default
         } else {
             CachePolicy::IgnoreExpiry
@@ -271,6 +339,14 @@ impl SpotifyApiClient for CachedSpotifyClient {
         self.client.update_token(new_token)
     }
 
+    fn set_offline(&self, offline: bool) {
+        self.client.set_offline(offline)
+    }
+
+    fn set_connected(&self, connected: bool) {
+        self.client.set_connected(connected)
+    }
+
     fn get_saved_albums(
         &self,
         offset: usize,
@@ -310,6 +386,25 @@ impl SpotifyApiClient for CachedSpotifyClient {
         })
     }
 
+    fn get_saved_episodes(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<SongBatch>> {
+        Box::pin(async move {
+            let page = self
+                .cache_get_or_write(SpotCacheKey::SavedEpisodes(offset, limit), None, |etag| {
+                    self.client
+                        .get_saved_episodes(offset, limit)
+                        .etag(etag)
+                        .send()
+                })
+                .await?;
+
+            Ok(page.into())
+        })
+    }
+
     fn get_saved_playlists(
         &self,
         offset: usize,
@@ -351,6 +446,28 @@ impl SpotifyApiClient for CachedSpotifyClient {
         })
     }
 
+    fn insert_into_playlist(
+        &self,
+        id: &str,
+        uris: Vec<String>,
+        position: usize,
+    ) -> BoxFuture<SpotifyResult<()>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            self.cache
+                .set_expired_pattern(&playlist_cache_key(&id))
+                .await
+                .unwrap_or(());
+
+            self.client
+                .insert_into_playlist(&id, uris, position)
+                .send_no_response()
+                .await?;
+            Ok(())
+        })
+    }
+
     fn create_new_playlist(
         &self,
         name: &str,
@@ -407,6 +524,28 @@ impl SpotifyApiClient for CachedSpotifyClient {
         })
     }
 
+    fn set_playlist_public(&self, id: &str, public: bool) -> BoxFuture<SpotifyResult<()>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            self.cache
+                .set_expired_pattern(&playlist_cache_key(&id))
+                .await
+                .unwrap_or(());
+            self.cache
+                .set_expired_pattern(&USER_PLAYLISTS_CACHE)
+                .await
+                .unwrap_or(());
+
+            self.client
+                .set_playlist_public(&id, public)
+                .send_no_response()
+                .await?;
+
+            Ok(())
+        })
+    }
+
     fn get_album(&self, id: &str) -> BoxFuture<SpotifyResult<AlbumFullDescription>> {
         let id = id.to_owned();
 
@@ -417,7 +556,7 @@ impl SpotifyApiClient for CachedSpotifyClient {
 
             let liked = self.cache_get_or_write(
                 SpotCacheKey::AlbumLiked(&id),
-                Some(if self.client.has_token() {
+                Some(if self.client.has_token() && !self.client.is_offline() {
                     CachePolicy::Revalidate
                 } else {
                     CachePolicy::IgnoreExpiry
@@ -464,10 +603,13 @@ impl SpotifyApiClient for CachedSpotifyClient {
     fn remove_saved_tracks(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<()>> {
         Box::pin(async move {
             let _ = self.cache.set_expired_pattern(&ME_TRACKS_CACHE).await;
-            self.client
-                .remove_saved_tracks(ids)
-                .send_no_response()
-                .await
+            for ids in ids.chunks(SAVED_TRACKS_MAX_IDS) {
+                self.client
+                    .remove_saved_tracks(ids.to_vec())
+                    .send_no_response()
+                    .await?;
+            }
+            Ok(())
         })
     }
 
@@ -502,6 +644,88 @@ impl SpotifyApiClient for CachedSpotifyClient {
         })
     }
 
+    fn get_track(&self, id: &str) -> BoxFuture<SpotifyResult<SongDescription>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            let track = self
+                .cache_get_or_write(SpotCacheKey::Track(&id), None, |etag| {
+                    self.client.get_track(&id).etag(etag).send()
+                })
+                .await?;
+
+            SongBatch::from(track)
+                .songs
+                .pop()
+                .ok_or(SpotifyApiError::NoContent)
+        })
+    }
+
+    fn get_show(&self, id: &str) -> BoxFuture<SpotifyResult<ShowDescription>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            // Resume points change as the user listens
+            let show = self
+                .cache_get_or_write(
+                    SpotCacheKey::Show(&id),
+                    Some(if self.client.has_token() && !self.client.is_offline() {
+                        CachePolicy::Revalidate
+                    } else {
+                        CachePolicy::IgnoreExpiry
+                    }),
+                    |etag| self.client.get_show(&id).etag(etag).send(),
+                )
+                .await?;
+
+            Ok(show.into())
+        })
+    }
+
+    fn get_episode(&self, id: &str) -> BoxFuture<SpotifyResult<EpisodeDescription>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            let episode = self
+                .cache_get_or_write(SpotCacheKey::Episode(&id), None, |etag| {
+                    self.client.get_episode(&id).etag(etag).send()
+                })
+                .await?;
+
+            Ok(episode.into())
+        })
+    }
+
+    fn get_show_episodes(
+        &self,
+        id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> BoxFuture<SpotifyResult<EpisodeBatch>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            let episodes = self
+                .cache_get_or_write(
+                    SpotCacheKey::ShowEpisodes(&id, offset, limit),
+                    Some(if self.client.has_token() && !self.client.is_offline() {
+                        CachePolicy::Revalidate
+                    } else {
+                        CachePolicy::IgnoreExpiry
+                    }),
+                    |etag| {
+                        self.client
+                            .get_show_episodes(&id, offset, limit)
+                            .etag(etag)
+                            .send()
+                    },
+                )
+                .await?;
+
+            Ok(episodes.into())
+        })
+    }
+
     fn get_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<PlaylistDescription>> {
         let id = id.to_owned();
 
@@ -564,11 +788,33 @@ impl SpotifyApiClient for CachedSpotifyClient {
                 )
                 .await?;
 
-            let albums = albums
+            let mut albums = albums
                 .into_iter()
                 .map(|a| a.into())
                 .collect::<Vec<AlbumDescription>>();
 
+            // Lists of albums leave out their popularity, it takes asking for the full albums
+            let ids = albums.iter().map(|a| a.id.clone()).collect::<Vec<String>>();
+            if ids.is_empty() {
+                return Ok(albums);
+            }
+            let full_albums = self
+                .cache_get_or_write(
+                    SpotCacheKey::ArtistAlbumsPopularity(&id, offset, limit),
+                    None,
+                    |etag| self.client.get_albums(&ids).etag(etag).send(),
+                )
+                .await;
+            match full_albums {
+                Ok(full_albums) => {
+                    for (album, full_album) in albums.iter_mut().zip(full_albums.albums) {
+                        album.popularity = full_album.and_then(|a| a.popularity);
+                    }
+                }
+                // Not worth failing the whole list for
+                Err(err) => debug!("Could not get album popularity: {}", err),
+            }
+
             Ok(albums)
         })
     }
@@ -683,12 +929,26 @@ impl SpotifyApiClient for CachedSpotifyClient {
             let result = UserDescription {
                 id: user.id,
                 name: user.display_name,
+                followers: user.followers.map(|f| f.total),
                 playlists: playlists?,
             };
             Ok(result)
         })
     }
 
+    fn get_followed_artists_count(&self) -> BoxFuture<SpotifyResult<u32>> {
+        Box::pin(async move {
+            let followed = self
+                .client
+                .get_followed_artists()
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?;
+            Ok(followed.artists.total)
+        })
+    }
+
     fn list_available_devices(&self) -> BoxFuture<SpotifyResult<Vec<ConnectDevice>>> {
         Box::pin(async move {
             let devices = self
@@ -723,6 +983,40 @@ impl SpotifyApiClient for CachedSpotifyClient {
         })
     }
 
+    fn get_recommendations(
+        &self,
+        seed_artists: Vec<String>,
+        seed_tracks: Vec<String>,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>> {
+        Box::pin(async move {
+            let recommendations = self
+                .client
+                .get_recommendations(&seed_artists, &seed_tracks, 50)
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?;
+            Ok(recommendations.into())
+        })
+    }
+
+    fn get_friend_activity(&self) -> BoxFuture<SpotifyResult<Vec<FriendActivity>>> {
+        Box::pin(async move {
+            let buddies = self
+                .client
+                .get_friend_activity()
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?;
+            Ok(buddies
+                .friends
+                .into_iter()
+                .map(FriendActivity::from)
+                .collect())
+        })
+    }
+
     fn player_pause(&self, device_id: String) -> BoxFuture<SpotifyResult<()>> {
         Box::pin(self.client.player_pause(&device_id).send_no_response())
     }
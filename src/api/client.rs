@@ -1,5 +1,4 @@
 use form_urlencoded::Serializer;
-use isahc::config::Configurable;
 use isahc::http::{method::Method, request::Builder, StatusCode, Uri};
 use isahc::{AsyncReadResponseExt, HttpClient, Request};
 use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
@@ -8,6 +7,7 @@ use serde_json::from_str;
 use std::convert::Into;
 use std::marker::PhantomData;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 use thiserror::Error;
 
@@ -15,6 +15,8 @@ pub use super::api_models::*;
 use super::cache::CacheError;
 
 const SPOTIFY_HOST: &str = "api.spotify.com";
+// Serves a few things the Web API doesn't, like the friend activity
+const SPCLIENT_HOST: &str = "guc-spclient.spotify.com";
 
 // https://url.spec.whatwg.org/#path-percent-encode-set
 const PATH_ENCODE_SET: &AsciiSet = &CONTROLS
@@ -48,14 +50,18 @@ where
         self
     }
 
-    fn uri(mut self, path: String, query: Option<&str>) -> Self {
+    fn uri(self, path: String, query: Option<&str>) -> Self {
+        self.uri_on(SPOTIFY_HOST, path, query)
+    }
+
+    fn uri_on(mut self, host: &str, path: String, query: Option<&str>) -> Self {
         let path_and_query = match query {
             None => path,
             Some(query) => format!("{path}?{query}"),
         };
         let uri = Uri::builder()
             .scheme("https")
-            .authority(SPOTIFY_HOST)
+            .authority(host)
             .path_and_query(&path_and_query[..])
             .build()
             .unwrap();
@@ -64,6 +70,10 @@ where
     }
 
     fn authenticated(mut self) -> Result<Self, SpotifyApiError> {
+        // Nothing leaves the computer in offline mode
+        if self.client.offline.load(Ordering::Relaxed) {
+            return Err(SpotifyApiError::Offline);
+        }
         let token = self.client.token.lock().unwrap();
         let token = token.as_ref().ok_or(SpotifyApiError::NoToken)?;
         self.request = self
@@ -152,6 +162,10 @@ pub enum SpotifyApiError {
     InvalidToken,
     #[error("No token")]
     NoToken,
+    #[error("Not available offline")]
+    Offline,
+    #[error("No network connection")]
+    NoConnection,
     #[error("No content from request")]
     NoContent,
     #[error("Request rate exceeded")]
@@ -172,19 +186,18 @@ pub enum SpotifyApiError {
 
 pub(crate) struct SpotifyClient {
     token: Mutex<Option<String>>,
+    offline: AtomicBool,
+    disconnected: AtomicBool,
     client: HttpClient,
 }
 
 impl SpotifyClient {
     pub(crate) fn new() -> Self {
-        let mut builder = HttpClient::builder();
-        if cfg!(debug_assertions) {
-            builder = builder.ssl_options(isahc::config::SslOption::DANGER_ACCEPT_INVALID_CERTS);
-        }
-        let client = builder.build().unwrap();
         Self {
             token: Mutex::new(None),
-            client,
+            offline: AtomicBool::new(false),
+            disconnected: AtomicBool::new(false),
+            client: super::http_client(),
         }
     }
 
@@ -201,6 +214,19 @@ impl SpotifyClient {
         self.token.lock().unwrap().is_some()
     }
 
+    // Whether cached content should be used even if stale, by choice or not
+    pub(crate) fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::Relaxed) || self.disconnected.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_connected(&self, connected: bool) {
+        self.disconnected.store(!connected, Ordering::Relaxed);
+    }
+
     pub(crate) fn update_token(&self, new_token: String) {
         if let Ok(mut token) = self.token.lock() {
             *token = Some(new_token)
@@ -228,7 +254,13 @@ impl SpotifyClient {
     where
         B: Into<isahc::AsyncBody>,
     {
-        let mut result = self.client.send_async(request).await?;
+        let is_web_api = request.uri().host() == Some(SPOTIFY_HOST);
+        let is_load = request.method() == Method::GET;
+        let mut result = self
+            .client
+            .send_async(request)
+            .await
+            .map_err(|err| self.connection_error(is_load, err))?;
         let etag = result
             .headers()
             .get("etag")
@@ -248,7 +280,8 @@ impl SpotifyClient {
                 max_age: cache_control.unwrap_or(10),
                 etag,
             }),
-            StatusCode::UNAUTHORIZED => {
+            // Other hosts might not accept a token that's otherwise fine
+            StatusCode::UNAUTHORIZED if is_web_api => {
                 self.clear_token();
                 Err(SpotifyApiError::InvalidToken)
             }
@@ -268,11 +301,26 @@ impl SpotifyClient {
         }
     }
 
+    // The network monitor can be wrong (VPN only, no default route...), so requests are attempted anyway.
+    // When they fail while it says we're disconnected, loads are left to the offline banner, changes are reported.
+    fn connection_error(&self, is_load: bool, err: isahc::Error) -> SpotifyApiError {
+        if is_load && self.disconnected.load(Ordering::Relaxed) {
+            SpotifyApiError::NoConnection
+        } else {
+            err.into()
+        }
+    }
+
     async fn send_req_no_response<B>(&self, request: Request<B>) -> Result<(), SpotifyApiError>
     where
         B: Into<isahc::AsyncBody>,
     {
-        let mut result = self.client.send_async(request).await?;
+        let is_load = request.method() == Method::GET;
+        let mut result = self
+            .client
+            .send_async(request)
+            .await
+            .map_err(|err| self.connection_error(is_load, err))?;
         match result.status() {
             StatusCode::UNAUTHORIZED => {
                 self.clear_token();
@@ -317,6 +365,18 @@ impl SpotifyClient {
             .uri(format!("/v1/artists/{id}/albums"), Some(&query))
     }
 
+    // Up to 20 ids
+    pub(crate) fn get_albums(&self, ids: &[String]) -> SpotifyRequest<'_, (), Albums> {
+        let query = make_query_params()
+            .append_pair("ids", &ids.join(","))
+            .append_pair("market", "from_token")
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/albums".to_string(), Some(&query))
+    }
+
     pub(crate) fn get_artist_top_tracks(&self, id: &str) -> SpotifyRequest<'_, (), TopTracks> {
         let query = make_query_params()
             .append_pair("market", "from_token")
@@ -327,6 +387,31 @@ impl SpotifyClient {
             .uri(format!("/v1/artists/{id}/top-tracks"), Some(&query))
     }
 
+    // Spotify accepts up to 5 seeds in total
+    pub(crate) fn get_recommendations(
+        &self,
+        seed_artists: &[String],
+        seed_tracks: &[String],
+        limit: usize,
+    ) -> SpotifyRequest<'_, (), Recommendations> {
+        let mut query = make_query_params();
+        // Empty seeds are rejected
+        if !seed_artists.is_empty() {
+            query.append_pair("seed_artists", &seed_artists.join(","));
+        }
+        if !seed_tracks.is_empty() {
+            query.append_pair("seed_tracks", &seed_tracks.join(","));
+        }
+        let query = query
+            .append_pair("market", "from_token")
+            .append_pair("limit", &limit.to_string()[..])
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/recommendations".to_string(), Some(&query))
+    }
+
     pub(crate) fn is_album_saved(&self, id: &str) -> SpotifyRequest<'_, (), Vec<bool>> {
         let query = make_query_params().append_pair("ids", id).finish();
         self.request()
@@ -363,9 +448,56 @@ impl SpotifyClient {
     }
 
     pub(crate) fn get_album(&self, id: &str) -> SpotifyRequest<'_, (), FullAlbum> {
+        let query = make_query_params()
+            .append_pair("market", "from_token")
+            .finish();
         self.request()
             .method(Method::GET)
-            .uri(format!("/v1/albums/{id}"), None)
+            .uri(format!("/v1/albums/{id}"), Some(&query))
+    }
+
+    pub(crate) fn get_track(&self, id: &str) -> SpotifyRequest<'_, (), TrackItem> {
+        let query = make_query_params()
+            .append_pair("market", "from_token")
+            .finish();
+        self.request()
+            .method(Method::GET)
+            .uri(format!("/v1/tracks/{id}"), Some(&query))
+    }
+
+    pub(crate) fn get_show(&self, id: &str) -> SpotifyRequest<'_, (), Show> {
+        let query = make_query_params()
+            .append_pair("market", "from_token")
+            .finish();
+        self.request()
+            .method(Method::GET)
+            .uri(format!("/v1/shows/{id}"), Some(&query))
+    }
+
+    pub(crate) fn get_episode(&self, id: &str) -> SpotifyRequest<'_, (), Episode> {
+        let query = make_query_params()
+            .append_pair("market", "from_token")
+            .finish();
+        self.request()
+            .method(Method::GET)
+            .uri(format!("/v1/episodes/{id}"), Some(&query))
+    }
+
+    pub(crate) fn get_show_episodes(
+        &self,
+        id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> SpotifyRequest<'_, (), Page<Option<Episode>>> {
+        let query = make_query_params()
+            .append_pair("market", "from_token")
+            .append_pair("offset", &offset.to_string()[..])
+            .append_pair("limit", &limit.to_string()[..])
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri(format!("/v1/shows/{id}/episodes"), Some(&query))
     }
 
     pub(crate) fn get_album_tracks(
@@ -375,6 +507,7 @@ impl SpotifyClient {
         limit: usize,
     ) -> SpotifyRequest<'_, (), Page<AlbumTrackItem>> {
         let query = make_query_params()
+            .append_pair("market", "from_token")
             .append_pair("offset", &offset.to_string()[..])
             .append_pair("limit", &limit.to_string()[..])
             .finish();
@@ -424,6 +557,18 @@ impl SpotifyClient {
             .json_body(Uris { uris })
     }
 
+    pub(crate) fn insert_into_playlist(
+        &self,
+        playlist: &str,
+        uris: Vec<String>,
+        position: usize,
+    ) -> SpotifyRequest<'_, Vec<u8>, ()> {
+        self.request()
+            .method(Method::POST)
+            .uri(format!("/v1/playlists/{playlist}/tracks"), None)
+            .json_body(PositionedUris { uris, position })
+    }
+
     pub(crate) fn create_new_playlist(
         &self,
         name: &str,
@@ -457,6 +602,17 @@ impl SpotifyClient {
             .json_body(PlaylistDetails { name })
     }
 
+    pub(crate) fn set_playlist_public(
+        &self,
+        playlist: &str,
+        public: bool,
+    ) -> SpotifyRequest<'_, Vec<u8>, ()> {
+        self.request()
+            .method(Method::PUT)
+            .uri(format!("/v1/playlists/{playlist}"), None)
+            .json_body(PlaylistVisibility { public })
+    }
+
     pub(crate) fn get_saved_albums(
         &self,
         offset: usize,
@@ -478,6 +634,7 @@ impl SpotifyClient {
         limit: usize,
     ) -> SpotifyRequest<'_, (), Page<SavedTrack>> {
         let query = make_query_params()
+            .append_pair("market", "from_token")
             .append_pair("offset", &offset.to_string()[..])
             .append_pair("limit", &limit.to_string()[..])
             .finish();
@@ -487,6 +644,22 @@ impl SpotifyClient {
             .uri("/v1/me/tracks".to_string(), Some(&query))
     }
 
+    pub(crate) fn get_saved_episodes(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> SpotifyRequest<'_, (), Page<SavedEpisode>> {
+        let query = make_query_params()
+            .append_pair("market", "from_token")
+            .append_pair("offset", &offset.to_string()[..])
+            .append_pair("limit", &limit.to_string()[..])
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/me/episodes".to_string(), Some(&query))
+    }
+
     pub(crate) fn get_saved_playlists(
         &self,
         offset: usize,
@@ -544,6 +717,18 @@ impl SpotifyClient {
             .uri(format!("/v1/users/{id}/playlists"), Some(&query))
     }
 
+    // Only the artists, the API doesn't tell which users are followed
+    pub(crate) fn get_followed_artists(&self) -> SpotifyRequest<'_, (), FollowedArtists> {
+        let query = make_query_params()
+            .append_pair("type", "artist")
+            .append_pair("limit", "1")
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/me/following".to_string(), Some(&query))
+    }
+
     pub(crate) fn get_player_devices(&self) -> SpotifyRequest<'_, (), Devices> {
         self.request()
             .method(Method::GET)
@@ -556,6 +741,14 @@ impl SpotifyClient {
             .uri("/v1/me/player/queue".to_string(), None)
     }
 
+    pub(crate) fn get_friend_activity(&self) -> SpotifyRequest<'_, (), BuddyList> {
+        self.request().method(Method::GET).uri_on(
+            SPCLIENT_HOST,
+            "/presence-view/v1/buddylist".to_string(),
+            None,
+        )
+    }
+
     pub(crate) fn player_state(&self) -> SpotifyRequest<'_, (), PlayerState> {
         self.request()
             .method(Method::GET)
@@ -0,0 +1,486 @@
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use super::{SpotifyApiClient, SpotifyResult};
+use crate::app::models::{AlbumDescription, SongBatch, SongDescription};
+use crate::app::state::parse_spotify_uri;
+
+const PAGE_SIZE: usize = 50;
+
+#[derive(Clone, Copy, Debug)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    // Anything that isn't explicitly CSV gets JSON
+    pub fn from_file_name(name: &str) -> Self {
+        if name.to_lowercase().ends_with(".csv") {
+            Self::Csv
+        } else {
+            Self::Json
+        }
+    }
+}
+
+// How far along the export is, to report it to the user
+#[derive(Clone, Copy, Debug)]
+pub enum ExportProgress {
+    SavedTracks { done: usize, total: usize },
+    SavedAlbums { done: usize },
+    Playlists { done: usize, total: usize },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExportedTrack {
+    pub uri: String,
+    pub title: String,
+    pub artists: String,
+    pub album: String,
+}
+
+impl From<SongDescription> for ExportedTrack {
+    fn from(song: SongDescription) -> Self {
+        Self {
+            artists: song.artists_name(),
+            uri: song.uri,
+            title: song.title,
+            album: song.album.name.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExportedAlbum {
+    pub uri: String,
+    pub title: String,
+    pub artists: String,
+}
+
+impl From<AlbumDescription> for ExportedAlbum {
+    fn from(album: AlbumDescription) -> Self {
+        Self {
+            uri: format!("spotify:album:{}", album.id),
+            artists: album.artists_name(),
+            title: album.title,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExportedPlaylist {
+    pub uri: String,
+    pub name: String,
+    pub tracks: Vec<ExportedTrack>,
+}
+
+// Everything in the user's library, enough to back it up or move it elsewhere
+#[derive(Clone, Serialize, Deserialize, Default)]
+pub struct LibraryExport {
+    pub saved_tracks: Vec<ExportedTrack>,
+    pub saved_albums: Vec<ExportedAlbum>,
+    pub playlists: Vec<ExportedPlaylist>,
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// The reverse of csv_field, for one line: quoted fields can't span several lines in our exports
+fn parse_csv_row(row: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = row.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+fn csv_row(fields: &[&str]) -> String {
+    let mut row = fields
+        .iter()
+        .map(|f| csv_field(f))
+        .collect::<Vec<String>>()
+        .join(",");
+    row.push('\n');
+    row
+}
+
+impl LibraryExport {
+    // One row per item; playlist tracks are listed along with the playlist they belong to
+    fn to_csv(&self) -> String {
+        let mut csv = csv_row(&["type", "playlist", "uri", "title", "artists", "album"]);
+        for track in &self.saved_tracks {
+            csv.push_str(&csv_row(&[
+                "saved_track",
+                "",
+                &track.uri,
+                &track.title,
+                &track.artists,
+                &track.album,
+            ]));
+        }
+        for album in &self.saved_albums {
+            csv.push_str(&csv_row(&[
+                "saved_album",
+                "",
+                &album.uri,
+                &album.title,
+                &album.artists,
+                "",
+            ]));
+        }
+        for playlist in &self.playlists {
+            for track in &playlist.tracks {
+                csv.push_str(&csv_row(&[
+                    "playlist_track",
+                    &playlist.name,
+                    &track.uri,
+                    &track.title,
+                    &track.artists,
+                    &track.album,
+                ]));
+            }
+        }
+        csv
+    }
+
+    fn from_csv(csv: &str) -> Option<Self> {
+        let mut lines = csv.lines();
+        let header = parse_csv_row(lines.next()?);
+        if header != ["type", "playlist", "uri", "title", "artists", "album"] {
+            return None;
+        }
+
+        let mut export = Self::default();
+        for line in lines.filter(|line| !line.is_empty()) {
+            let fields = parse_csv_row(line);
+            let [kind, playlist, uri, title, artists, album] =
+                <[String; 6]>::try_from(fields).ok()?;
+            match kind.as_str() {
+                "saved_track" => export.saved_tracks.push(ExportedTrack {
+                    uri,
+                    title,
+                    artists,
+                    album,
+                }),
+                "saved_album" => export.saved_albums.push(ExportedAlbum {
+                    uri,
+                    title,
+                    artists,
+                }),
+                "playlist_track" => {
+                    let track = ExportedTrack {
+                        uri,
+                        title,
+                        artists,
+                        album,
+                    };
+                    // Rows of a playlist follow each other
+                    match export.playlists.last_mut() {
+                        Some(last) if last.name == playlist => last.tracks.push(track),
+                        _ => export.playlists.push(ExportedPlaylist {
+                            uri: String::new(),
+                            name: playlist,
+                            tracks: vec![track],
+                        }),
+                    }
+                }
+                _ => return None,
+            }
+        }
+        Some(export)
+    }
+
+    // A file previously written by serialize, or None if it doesn't look like one
+    pub fn parse(text: &str, format: ExportFormat) -> Option<Self> {
+        match format {
+            ExportFormat::Json => serde_json::from_str(text).ok(),
+            ExportFormat::Csv => Self::from_csv(text),
+        }
+    }
+
+    pub fn serialize(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+            ExportFormat::Csv => self.to_csv(),
+        }
+    }
+}
+
+// All the songs of a paginated list
+async fn export_songs<F, Fut>(
+    mut get_page: F,
+    mut on_page: impl FnMut(usize, usize),
+) -> SpotifyResult<Vec<ExportedTrack>>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = SpotifyResult<SongBatch>>,
+{
+    let mut tracks = vec![];
+    let mut offset = 0;
+    loop {
+        // Pages can hold fewer songs than asked for (local files are left out), so we go by the pages' own offsets
+        let page = get_page(offset).await?;
+        let batch = page.batch;
+        tracks.extend(page.songs.into_iter().map(ExportedTrack::from));
+        on_page(
+            usize::min(batch.offset + batch.batch_size, batch.total),
+            batch.total,
+        );
+        match batch.next() {
+            Some(next) if next.offset > offset => offset = next.offset,
+            _ => return Ok(tracks),
+        }
+    }
+}
+
+// Fetches the whole library page by page, which can take a while for big ones
+pub async fn export_library(
+    api: Arc<dyn SpotifyApiClient + Send + Sync>,
+    on_progress: impl Fn(ExportProgress),
+) -> SpotifyResult<LibraryExport> {
+    let mut export = LibraryExport {
+        saved_tracks: export_songs(
+            |offset| api.get_saved_tracks(offset, PAGE_SIZE),
+            |done, total| on_progress(ExportProgress::SavedTracks { done, total }),
+        )
+        .await?,
+        ..Default::default()
+    };
+
+    loop {
+        let albums = api
+            .get_saved_albums(export.saved_albums.len(), PAGE_SIZE)
+            .await?;
+        let last_page = albums.len() < PAGE_SIZE;
+        export
+            .saved_albums
+            .extend(albums.into_iter().map(ExportedAlbum::from));
+        on_progress(ExportProgress::SavedAlbums {
+            done: export.saved_albums.len(),
+        });
+        if last_page {
+            break;
+        }
+    }
+
+    let mut playlists = vec![];
+    loop {
+        let page = api.get_saved_playlists(playlists.len(), PAGE_SIZE).await?;
+        let last_page = page.len() < PAGE_SIZE;
+        playlists.extend(page);
+        if last_page {
+            break;
+        }
+    }
+
+    let total = playlists.len();
+    for (i, playlist) in playlists.into_iter().enumerate() {
+        on_progress(ExportProgress::Playlists { done: i, total });
+        let tracks = export_songs(
+            |offset| api.get_playlist_tracks(&playlist.id, offset, PAGE_SIZE),
+            |_, _| {},
+        )
+        .await?;
+        export.playlists.push(ExportedPlaylist {
+            uri: format!("spotify:playlist:{}", playlist.id),
+            name: playlist.title,
+            tracks,
+        });
+    }
+    on_progress(ExportProgress::Playlists { done: total, total });
+
+    Ok(export)
+}
+
+fn uri_id<'a>(uri: &'a str, kind: &str) -> Option<&'a str> {
+    parse_spotify_uri(uri)
+        .filter(|(k, _)| *k == kind)
+        .map(|(_, id)| id)
+}
+
+// How far an import went, so that trying it again doesn't create the same playlists twice
+#[derive(Default)]
+pub struct ImportProgress {
+    track_pages: usize,
+    albums: usize,
+    playlists: usize,
+    // The playlist being filled, and how many pages of tracks it got so far
+    current_playlist: Option<(String, usize)>,
+}
+
+// Puts a backup back into the user's library: songs and albums are saved again, and playlists
+// are created anew (as copies, even for playlists that were only followed)
+// If it fails, calling it again with the same progress picks up where it stopped
+pub async fn import_library(
+    api: Arc<dyn SpotifyApiClient + Send + Sync>,
+    export: LibraryExport,
+    user_id: String,
+    progress: &Mutex<ImportProgress>,
+) -> SpotifyResult<()> {
+    let track_ids = export
+        .saved_tracks
+        .iter()
+        .filter_map(|track| uri_id(&track.uri, "track"))
+        .map(String::from)
+        .collect::<Vec<String>>();
+    let done = progress.lock().unwrap().track_pages;
+    for ids in track_ids.chunks(PAGE_SIZE).skip(done) {
+        api.save_tracks(ids.to_vec()).await?;
+        progress.lock().unwrap().track_pages += 1;
+    }
+
+    let done = progress.lock().unwrap().albums;
+    for album in export.saved_albums.iter().skip(done) {
+        if let Some(id) = uri_id(&album.uri, "album") {
+            api.save_album(id).await?;
+        }
+        progress.lock().unwrap().albums += 1;
+    }
+
+    let done = progress.lock().unwrap().playlists;
+    for playlist in export.playlists.into_iter().skip(done) {
+        let current = progress.lock().unwrap().current_playlist.clone();
+        let (id, added) = match current {
+            Some(current) => current,
+            None => {
+                let created = api.create_new_playlist(&playlist.name, &user_id).await?;
+                progress.lock().unwrap().current_playlist = Some((created.id.clone(), 0));
+                (created.id, 0)
+            }
+        };
+        // Local files can't be added through the API
+        let uris = playlist
+            .tracks
+            .into_iter()
+            .map(|track| track.uri)
+            .filter(|uri| uri_id(uri, "track").is_some() || uri_id(uri, "episode").is_some())
+            .collect::<Vec<String>>();
+        for uris in uris.chunks(PAGE_SIZE).skip(added) {
+            api.add_to_playlist(&id, uris.to_vec()).await?;
+            if let Some((_, added)) = progress.lock().unwrap().current_playlist.as_mut() {
+                *added += 1;
+            }
+        }
+        let mut progress = progress.lock().unwrap();
+        progress.current_playlist = None;
+        progress.playlists += 1;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::models::Batch;
+
+    #[test]
+    fn test_csv_escaping() {
+        let export = LibraryExport {
+            saved_tracks: vec![ExportedTrack {
+                uri: "spotify:track:1".to_string(),
+                title: "Hello, \"World\"".to_string(),
+                artists: "A, B".to_string(),
+                album: "Album".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            export.serialize(ExportFormat::Csv),
+            "type,playlist,uri,title,artists,album\n\
+             saved_track,,spotify:track:1,\"Hello, \"\"World\"\"\",\"A, B\",Album\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let track = |uri: &str| ExportedTrack {
+            uri: uri.to_string(),
+            title: "Hello, \"World\"".to_string(),
+            artists: "A, B".to_string(),
+            album: "Album".to_string(),
+        };
+        let export = LibraryExport {
+            saved_tracks: vec![track("spotify:track:1")],
+            saved_albums: vec![ExportedAlbum {
+                uri: "spotify:album:2".to_string(),
+                title: "Album".to_string(),
+                artists: "C".to_string(),
+            }],
+            playlists: vec![ExportedPlaylist {
+                uri: String::new(),
+                name: "Mix".to_string(),
+                tracks: vec![track("spotify:track:3"), track("spotify:track:4")],
+            }],
+        };
+
+        let csv = export.serialize(ExportFormat::Csv);
+        let parsed = LibraryExport::parse(&csv, ExportFormat::Csv).unwrap();
+        assert_eq!(parsed.serialize(ExportFormat::Csv), csv);
+        assert_eq!(parsed.playlists.len(), 1);
+        assert_eq!(parsed.playlists[0].tracks.len(), 2);
+    }
+
+    #[test]
+    fn test_export_songs_pages_by_offset() {
+        // Pages that come back without their songs, like when they only hold local files
+        let mut requested = vec![];
+        let tracks = async_std::task::block_on(export_songs(
+            |offset| {
+                requested.push(offset);
+                async move {
+                    Ok(SongBatch {
+                        songs: vec![],
+                        batch: Batch {
+                            offset,
+                            batch_size: 2,
+                            total: 5,
+                        },
+                    })
+                }
+            },
+            |_, _| {},
+        ))
+        .unwrap();
+
+        assert!(tracks.is_empty());
+        assert_eq!(requested, vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn test_parse_rejects_other_files() {
+        assert!(LibraryExport::parse("a,b\n1,2\n", ExportFormat::Csv).is_none());
+        assert!(LibraryExport::parse("[1, 2]", ExportFormat::Json).is_none());
+    }
+
+    #[test]
+    fn test_format_from_file_name() {
+        assert!(matches!(
+            ExportFormat::from_file_name("library.CSV"),
+            ExportFormat::Csv
+        ));
+        assert!(matches!(
+            ExportFormat::from_file_name("library.json"),
+            ExportFormat::Json
+        ));
+    }
+}
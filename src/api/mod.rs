@@ -3,12 +3,133 @@ mod cached_client;
 mod client;
 
 pub mod cache;
+pub mod library_export;
+pub mod oauth;
 
-pub use cached_client::{CachedSpotifyClient, SpotifyApiClient, SpotifyResult};
+pub use cached_client::{
+    CachedSpotifyClient, SpotifyApiClient, SpotifyResult, SAVED_TRACKS_MAX_IDS,
+};
 pub use client::SpotifyApiError;
 
+use isahc::config::Configurable;
+use isahc::HttpClient;
+use std::sync::RwLock;
+
+lazy_static! {
+    // Proxy URL for all HTTP requests, set once at startup from the preferences
+    static ref PROXY: RwLock<Option<String>> = RwLock::new(None);
+}
+
+pub fn set_proxy(proxy: Option<String>) {
+    *PROXY.write().unwrap() = proxy;
+}
+
+// All our HTTP clients should be configured the same way
+pub(crate) fn http_client() -> HttpClient {
+    let mut builder = HttpClient::builder();
+    if cfg!(debug_assertions) {
+        builder = builder.ssl_options(isahc::config::SslOption::DANGER_ACCEPT_INVALID_CERTS);
+    }
+    let proxy = PROXY
+        .read()
+        .unwrap()
+        .as_ref()
+        .and_then(|url| url.parse().ok());
+    if proxy.is_some() {
+        builder = builder.proxy(proxy);
+    }
+    builder.build().unwrap()
+}
+
+// What the cache is made of, as shown in the preferences
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheCategory {
+    Images,
+    // Metadata (albums, playlists...) fetched from the Web API
+    ApiResponses,
+    Audio,
+}
+
+impl CacheCategory {
+    pub const ALL: [CacheCategory; 3] = [Self::Images, Self::ApiResponses, Self::Audio];
+}
+
+// The caches we manage ourselves, librespot takes care of the audio one
+fn managed_cache(category: CacheCategory) -> Option<cache::CacheManager> {
+    match category {
+        CacheCategory::Images => cache::CacheManager::for_dir("spot/img"),
+        CacheCategory::ApiResponses => cache::CacheManager::for_account_dir("spot/net"),
+        CacheCategory::Audio => None,
+    }
+}
+
+fn managed_caches() -> Option<Vec<cache::CacheManager>> {
+    Some(vec![
+        managed_cache(CacheCategory::Images)?,
+        managed_cache(CacheCategory::ApiResponses)?,
+    ])
+}
+
+fn audio_cache_dir() -> async_std::path::PathBuf {
+    cache::librespot_cache_root().join("audio").into()
+}
+
+pub async fn cache_usage() -> Option<u64> {
+    let mut usage = 0;
+    for category in CacheCategory::ALL {
+        usage += cache_category_usage(category).await?;
+    }
+    Some(usage)
+}
+
+pub async fn cache_category_usage(category: CacheCategory) -> Option<u64> {
+    match category {
+        CacheCategory::Audio => Some(cache::dir_size(&audio_cache_dir()).await),
+        _ => managed_cache(category)?.disk_usage().await.ok(),
+    }
+}
+
+pub async fn trim_caches(max_size: u64) -> Option<()> {
+    cache::CacheManager::evict_lru(&managed_caches()?, max_size)
+        .await
+        .ok()
+        .map(|_| ())
+}
+
+// From then on, the caches are trimmed as they're written to
+pub fn limit_caches(max_size: Option<u64>) {
+    if let Some(caches) = managed_caches() {
+        cache::set_size_limit(max_size, caches);
+    }
+}
+
+pub async fn clear_caches() -> Option<()> {
+    for category in CacheCategory::ALL {
+        clear_cache_category(category).await?;
+    }
+    Some(())
+}
+
+pub async fn clear_cache_category(category: CacheCategory) -> Option<()> {
+    match category {
+        CacheCategory::Audio => {
+            let audio = audio_cache_dir();
+            if audio.exists().await {
+                async_std::fs::remove_dir_all(&audio).await.ok()?;
+                async_std::fs::create_dir_all(&audio).await.ok()?;
+            }
+            crate::app::offline_tracks::forget_all().await;
+            Some(())
+        }
+        _ => cache::CacheManager::evict_lru(&[managed_cache(category)?], 0)
+            .await
+            .ok()
+            .map(|_| ()),
+    }
+}
+
 pub async fn clear_user_cache() -> Option<()> {
-    cache::CacheManager::for_dir("spot/net")?
+    cache::CacheManager::for_account_dir("spot/net")?
         .clear_cache_pattern(&cached_client::USER_CACHE)
         .await
         .ok()
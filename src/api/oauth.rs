@@ -0,0 +1,228 @@
+use async_std::io::{ReadExt, WriteExt};
+use async_std::net::TcpListener;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use form_urlencoded::Serializer;
+use isahc::http::StatusCode;
+use isahc::{AsyncReadResponseExt, Request};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+
+use super::http_client;
+
+// The client id and redirect URI of Spotify's desktop client, which allow a loopback redirect
+const CLIENT_ID: &str = "65b708073fc0480ea92a077233ca87bd";
+const REDIRECT_ADDRESS: &str = "127.0.0.1:8898";
+const REDIRECT_URI: &str = "http://127.0.0.1:8898/login";
+// Long enough to type a password (and maybe a second factor) in the browser
+const REDIRECT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+const AUTHORIZE_URL: &str = "https://accounts.spotify.com/authorize";
+const TOKEN_URL: &str = "https://accounts.spotify.com/api/token";
+const ME_URL: &str = "https://api.spotify.com/v1/me";
+
+// Shown in the browser once we got what we needed
+const RESPONSE_PAGE: &str =
+    "<html><body><h1>Spot</h1><p>You can close this page and go back to Spot.</p></body></html>";
+const NOT_FOUND_RESPONSE: &str =
+    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
+#[derive(Error, Debug)]
+pub enum OAuthError {
+    #[error("Could not listen for the redirect: {0}")]
+    ListenError(std::io::Error),
+    #[error("Invalid redirect request")]
+    InvalidRedirect,
+    #[error("Timed out waiting for the redirect")]
+    TimedOut,
+    #[error("Authorization denied: {0}")]
+    Denied(String),
+    #[error("Request failed ({0})")]
+    BadStatus(u16),
+    #[error(transparent)]
+    ClientError(#[from] isahc::Error),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    ParseError(#[from] serde_json::Error),
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: u64,
+}
+
+#[derive(Deserialize)]
+struct Me {
+    id: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct OAuthToken {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expiry_time: SystemTime,
+}
+
+// A pending authorization: the user has to visit the URL, we then wait for the browser to be redirected to us
+pub struct OAuthFlow {
+    listener: TcpListener,
+    verifier: String,
+    state: String,
+    pub url: String,
+}
+
+impl OAuthFlow {
+    pub async fn start(scopes: &str) -> Result<Self, OAuthError> {
+        let listener = TcpListener::bind(REDIRECT_ADDRESS)
+            .await
+            .map_err(OAuthError::ListenError)?;
+
+        let verifier = random_string(64);
+        let state = random_string(16);
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+
+        let query = Serializer::new(String::new())
+            .append_pair("client_id", CLIENT_ID)
+            .append_pair("response_type", "code")
+            .append_pair("redirect_uri", REDIRECT_URI)
+            .append_pair("code_challenge_method", "S256")
+            .append_pair("code_challenge", &challenge)
+            .append_pair("state", &state)
+            .append_pair("scope", scopes)
+            .finish();
+
+        Ok(Self {
+            listener,
+            verifier,
+            state,
+            url: format!("{AUTHORIZE_URL}?{query}"),
+        })
+    }
+
+    pub async fn complete(self) -> Result<OAuthToken, OAuthError> {
+        let code = async_std::future::timeout(REDIRECT_TIMEOUT, self.receive_code())
+            .await
+            .map_err(|_| OAuthError::TimedOut)??;
+        let body = Serializer::new(String::new())
+            .append_pair("grant_type", "authorization_code")
+            .append_pair("code", &code)
+            .append_pair("redirect_uri", REDIRECT_URI)
+            .append_pair("client_id", CLIENT_ID)
+            .append_pair("code_verifier", &self.verifier)
+            .finish();
+        request_token(body).await
+    }
+
+    // Waits for the browser to hit our redirect URI, and extracts the authorization code from it.
+    // Other requests (preconnects, favicons, anything else hitting the port) are turned down.
+    async fn receive_code(&self) -> Result<String, OAuthError> {
+        loop {
+            let (mut stream, _) = self.listener.accept().await?;
+
+            let mut buffer = [0u8; 4096];
+            let Ok(len) = stream.read(&mut buffer).await else {
+                continue;
+            };
+            let request = String::from_utf8_lossy(&buffer[..len]);
+
+            let Some(result) = self.parse_redirect(&request) else {
+                let _ = stream.write_all(NOT_FOUND_RESPONSE.as_bytes()).await;
+                continue;
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                RESPONSE_PAGE.len(),
+                RESPONSE_PAGE
+            );
+            stream.write_all(response.as_bytes()).await?;
+            return result;
+        }
+    }
+
+    // The code (or why there's none), if that's the redirect for this flow
+    fn parse_redirect(&self, request: &str) -> Option<Result<String, OAuthError>> {
+        // ie GET /login?code=...&state=... HTTP/1.1
+        let (_, query) = request.lines().next()?.split(' ').nth(1)?.split_once('?')?;
+
+        let mut code = None;
+        let mut state = None;
+        let mut error = None;
+        for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+            match key.as_ref() {
+                "code" => code = Some(value.into_owned()),
+                "state" => state = Some(value.into_owned()),
+                "error" => error = Some(value.into_owned()),
+                _ => {}
+            }
+        }
+
+        if state.as_deref() != Some(&self.state) {
+            return None;
+        }
+        Some(match (code, error) {
+            (_, Some(error)) => Err(OAuthError::Denied(error)),
+            (Some(code), None) => Ok(code),
+            (None, None) => Err(OAuthError::InvalidRedirect),
+        })
+    }
+}
+
+pub async fn refresh_token(refresh_token: &str) -> Result<OAuthToken, OAuthError> {
+    let body = Serializer::new(String::new())
+        .append_pair("grant_type", "refresh_token")
+        .append_pair("refresh_token", refresh_token)
+        .append_pair("client_id", CLIENT_ID)
+        .finish();
+    let mut token = request_token(body).await?;
+    // The refresh token isn't always rotated
+    token
+        .refresh_token
+        .get_or_insert_with(|| refresh_token.to_string());
+    Ok(token)
+}
+
+// The username is needed for librespot, and isn't part of the token response
+pub async fn get_username(access_token: &str) -> Result<String, OAuthError> {
+    let request = Request::get(ME_URL)
+        .header("Authorization", format!("Bearer {access_token}"))
+        .body(())
+        .unwrap();
+    let mut response = http_client().send_async(request).await?;
+    if response.status() != StatusCode::OK {
+        return Err(OAuthError::BadStatus(response.status().as_u16()));
+    }
+    let me: Me = serde_json::from_str(&response.text().await?)?;
+    Ok(me.id)
+}
+
+async fn request_token(body: String) -> Result<OAuthToken, OAuthError> {
+    let request = Request::post(TOKEN_URL)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(body)
+        .unwrap();
+    let mut response = http_client().send_async(request).await?;
+    if response.status() != StatusCode::OK {
+        return Err(OAuthError::BadStatus(response.status().as_u16()));
+    }
+    let token: TokenResponse = serde_json::from_str(&response.text().await?)?;
+    Ok(OAuthToken {
+        access_token: token.access_token,
+        refresh_token: token.refresh_token,
+        expiry_time: SystemTime::now() + Duration::from_secs(token.expires_in),
+    })
+}
+
+fn random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
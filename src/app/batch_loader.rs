@@ -17,6 +17,7 @@ pub enum SongsSource {
     Playlist(String),
     Album(String),
     SavedTracks,
+    SavedEpisodes,
 }
 
 impl PartialEq for SongsSource {
@@ -25,6 +26,7 @@ impl PartialEq for SongsSource {
             (Self::Playlist(l), Self::Playlist(r)) => l == r,
             (Self::Album(l), Self::Album(r)) => l == r,
             (Self::SavedTracks, Self::SavedTracks) => true,
+            (Self::SavedEpisodes, Self::SavedEpisodes) => true,
             _ => false,
         }
     }
@@ -78,22 +80,58 @@ impl BatchLoader {
     where
         ActionCreator: FnOnce(SongsSource, SongBatch) -> AppAction,
     {
+        match self.fetch(&query).await {
+            Ok(batch) => Some(create_action(query.source, batch)),
+            Err(err) => Self::error_action(err),
+        }
+    }
+
+    // Query a batch and every one after it, for when a source is needed in full
+    // Nothing gets created unless all of them could be retrieved, the error is what we get instead
+    pub async fn query_all<ActionCreator>(
+        &self,
+        query: BatchQuery,
+        create_action: ActionCreator,
+    ) -> Result<Vec<AppAction>, Option<AppAction>>
+    where
+        ActionCreator: Fn(SongsSource, SongBatch) -> AppAction,
+    {
+        let mut actions = vec![];
+        let mut next_query = Some(query);
+        while let Some(query) = next_query {
+            let batch = self.fetch(&query).await.map_err(Self::error_action)?;
+            // The total might have changed since the first batch was loaded
+            next_query = BatchQuery {
+                source: query.source.clone(),
+                batch: batch.batch,
+            }
+            .next();
+            actions.push(create_action(query.source, batch));
+        }
+        Ok(actions)
+    }
+
+    async fn fetch(&self, query: &BatchQuery) -> Result<SongBatch, SpotifyApiError> {
         let api = Arc::clone(&self.api);
 
         let Batch {
             offset, batch_size, ..
         } = query.batch;
-        let result = match &query.source {
+        match &query.source {
             SongsSource::Playlist(id) => api.get_playlist_tracks(id, offset, batch_size).await,
             SongsSource::SavedTracks => api.get_saved_tracks(offset, batch_size).await,
+            SongsSource::SavedEpisodes => api.get_saved_episodes(offset, batch_size).await,
             SongsSource::Album(id) => api.get_album_tracks(id, offset, batch_size).await,
-        };
+        }
+    }
 
-        match result {
-            Ok(batch) => Some(create_action(query.source, batch)),
+    fn error_action(err: SpotifyApiError) -> Option<AppAction> {
+        match err {
             // No token? Why was the batch loader called? Ah, whatever
-            Err(SpotifyApiError::NoToken) => None,
-            Err(err) => {
+            SpotifyApiError::NoToken => None,
+            // Whatever was cached is all there is
+            SpotifyApiError::Offline | SpotifyApiError::NoConnection => None,
+            err => {
                 error!("Spotify API error: {}", err);
                 Some(AppAction::ShowNotification(gettext(
                     // translators: This notification is the default message for unhandled errors. Logs refer to console output.
@@ -1,6 +1,6 @@
-use crate::app::components::display_add_css_provider;
+use crate::app::components::{display_add_css_provider, labels};
 use crate::app::dispatch::Worker;
-use crate::app::loader::ImageLoader;
+use crate::app::loader::PendingImage;
 use crate::app::models::AlbumModel;
 
 use gtk::prelude::*;
@@ -29,6 +29,8 @@ mod imp {
 
         #[template_child]
         pub cover_image: TemplateChild<gtk::Image>,
+
+        pub cover: PendingImage,
     }
 
     #[glib::object_subclass]
@@ -46,7 +48,11 @@ mod imp {
         }
     }
 
-    impl ObjectImpl for AlbumWidget {}
+    impl ObjectImpl for AlbumWidget {
+        fn dispose(&self) {
+            self.cover.cancel();
+        }
+    }
     impl WidgetImpl for AlbumWidget {}
     impl BinImpl for AlbumWidget {}
 }
@@ -71,26 +77,23 @@ impl AlbumWidget {
         self.add_css_class("container--loaded");
     }
 
-    fn set_image(&self, pixbuf: Option<&gdk_pixbuf::Pixbuf>) {
-        self.imp().cover_image.set_from_pixbuf(pixbuf);
+    fn set_image(&self, texture: &gdk::Texture) {
+        self.imp().cover_image.set_from_paintable(Some(texture));
     }
 
     fn bind(&self, album_model: &AlbumModel, worker: Worker) {
         let widget = self.imp();
         widget.cover_image.set_overflow(gtk::Overflow::Hidden);
 
+        // The card and its icon stand in for the cover until it's loaded
+        self.set_loaded();
         if let Some(cover_art) = album_model.cover() {
             let _self = self.downgrade();
-            worker.send_local_task(async move {
-                if let Some(_self) = _self.upgrade() {
-                    let loader = ImageLoader::new();
-                    let result = loader.load_remote(&cover_art, "jpg", 200, 200).await;
-                    _self.set_image(result.as_ref());
-                    _self.set_loaded();
+            widget.cover.load(&worker, cover_art, 200, move |texture| {
+                if let (Some(_self), Some(texture)) = (_self.upgrade(), texture) {
+                    _self.set_image(&texture);
                 }
             });
-        } else {
-            self.set_loaded();
         }
 
         album_model
@@ -103,13 +106,11 @@ impl AlbumWidget {
             .flags(glib::BindingFlags::DEFAULT | glib::BindingFlags::SYNC_CREATE)
             .build();
 
-        if album_model.year() > 0 {
-            album_model
-                .bind_property("year", &*widget.year_label, "label")
-                .flags(glib::BindingFlags::DEFAULT | glib::BindingFlags::SYNC_CREATE)
-                .build();
-        } else {
-            widget.year_label.set_visible(false);
+        match album_model.release_date() {
+            Some(date) => widget
+                .year_label
+                .set_label(&labels::release_date_label(date)),
+            None => widget.year_label.set_visible(false),
         }
     }
 
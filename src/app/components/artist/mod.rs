@@ -74,8 +74,7 @@ impl ArtistWidget {
             worker.send_local_task(async move {
                 if let Some(avatar) = avatar.upgrade() {
                     let loader = ImageLoader::new();
-                    let pixbuf = loader.load_remote(&url, "jpg", 200, 200).await;
-                    let texture = pixbuf.as_ref().map(gdk::Texture::for_pixbuf);
+                    let texture = loader.load_texture(&url, 200).await;
                     avatar.set_custom_image(texture.as_ref());
                 }
             });
@@ -6,6 +6,7 @@ use std::rc::Rc;
 use crate::app::components::{
     display_add_css_provider, AlbumWidget, Component, EventListener, Playlist,
 };
+use crate::app::state::{NetworkEvent, ReleaseSort};
 use crate::app::{models::*, ListStore};
 use crate::app::{AppEvent, BrowserEvent, Worker};
 
@@ -24,8 +25,20 @@ mod imp {
         #[template_child]
         pub top_tracks: TemplateChild<gtk::ListView>,
 
+        #[template_child]
+        pub radio_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub shuffle_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub share_button: TemplateChild<gtk::Button>,
+
         #[template_child]
         pub artist_releases: TemplateChild<gtk::FlowBox>,
+
+        #[template_child]
+        pub releases_sort: TemplateChild<gtk::DropDown>,
     }
 
     #[glib::object_subclass]
@@ -62,6 +75,55 @@ impl ArtistDetailsWidget {
         self.imp().top_tracks.as_ref()
     }
 
+    fn set_share_uri(&self, uri: &str) {
+        self.imp()
+            .share_button
+            .set_action_target_value(Some(&uri.to_variant()));
+    }
+
+    fn connect_shuffle_clicked<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().shuffle_button.connect_clicked(move |_| f());
+    }
+
+    fn connect_radio_clicked<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().radio_button.connect_clicked(move |_| f());
+    }
+
+    // In the same order as the options of the drop down
+    const RELEASE_SORTS: [ReleaseSort; 4] = [
+        ReleaseSort::Default,
+        ReleaseSort::NewestFirst,
+        ReleaseSort::OldestFirst,
+        ReleaseSort::MostPopular,
+    ];
+
+    fn set_release_sort(&self, sort: ReleaseSort) {
+        let position = Self::RELEASE_SORTS
+            .iter()
+            .position(|s| *s == sort)
+            .unwrap_or_default();
+        self.imp().releases_sort.set_selected(position as u32);
+    }
+
+    fn connect_release_sort_changed<F>(&self, f: F)
+    where
+        F: Fn(ReleaseSort) + 'static,
+    {
+        self.imp()
+            .releases_sort
+            .connect_selected_notify(move |dropdown| {
+                if let Some(sort) = Self::RELEASE_SORTS.get(dropdown.selected() as usize) {
+                    f(*sort);
+                }
+            });
+    }
+
     fn set_loaded(&self) {
         self.add_css_class("artist__loaded");
     }
@@ -114,11 +176,27 @@ impl ArtistDetails {
         model.load_artist_details(model.id.clone());
 
         let widget = ArtistDetailsWidget::new();
+        widget.set_share_uri(&format!("spotify:artist:{}", model.id));
 
         widget.connect_bottom_edge(clone!(@weak model => move || {
             model.load_more();
         }));
 
+        widget.connect_shuffle_clicked(clone!(@weak model => move || {
+            model.shuffle_play_top_tracks();
+        }));
+
+        widget.connect_radio_clicked(clone!(@weak model => move || {
+            model.play_radio();
+        }));
+
+        if let Some(sort) = model.release_sort() {
+            widget.set_release_sort(sort);
+        }
+        widget.connect_release_sort_changed(clone!(@weak model => move |sort| {
+            model.set_release_sort(sort);
+        }));
+
         if let Some(store) = model.get_list_store() {
             widget.bind_artist_releases(
                 worker.clone(),
@@ -161,6 +239,11 @@ impl EventListener for ArtistDetails {
             {
                 self.widget.set_loaded();
             }
+            AppEvent::NetworkEvent(NetworkEvent::BackOnline)
+                if self.model.get_artist_name().is_none() =>
+            {
+                self.model.load_artist_details(self.model.id.clone());
+            }
             _ => {}
         }
         self.broadcast_event(event);
@@ -5,12 +5,12 @@ use std::rc::Rc;
 
 use crate::api::SpotifyApiError;
 use crate::app::components::SimpleHeaderBarModel;
-use crate::app::components::{labels, PlaylistModel};
+use crate::app::components::{labels, play_radio_actions, PlaylistModel};
 use crate::app::models::*;
-use crate::app::state::SelectionContext;
 use crate::app::state::{
     BrowserAction, BrowserEvent, PlaybackAction, SelectionAction, SelectionState,
 };
+use crate::app::state::{ReleaseSort, SelectionContext};
 use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel, ListStore};
 
 pub struct ArtistDetailsModel {
@@ -54,6 +54,49 @@ impl ArtistDetailsModel {
             });
     }
 
+    pub fn release_sort(&self) -> Option<ReleaseSort> {
+        Some(
+            self.app_model
+                .get_state()
+                .browser
+                .artist_state(&self.id)?
+                .release_sort,
+        )
+    }
+
+    pub fn set_release_sort(&self, sort: ReleaseSort) {
+        self.dispatcher
+            .dispatch(BrowserAction::SetArtistReleaseSort(self.id.clone(), sort).into());
+        if sort != ReleaseSort::Default {
+            self.load_all_releases();
+        }
+    }
+
+    // Sorting takes all of them, the remaining pages are added together
+    fn load_all_releases(&self) -> Option<()> {
+        let api = self.app_model.get_spotify();
+        let state = self.app_model.get_state();
+        let next_page = &state.browser.artist_state(&self.id)?.next_page;
+
+        let id = next_page.data.clone();
+        let batch_size = next_page.batch_size;
+        let offset = next_page.next_offset?;
+
+        self.dispatcher
+            .call_spotify_and_dispatch_many(move || async move {
+                let mut actions = vec![];
+                let mut next_offset = Some(offset);
+                while let Some(offset) = next_offset {
+                    let albums = api.get_artist_albums(&id, offset, batch_size).await?;
+                    next_offset = Some(offset + batch_size).filter(|_| albums.len() >= batch_size);
+                    actions.push(BrowserAction::AppendArtistReleases(id.clone(), albums).into());
+                }
+                Ok(actions)
+            });
+
+        Some(())
+    }
+
     pub fn open_album(&self, id: String) {
         self.dispatcher.dispatch(AppAction::ViewAlbum(id));
     }
@@ -76,6 +119,38 @@ impl ArtistDetailsModel {
 
         Some(())
     }
+
+    // Top tracks all come at once, so there's nothing more to load
+    pub fn shuffle_play_top_tracks(&self) {
+        let tracks: Vec<SongDescription> = self.song_list_model().collect();
+        if tracks.is_empty() {
+            return;
+        }
+        self.dispatcher.dispatch_many(vec![
+            PlaybackAction::LoadSongs(tracks, 0).into(),
+            PlaybackAction::PlayShuffled.into(),
+        ]);
+    }
+
+    // Spotify takes up to 5 seeds: the artist, and as many of their top tracks as possible
+    // Unscoped, so that the radio still starts if the user leaves the page in the meantime
+    pub fn play_radio(&self) {
+        let api = self.app_model.get_spotify();
+        let id = self.id.clone();
+        let top_tracks = self
+            .song_list_model()
+            .map_collect(|song| song.id)
+            .into_iter()
+            .take(4)
+            .collect::<Vec<String>>();
+
+        self.dispatcher
+            .unscoped()
+            .call_spotify_and_dispatch_many(move || async move {
+                let songs = api.get_recommendations(vec![id], top_tracks).await?;
+                Ok(play_radio_actions(songs))
+            });
+    }
 }
 
 impl PlaylistModel for ArtistDetailsModel {
@@ -100,22 +175,43 @@ impl PlaylistModel for ArtistDetailsModel {
     fn play_song_at(&self, _pos: usize, id: &str) {
         let tracks: Vec<SongDescription> = self.song_list_model().collect();
         self.dispatcher
-            .dispatch(PlaybackAction::LoadSongs(tracks).into());
+            .dispatch(PlaybackAction::LoadSongs(tracks, 0).into());
         self.dispatcher
             .dispatch(PlaybackAction::Load(id.to_string()).into());
     }
 
+    fn autoscroll_to_playing(&self) -> bool {
+        self.app_model
+            .get_state()
+            .settings
+            .settings
+            .autoscroll
+            .artists
+    }
+
     fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
         let song = self.song_list_model().get(id)?;
         let song = song.description();
 
         let group = SimpleActionGroup::new();
 
+        group.add_action(&song.make_play_from_here_action(
+            self.song_list_model(),
+            None,
+            self.dispatcher.box_clone(),
+            None,
+        ));
+
         for view_artist in song.make_artist_actions(self.dispatcher.box_clone(), None) {
             group.add_action(&view_artist);
         }
         group.add_action(&song.make_album_action(self.dispatcher.box_clone(), None));
         group.add_action(&song.make_link_action(None));
+        group.add_action(&song.make_radio_action(
+            self.app_model.get_spotify(),
+            self.dispatcher.unscoped(),
+            None,
+        ));
         group.add_action(&song.make_queue_action(self.dispatcher.box_clone(), None));
 
         Some(group.upcast())
@@ -126,15 +222,23 @@ impl PlaylistModel for ArtistDetailsModel {
         let song = song.description();
 
         let menu = gio::Menu::new();
+        menu.append(Some(&*labels::PLAY_FROM_HERE), Some("song.play_from_here"));
         menu.append(Some(&*labels::VIEW_ALBUM), Some("song.view_album"));
-        for artist in song.artists.iter().filter(|a| self.id != a.id) {
+        for artist in song.artists.iter().filter(|a| self.id != *a.id) {
             menu.append(
                 Some(&labels::more_from_label(&artist.name)),
                 Some(&format!("song.view_artist_{}", artist.id)),
             );
         }
 
+        if !song.is_episode() {
+            menu.append(Some(&*labels::SONG_RADIO), Some("song.radio"));
+        }
         menu.append(Some(&*labels::COPY_LINK), Some("song.copy_link"));
+        menu.append(
+            Some(&*labels::SHARE),
+            Some(&format!("app.share::{}", song.uri)),
+        );
         menu.append(Some(&*labels::ADD_TO_QUEUE), Some("song.queue"));
         Some(menu.upcast())
     }
@@ -147,6 +251,11 @@ impl PlaylistModel for ArtistDetailsModel {
         }
     }
 
+    fn select_songs(&self, songs: Vec<SongDescription>) {
+        self.dispatcher
+            .dispatch(SelectionAction::Select(songs).into());
+    }
+
     fn deselect_song(&self, id: &str) {
         self.dispatcher
             .dispatch(SelectionAction::Deselect(vec![id.to_string()]).into());
@@ -154,12 +263,18 @@ impl PlaylistModel for ArtistDetailsModel {
 
     fn enable_selection(&self) -> bool {
         self.dispatcher
-            .dispatch(AppAction::EnableSelection(SelectionContext::Default));
+            .dispatch(AppAction::EnableSelection(SelectionContext::ArtistDetails(
+                self.id.clone(),
+            )));
         true
     }
 
     fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
-        Some(Box::new(self.app_model.map_state(|s| &s.selection)))
+        let context = SelectionContext::ArtistDetails(self.id.clone());
+        let selection = self
+            .app_model
+            .map_state_opt(|s| Some(&s.selection).filter(|s| s.context == context))?;
+        Some(Box::new(selection))
     }
 }
 
@@ -176,7 +291,7 @@ impl SimpleHeaderBarModel for ArtistDetailsModel {
     }
 
     fn selection_context(&self) -> Option<SelectionContext> {
-        Some(SelectionContext::Default)
+        Some(SelectionContext::ArtistDetails(self.id.clone()))
     }
 
     fn select_all(&self) {
@@ -0,0 +1,145 @@
+use std::rc::Rc;
+
+use glib::Cast;
+
+use crate::app::components::{Component, EventListener};
+use crate::app::state::{PlaybackAction, PlaybackEvent, SettingsAction, SettingsEvent};
+use crate::app::{ActionDispatcher, AppEvent, AppModel, Worker};
+
+use super::widget::CarModeWidget;
+
+// Names of the pages of the stack holding the two layouts, see window.blp
+const REGULAR_LAYOUT: &str = "regular";
+const CAR_LAYOUT: &str = "car";
+
+pub struct CarModeModel {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl CarModeModel {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    fn is_car_mode(&self) -> bool {
+        self.app_model.get_state().settings.settings.car_mode
+    }
+
+    fn is_playing(&self) -> bool {
+        self.app_model.get_state().playback.is_playing()
+    }
+
+    fn toggle_playback(&self) {
+        self.dispatcher.dispatch(PlaybackAction::TogglePlay.into());
+    }
+
+    fn play_next_song(&self) {
+        self.dispatcher.dispatch(PlaybackAction::Next.into());
+    }
+
+    fn play_prev_song(&self) {
+        self.dispatcher.dispatch(PlaybackAction::Previous.into());
+    }
+
+    fn leave(&self) {
+        crate::settings::save_car_mode(false);
+        self.dispatcher
+            .dispatch(SettingsAction::SetCarMode(false).into());
+    }
+}
+
+// A simplified layout with huge controls, swapped with the regular one when car mode is on
+pub struct CarModePanel {
+    widget: CarModeWidget,
+    layouts: gtk::Stack,
+    model: Rc<CarModeModel>,
+    worker: Worker,
+}
+
+impl CarModePanel {
+    pub fn new(
+        widget: CarModeWidget,
+        layouts: gtk::Stack,
+        model: CarModeModel,
+        worker: Worker,
+    ) -> Self {
+        let model = Rc::new(model);
+
+        widget.connect_play_pause(clone!(@weak model => move || model.toggle_playback()));
+        widget.connect_next(clone!(@weak model => move || model.play_next_song()));
+        widget.connect_prev(clone!(@weak model => move || model.play_prev_song()));
+        widget.connect_exit(clone!(@weak model => move || model.leave()));
+
+        Self {
+            widget,
+            layouts,
+            model,
+            worker,
+        }
+    }
+
+    fn update_layout(&self) {
+        let layout = if self.model.is_car_mode() {
+            CAR_LAYOUT
+        } else {
+            REGULAR_LAYOUT
+        };
+        self.layouts.set_visible_child_name(layout);
+    }
+
+    fn update_current_info(&self) {
+        let song = self.model.app_model.get_state().playback.current_song();
+        match song {
+            Some(song) => {
+                self.widget.set_song(&song.title, &song.artists_name());
+                match song.art {
+                    Some(url) => self
+                        .widget
+                        .set_artwork_from_url(url.to_string(), &self.worker),
+                    None => self.widget.reset_artwork(),
+                }
+            }
+            None => self.widget.reset_song(),
+        }
+    }
+
+    fn update_playing(&self) {
+        self.widget.set_playing(self.model.is_playing());
+    }
+}
+
+impl Component for CarModePanel {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        self.widget.upcast_ref()
+    }
+}
+
+impl EventListener for CarModePanel {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started => {
+                self.update_layout();
+                self.update_current_info();
+                self.update_playing();
+            }
+            AppEvent::SettingsEvent(
+                SettingsEvent::CarModeChanged(_) | SettingsEvent::SettingsChanged,
+            ) => self.update_layout(),
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) => {
+                self.update_current_info();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackStopped) => {
+                self.update_current_info();
+                self.update_playing();
+            }
+            AppEvent::PlaybackEvent(
+                PlaybackEvent::PlaybackPaused | PlaybackEvent::PlaybackResumed,
+            ) => self.update_playing(),
+            _ => {}
+        }
+    }
+}
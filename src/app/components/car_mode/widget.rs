@@ -0,0 +1,173 @@
+use gettextrs::gettext;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use std::cell::RefCell;
+
+use crate::app::components::display_add_css_provider;
+use crate::app::loader::ImageLoader;
+use crate::app::Worker;
+
+// How fast a swipe must be to skip a track, in pixels per second
+const SWIPE_VELOCITY: f64 = 800.0;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/dev/alextren/Spot/components/car_mode.ui")]
+    pub struct CarModeWidget {
+        #[template_child]
+        pub exit_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub art: TemplateChild<gtk::Image>,
+
+        #[template_child]
+        pub title: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub artist: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub prev_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub play_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub play_icon: TemplateChild<gtk::Image>,
+
+        #[template_child]
+        pub next_button: TemplateChild<gtk::Button>,
+
+        // The artwork being shown or loaded, a slow one mustn't replace the next
+        pub art_url: RefCell<Option<String>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for CarModeWidget {
+        const NAME: &'static str = "CarModeWidget";
+        type Type = super::CarModeWidget;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            display_add_css_provider(resource!("/components/car_mode.css"));
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for CarModeWidget {}
+    impl WidgetImpl for CarModeWidget {}
+    impl BoxImpl for CarModeWidget {}
+}
+
+glib::wrapper! {
+    pub struct CarModeWidget(ObjectSubclass<imp::CarModeWidget>) @extends gtk::Widget, gtk::Box;
+}
+
+impl CarModeWidget {
+    pub fn set_song(&self, title: &str, artist: &str) {
+        let widget = self.imp();
+        widget.title.set_text(title);
+        widget.artist.set_text(artist);
+    }
+
+    pub fn reset_song(&self) {
+        let widget = self.imp();
+        widget.title.set_text("");
+        widget.artist.set_text("");
+        self.reset_artwork();
+    }
+
+    pub fn reset_artwork(&self) {
+        let widget = self.imp();
+        widget.art_url.replace(None);
+        widget.art.set_icon_name(Some("emblem-music-symbolic"));
+    }
+
+    pub fn set_artwork_from_url(&self, url: String, worker: &Worker) {
+        self.imp().art_url.replace(Some(url.clone()));
+        let weak_self = self.downgrade();
+        worker.send_local_task(async move {
+            let loader = ImageLoader::new();
+            let result = loader.load_texture(&url, 320).await;
+            if let (Some(_self), Some(texture)) = (weak_self.upgrade(), result) {
+                let widget = _self.imp();
+                if widget.art_url.borrow().as_deref() == Some(url.as_str()) {
+                    widget.art.set_from_paintable(Some(&texture));
+                }
+            }
+        });
+    }
+
+    pub fn set_playing(&self, is_playing: bool) {
+        let widget = self.imp();
+        let (icon, tooltip) = if is_playing {
+            ("media-playback-pause-symbolic", gettext("Pause"))
+        } else {
+            ("media-playback-start-symbolic", gettext("Play"))
+        };
+        widget.play_icon.set_icon_name(Some(icon));
+        widget.play_button.set_tooltip_text(Some(&tooltip));
+    }
+
+    pub fn connect_exit<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().exit_button.connect_clicked(move |_| f());
+    }
+
+    pub fn connect_play_pause<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().play_button.connect_clicked(move |_| f());
+    }
+
+    pub fn connect_prev<F>(&self, f: F)
+    where
+        F: Fn() + Clone + 'static,
+    {
+        let prev = f.clone();
+        self.imp().prev_button.connect_clicked(move |_| prev());
+        self.connect_swipe(move |forward| {
+            if !forward {
+                f()
+            }
+        });
+    }
+
+    pub fn connect_next<F>(&self, f: F)
+    where
+        F: Fn() + Clone + 'static,
+    {
+        let next = f.clone();
+        self.imp().next_button.connect_clicked(move |_| next());
+        self.connect_swipe(move |forward| {
+            if forward {
+                f()
+            }
+        });
+    }
+
+    // Swiping to the left goes forward, like turning a page
+    fn connect_swipe<F>(&self, f: F)
+    where
+        F: Fn(bool) + 'static,
+    {
+        let swipe = gtk::GestureSwipe::new();
+        swipe.connect_swipe(move |_, velocity_x, velocity_y| {
+            if velocity_x.abs() > SWIPE_VELOCITY && velocity_x.abs() > velocity_y.abs() {
+                f(velocity_x < 0.0);
+            }
+        });
+        self.add_controller(swipe);
+    }
+}
@@ -0,0 +1,97 @@
+use gettextrs::*;
+use gio::prelude::*;
+use gtk::prelude::*;
+use std::rc::Rc;
+
+use crate::app::components::{labels, EventListener};
+use crate::app::loader::ImageLoader;
+use crate::app::models::SongDescription;
+use crate::app::state::PlaybackEvent;
+use crate::app::{AppEvent, AppModel, Worker};
+use crate::settings::NotificationDetail;
+
+// Using the same id every time means a new notification replaces the previous one
+const NOTIFICATION_ID: &str = "track-changed";
+
+// A component that sends a desktop notification when the track changes while Spot is in the background
+pub struct DesktopNotifier {
+    app_model: Rc<AppModel>,
+    worker: Worker,
+}
+
+impl DesktopNotifier {
+    pub fn new(app_model: Rc<AppModel>, worker: Worker) -> Self {
+        Self { app_model, worker }
+    }
+
+    // None when notifications are disabled
+    fn detail(&self) -> Option<NotificationDetail> {
+        let state = self.app_model.get_state();
+        let settings = &state.settings.settings;
+        Some(settings.notification_detail).filter(|_| settings.track_change_notifications)
+    }
+
+    fn is_window_focused(app: &gio::Application) -> bool {
+        app.downcast_ref::<gtk::Application>()
+            .and_then(|app| app.active_window())
+            .map(|window| window.is_active())
+            .unwrap_or(false)
+    }
+
+    fn make_notification(song: &SongDescription, detail: NotificationDetail) -> gio::Notification {
+        let notification = gio::Notification::new(&song.title);
+        if detail == NotificationDetail::Title {
+            notification.set_body(Some(&song.artists_name()));
+        } else {
+            let body = labels::track_notification_body(&song.artists_name(), &song.album.name);
+            notification.set_body(Some(&body));
+        }
+        if detail == NotificationDetail::Controls {
+            // translators: This is a button in the notification shown when the track changes
+            notification.add_button(&gettext("Previous"), "app.player_prev");
+            // translators: This is a button in the notification shown when the track changes
+            notification.add_button(&gettext("Pause"), "app.toggle_playback");
+            // translators: This is a button in the notification shown when the track changes
+            notification.add_button(&gettext("Next"), "app.player_next");
+        }
+        notification
+    }
+
+    fn notify_track_changed(&self, detail: NotificationDetail) {
+        let Some(app) = gio::Application::default() else {
+            return;
+        };
+        if Self::is_window_focused(&app) {
+            return;
+        }
+        let Some(song) = self.app_model.get_state().playback.current_song() else {
+            return;
+        };
+
+        let notification = Self::make_notification(&song, detail);
+        // The cover was most likely cached already to be shown in the window
+        let art = song.art.filter(|_| detail >= NotificationDetail::Cover);
+        if let Some(url) = art {
+            self.worker.send_local_task(async move {
+                let file = ImageLoader::new().load_remote_to_file(&url, "jpg").await;
+                if let Some(path) = file {
+                    let icon = gio::FileIcon::new(&gio::File::for_path(path));
+                    notification.set_icon(&icon);
+                }
+                app.send_notification(Some(NOTIFICATION_ID), &notification);
+            });
+        } else {
+            app.send_notification(Some(NOTIFICATION_ID), &notification);
+        }
+    }
+}
+
+impl EventListener for DesktopNotifier {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) = event {
+            if let Some(detail) = self.detail() {
+                self.notify_track_changed(detail);
+            }
+        }
+    }
+}
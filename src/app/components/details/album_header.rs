@@ -1,4 +1,5 @@
-use crate::app::components::display_add_css_provider;
+use crate::app::components::{display_add_css_provider, labels};
+use crate::app::models::ReleaseDate;
 use gettextrs::gettext;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
@@ -29,9 +30,18 @@ mod imp {
         #[template_child]
         pub play_button: TemplateChild<gtk::Button>,
 
+        #[template_child]
+        pub shuffle_button: TemplateChild<gtk::Button>,
+
         #[template_child]
         pub info_button: TemplateChild<gtk::Button>,
 
+        #[template_child]
+        pub share_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub pin_button: TemplateChild<gtk::ToggleButton>,
+
         #[template_child]
         pub album_info: TemplateChild<gtk::Box>,
 
@@ -82,6 +92,13 @@ impl AlbumHeaderWidget {
         self.imp().play_button.connect_clicked(move |_| f());
     }
 
+    pub fn connect_shuffle<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().shuffle_button.connect_clicked(move |_| f());
+    }
+
     pub fn connect_liked<F>(&self, f: F)
     where
         F: Fn() + 'static,
@@ -89,6 +106,13 @@ impl AlbumHeaderWidget {
         self.imp().like_button.connect_clicked(move |_| f());
     }
 
+    pub fn connect_pin<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().pin_button.connect_clicked(move |_| f());
+    }
+
     pub fn connect_info<F>(&self, f: F)
     where
         F: Fn() + 'static,
@@ -106,6 +130,12 @@ impl AlbumHeaderWidget {
         });
     }
 
+    pub fn set_share_uri(&self, uri: &str) {
+        self.imp()
+            .share_button
+            .set_action_target_value(Some(&uri.to_variant()));
+    }
+
     pub fn set_liked(&self, is_liked: bool) {
         self.imp().like_button.set_icon_name(if is_liked {
             "starred-symbolic"
@@ -114,6 +144,20 @@ impl AlbumHeaderWidget {
         });
     }
 
+    pub fn set_like_visible(&self, visible: bool) {
+        self.imp().like_button.set_visible(visible);
+    }
+
+    pub fn set_pinned(&self, is_pinned: bool) {
+        let pin_button = &self.imp().pin_button;
+        pin_button.set_active(is_pinned);
+        pin_button.set_tooltip_text(Some(if is_pinned {
+            labels::UNPIN_FROM_SIDEBAR.as_str()
+        } else {
+            labels::PIN_TO_SIDEBAR.as_str()
+        }));
+    }
+
     pub fn set_playing(&self, is_playing: bool) {
         let playback_icon = if is_playing {
             "media-playback-pause-symbolic"
@@ -132,16 +176,23 @@ impl AlbumHeaderWidget {
         self.imp().play_button.set_tooltip_text(tooltip_text);
     }
 
-    pub fn set_artwork(&self, art: &gdk_pixbuf::Pixbuf) {
-        self.imp().album_art.set_from_pixbuf(Some(art));
+    pub fn set_artwork(&self, art: &gdk::Texture) {
+        self.imp().album_art.set_from_paintable(Some(art));
     }
 
-    pub fn set_album_and_artist_and_year(&self, album: &str, artist: &str, year: Option<u32>) {
+    pub fn set_album_and_artist_and_release_date(
+        &self,
+        album: &str,
+        artist: &str,
+        release_date: Option<ReleaseDate>,
+    ) {
         let widget = self.imp();
         widget.album_label.set_label(album);
         widget.artist_button_label.set_label(artist);
-        match year {
-            Some(year) => widget.year_label.set_label(&year.to_string()),
+        match release_date {
+            Some(date) => widget
+                .year_label
+                .set_label(&labels::release_date_label(date)),
             None => widget.year_label.set_visible(false),
         }
     }
@@ -8,11 +8,13 @@ use super::release_details::ReleaseDetailsWindow;
 use super::DetailsModel;
 
 use crate::app::components::{
-    Component, EventListener, HeaderBarComponent, HeaderBarWidget, Playlist, ScrollingHeaderWidget,
+    labels, Component, EventListener, HeaderBarComponent, HeaderBarWidget, Playlist,
+    ScrollingHeaderWidget,
 };
 use crate::app::dispatch::Worker;
 use crate::app::loader::ImageLoader;
-use crate::app::state::PlaybackEvent;
+use crate::app::models::ReleaseDate;
+use crate::app::state::{NetworkEvent, PlaybackEvent, SettingsEvent};
 use crate::app::{AppEvent, BrowserEvent};
 
 mod imp {
@@ -124,6 +126,14 @@ impl AlbumDetailsWidget {
         self.imp().header_mobile.connect_liked(f);
     }
 
+    fn connect_pin<F>(&self, f: F)
+    where
+        F: Fn() + Clone + 'static,
+    {
+        self.imp().header_widget.connect_pin(f.clone());
+        self.imp().header_mobile.connect_pin(f);
+    }
+
     fn connect_play<F>(&self, f: F)
     where
         F: Fn() + Clone + 'static,
@@ -132,6 +142,14 @@ impl AlbumDetailsWidget {
         self.imp().header_mobile.connect_play(f);
     }
 
+    fn connect_shuffle<F>(&self, f: F)
+    where
+        F: Fn() + Clone + 'static,
+    {
+        self.imp().header_widget.connect_shuffle(f.clone());
+        self.imp().header_mobile.connect_shuffle(f);
+    }
+
     fn connect_info<F>(&self, f: F)
     where
         F: Fn() + Clone + 'static,
@@ -140,27 +158,47 @@ impl AlbumDetailsWidget {
         self.imp().header_mobile.connect_info(f);
     }
 
+    fn set_share_uri(&self, uri: &str) {
+        self.imp().header_widget.set_share_uri(uri);
+        self.imp().header_mobile.set_share_uri(uri);
+    }
+
     fn set_liked(&self, is_liked: bool) {
         self.imp().header_widget.set_liked(is_liked);
         self.imp().header_mobile.set_liked(is_liked);
     }
 
+    fn set_like_visible(&self, visible: bool) {
+        self.imp().header_widget.set_like_visible(visible);
+        self.imp().header_mobile.set_like_visible(visible);
+    }
+
+    fn set_pinned(&self, is_pinned: bool) {
+        self.imp().header_widget.set_pinned(is_pinned);
+        self.imp().header_mobile.set_pinned(is_pinned);
+    }
+
     fn set_playing(&self, is_playing: bool) {
         self.imp().header_widget.set_playing(is_playing);
         self.imp().header_mobile.set_playing(is_playing);
     }
 
-    fn set_album_and_artist_and_year(&self, album: &str, artist: &str, year: Option<u32>) {
+    fn set_album_and_artist_and_release_date(
+        &self,
+        album: &str,
+        artist: &str,
+        release_date: Option<ReleaseDate>,
+    ) {
         self.imp()
             .header_widget
-            .set_album_and_artist_and_year(album, artist, year);
+            .set_album_and_artist_and_release_date(album, artist, release_date);
         self.imp()
             .header_mobile
-            .set_album_and_artist_and_year(album, artist, year);
+            .set_album_and_artist_and_release_date(album, artist, release_date);
         self.imp().headerbar.set_title_and_subtitle(album, artist);
     }
 
-    fn set_artwork(&self, art: &gdk_pixbuf::Pixbuf) {
+    fn set_artwork(&self, art: &gdk::Texture) {
         self.imp().header_widget.set_artwork(art);
         self.imp().header_mobile.set_artwork(art);
     }
@@ -205,10 +243,18 @@ impl Details {
 
         let modal = ReleaseDetailsWindow::new();
 
+        widget.set_share_uri(&format!("spotify:album:{}", model.id));
+
         widget.connect_liked(clone!(@weak model => move || model.toggle_save_album()));
+        widget.set_like_visible(!model.is_guest_mode());
 
         widget.connect_play(clone!(@weak model => move || model.toggle_play_album()));
 
+        widget.connect_shuffle(clone!(@weak model => move || model.shuffle_play_album()));
+
+        widget.set_pinned(model.is_pinned());
+        widget.connect_pin(clone!(@weak model => move || model.toggle_pinned()));
+
         widget.connect_header();
 
         widget.connect_bottom_edge(clone!(@weak model => move || {
@@ -259,10 +305,10 @@ impl Details {
 
             self.widget.set_liked(album.is_liked);
 
-            self.widget.set_album_and_artist_and_year(
+            self.widget.set_album_and_artist_and_release_date(
                 &album.title[..],
                 &album.artists_name(),
-                album.year(),
+                album.release_date,
             );
 
             self.widget.connect_artist_clicked(
@@ -273,7 +319,10 @@ impl Details {
                 &album.title,
                 &album.artists_name(),
                 &details.label,
-                album.release_date.as_ref().unwrap(),
+                &album
+                    .release_date
+                    .map(labels::release_date_label)
+                    .unwrap_or_default(),
                 details.total_tracks,
                 &details.copyright_text,
             );
@@ -282,11 +331,9 @@ impl Details {
                 let widget = self.widget.downgrade();
 
                 self.worker.send_local_task(async move {
-                    let pixbuf = ImageLoader::new()
-                        .load_remote(&art[..], "jpg", 320, 320)
-                        .await;
-                    if let (Some(widget), Some(ref pixbuf)) = (widget.upgrade(), pixbuf) {
-                        widget.set_artwork(pixbuf);
+                    let texture = ImageLoader::new().load_texture(&art, 320).await;
+                    if let (Some(widget), Some(ref texture)) = (widget.upgrade(), texture) {
+                        widget.set_artwork(texture);
                         widget.set_loaded();
                     }
                 });
@@ -322,6 +369,17 @@ impl EventListener for Details {
             {
                 self.update_liked();
             }
+            AppEvent::SettingsEvent(SettingsEvent::PinnedChanged) => {
+                self.widget.set_pinned(self.model.is_pinned());
+            }
+            AppEvent::NetworkEvent(NetworkEvent::BackOnline)
+                if self.model.get_album_info().is_none() =>
+            {
+                self.model.load_album_info();
+            }
+            AppEvent::SettingsEvent(SettingsEvent::GuestModeChanged(guest_mode)) => {
+                self.widget.set_like_visible(!guest_mode);
+            }
             AppEvent::PlaybackEvent(PlaybackEvent::PlaybackPaused) => {
                 self.update_playing(false);
             }
@@ -1,3 +1,4 @@
+use gettextrs::gettext;
 use gio::prelude::*;
 use gio::SimpleActionGroup;
 use std::cell::Ref;
@@ -5,16 +6,19 @@ use std::ops::Deref;
 use std::rc::Rc;
 
 use crate::api::SpotifyApiError;
-use crate::app::components::labels;
 use crate::app::components::HeaderBarModel;
 use crate::app::components::PlaylistModel;
 use crate::app::components::SimpleHeaderBarModel;
 use crate::app::components::SimpleHeaderBarModelWrapper;
+use crate::app::components::{labels, shuffle_play};
 use crate::app::dispatch::ActionDispatcher;
 use crate::app::models::*;
-use crate::app::state::SelectionContext;
-use crate::app::state::{BrowserAction, PlaybackAction, SelectionAction, SelectionState};
+use crate::app::state::{
+    BrowserAction, LibraryMutation, PlaybackAction, SelectionAction, SelectionState, SettingsAction,
+};
+use crate::app::state::{SelectionContext, Undoable};
 use crate::app::{AppAction, AppEvent, AppModel, AppState, BatchQuery, SongsSource};
+use crate::settings::PinnedItem;
 
 pub struct DetailsModel {
     pub id: String,
@@ -73,29 +77,73 @@ impl DetailsModel {
         if let Some(album) = self.get_album_description() {
             let artist = &album.artists.first().unwrap().id;
             self.dispatcher
-                .dispatch(AppAction::ViewArtist(artist.to_owned()));
+                .dispatch(AppAction::ViewArtist(artist.to_string()));
         }
     }
 
     pub fn toggle_save_album(&self) {
+        // Wait for the previous toggle to go through first
+        if self.state().browser.is_mutation_pending(&self.id) {
+            return;
+        }
         if let Some(album) = self.get_album_description() {
             let id = album.id.clone();
             let is_liked = album.is_liked;
+            let album = Box::new(album.clone());
 
             let api = self.app_model.get_spotify();
 
+            // Should go through even if the screen is closed in the meantime
+            let dispatcher = self.dispatcher.unscoped();
+            if !is_liked {
+                dispatcher.call_spotify_and_mutate(
+                    LibraryMutation::SaveAlbum(album),
+                    move || async move {
+                        api.save_album(&id).await?;
+                        Ok(vec![])
+                    },
+                );
+            } else {
+                dispatcher.call_spotify_and_mutate(
+                    LibraryMutation::UnsaveAlbum(album),
+                    move || async move {
+                        api.remove_saved_album(&id).await?;
+                        // translators: This notification shows up after removing an album from the library, it can be undone.
+                        let message = gettext("Album removed from library");
+                        Ok(vec![AppAction::PushUndoable(
+                            Undoable::UnsavedAlbum(id),
+                            message,
+                        )])
+                    },
+                );
+            }
+        }
+    }
+
+    pub fn is_guest_mode(&self) -> bool {
+        self.state().settings.settings.guest_mode
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        let uri = format!("spotify:album:{}", self.id);
+        self.state()
+            .settings
+            .settings
+            .pinned
+            .iter()
+            .any(|p| p.uri == uri)
+    }
+
+    // Pinning needs a title to show in the sidebar, so the album has to be loaded first
+    pub fn toggle_pinned(&self) {
+        if let Some(album) = self.get_album_description() {
+            let item = PinnedItem {
+                uri: format!("spotify:album:{}", album.id),
+                title: album.title.clone(),
+            };
+            drop(album);
             self.dispatcher
-                .call_spotify_and_dispatch(move || async move {
-                    if !is_liked {
-                        api.save_album(&id)
-                            .await
-                            .map(|album| BrowserAction::SaveAlbum(Box::new(album)).into())
-                    } else {
-                        api.remove_saved_album(&id)
-                            .await
-                            .map(|_| BrowserAction::UnsaveAlbum(id).into())
-                    }
-                });
+                .dispatch(SettingsAction::TogglePinned(item).into());
         }
     }
 
@@ -130,6 +178,15 @@ impl DetailsModel {
         }
     }
 
+    pub fn shuffle_play_album(&self) {
+        let Some(first_batch) = self.song_list_model().song_batch_for(0) else {
+            return;
+        };
+        let source = SongsSource::Album(self.id.clone());
+        let loader = self.app_model.get_batch_loader();
+        shuffle_play(self.dispatcher.as_ref(), loader, source, first_batch);
+    }
+
     pub fn load_more(&self) -> Option<()> {
         let last_batch = self.song_list_model().last_batch()?;
         let query = BatchQuery {
@@ -188,6 +245,11 @@ impl PlaylistModel for DetailsModel {
         }
     }
 
+    fn select_songs(&self, songs: Vec<SongDescription>) {
+        self.dispatcher
+            .dispatch(SelectionAction::Select(songs).into());
+    }
+
     fn deselect_song(&self, id: &str) {
         self.dispatcher
             .dispatch(SelectionAction::Deselect(vec![id.to_string()]).into());
@@ -195,12 +257,18 @@ impl PlaylistModel for DetailsModel {
 
     fn enable_selection(&self) -> bool {
         self.dispatcher
-            .dispatch(AppAction::EnableSelection(SelectionContext::Default));
+            .dispatch(AppAction::EnableSelection(SelectionContext::AlbumDetails(
+                self.id.clone(),
+            )));
         true
     }
 
     fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
-        Some(Box::new(self.app_model.map_state(|s| &s.selection)))
+        let context = SelectionContext::AlbumDetails(self.id.clone());
+        let selection = self
+            .app_model
+            .map_state_opt(|s| Some(&s.selection).filter(|s| s.context == context))?;
+        Some(Box::new(selection))
     }
 
     fn current_song_id(&self) -> Option<String> {
@@ -212,22 +280,43 @@ impl PlaylistModel for DetailsModel {
         let batch = self.song_list_model().song_batch_for(pos);
         if let Some(batch) = batch {
             self.dispatcher
-                .dispatch(PlaybackAction::LoadPagedSongs(source, batch).into());
+                .dispatch(PlaybackAction::LoadPagedSongs(source, batch, 0).into());
             self.dispatcher
                 .dispatch(PlaybackAction::Load(id.to_string()).into());
         }
     }
 
+    fn autoscroll_to_playing(&self) -> bool {
+        self.app_model
+            .get_state()
+            .settings
+            .settings
+            .autoscroll
+            .albums
+    }
+
     fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
         let song = self.song_list_model().get(id)?;
         let song = song.description();
 
         let group = SimpleActionGroup::new();
 
+        group.add_action(&song.make_play_from_here_action(
+            self.song_list_model(),
+            Some(SongsSource::Album(self.id.clone())),
+            self.dispatcher.box_clone(),
+            None,
+        ));
+
         for view_artist in song.make_artist_actions(self.dispatcher.box_clone(), None) {
             group.add_action(&view_artist);
         }
         group.add_action(&song.make_link_action(None));
+        group.add_action(&song.make_radio_action(
+            self.app_model.get_spotify(),
+            self.dispatcher.unscoped(),
+            None,
+        ));
         group.add_action(&song.make_queue_action(self.dispatcher.box_clone(), None));
 
         Some(group.upcast())
@@ -238,6 +327,7 @@ impl PlaylistModel for DetailsModel {
         let song = song.description();
 
         let menu = gio::Menu::new();
+        menu.append(Some(&*labels::PLAY_FROM_HERE), Some("song.play_from_here"));
         for artist in song.artists.iter() {
             menu.append(
                 Some(&labels::more_from_label(&artist.name)),
@@ -245,7 +335,14 @@ impl PlaylistModel for DetailsModel {
             );
         }
 
+        if !song.is_episode() {
+            menu.append(Some(&*labels::SONG_RADIO), Some("song.radio"));
+        }
         menu.append(Some(&*labels::COPY_LINK), Some("song.copy_link"));
+        menu.append(
+            Some(&*labels::SHARE),
+            Some(&format!("app.share::{}", song.uri)),
+        );
         menu.append(Some(&*labels::ADD_TO_QUEUE), Some("song.queue"));
         Some(menu.upcast())
     }
@@ -261,7 +358,7 @@ impl SimpleHeaderBarModel for DetailsModel {
     }
 
     fn selection_context(&self) -> Option<SelectionContext> {
-        Some(SelectionContext::Default)
+        Some(SelectionContext::AlbumDetails(self.id.clone()))
     }
 
     fn select_all(&self) {
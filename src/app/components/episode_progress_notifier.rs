@@ -0,0 +1,123 @@
+use gio::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::app::components::EventListener;
+use crate::app::episode_progress;
+use crate::app::state::PlaybackEvent;
+use crate::app::{AppEvent, AppModel};
+
+// What we know about the episode being played
+struct PlayingEpisode {
+    id: String,
+    duration: u32,
+    // Until it was last paused or seeked (in milliseconds)
+    position: u32,
+    // Set while playing
+    resumed_at: Option<Instant>,
+}
+
+impl PlayingEpisode {
+    fn position(&self) -> u32 {
+        let elapsed = self
+            .resumed_at
+            .map(|t| t.elapsed().as_millis() as u32)
+            .unwrap_or(0);
+        self.position.saturating_add(elapsed)
+    }
+
+    fn seek(&mut self, position: u32) {
+        self.position = position;
+        if self.resumed_at.is_some() {
+            self.resumed_at = Some(Instant::now());
+        }
+    }
+
+    fn pause(&mut self) {
+        self.position = self.position();
+        self.resumed_at = None;
+    }
+
+    fn resume(&mut self) {
+        self.resumed_at.get_or_insert_with(Instant::now);
+    }
+}
+
+// Remembers where podcast episodes were left off, so that they can be resumed later
+pub struct EpisodeProgressNotifier {
+    app_model: Rc<AppModel>,
+    // Shared with the shutdown handler
+    current: Rc<RefCell<Option<PlayingEpisode>>>,
+}
+
+impl EpisodeProgressNotifier {
+    pub fn new(app_model: Rc<AppModel>) -> Self {
+        let current: Rc<RefCell<Option<PlayingEpisode>>> = Default::default();
+        // Otherwise whatever was played since the last pause or seek would be lost on quit
+        if let Some(app) = gio::Application::default() {
+            app.connect_shutdown(clone!(@weak current => move |_| {
+                Self::save(current.borrow().as_ref());
+                episode_progress::save_blocking();
+            }));
+        }
+        Self { app_model, current }
+    }
+
+    fn save(current: Option<&PlayingEpisode>) {
+        if let Some(current) = current {
+            episode_progress::save_position(&current.id, current.position(), current.duration);
+        }
+    }
+
+    fn start_episode(&mut self) {
+        let mut current = self.current.borrow_mut();
+        Self::save(current.as_ref());
+
+        let state = self.app_model.get_state();
+        let is_playing = state.playback.is_playing();
+        *current = state
+            .playback
+            .current_song()
+            .filter(|song| song.is_episode())
+            .map(|song| PlayingEpisode {
+                position: episode_progress::resume_position(&song.id),
+                id: song.id,
+                duration: song.duration,
+                resumed_at: Some(Instant::now()).filter(|_| is_playing),
+            });
+    }
+}
+
+impl EventListener for EpisodeProgressNotifier {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) => self.start_episode(),
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackPaused) => {
+                let mut current = self.current.borrow_mut();
+                if let Some(current) = current.as_mut() {
+                    current.pause();
+                }
+                Self::save(current.as_ref());
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackResumed) => {
+                if let Some(current) = self.current.borrow_mut().as_mut() {
+                    current.resume();
+                }
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackStopped) => {
+                Self::save(self.current.take().as_ref());
+            }
+            AppEvent::PlaybackEvent(
+                PlaybackEvent::SeekSynced(pos) | PlaybackEvent::TrackSeeked(pos),
+            ) => {
+                let mut current = self.current.borrow_mut();
+                if let Some(current) = current.as_mut() {
+                    current.seek(*pos);
+                }
+                Self::save(current.as_ref());
+            }
+            _ => {}
+        }
+    }
+}
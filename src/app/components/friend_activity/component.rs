@@ -0,0 +1,146 @@
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use glib::Cast;
+
+use crate::api::SpotifyApiError;
+use crate::app::components::utils::Clock;
+use crate::app::components::{Component, EventListener};
+use crate::app::models::FriendActivity;
+use crate::app::state::{LoginAction, LoginEvent};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
+
+use super::widget::FriendActivityWidget;
+
+// How often to check what friends are up to
+const REFRESH_INTERVAL_MS: u32 = 2 * 60 * 1000;
+
+pub struct FriendActivityModel {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    // Cleared when Spotify won't take our token there, so that we stop asking until the next login
+    available: Arc<AtomicBool>,
+}
+
+impl FriendActivityModel {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            available: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    pub fn reset_availability(&self) {
+        self.available.store(true, Ordering::Relaxed);
+    }
+
+    pub fn refresh_friend_activity(&self) {
+        if !self.available.load(Ordering::Relaxed) {
+            return;
+        }
+        let api = self.app_model.get_spotify();
+        let available = Arc::clone(&self.available);
+
+        self.dispatcher
+            .call_spotify_and_dispatch(move || async move {
+                // This isn't part of the public API and could be refused at any time (or we might be offline):
+                // rather than bothering the user about it, the panel just gets hidden
+                let activity = match api.get_friend_activity().await {
+                    Ok(activity) => activity,
+                    // Depending on how we logged in, the token might not be accepted there
+                    Err(e @ SpotifyApiError::BadStatus(401 | 403, _)) => {
+                        warn!("Friend activity is not available with this login: {}", e);
+                        available.store(false, Ordering::Relaxed);
+                        vec![]
+                    }
+                    Err(e) => {
+                        debug!("Could not get the friend activity: {}", e);
+                        vec![]
+                    }
+                };
+                Ok(LoginAction::SetFriendActivity(activity).into())
+            });
+    }
+
+    pub fn get_friend_activity(&self) -> impl Deref<Target = Vec<FriendActivity>> + '_ {
+        self.app_model.map_state(|s| &s.logged_user.friend_activity)
+    }
+
+    // Where the friend is playing from if we can open it, or else the track itself
+    pub fn open_friend_activity(&self, index: usize) {
+        let activity = self.get_friend_activity();
+        let Some(friend) = activity.get(index) else {
+            return;
+        };
+        let action = friend
+            .context_uri
+            .clone()
+            .and_then(AppAction::OpenURI)
+            .or_else(|| AppAction::OpenURI(friend.track_uri.clone()));
+        drop(activity);
+
+        if let Some(action) = action {
+            self.dispatcher.dispatch(action);
+        }
+    }
+}
+
+pub struct FriendActivityPanel {
+    widget: FriendActivityWidget,
+    model: Rc<FriendActivityModel>,
+    clock: Clock,
+}
+
+impl FriendActivityPanel {
+    pub fn new(widget: FriendActivityWidget, model: FriendActivityModel) -> Self {
+        let model = Rc::new(model);
+
+        widget.connect_friend_activated(clone!(@weak model => move |index| {
+            model.open_friend_activity(index);
+        }));
+
+        widget.connect_expanded(clone!(@weak model => move || {
+            model.refresh_friend_activity();
+        }));
+
+        Self {
+            widget,
+            model,
+            clock: Clock::new(REFRESH_INTERVAL_MS),
+        }
+    }
+}
+
+impl Component for FriendActivityPanel {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        self.widget.upcast_ref()
+    }
+}
+
+impl EventListener for FriendActivityPanel {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => {
+                self.model.reset_availability();
+                self.model.refresh_friend_activity();
+                let model = Rc::downgrade(&self.model);
+                self.clock.start(move || {
+                    if let Some(model) = model.upgrade() {
+                        model.refresh_friend_activity();
+                    }
+                });
+            }
+            AppEvent::LoginEvent(LoginEvent::LogoutCompleted | LoginEvent::AccountSwitched) => {
+                self.clock.stop();
+            }
+            AppEvent::LoginEvent(LoginEvent::FriendActivityUpdated) => {
+                self.widget
+                    .update_activity(&self.model.get_friend_activity());
+            }
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use glib::StaticType;
+
+mod component;
+pub use component::*;
+
+mod widget;
+pub use widget::*;
+
+pub fn expose_widgets() {
+    widget::FriendActivityWidget::static_type();
+}
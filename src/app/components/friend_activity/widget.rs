@@ -0,0 +1,115 @@
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use libadwaita::prelude::*;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::app::components::labels;
+use crate::app::models::FriendActivity;
+
+// Friends who played something this recently are most likely still listening
+const LISTENING_NOW_MS: u64 = 10 * 60 * 1000;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/dev/alextren/Spot/components/friend_activity.ui")]
+    pub struct FriendActivityWidget {
+        #[template_child]
+        pub expander: TemplateChild<gtk::Expander>,
+
+        #[template_child]
+        pub friends: TemplateChild<gtk::ListBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for FriendActivityWidget {
+        const NAME: &'static str = "FriendActivityWidget";
+        type Type = super::FriendActivityWidget;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for FriendActivityWidget {}
+    impl WidgetImpl for FriendActivityWidget {}
+    impl BoxImpl for FriendActivityWidget {}
+}
+
+glib::wrapper! {
+    pub struct FriendActivityWidget(ObjectSubclass<imp::FriendActivityWidget>) @extends gtk::Widget, gtk::Box;
+}
+
+impl FriendActivityWidget {
+    fn make_row(friend: &FriendActivity, now: u64) -> libadwaita::ActionRow {
+        let subtitle = format!("{} — {}", friend.track_title, friend.artist_name);
+        let row = libadwaita::ActionRow::builder()
+            .title(glib::markup_escape_text(&friend.user_name).as_str())
+            .subtitle(glib::markup_escape_text(&subtitle).as_str())
+            .activatable(true)
+            .build();
+
+        let elapsed = now.saturating_sub(friend.timestamp);
+        let when = if elapsed < LISTENING_NOW_MS {
+            let icon = gtk::Image::from_icon_name("audio-volume-high-symbolic");
+            icon.set_tooltip_text(Some(&*labels::LISTENING_NOW));
+            row.add_suffix(&icon);
+            labels::LISTENING_NOW.clone()
+        } else {
+            labels::listened_ago_label(elapsed / 60_000)
+        };
+        let tooltip = match friend.context_name.as_ref() {
+            Some(context) => format!("{when} · {context}"),
+            None => when,
+        };
+        row.set_tooltip_text(Some(&tooltip));
+        row
+    }
+
+    pub fn update_activity(&self, activity: &[FriendActivity]) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        let friends = &self.imp().friends;
+        while let Some(row) = friends.first_child() {
+            friends.remove(&row);
+        }
+        for friend in activity {
+            friends.append(&Self::make_row(friend, now));
+        }
+        // Nothing to show when no one is followed, or when Spotify won't tell us
+        self.set_visible(!activity.is_empty());
+    }
+
+    pub fn connect_friend_activated<F>(&self, f: F)
+    where
+        F: Fn(usize) + 'static,
+    {
+        self.imp()
+            .friends
+            .connect_row_activated(move |_, row| f(row.index() as usize));
+    }
+
+    pub fn connect_expanded<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp()
+            .expander
+            .connect_expanded_notify(move |expander| {
+                if expander.is_expanded() {
+                    f();
+                }
+            });
+    }
+}
@@ -17,6 +17,7 @@ pub trait HeaderBarModel {
     fn go_back(&self);
     fn can_go_back(&self) -> bool;
     fn selection_context(&self) -> Option<SelectionContext>;
+    fn is_selection_active(&self) -> bool;
     fn can_select_all(&self) -> bool;
     fn start_selection(&self);
     fn select_all(&self);
@@ -69,6 +70,17 @@ impl HeaderBarModel for DefaultHeaderBarModel {
         self.selection_context.clone()
     }
 
+    fn is_selection_active(&self) -> bool {
+        self.selection_context()
+            .map(|context| {
+                self.app_model
+                    .get_state()
+                    .selection
+                    .is_selection_enabled_in(&context)
+            })
+            .unwrap_or(false)
+    }
+
     fn can_select_all(&self) -> bool {
         false
     }
@@ -143,6 +155,17 @@ where
         self.wrapped_model.selection_context()
     }
 
+    fn is_selection_active(&self) -> bool {
+        self.selection_context()
+            .map(|context| {
+                self.app_model
+                    .get_state()
+                    .selection
+                    .is_selection_enabled_in(&context)
+            })
+            .unwrap_or(false)
+    }
+
     fn can_select_all(&self) -> bool {
         true
     }
@@ -176,8 +199,8 @@ mod common {
         Model: HeaderBarModel + 'static,
     {
         match event {
-            AppEvent::SelectionEvent(SelectionEvent::SelectionModeChanged(active)) => {
-                widget.set_selection_active(*active);
+            AppEvent::SelectionEvent(SelectionEvent::SelectionModeChanged(_)) => {
+                widget.set_selection_active(model.is_selection_active());
             }
             AppEvent::SelectionEvent(SelectionEvent::SelectionChanged) => {
                 widget.set_selection_count(model.selected_count());
@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::api::cache::cache_root;
+use crate::app::components::EventListener;
+use crate::app::credentials;
+use crate::app::models::*;
+use crate::app::state::{BrowserAction, BrowserEvent, LoginEvent};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel, Worker};
+
+// Library updates tend to come in bursts (one per batch), no need to write after each one
+const SAVE_DELAY: Duration = Duration::from_secs(2);
+
+// What we keep of the home screen (library, playlists and saved tracks) between runs
+#[derive(Serialize, Deserialize)]
+struct HomeSnapshot {
+    albums: Vec<AlbumDescription>,
+    playlists: Vec<PlaylistDescription>,
+    saved_tracks: Vec<SongBatch>,
+}
+
+impl HomeSnapshot {
+    fn path(username: &str) -> PathBuf {
+        cache_root()
+            .join("spot")
+            .join("home")
+            .join(format!("{username}.json"))
+    }
+
+    // The home screen only keeps what it displays (and the artist to go to), which is all we need to display it again
+    fn album_from_model(album: AlbumModel) -> AlbumDescription {
+        AlbumDescription {
+            id: album.uri(),
+            title: album.album(),
+            artists: vec![ArtistRef {
+                id: intern(&album.artist_id()),
+                name: intern(&album.artist()),
+            }],
+            release_date: album.release_date(),
+            popularity: None,
+            art: album.cover(),
+            songs: SongBatch::empty(),
+            is_liked: true,
+        }
+    }
+
+    fn playlist_from_model(playlist: AlbumModel) -> PlaylistDescription {
+        PlaylistDescription {
+            id: playlist.uri(),
+            title: playlist.album(),
+            art: playlist.cover(),
+            songs: SongBatch::empty(),
+            owner: UserRef {
+                id: playlist.artist_id(),
+                display_name: playlist.artist(),
+            },
+            public: None,
+        }
+    }
+
+    fn from_model(app_model: &AppModel, username: &str) -> Option<Self> {
+        let state = app_model.get_state();
+        // We might have switched accounts in the meantime
+        if state.logged_user.user.as_deref() != Some(username) {
+            return None;
+        }
+        let home = state.browser.home_state()?;
+        let saved_tracks = home
+            .saved_tracks
+            .last_batch()
+            .map(|last| {
+                (0..=last.offset)
+                    .step_by(last.batch_size.max(1))
+                    .filter_map(|offset| home.saved_tracks.song_batch_for(offset))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(Self {
+            albums: home.albums.iter().map(Self::album_from_model).collect(),
+            playlists: home
+                .playlists
+                .iter()
+                .map(Self::playlist_from_model)
+                .collect(),
+            saved_tracks,
+        })
+    }
+
+    fn into_actions(self) -> Vec<AppAction> {
+        let mut actions: Vec<AppAction> = vec![
+            BrowserAction::SetLibraryContent(self.albums).into(),
+            BrowserAction::SetPlaylistsContent(self.playlists).into(),
+        ];
+        let mut saved_tracks = self.saved_tracks.into_iter();
+        if let Some(first) = saved_tracks.next() {
+            actions.push(BrowserAction::SetSavedTracks(Box::new(first)).into());
+            actions
+                .extend(saved_tracks.map(|b| BrowserAction::AppendSavedTracks(Box::new(b)).into()));
+        }
+        actions
+    }
+}
+
+// Saves the home screen to disk as it changes, and restores it on startup so that it shows up right away
+// (it is then refreshed as usual once logged in)
+pub struct HomeSnapshots {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    worker: Worker,
+    save_pending: Rc<Cell<bool>>,
+    // Who the snapshot we're writing belongs to
+    username: Option<String>,
+}
+
+impl HomeSnapshots {
+    pub fn new(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        worker: Worker,
+    ) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            worker,
+            save_pending: Rc::new(Cell::new(false)),
+            username: None,
+        }
+    }
+
+    fn restore(&self) {
+        let Some(username) = credentials::current_account() else {
+            return;
+        };
+        let app_model = Rc::downgrade(&self.app_model);
+        let dispatcher = self.dispatcher.box_clone();
+        self.worker.send_local_task(async move {
+            let Ok(content) = async_std::fs::read(HomeSnapshot::path(&username)).await else {
+                return;
+            };
+            let Ok(snapshot) = serde_json::from_slice::<HomeSnapshot>(&content) else {
+                return;
+            };
+            let Some(app_model) = app_model.upgrade() else {
+                return;
+            };
+            // Too late if something was fetched in the meantime (or if we switched accounts), it's more recent
+            let state = app_model.get_state();
+            let still_empty = state.browser.home_state().is_some_and(|home| {
+                home.albums.len() == 0 && home.playlists.len() == 0 && home.saved_tracks.len() == 0
+            });
+            if still_empty && credentials::current_account().as_deref() == Some(&username) {
+                drop(state);
+                dispatcher.dispatch_many(snapshot.into_actions());
+            }
+        });
+    }
+
+    fn schedule_save(&self) {
+        let Some(username) = self.username.clone() else {
+            return;
+        };
+        if self.save_pending.replace(true) {
+            return;
+        }
+
+        let app_model = Rc::downgrade(&self.app_model);
+        let save_pending = Rc::clone(&self.save_pending);
+        let worker = self.worker.clone();
+        glib::timeout_add_local_once(SAVE_DELAY, move || {
+            save_pending.set(false);
+            let Some(app_model) = app_model.upgrade() else {
+                return;
+            };
+            let Some(snapshot) = HomeSnapshot::from_model(&app_model, &username) else {
+                return;
+            };
+            let Ok(content) = serde_json::to_vec(&snapshot) else {
+                return;
+            };
+            worker.send_task(async move {
+                let path = HomeSnapshot::path(&username);
+                if let Some(parent) = path.parent() {
+                    let _ = async_std::fs::create_dir_all(parent).await;
+                }
+                if let Err(err) = async_std::fs::write(&path, content).await {
+                    warn!("Could not save home screen: {}", err);
+                }
+            });
+        });
+    }
+
+    fn forget(&mut self) {
+        if let Some(username) = self.username.take() {
+            self.worker.send_task(async move {
+                let _ = async_std::fs::remove_file(HomeSnapshot::path(&username)).await;
+            });
+        }
+    }
+}
+
+impl EventListener for HomeSnapshots {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started => self.restore(),
+            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => {
+                self.username = self.app_model.get_state().logged_user.user.clone();
+            }
+            AppEvent::LoginEvent(LoginEvent::LogoutCompleted) => self.forget(),
+            AppEvent::LoginEvent(LoginEvent::AccountSwitched) => {
+                self.username = None;
+                self.restore();
+            }
+            AppEvent::BrowserEvent(
+                BrowserEvent::LibraryUpdated
+                | BrowserEvent::SavedPlaylistsUpdated
+                | BrowserEvent::SavedTracksUpdated,
+            ) => self.schedule_save(),
+            _ => {}
+        }
+    }
+}
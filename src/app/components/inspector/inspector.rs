@@ -0,0 +1,337 @@
+use gettextrs::gettext;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use crate::app::middleware::ActionLog;
+use crate::app::state::SettingsAction;
+use crate::app::{ActionDispatcher, AppAction, AppModel, AppState};
+
+mod imp {
+
+    use super::*;
+    use libadwaita::subclass::prelude::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/dev/alextren/Spot/components/inspector.ui")]
+    pub struct InspectorWindow {
+        #[template_child]
+        pub export_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub refresh_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub paned: TemplateChild<gtk::Paned>,
+
+        #[template_child]
+        pub state_view: TemplateChild<gtk::TextView>,
+
+        #[template_child]
+        pub actions_scroll: TemplateChild<gtk::ScrolledWindow>,
+
+        #[template_child]
+        pub actions_list: TemplateChild<gtk::ListBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for InspectorWindow {
+        const NAME: &'static str = "InspectorWindow";
+        type Type = super::InspectorWindow;
+        type ParentType = libadwaita::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for InspectorWindow {}
+    impl WidgetImpl for InspectorWindow {}
+    impl WindowImpl for InspectorWindow {}
+    impl AdwWindowImpl for InspectorWindow {}
+}
+
+glib::wrapper! {
+    pub struct InspectorWindow(ObjectSubclass<imp::InspectorWindow>) @extends gtk::Widget, gtk::Window, libadwaita::Window;
+}
+
+impl InspectorWindow {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    fn paned(&self) -> &gtk::Paned {
+        &self.imp().paned
+    }
+
+    fn connect_refresh<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().refresh_button.connect_clicked(move |_| f());
+    }
+
+    fn connect_export<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().export_button.connect_clicked(move |_| f());
+    }
+
+    fn set_state(&self, text: &str) {
+        self.imp().state_view.buffer().set_text(text);
+    }
+
+    fn set_actions<F>(&self, actions: Vec<String>, on_replay: F)
+    where
+        F: Fn(usize) + Clone + 'static,
+    {
+        let list = &self.imp().actions_list;
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+
+        for (i, text) in actions.into_iter().enumerate() {
+            let label = gtk::Label::builder()
+                .label(&text)
+                .tooltip_text(&text)
+                .xalign(0.0)
+                .hexpand(true)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .build();
+            label.add_css_class("monospace");
+
+            let replay = gtk::Button::builder()
+                .icon_name("media-playlist-repeat-symbolic")
+                // translators: Tooltip for the button dispatching a recorded action again, in the state inspector
+                .tooltip_text(gettext("Dispatch Again"))
+                .build();
+            replay.add_css_class("flat");
+            let on_replay = on_replay.clone();
+            replay.connect_clicked(move |_| on_replay(i));
+
+            let row = gtk::Box::builder()
+                .orientation(gtk::Orientation::Horizontal)
+                .spacing(6)
+                .margin_start(6)
+                .margin_end(6)
+                .build();
+            row.append(&label);
+            row.append(&replay);
+            list.append(&row);
+        }
+
+        // Most recent actions are the most interesting
+        let adjustment = self.imp().actions_scroll.vadjustment();
+        glib::idle_add_local_once(move || {
+            adjustment.set_value(adjustment.upper());
+        });
+    }
+}
+
+fn format_time(time: &SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    glib::DateTime::from_unix_local(secs)
+        .and_then(|t| t.format("%T"))
+        .map(|t| t.to_string())
+        .unwrap_or_default()
+}
+
+// A readable summary of the state, which is too big (and too private) to be dumped as is
+fn describe_state(state: &AppState) -> String {
+    let playback = &state.playback;
+    let browser = &state.browser;
+    let selection = &state.selection;
+    let login = &state.logged_user;
+    let screens = browser
+        .screen_names()
+        .map(|name| name.identifier().into_owned())
+        .collect::<Vec<String>>()
+        .join(" > ");
+
+    [
+        "playback".to_string(),
+        format!("  playing: {}", playback.is_playing()),
+        format!("  current song: {:?}", playback.current_song_id()),
+        format!("  current index: {:?}", playback.current_song_index()),
+        format!("  source: {:?}", playback.current_source()),
+        format!(
+            "  queue: {} loaded, {} total",
+            playback.songs().partial_len(),
+            playback.songs().len()
+        ),
+        format!("  next in queue: {}", playback.queued_songs().len()),
+        format!("  playing queued: {}", playback.is_playing_queued()),
+        format!("  shuffled: {}", playback.is_shuffled()),
+        format!("  repeat: {:?}", playback.repeat_mode()),
+        format!("  device: {:?}", playback.current_device()),
+        "browser".to_string(),
+        format!("  screens: {screens}"),
+        format!("  scroll offsets: {:?}", browser.scroll_offsets()),
+        "selection".to_string(),
+        format!("  enabled: {}", selection.is_selection_enabled()),
+        format!("  context: {:?}", selection.context),
+        format!("  selected: {}", selection.count()),
+        "login".to_string(),
+        format!("  user: {:?}", login.user),
+        format!("  playlists: {}", login.playlists.len()),
+        "settings".to_string(),
+        format!("  offline mode: {}", state.settings.settings.offline_mode),
+    ]
+    .join("\n")
+}
+
+pub struct InspectorModel {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    action_log: ActionLog,
+}
+
+impl InspectorModel {
+    pub fn new(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        action_log: ActionLog,
+    ) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            action_log,
+        }
+    }
+
+    fn state_description(&self) -> String {
+        describe_state(&self.app_model.get_state())
+    }
+
+    // The log keeps moving, so we work on a copy of it
+    fn recorded_actions(&self) -> Vec<(SystemTime, AppAction)> {
+        self.action_log.borrow().iter().cloned().collect()
+    }
+
+    fn format_action((time, action): &(SystemTime, AppAction)) -> String {
+        format!("{} {:?}", format_time(time), action)
+    }
+
+    fn paned_position(&self) -> i32 {
+        self.app_model
+            .get_state()
+            .settings
+            .settings
+            .inspector_paned_position
+    }
+
+    fn save_paned_position(&self, position: i32) {
+        if position != self.paned_position() {
+            self.dispatcher
+                .dispatch(SettingsAction::SetInspectorPanedPosition(position).into());
+        }
+    }
+
+    fn replay(&self, action: AppAction) {
+        self.dispatcher.dispatch(action);
+    }
+
+    fn trace(&self) -> String {
+        let actions = self
+            .recorded_actions()
+            .iter()
+            .map(Self::format_action)
+            .collect::<Vec<String>>()
+            .join("\n");
+        format!("{}\n\nactions\n{}\n", self.state_description(), actions)
+    }
+}
+
+// A developer window to look at the live state, and at (and replay) recent actions
+pub struct Inspector {
+    parent: gtk::Window,
+    window: InspectorWindow,
+    model: Rc<InspectorModel>,
+}
+
+impl Inspector {
+    pub fn new(parent: gtk::Window, model: InspectorModel) -> Self {
+        let model = Rc::new(model);
+        let window = InspectorWindow::new();
+        window.paned().set_position(model.paned_position());
+
+        window.connect_close_request(
+            clone!(@weak model => @default-return gtk::Inhibit(false), move |window| {
+                model.save_paned_position(window.paned().position());
+                gtk::Inhibit(false)
+            }),
+        );
+
+        window.connect_refresh(clone!(@weak window, @weak model => move || {
+            Self::refresh(&window, &model);
+        }));
+        window.connect_export(clone!(@weak window, @weak model => move || {
+            Self::export(&window, &model);
+        }));
+
+        Self {
+            parent,
+            window,
+            model,
+        }
+    }
+
+    fn refresh(window: &InspectorWindow, model: &Rc<InspectorModel>) {
+        window.set_state(&model.state_description());
+        let actions = Rc::new(model.recorded_actions());
+        let texts = actions.iter().map(InspectorModel::format_action).collect();
+        let weak_model = Rc::downgrade(model);
+        window.set_actions(texts, move |i| {
+            if let (Some(model), Some((_, action))) = (weak_model.upgrade(), actions.get(i)) {
+                model.replay(action.clone());
+            }
+        });
+    }
+
+    fn export(window: &InspectorWindow, model: &Rc<InspectorModel>) {
+        let trace = model.trace();
+        let dialog = gtk::FileDialog::builder()
+            // translators: Title of the dialog to save a trace of the app's recent actions
+            .title(gettext("Export Trace"))
+            .initial_name("spot-trace.txt")
+            .modal(true)
+            .build();
+        dialog.save(Some(window), gio::Cancellable::NONE, move |result| {
+            let Ok(file) = result else {
+                return;
+            };
+            if let Err(err) = file.replace_contents(
+                trace.as_bytes(),
+                None,
+                false,
+                gio::FileCreateFlags::REPLACE_DESTINATION,
+                gio::Cancellable::NONE,
+            ) {
+                warn!("Could not export trace: {}", err);
+            }
+        });
+    }
+
+    pub fn show_self(&self) {
+        self.window.set_transient_for(Some(&self.parent));
+        Self::refresh(&self.window, &self.model);
+        self.window.present();
+    }
+
+    // Keeps the state live while the window is open
+    pub fn on_state_changed(&self) {
+        if self.window.is_visible() {
+            self.window.set_state(&self.model.state_description());
+        }
+    }
+}
@@ -1,9 +1,27 @@
 use gettextrs::*;
 
+use crate::api::library_export::ExportProgress;
+use crate::app::models::{ReleaseDate, Restriction};
+
 lazy_static! {
     // translators: This is part of a contextual menu attached to a single track; this entry allows viewing the album containing a specific track.
     pub static ref VIEW_ALBUM: String = gettext("View album");
 
+    // translators: This is part of the contextual menu of a saved podcast episode; this entry opens the podcast it belongs to.
+    pub static ref VIEW_SHOW: String = gettext("View podcast");
+
+    // translators: This is part of a contextual menu attached to a single track; this entry plays songs similar to that track.
+    pub static ref SONG_RADIO: String = gettext("Go to song radio");
+
+    // translators: This is part of a contextual menu attached to a single track; this entry plays the list starting from that track, leaving out the tracks above it.
+    pub static ref PLAY_FROM_HERE: String = gettext("Play from here");
+
+    // translators: This is part of the contextual menu of playlists in the sidebar, and the tooltip of the pin button on albums; it keeps them in a section at the top of the sidebar.
+    pub static ref PIN_TO_SIDEBAR: String = gettext("Pin to sidebar");
+
+    // translators: This is part of the contextual menu of pinned items in the sidebar, and the tooltip of the pin button on pinned albums.
+    pub static ref UNPIN_FROM_SIDEBAR: String = gettext("Unpin from sidebar");
+
     // translators: This is part of a contextual menu attached to a single track; the intent is to copy the link (public URL) to a specific track.
     pub static ref COPY_LINK: String = gettext("Copy link");
 
@@ -12,15 +30,82 @@ lazy_static! {
 
     // translators: This is part of a contextual menu attached to a single track; this entry removes a track from the play queue.
     pub static ref REMOVE_FROM_QUEUE: String = gettext("Remove from queue");
+
+    // translators: This is part of a contextual menu attached to a single track, and the tooltip of buttons on albums, artists and playlists; it opens a window with a link and a QR code.
+    pub static ref SHARE: String = gettext("Share…");
+
+    // translators: This is part of the contextual menu of a podcast episode, and the tooltip of the checkmark shown on episodes that were listened to.
+    pub static ref PLAYED: String = gettext("Played");
+
+    // translators: Tooltip of the button that opens the menu of a podcast episode.
+    pub static ref EPISODE_MENU: String = gettext("Episode menu");
+
+    // translators: Shown next to friends in the friend activity panel when they are playing something right now.
+    pub static ref LISTENING_NOW: String = gettext("Listening now");
+
+    // translators: Header above the liked songs saved in the last seven days, when they are grouped by date.
+    pub static ref ADDED_THIS_WEEK: String = gettext("This week");
+
+    // translators: Header above the liked songs saved earlier this month, when they are grouped by date.
+    pub static ref ADDED_THIS_MONTH: String = gettext("This month");
+
+    // translators: Header above the liked songs saved before this month, when they are grouped by date.
+    pub static ref ADDED_EARLIER: String = gettext("Earlier");
+
+    // translators: Header above the rest of the play queue, after the songs queued by hand, when we don't know the name of what is playing.
+    pub static ref NEXT_UP: String = gettext("Next up");
+
+    // translators: Tooltip of a song that can't be played in offline mode, because it was never downloaded.
+    pub static ref NOT_AVAILABLE_OFFLINE: String = gettext("Not available offline");
+
+    // translators: This notification shows up when saving or removing something from the library failed, and the change was undone.
+    pub static ref LIBRARY_UPDATE_FAILED: String = gettext("Couldn't update your library");
 }
 
-pub fn add_to_playlist_label(playlist: &str) -> String {
+pub fn unplayable_label(restriction: Restriction) -> String {
+    match restriction {
+        // translators: Tooltip of a song that can't be played because Spotify doesn't offer it in the user's country.
+        Restriction::Market => gettext("Not available in your country"),
+        // translators: Tooltip of a song that can't be played with the user's subscription.
+        Restriction::Product => gettext("Not available with your subscription"),
+        // translators: Tooltip of a song that can't be played because the user turned off explicit content.
+        Restriction::Explicit => gettext("Explicit content is turned off"),
+        // translators: Tooltip of a song that Spotify won't let the user play, without saying why.
+        Restriction::Other => gettext("Not available"),
+    }
+}
+
+pub fn n_songs_added_to_playlist_label(n: usize, playlist: &str) -> String {
     // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
     if cfg!(debug_assertions) {
-        // translators: This is part of a larger text that says "Add to <playlist name>". This text should be as short as possible.
-        gettext("Add to {}");
+        // translators: This notification shows up after adding the selected songs to a playlist, it reads "<n> songs added to <playlist name>"
+        ngettext("{} song added to {}", "{} songs added to {}", n as u32);
     }
-    gettext!("Add to {}", playlist)
+    ngettext!(
+        "{} song added to {}",
+        "{} songs added to {}",
+        n as u32,
+        n,
+        playlist
+    )
+}
+
+pub fn n_songs_removed_label(n: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This notification shows up after removing the selected songs from a playlist or from the saved tracks, it can be undone.
+        ngettext("{} song removed", "{} songs removed", n as u32);
+    }
+    ngettext!("{} song removed", "{} songs removed", n as u32, n)
+}
+
+pub fn n_links_copied_label(n: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This notification shows up after copying the links to the selected songs.
+        ngettext("{} link copied", "{} links copied", n as u32);
+    }
+    ngettext!("{} link copied", "{} links copied", n as u32, n)
 }
 
 pub fn n_songs_selected_label(n: usize) -> String {
@@ -32,6 +117,67 @@ pub fn n_songs_selected_label(n: usize) -> String {
     ngettext!("{} song selected", "{} songs selected", n as u32, n)
 }
 
+// A rough duration, for instance "26 min" or "2 h 5 min"
+fn minutes_label(duration_ms: u64) -> String {
+    let minutes = (duration_ms + 30_000) / 60_000;
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Total duration of a bunch of songs, as in "7 songs · 26 min"
+        gettext("{} min");
+        // translators: Total duration of a bunch of songs when it lasts more than an hour, as in "42 songs · 2 h 5 min"
+        gettext("{} h {} min");
+    }
+    if hours > 0 {
+        gettext!("{} h {} min", hours, minutes)
+    } else {
+        gettext!("{} min", minutes)
+    }
+}
+
+// Number of songs and how long they last, for instance "7 songs · 26 min"
+pub fn selection_summary_label(n: usize, duration_ms: u64) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Part of the summary of the selection, next to its total duration, as in "7 songs · 26 min"
+        ngettext("{} song", "{} songs", n as u32);
+    }
+    let songs = ngettext!("{} song", "{} songs", n as u32, n);
+    format!("{songs} · {}", minutes_label(duration_ms))
+}
+
+// What's left to play after the current song, for instance "12 songs left · 48 min"
+pub fn queue_summary_label(n: usize, duration_ms: Option<u64>) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Shown in the playback bar, how many songs will play after the current one, possibly followed by how long they last as in "12 songs left · 48 min"
+        ngettext("{} song left", "{} songs left", n as u32);
+    }
+    let songs = ngettext!("{} song left", "{} songs left", n as u32, n);
+    match duration_ms {
+        Some(duration_ms) => format!("{songs} · {}", minutes_label(duration_ms)),
+        None => songs,
+    }
+}
+
+pub fn n_followers_label(n: u32) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Shown under the name of a user on their profile page.
+        ngettext("{} follower", "{} followers", n);
+    }
+    ngettext!("{} follower", "{} followers", n, n)
+}
+
+pub fn n_following_label(n: u32) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Shown under the name of the logged in user on their own profile page, for how many artists they follow.
+        ngettext("Following {} artist", "Following {} artists", n);
+    }
+    ngettext!("Following {} artist", "Following {} artists", n, n)
+}
+
 pub fn more_from_label(artist: &str) -> String {
     // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
     if cfg!(debug_assertions) {
@@ -53,3 +199,169 @@ pub fn album_by_artist_label(album: &str, artist: &str) -> String {
         glib::markup_escape_text(artist)
     )
 }
+
+pub fn track_notification_body(artists: &str, album: &str) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This is the body of the notification shown when the track changes, it reads "<Artists> — <Album>"
+        gettext("{} — {}");
+    }
+    gettext!("{} — {}", artists, album)
+}
+
+pub fn song_row_label(artists: &str, title: &str, duration: &str) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This is read by screen readers for each song of a list, it reads "<Artists> – <Title>, <Duration>"
+        gettext("{} – {}, {}");
+    }
+    gettext!("{} – {}, {}", artists, title, duration)
+}
+
+pub fn skip_back_label(seconds: u32) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Tooltip of the button that skips back in a podcast episode, it reads "Back <n> seconds"
+        ngettext("Back {} second", "Back {} seconds", seconds);
+    }
+    ngettext!("Back {} second", "Back {} seconds", seconds, seconds)
+}
+
+pub fn skip_forward_label(seconds: u32) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Tooltip of the button that skips ahead in a podcast episode, it reads "Forward <n> seconds"
+        ngettext("Forward {} second", "Forward {} seconds", seconds);
+    }
+    ngettext!("Forward {} second", "Forward {} seconds", seconds, seconds)
+}
+
+pub fn listened_ago_label(minutes: u64) -> String {
+    let (hours, days) = (minutes / 60, minutes / (60 * 24));
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Shown next to friends in the friend activity panel, for how long ago they listened to a track.
+        ngettext("{} minute ago", "{} minutes ago", minutes as u32);
+        // translators: Shown next to friends in the friend activity panel, for how long ago they listened to a track.
+        ngettext("{} hour ago", "{} hours ago", hours as u32);
+        // translators: Shown next to friends in the friend activity panel, for how long ago they listened to a track.
+        ngettext("{} day ago", "{} days ago", days as u32);
+    }
+    if days > 0 {
+        ngettext!("{} day ago", "{} days ago", days as u32, days)
+    } else if hours > 0 {
+        ngettext!("{} hour ago", "{} hours ago", hours as u32, hours)
+    } else {
+        ngettext!("{} minute ago", "{} minutes ago", minutes as u32, minutes)
+    }
+}
+
+// Older songs just show the date they were saved on, formatted by the caller
+pub fn added_ago_label(days: u32) -> String {
+    let weeks = days / 7;
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Shown next to liked songs, for how many days ago they were saved.
+        ngettext("{} day ago", "{} days ago", days);
+        // translators: Shown next to liked songs, for how many weeks ago they were saved.
+        ngettext("{} week ago", "{} weeks ago", weeks);
+    }
+    match days {
+        // translators: Shown next to liked songs that were saved today.
+        0 => gettext("Today"),
+        // translators: Shown next to liked songs that were saved yesterday.
+        1 => gettext("Yesterday"),
+        2..=6 => ngettext!("{} day ago", "{} days ago", days, days),
+        _ => ngettext!("{} week ago", "{} weeks ago", weeks, weeks),
+    }
+}
+
+pub fn now_playing_label(title: &str, artist: &str) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This is read by screen readers for the current song, it reads "Now playing: <Title> by <Artist>"
+        gettext("Now playing: {} by {}");
+    }
+    gettext!("Now playing: {} by {}", title, artist)
+}
+
+pub fn up_next_label(title: &str, artist: &str) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Shown for a few seconds before the current track ends, it reads "Up next: <Title> by <Artist>"
+        gettext("Up next: {} by {}");
+    }
+    gettext!("Up next: {} by {}", title, artist)
+}
+
+pub fn next_from_label(source: &str) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Header above the rest of the play queue, after the songs queued by hand; the full text is "Next from <album or playlist>".
+        gettext("Next from {}");
+    }
+    gettext!("Next from {}", source)
+}
+
+pub fn cache_usage(size: u64) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: This shows how much disk space the cache uses, {} is a size such as "12.3 MB"
+        gettext("{} used");
+    }
+    gettext!("{} used", glib::format_size(size))
+}
+
+pub fn library_export_progress(progress: ExportProgress) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Progress of the library export, it reads "Saved tracks: <done>/<total>"
+        gettext("Saved tracks: {}/{}");
+        // translators: Progress of the library export, it reads "Saved albums: <done>"
+        gettext("Saved albums: {}");
+        // translators: Progress of the library export, it reads "Playlists: <done>/<total>"
+        gettext("Playlists: {}/{}");
+    }
+    match progress {
+        ExportProgress::SavedTracks { done, total } => {
+            gettext!("Saved tracks: {}/{}", done, total)
+        }
+        ExportProgress::SavedAlbums { done } => gettext!("Saved albums: {}", done),
+        ExportProgress::Playlists { done, total } => gettext!("Playlists: {}/{}", done, total),
+    }
+}
+
+pub fn library_import_summary(tracks: usize, albums: usize, playlists: usize) -> String {
+    // this is just to fool xgettext, it doesn't like macros (or rust for that matter) :(
+    if cfg!(debug_assertions) {
+        // translators: Text of the dialog confirming a library import, it reads "<n> saved songs, <n> saved albums and <n> playlists will be added to your library."
+        gettext("{} saved songs, {} saved albums and {} playlists will be added to your library.");
+    }
+    gettext!(
+        "{} saved songs, {} saved albums and {} playlists will be added to your library.",
+        tracks,
+        albums,
+        playlists
+    )
+}
+
+pub fn release_date_label(date: ReleaseDate) -> String {
+    let formatted = match date {
+        ReleaseDate {
+            month: Some(month),
+            day: Some(day),
+            ..
+        } => glib::DateTime::from_local(date.year as i32, month as i32, day as i32, 0, 0, 0.0)
+            // translators: Format of a full release date, see https://docs.gtk.org/glib/method.DateTime.format.html
+            .and_then(|d| d.format(&gettext("%B %-d, %Y"))),
+        ReleaseDate {
+            month: Some(month), ..
+        } => glib::DateTime::from_local(date.year as i32, month as i32, 1, 0, 0, 0.0)
+            // translators: Format of a release date when only the month is known, see https://docs.gtk.org/glib/method.DateTime.format.html
+            .and_then(|d| d.format(&gettext("%OB %Y"))),
+        _ => return date.year.to_string(),
+    };
+    formatted
+        .map(|s| s.to_string())
+        .unwrap_or_else(|_| date.year.to_string())
+}
@@ -8,7 +8,7 @@ use crate::app::components::utils::wrap_flowbox_item;
 use crate::app::components::{AlbumWidget, Component, EventListener};
 use crate::app::dispatch::Worker;
 use crate::app::models::AlbumModel;
-use crate::app::state::LoginEvent;
+use crate::app::state::{LoginEvent, NetworkEvent};
 use crate::app::{AppEvent, BrowserEvent, ListStore};
 
 mod imp {
@@ -24,6 +24,12 @@ mod imp {
         #[template_child]
         pub flowbox: TemplateChild<gtk::FlowBox>,
 
+        #[template_child]
+        pub album_list: TemplateChild<gtk::ListBox>,
+
+        #[template_child]
+        pub list_toggle: TemplateChild<gtk::ToggleButton>,
+
         #[template_child]
         pub status_page: TemplateChild<libadwaita::StatusPage>,
     }
@@ -88,6 +94,84 @@ impl LibraryWidget {
             });
     }
 
+    // The dense alternative to the covers, one album per row with its artist and year
+    fn bind_album_list(&self, store: &ListStore<AlbumModel>) {
+        self.imp()
+            .album_list
+            .bind_model(Some(store.unsafe_store()), |item| {
+                let album_model = item.downcast_ref::<AlbumModel>().unwrap();
+                Self::make_album_row(album_model).upcast()
+            });
+    }
+
+    fn make_album_row(album_model: &AlbumModel) -> gtk::ListBoxRow {
+        let make_label = |text: &str| {
+            gtk::Label::builder()
+                .label(text)
+                .xalign(0.0)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .build()
+        };
+
+        let title = make_label(&album_model.album());
+        title.set_hexpand(true);
+        title.set_width_chars(1);
+        let artist = make_label(&album_model.artist());
+        artist.set_hexpand(true);
+        artist.set_width_chars(1);
+        artist.add_css_class("dim-label");
+        let year = album_model.year();
+        let year = make_label(&if year > 0 {
+            year.to_string()
+        } else {
+            String::new()
+        });
+        year.set_width_chars(4);
+        year.add_css_class("dim-label");
+        year.add_css_class("numeric");
+
+        let columns = gtk::Box::builder()
+            .spacing(12)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(8)
+            .margin_bottom(8)
+            .build();
+        columns.append(&title);
+        columns.append(&artist);
+        columns.append(&year);
+
+        gtk::ListBoxRow::builder()
+            .activatable(true)
+            .child(&columns)
+            .build()
+    }
+
+    fn connect_list_row_activated<F>(&self, f: F)
+    where
+        F: Fn(u32) + 'static,
+    {
+        self.imp()
+            .album_list
+            .connect_row_activated(move |_, row| f(row.index() as u32));
+    }
+
+    fn connect_list_view_toggled<F>(&self, f: F)
+    where
+        F: Fn(bool) + 'static,
+    {
+        self.imp()
+            .list_toggle
+            .connect_toggled(move |toggle| f(toggle.is_active()));
+    }
+
+    fn set_list_view(&self, list_view: bool) {
+        let widget = self.imp();
+        widget.list_toggle.set_active(list_view);
+        widget.flowbox.set_visible(!list_view);
+        widget.album_list.set_visible(list_view);
+    }
+
     pub fn status_page(&self) -> &libadwaita::StatusPage {
         &self.imp().status_page
     }
@@ -103,10 +187,21 @@ impl Library {
     pub fn new(worker: Worker, model: LibraryModel) -> Self {
         let model = Rc::new(model);
         let widget = LibraryWidget::new();
+        // The page might only be built once logged in, after LoginCompleted
+        widget.set_list_view(model.is_list_view());
         widget.connect_bottom_edge(clone!(@weak model => move || {
             model.load_more_albums();
         }));
 
+        widget.connect_list_row_activated(clone!(@weak model => move |index| {
+            model.open_album_at(index);
+        }));
+
+        widget.connect_list_view_toggled(clone!(@weak model, @weak widget => move |list_view| {
+            model.set_list_view(list_view);
+            widget.set_list_view(list_view);
+        }));
+
         Self {
             widget,
             worker,
@@ -115,13 +210,15 @@ impl Library {
     }
 
     fn bind_flowbox(&self) {
+        let store = self.model.get_list_store().unwrap();
         self.widget.bind_albums(
             self.worker.clone(),
-            &self.model.get_list_store().unwrap(),
+            &store,
             clone!(@weak self.model as model => move |id| {
                 model.open_album(id);
             }),
         );
+        self.widget.bind_album_list(&store);
     }
 }
 
@@ -134,6 +231,10 @@ impl EventListener for Library {
             }
             AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => {
                 let _ = self.model.refresh_saved_albums();
+                self.widget.set_list_view(self.model.is_list_view());
+            }
+            AppEvent::NetworkEvent(NetworkEvent::BackOnline) => {
+                let _ = self.model.refresh_saved_albums();
             }
             AppEvent::BrowserEvent(BrowserEvent::LibraryUpdated) => {
                 self.widget
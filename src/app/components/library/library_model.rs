@@ -3,7 +3,7 @@ use std::ops::Deref;
 use std::rc::Rc;
 
 use crate::app::models::*;
-use crate::app::state::HomeState;
+use crate::app::state::{HomeState, SettingsAction};
 use crate::app::{ActionDispatcher, AppAction, AppModel, BrowserAction, ListStore};
 
 pub struct LibraryModel {
@@ -67,4 +67,31 @@ impl LibraryModel {
     pub fn open_album(&self, album_id: String) {
         self.dispatcher.dispatch(AppAction::ViewAlbum(album_id));
     }
+
+    pub fn open_album_at(&self, index: u32) {
+        let Some(uri) = self.get_list_store().map(|list| list.get(index).uri()) else {
+            return;
+        };
+        self.open_album(uri);
+    }
+
+    fn username(&self) -> Option<String> {
+        self.app_model.get_state().logged_user.user.clone()
+    }
+
+    pub fn is_list_view(&self) -> bool {
+        let Some(user) = self.username() else {
+            return false;
+        };
+        let state = self.app_model.get_state();
+        state.settings.settings.library_list_view.contains(&user)
+    }
+
+    pub fn set_list_view(&self, list_view: bool) {
+        let Some(user) = self.username() else {
+            return;
+        };
+        self.dispatcher
+            .dispatch(SettingsAction::SetLibraryListView(user, list_view).into());
+    }
 }
@@ -0,0 +1,128 @@
+use gettextrs::gettext;
+use gtk::prelude::*;
+use libadwaita::prelude::*;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use crate::api::library_export::{import_library, ExportFormat, ImportProgress, LibraryExport};
+use crate::app::components::{labels, EventListener};
+use crate::app::state::LoginAction;
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
+
+// Puts back a library backup (see the export in the preferences), once the user agreed to it
+pub struct LibraryImport {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    window: gtk::Window,
+}
+
+impl LibraryImport {
+    pub fn new(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        window: gtk::Window,
+    ) -> Self {
+        let drop_target = gtk::DropTarget::new(gio::File::static_type(), gdk::DragAction::COPY);
+        let drop_dispatcher = dispatcher.box_clone();
+        drop_target.connect_drop(move |_, value, _, _| {
+            let Some(path) = value.get::<gio::File>().ok().and_then(|file| file.path()) else {
+                return false;
+            };
+            drop_dispatcher.dispatch(AppAction::ImportLibrary(path));
+            true
+        });
+        window.add_controller(drop_target);
+
+        Self {
+            app_model,
+            dispatcher,
+            window,
+        }
+    }
+
+    async fn read_export(path: &Path) -> Option<LibraryExport> {
+        let (content, _) = gio::File::for_path(path)
+            .load_contents_future()
+            .await
+            .ok()?;
+        let text = String::from_utf8(content.to_vec()).ok()?;
+        let name = path.file_name()?.to_string_lossy();
+        LibraryExport::parse(&text, ExportFormat::from_file_name(&name))
+    }
+
+    fn read_and_ask_import(&self, path: PathBuf) {
+        let app_model = Rc::clone(&self.app_model);
+        let dispatcher = self.dispatcher.box_clone();
+        let window = self.window.clone();
+        glib::MainContext::default().spawn_local(async move {
+            match Self::read_export(&path).await {
+                Some(export) => Self::ask_import(&app_model, dispatcher, &window, export),
+                None => dispatcher.dispatch(AppAction::ShowNotification(
+                    // translators: Shown when a file dropped on the window isn't a library backup
+                    gettext("This file isn't a library backup"),
+                )),
+            }
+        });
+    }
+
+    fn ask_import(
+        app_model: &AppModel,
+        dispatcher: Box<dyn ActionDispatcher>,
+        window: &gtk::Window,
+        export: LibraryExport,
+    ) {
+        let dialog = libadwaita::MessageDialog::new(
+            Some(window),
+            // translators: Title of the dialog confirming a library import
+            Some(&gettext("Import Library?")),
+            Some(&labels::library_import_summary(
+                export.saved_tracks.len(),
+                export.saved_albums.len(),
+                export.playlists.len(),
+            )),
+        );
+        // translators: Button of the dialog confirming a library import
+        dialog.add_response("cancel", &gettext("Cancel"));
+        // translators: Button of the dialog confirming a library import
+        dialog.add_response("import", &gettext("Import"));
+        dialog.set_response_appearance("import", libadwaita::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("import"));
+        dialog.set_close_response("cancel");
+
+        let api = app_model.get_spotify();
+        let user = app_model.get_state().logged_user.user.clone();
+        dialog.connect_response(Some("import"), move |_, _| {
+            let Some(user) = user.clone() else {
+                return;
+            };
+            let (api, export) = (api.clone(), export.clone());
+            // Shared by the retries (after a token refresh, or from the error notification)
+            let progress: Arc<Mutex<ImportProgress>> = Default::default();
+            dispatcher.spawn_spotify_call_many(move || async move {
+                import_library(api.clone(), export, user.clone(), &progress).await?;
+                // The playlists just created show up in the sidebar
+                let playlists = api.get_saved_playlists(0, 30).await?;
+                let summaries = playlists
+                    .into_iter()
+                    .filter(|p| p.owner.id == user)
+                    .map(|p| p.into())
+                    .collect();
+                Ok(vec![
+                    LoginAction::SetUserPlaylists(summaries).into(),
+                    // translators: Shown once a library backup was imported
+                    AppAction::ShowNotification(gettext("Library imported")),
+                ])
+            });
+        });
+        dialog.present();
+    }
+}
+
+impl EventListener for LibraryImport {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::LibraryImportRequested(path) = event {
+            self.read_and_ask_import(path.clone());
+        }
+    }
+}
@@ -27,6 +27,18 @@ mod imp {
         #[template_child]
         pub login_button: TemplateChild<gtk::Button>,
 
+        #[template_child]
+        pub browser_login_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub cancel_browser_login_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub browser_login_fallback: TemplateChild<gtk::Box>,
+
+        #[template_child]
+        pub browser_login_url: TemplateChild<gtk::Label>,
+
         #[template_child]
         pub auth_error_container: TemplateChild<gtk::Revealer>,
     }
@@ -98,6 +110,44 @@ impl LoginWindow {
             }));
     }
 
+    fn connect_browser_login<F>(&self, on_login: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().browser_login_button.connect_clicked(move |_| {
+            on_login();
+        });
+    }
+
+    fn connect_cancel_browser_login<F>(&self, on_cancel: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp()
+            .cancel_browser_login_button
+            .connect_clicked(move |_| {
+                on_cancel();
+            });
+    }
+
+    // While waiting for the browser, the login can only be cancelled
+    fn set_browser_login_pending(&self, pending: bool) {
+        let widget = self.imp();
+        widget.browser_login_button.set_visible(!pending);
+        widget.cancel_browser_login_button.set_visible(pending);
+        widget.login_button.set_sensitive(!pending);
+        if !pending {
+            widget.browser_login_fallback.set_visible(false);
+        }
+    }
+
+    // When no browser could be opened, the user can still copy the address
+    fn show_browser_login_url(&self, url: &str) {
+        let widget = self.imp();
+        widget.browser_login_url.set_label(url);
+        widget.browser_login_fallback.set_visible(true);
+    }
+
     fn show_auth_error(&self, shown: bool) {
         let error_class = "error";
         let widget = self.imp();
@@ -154,6 +204,25 @@ impl Login {
             model.login(username.to_string(), password.to_string());
         }));
 
+        login_window.connect_browser_login(clone!(@weak model, @weak login_window => move || {
+            login_window.set_browser_login_pending(true);
+            model.login_with_browser(
+                clone!(@weak login_window => move |url| {
+                    login_window.show_browser_login_url(&url);
+                }),
+                clone!(@weak login_window => move || {
+                    login_window.set_browser_login_pending(false);
+                }),
+            );
+        }));
+
+        login_window.connect_cancel_browser_login(
+            clone!(@weak model, @weak login_window => move || {
+                model.cancel_browser_login();
+                login_window.set_browser_login_pending(false);
+            }),
+        );
+
         Self {
             parent,
             login_window,
@@ -171,6 +240,10 @@ impl Login {
         self.window().set_visible(true);
     }
 
+    fn hide(&self) {
+        self.window().set_visible(false);
+    }
+
     fn hide_and_save_creds(&self, credentials: Credentials) {
         self.window().set_visible(false);
         self.model.save_for_autologin(credentials);
@@ -189,11 +262,15 @@ impl EventListener for Login {
             ))) => {
                 self.hide_and_save_creds(creds.clone());
             }
+            // Token credentials are saved before trying to log in
+            AppEvent::LoginEvent(LoginEvent::LoginCompleted(LoginCompletedEvent::Token)) => {
+                self.hide();
+            }
             AppEvent::LoginEvent(LoginEvent::LoginFailed) => {
                 self.model.clear_saved_credentials();
                 self.reveal_error();
             }
-            AppEvent::Started => {
+            AppEvent::Started | AppEvent::LoginEvent(LoginEvent::AccountSwitched) => {
                 self.model.try_autologin();
             }
             AppEvent::LoginEvent(LoginEvent::LogoutCompleted | LoginEvent::LoginShown) => {
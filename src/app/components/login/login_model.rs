@@ -1,35 +1,59 @@
+use std::cell::RefCell;
 use std::time::SystemTime;
 
+use futures::future::{AbortHandle, Abortable};
 use gettextrs::*;
 
+use crate::api::oauth::{self, OAuthFlow, OAuthToken};
 use crate::app::credentials::Credentials;
 use crate::app::state::{LoginAction, TryLoginAction};
 use crate::app::{ActionDispatcher, AppAction, Worker};
+use crate::player::SCOPES;
 
 pub struct LoginModel {
     dispatcher: Box<dyn ActionDispatcher>,
     worker: Worker,
+    // To stop waiting for the browser
+    browser_login: RefCell<Option<AbortHandle>>,
 }
 
 impl LoginModel {
     pub fn new(dispatcher: Box<dyn ActionDispatcher>, worker: Worker) -> Self {
-        Self { dispatcher, worker }
+        Self {
+            dispatcher,
+            worker,
+            browser_login: RefCell::new(None),
+        }
     }
 
     pub fn try_autologin(&self) {
         self.dispatcher.dispatch_async(Box::pin(async {
             let action = match Credentials::retrieve().await {
-                Ok(creds) => LoginAction::TryLogin(if creds.token_expired() {
-                    TryLoginAction::Password {
-                        username: creds.username,
-                        password: creds.password,
-                    }
-                } else {
-                    TryLoginAction::Token {
+                Ok(creds) if !creds.token_expired() => {
+                    LoginAction::TryLogin(TryLoginAction::Token {
                         username: creds.username,
                         token: creds.token,
+                    })
+                }
+                // Logged in through the browser, we can get a fresh token without asking the user
+                Ok(Credentials {
+                    username,
+                    refresh_token: Some(refresh_token),
+                    ..
+                }) => match oauth::refresh_token(&refresh_token).await {
+                    Ok(token) => Self::save_oauth_token(username, token).await,
+                    Err(err) => {
+                        warn!("Could not refresh token: {}", err);
+                        LoginAction::ShowLogin
                     }
-                }),
+                },
+                Ok(creds) if !creds.password.is_empty() => {
+                    LoginAction::TryLogin(TryLoginAction::Password {
+                        username: creds.username,
+                        password: creds.password,
+                    })
+                }
+                Ok(_) => LoginAction::ShowLogin,
                 Err(err) => {
                     warn!("Could not retrieve credentials: {}", err);
                     LoginAction::ShowLogin
@@ -74,4 +98,77 @@ impl LoginModel {
         self.dispatcher
             .dispatch(LoginAction::TryLogin(TryLoginAction::Password { username, password }).into())
     }
+
+    // Opens the browser on Spotify's login page, and waits for it to send us back an authorization code.
+    // on_url gets the page to visit if no browser could be opened.
+    // on_done is called once that's over, unless cancel_browser_login was called first.
+    pub fn login_with_browser<U, F>(&self, on_url: U, on_done: F)
+    where
+        U: FnOnce(String) + 'static,
+        F: FnOnce() + 'static,
+    {
+        let (handle, registration) = AbortHandle::new_pair();
+        if let Some(previous) = self.browser_login.replace(Some(handle)) {
+            previous.abort();
+        }
+        let login = Abortable::new(Self::browser_login(on_url), registration);
+        let dispatcher = self.dispatcher.box_clone();
+        // Not on the worker, which would hold up every other task while waiting for the browser
+        glib::MainContext::default().spawn_local(async move {
+            if let Ok(action) = login.await {
+                dispatcher.dispatch(action);
+                on_done();
+            }
+        });
+    }
+
+    pub fn cancel_browser_login(&self) {
+        if let Some(handle) = self.browser_login.take() {
+            handle.abort();
+        }
+    }
+
+    async fn browser_login(on_url: impl FnOnce(String)) -> AppAction {
+        let result = async {
+            let flow = OAuthFlow::start(&SCOPES.replace(',', " ")).await?;
+            if let Err(err) =
+                gio::AppInfo::launch_default_for_uri(&flow.url, None::<&gio::AppLaunchContext>)
+            {
+                warn!("Could not open browser: {}", err);
+                on_url(flow.url.clone());
+            }
+            let token = flow.complete().await?;
+            let username = oauth::get_username(&token.access_token).await?;
+            Ok::<_, oauth::OAuthError>(Self::save_oauth_token(username, token).await)
+        };
+        match result.await {
+            Ok(action) => action.into(),
+            Err(err) => {
+                warn!("Browser login failed: {}", err);
+                AppAction::ShowNotification(gettext(
+                    // translators: This notification shows up when logging in through the browser did not work
+                    "Could not log in through the browser.",
+                ))
+            }
+        }
+    }
+
+    // The access token works for both the Web API and librespot
+    async fn save_oauth_token(username: String, token: OAuthToken) -> LoginAction {
+        let credentials = Credentials {
+            username: username.clone(),
+            password: String::new(),
+            token: token.access_token.clone(),
+            token_expiry_time: Some(token.expiry_time),
+            country: String::new(),
+            refresh_token: token.refresh_token,
+        };
+        if let Err(err) = credentials.save().await {
+            warn!("Could not save credentials: {}", err);
+        }
+        LoginAction::TryLogin(TryLoginAction::Token {
+            username,
+            token: token.access_token,
+        })
+    }
 }
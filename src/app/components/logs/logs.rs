@@ -0,0 +1,145 @@
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use log::Level;
+use std::time::SystemTime;
+
+use crate::app::components::EventListener;
+use crate::app::AppEvent;
+use crate::logging::{recent_lines, LogLine};
+
+mod imp {
+
+    use super::*;
+    use libadwaita::subclass::prelude::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/dev/alextren/Spot/components/logs.ui")]
+    pub struct LogsWindow {
+        #[template_child]
+        pub level: TemplateChild<gtk::DropDown>,
+
+        #[template_child]
+        pub copy_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub refresh_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub text_view: TemplateChild<gtk::TextView>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for LogsWindow {
+        const NAME: &'static str = "LogsWindow";
+        type Type = super::LogsWindow;
+        type ParentType = libadwaita::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for LogsWindow {}
+    impl WidgetImpl for LogsWindow {}
+    impl WindowImpl for LogsWindow {}
+    impl AdwWindowImpl for LogsWindow {}
+}
+
+glib::wrapper! {
+    pub struct LogsWindow(ObjectSubclass<imp::LogsWindow>) @extends gtk::Widget, gtk::Window, libadwaita::Window;
+}
+
+impl LogsWindow {
+    pub fn new() -> Self {
+        let window: Self = glib::Object::new();
+        let widget = window.imp();
+
+        // Show warnings and errors by default
+        widget.level.set_selected(1);
+        widget
+            .level
+            .connect_selected_notify(clone!(@weak window => move |_| window.refresh()));
+        widget
+            .refresh_button
+            .connect_clicked(clone!(@weak window => move |_| window.refresh()));
+        widget
+            .copy_button
+            .connect_clicked(clone!(@weak window => move |_| window.copy_to_clipboard()));
+
+        window
+    }
+
+    fn max_level(&self) -> Level {
+        match self.imp().level.selected() {
+            0 => Level::Error,
+            1 => Level::Warn,
+            2 => Level::Info,
+            _ => Level::Debug,
+        }
+    }
+
+    fn format_line(line: &LogLine) -> String {
+        let secs = line
+            .time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let time = glib::DateTime::from_unix_local(secs)
+            .and_then(|t| t.format("%T"))
+            .map(|t| t.to_string())
+            .unwrap_or_default();
+        format!(
+            "{} {:<5} [{}] {}",
+            time, line.level, line.target, line.message
+        )
+    }
+
+    pub fn refresh(&self) {
+        let text = recent_lines(self.max_level())
+            .iter()
+            .map(Self::format_line)
+            .collect::<Vec<String>>()
+            .join("\n");
+        let text_view = &self.imp().text_view;
+        text_view.buffer().set_text(&text);
+
+        // Most recent lines are the most interesting
+        let mut end = text_view.buffer().end_iter();
+        text_view.scroll_to_iter(&mut end, 0.0, false, 0.0, 0.0);
+    }
+
+    fn copy_to_clipboard(&self) {
+        let buffer = self.imp().text_view.buffer();
+        let (start, end) = buffer.bounds();
+        self.clipboard().set_text(&buffer.text(&start, &end, false));
+    }
+}
+
+pub struct Logs {
+    parent: gtk::Window,
+    logs_window: LogsWindow,
+}
+
+impl Logs {
+    pub fn new(parent: gtk::Window) -> Self {
+        Self {
+            parent,
+            logs_window: LogsWindow::new(),
+        }
+    }
+
+    pub fn show_self(&self) {
+        self.logs_window.set_transient_for(Some(&self.parent));
+        self.logs_window.refresh();
+        self.logs_window.present();
+    }
+}
+
+impl EventListener for Logs {
+    fn on_event(&mut self, _: &AppEvent) {}
+}
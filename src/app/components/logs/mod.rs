@@ -0,0 +1,3 @@
+mod logs;
+
+pub use logs::*;
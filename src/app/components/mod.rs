@@ -10,8 +10,11 @@ use std::cell::RefCell;
 use std::collections::HashSet;
 use std::future::Future;
 
+use futures::future::BoxFuture;
+
 use crate::api::SpotifyApiError;
-use crate::app::{state::LoginAction, ActionDispatcher, AppAction, AppEvent};
+use crate::app::state::{BrowserAction, LibraryMutation, LoginAction};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, Retry};
 
 mod navigation;
 pub use navigation::*;
@@ -28,9 +31,51 @@ pub use login::*;
 mod settings;
 pub use settings::*;
 
+mod logs;
+pub use logs::*;
+
+mod inspector;
+pub use inspector::*;
+
 mod player_notifier;
 pub use player_notifier::PlayerNotifier;
 
+mod desktop_notifier;
+pub use desktop_notifier::DesktopNotifier;
+
+mod scrobbling_notifier;
+pub use scrobbling_notifier::ScrobblingNotifier;
+
+mod episode_progress_notifier;
+pub use episode_progress_notifier::EpisodeProgressNotifier;
+
+mod play_history_notifier;
+pub use play_history_notifier::PlayHistoryNotifier;
+
+mod up_next_notifier;
+pub use up_next_notifier::UpNextNotifier;
+
+mod network_watcher;
+pub use network_watcher::NetworkWatcher;
+
+mod home_snapshot;
+pub use home_snapshot::HomeSnapshots;
+
+mod undo;
+pub use undo::Undo;
+
+mod library_import;
+pub use library_import::LibraryImport;
+
+mod navigation_snapshot;
+pub use navigation_snapshot::NavigationSnapshot;
+
+mod track_player;
+pub use track_player::TrackPlayer;
+
+mod playback_query;
+pub use playback_query::PlaybackQuery;
+
 mod library;
 pub use library::*;
 
@@ -52,15 +97,30 @@ pub use artist_details::*;
 mod user_details;
 pub use user_details::*;
 
+mod show_details;
+pub use show_details::*;
+
+mod share;
+pub use share::*;
+
 mod now_playing;
 pub use now_playing::*;
 
 mod device_selector;
 pub use device_selector::*;
 
+mod friend_activity;
+pub use friend_activity::*;
+
+mod car_mode;
+pub use car_mode::*;
+
 mod saved_tracks;
 pub use saved_tracks::*;
 
+mod saved_episodes;
+pub use saved_episodes::*;
+
 mod user_menu;
 pub use user_menu::*;
 
@@ -97,6 +157,8 @@ pub fn expose_custom_widgets() {
     selection::expose_widgets();
     headerbar::expose_widgets();
     device_selector::expose_widgets();
+    friend_activity::expose_widgets();
+    car_mode::expose_widgets();
     playlist_details::expose_widgets();
     scrolling_header::expose_widgets();
 }
@@ -115,29 +177,93 @@ impl dyn ActionDispatcher {
         C: 'static + Send + Clone + FnOnce() -> F,
         F: Send + Future<Output = Result<Vec<AppAction>, SpotifyApiError>>,
     {
+        self.dispatch_many_async(call_spotify(call))
+    }
+
+    // For long-running calls: those above go through the worker one at a time, which would hold up every other call
+    fn spawn_spotify_call_many<F, C>(&self, call: C)
+    where
+        C: 'static + Send + Clone + FnOnce() -> F,
+        F: Send + Future<Output = Result<Vec<AppAction>, SpotifyApiError>>,
+    {
+        let dispatcher = self.box_clone();
+        glib::MainContext::default().spawn_local(async move {
+            dispatcher.dispatch_many(call_spotify(call).await);
+        });
+    }
+
+    // Shows a change to the library right away, and takes it back if Spotify doesn't go along with it
+    fn call_spotify_and_mutate<F, C>(&self, mutation: LibraryMutation, call: C)
+    where
+        C: 'static + Send + Clone + FnOnce() -> F,
+        F: Send + Future<Output = Result<Vec<AppAction>, SpotifyApiError>>,
+    {
+        self.dispatch(BrowserAction::ApplyMutation(mutation.clone()).into());
+        // Whatever happens, the mutation ends up confirmed or reverted, so errors are all handled here
         self.dispatch_many_async(Box::pin(async move {
             let first_call = call.clone();
-            let result = first_call().await;
-            match result {
-                Ok(actions) => actions,
-                Err(SpotifyApiError::NoToken) => vec![],
-                Err(SpotifyApiError::InvalidToken) => {
-                    let mut retried = call().await.unwrap_or_else(|_| Vec::new());
-                    retried.insert(0, LoginAction::RefreshToken.into());
-                    retried
+            let (result, token_expired) = match first_call().await {
+                Err(SpotifyApiError::InvalidToken) => (call().await, true),
+                result => (result, false),
+            };
+            let mut actions = match result {
+                Ok(mut actions) => {
+                    actions.insert(0, BrowserAction::ConfirmMutation(mutation).into());
+                    actions
                 }
                 Err(err) => {
                     error!("Spotify API error: {}", err);
-                    vec![AppAction::ShowNotification(gettext(
-                        // translators: This notification is the default message for unhandled errors. Logs refer to console output.
-                        "An error occured. Check logs for details!",
-                    ))]
+                    vec![
+                        BrowserAction::RevertMutation(mutation).into(),
+                        AppAction::ShowNotification(labels::LIBRARY_UPDATE_FAILED.clone()),
+                    ]
                 }
+            };
+            if token_expired {
+                actions.insert(0, LoginAction::RefreshToken.into());
             }
+            actions
         }))
     }
 }
 
+// The actions resulting from an API call, or telling what went wrong with it
+fn call_spotify<F, C>(call: C) -> BoxFuture<'static, Vec<AppAction>>
+where
+    C: 'static + Send + Clone + FnOnce() -> F,
+    F: Send + Future<Output = Result<Vec<AppAction>, SpotifyApiError>>,
+{
+    Box::pin(async move {
+        let first_call = call.clone();
+        let result = first_call().await;
+        match result {
+            Ok(actions) => actions,
+            Err(SpotifyApiError::NoToken) => vec![],
+            // Only loads fail this way, the offline banner already says it all
+            Err(SpotifyApiError::NoConnection) => vec![],
+            Err(SpotifyApiError::Offline) => vec![AppAction::ShowNotification(gettext(
+                // translators: This notification shows up when trying to do something that needs a connection while in offline mode.
+                "Not available in offline mode",
+            ))],
+            Err(SpotifyApiError::InvalidToken) => {
+                let mut retried = call().await.unwrap_or_else(|_| Vec::new());
+                retried.insert(0, LoginAction::RefreshToken.into());
+                retried
+            }
+            Err(err) => {
+                error!("Spotify API error: {}", err);
+                vec![AppAction::ShowRetryableNotification(
+                    gettext(
+                        // translators: This notification is the default message for unhandled errors. Logs refer to console output.
+                        "An error occured. Check logs for details!",
+                    ),
+                    Retry::new(move || call_spotify(call.clone())),
+                )]
+            }
+        }
+    })
+}
+
 thread_local!(static CSS_ADDED: RefCell<HashSet<&'static str>> = RefCell::new(HashSet::new()));
 
 pub fn display_add_css_provider(resource: &'static str) {
@@ -3,7 +3,7 @@ use std::rc::Rc;
 use crate::app::components::sidebar::{Sidebar, SidebarModel};
 use crate::app::components::*;
 use crate::app::state::SelectionContext;
-use crate::app::{ActionDispatcher, AppModel, Worker};
+use crate::app::{ActionDispatcher, AppModel, ScopedActionDispatcher, Worker};
 
 pub struct ScreenFactory {
     app_model: Rc<AppModel>,
@@ -27,6 +27,11 @@ impl ScreenFactory {
         }
     }
 
+    // Pushed screens can be popped while still loading, what they requested is then aborted
+    fn scoped_dispatcher(&self) -> Box<dyn ActionDispatcher> {
+        Box::new(ScopedActionDispatcher::new(self.dispatcher.box_clone()))
+    }
+
     pub fn make_library(&self) -> impl ListenerComponent {
         let model = LibraryModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
         let screen_model = DefaultHeaderBarModel::new(
@@ -89,31 +94,51 @@ impl ScreenFactory {
         )
     }
 
+    pub fn make_saved_episodes(&self) -> impl ListenerComponent {
+        let screen_model = DefaultHeaderBarModel::new(
+            Some(gettext("Saved episodes")),
+            None,
+            Rc::clone(&self.app_model),
+            self.dispatcher.box_clone(),
+        );
+        let model = Rc::new(SavedEpisodesModel::new(
+            Rc::clone(&self.app_model),
+            self.dispatcher.box_clone(),
+        ));
+        StandardScreen::new(
+            SavedEpisodes::new(model, self.worker.clone()),
+            &self.leaflet,
+            Rc::new(screen_model),
+        )
+    }
+
     pub fn make_album_details(&self, id: String) -> impl ListenerComponent {
+        let dispatcher = self.scoped_dispatcher();
         let model = Rc::new(DetailsModel::new(
             id,
             Rc::clone(&self.app_model),
-            self.dispatcher.box_clone(),
+            dispatcher.box_clone(),
         ));
         Details::new(model, self.worker.clone(), &self.leaflet)
     }
 
     pub fn make_search_results(&self) -> impl ListenerComponent {
-        let model =
-            SearchResultsModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
+        let dispatcher = self.scoped_dispatcher();
+        let model = SearchResultsModel::new(Rc::clone(&self.app_model), dispatcher.box_clone());
         SearchResults::new(model, self.worker.clone(), &self.leaflet)
     }
 
     pub fn make_artist_details(&self, id: String) -> impl ListenerComponent {
+        let dispatcher = self.scoped_dispatcher();
         let model = Rc::new(ArtistDetailsModel::new(
             id,
             Rc::clone(&self.app_model),
-            self.dispatcher.box_clone(),
+            dispatcher.box_clone(),
         ));
         let screen_model = SimpleHeaderBarModelWrapper::new(
             Rc::clone(&model),
             Rc::clone(&self.app_model),
-            self.dispatcher.box_clone(),
+            dispatcher.box_clone(),
         );
         StandardScreen::new(
             ArtistDetails::new(model, self.worker.clone()),
@@ -123,27 +148,44 @@ impl ScreenFactory {
     }
 
     pub fn make_playlist_details(&self, id: String) -> impl ListenerComponent {
+        let dispatcher = self.scoped_dispatcher();
         let model = Rc::new(PlaylistDetailsModel::new(
             id,
             Rc::clone(&self.app_model),
-            self.dispatcher.box_clone(),
+            dispatcher.box_clone(),
         ));
         PlaylistDetails::new(model, self.worker.clone())
     }
 
     pub fn make_user_details(&self, id: String) -> impl ListenerComponent {
+        let dispatcher = self.scoped_dispatcher();
         let screen_model = DefaultHeaderBarModel::new(
             None,
             None,
             Rc::clone(&self.app_model),
-            self.dispatcher.box_clone(),
+            dispatcher.box_clone(),
         );
-        let model =
-            UserDetailsModel::new(id, Rc::clone(&self.app_model), self.dispatcher.box_clone());
+        let model = UserDetailsModel::new(id, Rc::clone(&self.app_model), dispatcher.box_clone());
         StandardScreen::new(
             UserDetails::new(model, self.worker.clone()),
             &self.leaflet,
             Rc::new(screen_model),
         )
     }
+
+    pub fn make_show_details(&self, id: String) -> impl ListenerComponent {
+        let dispatcher = self.scoped_dispatcher();
+        let screen_model = DefaultHeaderBarModel::new(
+            None,
+            None,
+            Rc::clone(&self.app_model),
+            dispatcher.box_clone(),
+        );
+        let model = ShowDetailsModel::new(id, Rc::clone(&self.app_model), dispatcher.box_clone());
+        StandardScreen::new(
+            ShowDetails::new(model, self.worker.clone()),
+            &self.leaflet,
+            Rc::new(screen_model),
+        )
+    }
 }
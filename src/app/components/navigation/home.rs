@@ -1,63 +1,70 @@
 use gtk::prelude::*;
+use std::rc::Rc;
 
 use crate::app::components::sidebar::SidebarDestination;
-use crate::app::components::{Component, EventListener, ScreenFactory};
+use crate::app::components::{Component, EventListener, ListenerComponent, ScreenFactory};
 use crate::app::{AppEvent, BrowserEvent};
 
 pub struct HomePane {
     stack: gtk::Stack,
+    screen_factory: Rc<ScreenFactory>,
     components: Vec<Box<dyn EventListener>>,
 }
 
 impl HomePane {
-    pub fn new(listbox: gtk::ListBox, screen_factory: &ScreenFactory) -> Self {
-        let library = screen_factory.make_library();
-        let saved_playlists = screen_factory.make_saved_playlists();
-        let saved_tracks = screen_factory.make_saved_tracks();
-        let now_playing = screen_factory.make_now_playing();
+    // Only the page initially visible is built, the others are when first shown
+    pub fn new(listbox: gtk::ListBox, screen_factory: Rc<ScreenFactory>, page: &str) -> Self {
         let sidebar = screen_factory.make_sidebar(listbox);
 
         let stack = gtk::Stack::new();
         stack.set_transition_type(gtk::StackTransitionType::Crossfade);
 
-        let dest = SidebarDestination::Library;
-        stack.add_titled(
-            library.get_root_widget(),
-            Option::from(dest.id()),
-            &dest.title(),
-        );
-
-        let dest = SidebarDestination::SavedTracks;
-        stack.add_titled(
-            saved_tracks.get_root_widget(),
-            Option::from(dest.id()),
-            &dest.title(),
-        );
+        let mut home = Self {
+            stack,
+            screen_factory,
+            components: vec![Box::new(sidebar)],
+        };
+        home.make_page(page);
+        home.stack.set_visible_child_name(page);
+        home
+    }
 
-        let dest = SidebarDestination::SavedPlaylists;
-        stack.add_titled(
-            saved_playlists.get_root_widget(),
+    fn add_page<C>(&mut self, dest: SidebarDestination, component: C)
+    where
+        C: ListenerComponent + 'static,
+    {
+        self.stack.add_titled(
+            component.get_root_widget(),
             Option::from(dest.id()),
             &dest.title(),
         );
+        self.components.push(Box::new(component));
+    }
 
-        let dest = SidebarDestination::NowPlaying;
-        stack.add_titled(
-            now_playing.get_root_widget(),
-            Option::from(dest.id()),
-            &dest.title(),
-        );
+    fn make_page(&mut self, page: &str) -> Option<()> {
+        let dest = SidebarDestination::home_page(page)?;
+        let factory = Rc::clone(&self.screen_factory);
+        match dest {
+            SidebarDestination::Library => self.add_page(dest, factory.make_library()),
+            SidebarDestination::SavedTracks => self.add_page(dest, factory.make_saved_tracks()),
+            SidebarDestination::SavedEpisodes => self.add_page(dest, factory.make_saved_episodes()),
+            SidebarDestination::SavedPlaylists => {
+                self.add_page(dest, factory.make_saved_playlists())
+            }
+            SidebarDestination::NowPlaying => self.add_page(dest, factory.make_now_playing()),
+            SidebarDestination::Playlist(_) | SidebarDestination::Pinned(_) => return None,
+        }
+        Some(())
+    }
 
-        Self {
-            stack,
-            components: vec![
-                Box::new(sidebar),
-                Box::new(library),
-                Box::new(saved_playlists),
-                Box::new(saved_tracks),
-                Box::new(now_playing),
-            ],
+    fn show_page(&mut self, page: &str) {
+        if self.stack.child_by_name(page).is_none() && self.make_page(page).is_some() {
+            // The app was started before this page existed, which is when pages load their content
+            if let Some(component) = self.components.last_mut() {
+                component.on_event(&AppEvent::Started);
+            }
         }
+        self.stack.set_visible_child_name(page);
     }
 }
 
@@ -75,11 +82,10 @@ impl EventListener for HomePane {
     fn on_event(&mut self, event: &AppEvent) {
         match event {
             AppEvent::NowPlayingShown => {
-                self.stack
-                    .set_visible_child_name(SidebarDestination::NowPlaying.id());
+                self.show_page(SidebarDestination::NowPlaying.id());
             }
             AppEvent::BrowserEvent(BrowserEvent::HomeVisiblePageChanged(page)) => {
-                self.stack.set_visible_child_name(page);
+                self.show_page(page);
             }
             _ => {}
         }
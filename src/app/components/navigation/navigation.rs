@@ -1,7 +1,8 @@
-use gtk::traits::WidgetExt;
+use gtk::prelude::*;
 use libadwaita::NavigationDirection;
 use std::rc::Rc;
 
+use crate::app::components::utils::is_horizontal_swipe;
 use crate::app::components::{EventListener, ListenerComponent};
 use crate::app::state::ScreenName;
 use crate::app::{AppEvent, BrowserEvent};
@@ -13,7 +14,7 @@ pub struct Navigation {
     leaflet: libadwaita::Leaflet,
     navigation_stack: gtk::Stack,
     home_listbox: gtk::ListBox,
-    screen_factory: ScreenFactory,
+    screen_factory: Rc<ScreenFactory>,
     children: Vec<Box<dyn ListenerComponent>>,
 }
 
@@ -43,12 +44,22 @@ impl Navigation {
             })
         );
 
+        // Swiping right goes back, like the back button. Swiping left is left to song rows.
+        let swipe_gesture = gtk::GestureSwipe::new();
+        swipe_gesture.set_touch_only(true);
+        swipe_gesture.connect_swipe(clone!(@weak model => move |_, velocity_x, velocity_y| {
+            if is_horizontal_swipe(velocity_x, velocity_y) && velocity_x > 0.0 && model.can_go_back() {
+                model.go_back();
+            }
+        }));
+        navigation_stack.add_controller(swipe_gesture);
+
         Self {
             model,
             leaflet,
             navigation_stack,
             home_listbox,
-            screen_factory,
+            screen_factory: Rc::new(screen_factory),
             children: vec![],
         }
     }
@@ -56,7 +67,8 @@ impl Navigation {
     fn make_home(&self) -> Box<dyn ListenerComponent> {
         Box::new(HomePane::new(
             self.home_listbox.clone(),
-            &self.screen_factory,
+            Rc::clone(&self.screen_factory),
+            self.model.home_visible_page(),
         ))
     }
 
@@ -64,7 +76,57 @@ impl Navigation {
         self.leaflet.navigate(NavigationDirection::Back);
     }
 
+    // Screens that have several pages (like home) have several scrolled windows, we want the one on screen
+    fn visible_scrolled_window(widget: &gtk::Widget) -> Option<gtk::ScrolledWindow> {
+        let mut widgets = vec![widget.clone()];
+        while let Some(widget) = widgets.pop() {
+            if !widget.is_mapped() {
+                continue;
+            }
+            if let Some(scrolled_window) = widget.downcast_ref::<gtk::ScrolledWindow>() {
+                return Some(scrolled_window.clone());
+            }
+            let mut child = widget.last_child();
+            while let Some(next) = child {
+                child = next.prev_sibling();
+                widgets.push(next);
+            }
+        }
+        None
+    }
+
+    fn save_scroll_offset(&self) {
+        let name = self
+            .navigation_stack
+            .visible_child_name()
+            .and_then(|name| ScreenName::from_identifier(name.as_str()));
+        let offset = self
+            .navigation_stack
+            .visible_child()
+            .and_then(|widget| Self::visible_scrolled_window(&widget))
+            .map(|scrolled_window| scrolled_window.vadjustment().value());
+        if let (Some(name), Some(offset)) = (name, offset) {
+            self.model.set_scroll_offset(name, offset);
+        }
+    }
+
+    // Done once the screen is shown again, as hidden widgets aren't mapped
+    fn restore_scroll_offset(&self) {
+        let offset = self.model.scroll_offset(&self.model.visible_child_name());
+        let widget = self.navigation_stack.visible_child();
+        if let (Some(offset), Some(widget)) = (offset, widget) {
+            glib::source::idle_add_local_once(move || {
+                if let Some(scrolled_window) = Self::visible_scrolled_window(&widget) {
+                    scrolled_window.vadjustment().set_value(offset);
+                }
+            });
+        }
+    }
+
     fn push_screen(&mut self, name: &ScreenName) {
+        // Before the screen we're leaving is hidden
+        self.save_scroll_offset();
+
         let component: Box<dyn ListenerComponent> = match name {
             ScreenName::Home => self.make_home(),
             ScreenName::AlbumDetails(id) => {
@@ -78,6 +140,9 @@ impl Navigation {
                 Box::new(self.screen_factory.make_playlist_details(id.to_owned()))
             }
             ScreenName::User(id) => Box::new(self.screen_factory.make_user_details(id.to_owned())),
+            ScreenName::ShowDetails(id) => {
+                Box::new(self.screen_factory.make_show_details(id.to_owned()))
+            }
         };
 
         let widget = component.get_root_widget().clone();
@@ -105,6 +170,7 @@ impl Navigation {
         if let Some(child) = popped {
             self.navigation_stack.remove(child.get_root_widget());
         }
+        self.restore_scroll_offset();
     }
 
     fn pop_to(&mut self, screen: &ScreenName) {
@@ -114,6 +180,7 @@ impl Navigation {
         for widget in remainder {
             self.navigation_stack.remove(widget.get_root_widget());
         }
+        self.restore_scroll_offset();
     }
 }
 
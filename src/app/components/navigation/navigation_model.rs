@@ -20,11 +20,38 @@ impl NavigationModel {
         self.app_model.map_state(|s| s.browser.current_screen())
     }
 
+    pub fn home_visible_page(&self) -> &'static str {
+        let state = self.app_model.get_state();
+        state
+            .browser
+            .home_state()
+            .map(|home| home.visible_page)
+            .unwrap_or("library")
+    }
+
     pub fn set_nav_hidden(&self, hidden: bool) {
         self.dispatcher
             .dispatch(BrowserAction::SetNavigationHidden(hidden).into());
     }
 
+    pub fn can_go_back(&self) -> bool {
+        self.app_model.get_state().browser.can_pop()
+    }
+
+    pub fn go_back(&self) {
+        self.dispatcher
+            .dispatch(BrowserAction::NavigationPop.into());
+    }
+
+    pub fn scroll_offset(&self, name: &ScreenName) -> Option<f64> {
+        self.app_model.get_state().browser.scroll_offset(name)
+    }
+
+    pub fn set_scroll_offset(&self, name: ScreenName, offset: f64) {
+        self.dispatcher
+            .dispatch(BrowserAction::SetScrollOffset(name, offset).into());
+    }
+
     pub fn children_count(&self) -> usize {
         self.app_model.get_state().browser.count()
     }
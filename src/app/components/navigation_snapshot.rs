@@ -0,0 +1,109 @@
+use gio::prelude::{SettingsExt, SettingsExtManual};
+use glib::ToVariant;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::app::components::sidebar::SidebarDestination;
+use crate::app::components::EventListener;
+use crate::app::state::{BrowserAction, BrowserEvent, LoginEvent, ScreenName};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
+
+const SETTINGS: &str = "dev.alextren.Spot";
+
+// Remembers which screens were open (and which page of the home screen), and opens them again on the next run
+pub struct NavigationSnapshot {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    settings: gio::Settings,
+    restored: bool,
+}
+
+impl NavigationSnapshot {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            settings: gio::Settings::new(SETTINGS),
+            restored: false,
+        }
+    }
+
+    // Screens need to be logged in to load, so this waits for the login to complete
+    fn restore(&mut self) {
+        if self.restored {
+            return;
+        }
+        self.restored = true;
+        // Offsets first, so that they're there when navigating back
+        let offsets: HashMap<String, f64> = self
+            .settings
+            .value("navigation-scroll-offsets")
+            .get()
+            .unwrap_or_default();
+        let offsets = offsets.into_iter().filter_map(|(identifier, offset)| {
+            let name = ScreenName::from_identifier(&identifier)?;
+            Some(AppAction::from(BrowserAction::SetScrollOffset(
+                name, offset,
+            )))
+        });
+        let home_page = SidebarDestination::home_page(&self.settings.string("home-visible-page"))
+            .map(|page| AppAction::from(BrowserAction::SetHomeVisiblePage(page.id())));
+        let stack = self.settings.strv("navigation-stack");
+        let pushes = stack
+            .iter()
+            .filter_map(|identifier| ScreenName::from_identifier(identifier.to_str()))
+            .map(|name| AppAction::from(BrowserAction::NavigationPush(name)));
+        self.dispatcher
+            .dispatch_many(offsets.chain(home_page).chain(pushes).collect());
+    }
+
+    fn save(&self) {
+        // Home is always at the bottom of the stack, no need to remember it
+        let state = self.app_model.get_state();
+        let identifiers: Vec<String> = state
+            .browser
+            .screen_names()
+            .filter(|name| **name != ScreenName::Home)
+            .map(|name| name.identifier().into_owned())
+            .collect();
+        let identifiers: Vec<&str> = identifiers.iter().map(|s| s.as_str()).collect();
+        if let Err(err) = self
+            .settings
+            .set_strv("navigation-stack", identifiers.as_slice())
+        {
+            warn!("Could not save navigation: {}", err);
+        }
+        let offsets = state.browser.scroll_offsets().to_variant();
+        if let Err(err) = self
+            .settings
+            .set_value("navigation-scroll-offsets", &offsets)
+        {
+            warn!("Could not save scroll offsets: {}", err);
+        }
+    }
+
+    fn save_home_page(&self, page: &str) {
+        if let Err(err) = self.settings.set_string("home-visible-page", page) {
+            warn!("Could not save the home page: {}", err);
+        }
+    }
+}
+
+impl EventListener for NavigationSnapshot {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => self.restore(),
+            // Saving before having restored would forget what we want to restore
+            AppEvent::BrowserEvent(
+                BrowserEvent::NavigationPushed(_)
+                | BrowserEvent::NavigationPopped
+                | BrowserEvent::NavigationPoppedTo(_)
+                | BrowserEvent::ScrollOffsetChanged(_),
+            ) if self.restored => self.save(),
+            AppEvent::BrowserEvent(BrowserEvent::HomeVisiblePageChanged(page)) if self.restored => {
+                self.save_home_page(page)
+            }
+            _ => {}
+        }
+    }
+}
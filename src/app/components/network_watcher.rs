@@ -0,0 +1,35 @@
+use gio::prelude::*;
+
+use crate::app::components::EventListener;
+use crate::app::state::NetworkAction;
+use crate::app::{ActionDispatcher, AppEvent};
+
+// Tells the app whenever the network comes and goes, as seen by GNetworkMonitor
+pub struct NetworkWatcher {
+    monitor: gio::NetworkMonitor,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl NetworkWatcher {
+    pub fn new(dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        let monitor = gio::NetworkMonitor::default();
+        let changes_dispatcher = dispatcher.box_clone();
+        monitor.connect_network_changed(move |_, available| {
+            changes_dispatcher.dispatch(NetworkAction::SetConnected(available).into());
+        });
+        Self {
+            monitor,
+            dispatcher,
+        }
+    }
+}
+
+impl EventListener for NetworkWatcher {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::Started = event {
+            let available = self.monitor.is_network_available();
+            self.dispatcher
+                .dispatch(NetworkAction::SetConnected(available).into());
+        }
+    }
+}
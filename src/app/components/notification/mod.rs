@@ -1,15 +1,23 @@
 use crate::app::components::EventListener;
-use crate::app::AppEvent;
+use crate::app::state::UNDO_TIMEOUT;
+use crate::app::{ActionDispatcher, AppEvent, Retry};
 use gettextrs::*;
 use glib::ToVariant;
 
 pub struct Notification {
     toast_overlay: libadwaita::ToastOverlay,
+    dispatcher: Box<dyn ActionDispatcher>,
 }
 
 impl Notification {
-    pub fn new(toast_overlay: libadwaita::ToastOverlay) -> Self {
-        Self { toast_overlay }
+    pub fn new(
+        toast_overlay: libadwaita::ToastOverlay,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Self {
+        Self {
+            toast_overlay,
+            dispatcher,
+        }
     }
 
     fn show(&self, content: &str) {
@@ -34,6 +42,32 @@ impl Notification {
             .build();
         self.toast_overlay.add_toast(toast);
     }
+
+    fn show_undoable(&self, content: &str) {
+        // translators: This is a label in the notification shown after removing songs, albums... If it is clicked, the removal is reverted.
+        let label = gettext("Undo");
+        let toast = libadwaita::Toast::builder()
+            .title(content)
+            .timeout(UNDO_TIMEOUT.as_secs() as u32)
+            .action_name("app.undo")
+            .button_label(label)
+            .build();
+        self.toast_overlay.add_toast(toast);
+    }
+
+    fn show_retryable(&self, content: &str, retry: &Retry) {
+        // translators: This is a label in the notification shown when something went wrong. If it is clicked, the failed request is made again.
+        let label = gettext("Retry");
+        let toast = libadwaita::Toast::builder()
+            .title(content)
+            .timeout(4)
+            .button_label(label)
+            .build();
+        let dispatcher = self.dispatcher.box_clone();
+        let retry = retry.clone();
+        toast.connect_button_clicked(move |_| dispatcher.dispatch_many_async(retry.run()));
+        self.toast_overlay.add_toast(toast);
+    }
 }
 
 impl EventListener for Notification {
@@ -42,6 +76,10 @@ impl EventListener for Notification {
             self.show(content)
         } else if let AppEvent::PlaylistCreatedNotificationShown(id) = event {
             self.show_playlist_created(id)
+        } else if let AppEvent::UndoableNotificationShown(content) = event {
+            self.show_undoable(content)
+        } else if let AppEvent::RetryableNotificationShown(content, retry) = event {
+            self.show_retryable(content, retry)
         }
     }
 }
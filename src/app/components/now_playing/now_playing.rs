@@ -3,7 +3,7 @@ use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use std::rc::Rc;
 
-use super::NowPlayingModel;
+use super::{NowPlayingModel, QueuedSongsModel};
 use crate::app::components::{
     Component, DeviceSelector, DeviceSelectorWidget, EventListener, HeaderBarComponent,
     HeaderBarWidget, Playlist,
@@ -21,6 +21,18 @@ mod imp {
         #[template_child]
         pub song_list: TemplateChild<gtk::ListView>,
 
+        #[template_child]
+        pub queued_section: TemplateChild<libadwaita::Clamp>,
+
+        #[template_child]
+        pub queued_list: TemplateChild<gtk::ListView>,
+
+        #[template_child]
+        pub clear_queue_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub context_header: TemplateChild<gtk::Label>,
+
         #[template_child]
         pub headerbar: TemplateChild<HeaderBarWidget>,
 
@@ -78,10 +90,28 @@ impl NowPlayingWidget {
             });
     }
 
+    fn connect_clear_queue<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().clear_queue_button.connect_clicked(move |_| f());
+    }
+
+    // "Next in queue" only shows up if something was queued
+    fn update_sections(&self, queued_count: usize, context_header: &str) {
+        let widget = self.imp();
+        widget.queued_section.set_visible(queued_count > 0);
+        widget.context_header.set_text(context_header);
+    }
+
     fn song_list_widget(&self) -> &gtk::ListView {
         self.imp().song_list.as_ref()
     }
 
+    fn queued_list_widget(&self) -> &gtk::ListView {
+        self.imp().queued_list.as_ref()
+    }
+
     fn headerbar_widget(&self) -> &HeaderBarWidget {
         self.imp().headerbar.as_ref()
     }
@@ -105,9 +135,19 @@ impl NowPlaying {
             model.load_more();
         }));
 
+        widget.connect_clear_queue(clone!(@weak model => move || {
+            model.clear_queue();
+        }));
+
         let playlist = Box::new(Playlist::new(
             widget.song_list_widget().clone(),
             model.clone(),
+            worker.clone(),
+        ));
+
+        let queued_playlist = Box::new(Playlist::new(
+            widget.queued_list_widget().clone(),
+            Rc::new(QueuedSongsModel::new(model.clone())),
             worker,
         ));
 
@@ -123,10 +163,12 @@ impl NowPlaying {
             model.device_selector_model(),
         ));
 
+        widget.update_sections(model.queued_count(), &model.context_header());
+
         Self {
             widget,
             model,
-            children: vec![playlist, headerbar, device_selector],
+            children: vec![playlist, queued_playlist, headerbar, device_selector],
         }
     }
 }
@@ -143,8 +185,19 @@ impl Component for NowPlaying {
 
 impl EventListener for NowPlaying {
     fn on_event(&mut self, event: &AppEvent) {
-        if let AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) = event {
-            self.model.load_more();
+        match event {
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) => {
+                self.model.load_more();
+                self.widget
+                    .update_sections(self.model.queued_count(), &self.model.context_header());
+            }
+            AppEvent::PlaybackEvent(
+                PlaybackEvent::PlaylistChanged | PlaybackEvent::SourceChanged,
+            ) => {
+                self.widget
+                    .update_sections(self.model.queued_count(), &self.model.context_header());
+            }
+            _ => {}
         }
         self.broadcast_event(event);
     }
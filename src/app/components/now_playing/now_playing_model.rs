@@ -1,3 +1,4 @@
+use gettextrs::gettext;
 use gio::prelude::*;
 use gio::SimpleActionGroup;
 use std::ops::Deref;
@@ -5,14 +6,14 @@ use std::rc::Rc;
 
 use crate::app::components::{
     labels, DeviceSelectorModel, HeaderBarModel, PlaylistModel, SimpleHeaderBarModel,
-    SimpleHeaderBarModelWrapper,
+    SimpleHeaderBarModelWrapper, SongColumns,
 };
 use crate::app::models::{SongDescription, SongListModel};
 use crate::app::state::Device;
 use crate::app::state::{
     PlaybackAction, PlaybackState, SelectionAction, SelectionContext, SelectionState,
 };
-use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel, SongsSource};
 
 pub struct NowPlayingModel {
     app_model: Rc<AppModel>,
@@ -35,12 +36,13 @@ impl NowPlayingModel {
         let queue = self.queue();
         let loader = self.app_model.get_batch_loader();
         let query = queue.next_query()?;
+        let start = queue.queue_start();
         debug!("next_query = {:?}", &query);
 
         self.dispatcher.dispatch_async(Box::pin(async move {
             loader
                 .query(query, |source, song_batch| {
-                    PlaybackAction::LoadPagedSongs(source, song_batch).into()
+                    PlaybackAction::LoadPagedSongs(source, song_batch, start).into()
                 })
                 .await
         }));
@@ -60,6 +62,38 @@ impl NowPlayingModel {
         DeviceSelectorModel::new(self.app_model.clone(), self.dispatcher.box_clone())
     }
 
+    pub fn queued_count(&self) -> usize {
+        self.queue().queued_songs().len()
+    }
+
+    pub fn clear_queue(&self) {
+        self.dispatcher.dispatch(PlaybackAction::ClearQueue.into());
+    }
+
+    // The header above the rest of the queue, naming what it comes from if possible
+    pub fn context_header(&self) -> String {
+        let state = self.app_model.get_state();
+        let title = match state.playback.current_source() {
+            Some(SongsSource::Album(_)) => state
+                .playback
+                .songs()
+                .index(0)
+                .map(|song| song.description().album.name.to_string()),
+            Some(SongsSource::Playlist(id)) => state
+                .logged_user
+                .playlists
+                .iter()
+                .find(|playlist| &playlist.id == id)
+                .map(|playlist| playlist.title.clone()),
+            Some(SongsSource::SavedTracks) => Some(gettext("Saved tracks")),
+            Some(SongsSource::SavedEpisodes) => Some(gettext("Saved episodes")),
+            None => None,
+        };
+        title
+            .map(|title| labels::next_from_label(&title))
+            .unwrap_or_else(|| labels::NEXT_UP.clone())
+    }
+
     fn current_selection_context(&self) -> SelectionContext {
         let state = self.app_model.get_state();
         match state.playback.current_device() {
@@ -78,6 +112,14 @@ impl PlaylistModel for NowPlayingModel {
         !self.app_model.get_state().playback.is_playing()
     }
 
+    // Unlike on an album, songs in the queue rarely share the same album
+    fn song_columns(&self) -> SongColumns {
+        SongColumns {
+            album: true,
+            ..Default::default()
+        }
+    }
+
     fn current_song_id(&self) -> Option<String> {
         self.queue().current_song_id()
     }
@@ -92,9 +134,7 @@ impl PlaylistModel for NowPlayingModel {
     }
 
     fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
-        let queue = self.queue();
-        let song = queue.songs().get(id)?;
-        let song = song.description();
+        let song = self.queue().song(id)?;
         let group = SimpleActionGroup::new();
 
         for view_artist in song.make_artist_actions(self.dispatcher.box_clone(), None) {
@@ -102,15 +142,18 @@ impl PlaylistModel for NowPlayingModel {
         }
         group.add_action(&song.make_album_action(self.dispatcher.box_clone(), None));
         group.add_action(&song.make_link_action(None));
+        group.add_action(&song.make_radio_action(
+            self.app_model.get_spotify(),
+            self.dispatcher.unscoped(),
+            None,
+        ));
         group.add_action(&song.make_dequeue_action(self.dispatcher.box_clone(), None));
 
         Some(group.upcast())
     }
 
     fn menu_for(&self, id: &str) -> Option<gio::MenuModel> {
-        let queue = self.queue();
-        let song = queue.songs().get(id)?;
-        let song = song.description();
+        let song = self.queue().song(id)?;
 
         let menu = gio::Menu::new();
         menu.append(Some(&*labels::VIEW_ALBUM), Some("song.view_album"));
@@ -121,21 +164,31 @@ impl PlaylistModel for NowPlayingModel {
             );
         }
 
+        if !song.is_episode() {
+            menu.append(Some(&*labels::SONG_RADIO), Some("song.radio"));
+        }
         menu.append(Some(&*labels::COPY_LINK), Some("song.copy_link"));
+        menu.append(
+            Some(&*labels::SHARE),
+            Some(&format!("app.share::{}", song.uri)),
+        );
         menu.append(Some(&*labels::REMOVE_FROM_QUEUE), Some("song.dequeue"));
 
         Some(menu.upcast())
     }
 
     fn select_song(&self, id: &str) {
-        let queue = self.queue();
-        if let Some(song) = queue.songs().get(id) {
-            let song = song.description().clone();
+        if let Some(song) = self.queue().song(id) {
             self.dispatcher
                 .dispatch(SelectionAction::Select(vec![song]).into());
         }
     }
 
+    fn select_songs(&self, songs: Vec<SongDescription>) {
+        self.dispatcher
+            .dispatch(SelectionAction::Select(songs).into());
+    }
+
     fn deselect_song(&self, id: &str) {
         self.dispatcher
             .dispatch(SelectionAction::Deselect(vec![id.to_string()]).into());
@@ -148,7 +201,10 @@ impl PlaylistModel for NowPlayingModel {
     }
 
     fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
-        let selection = self.app_model.map_state(|s| &s.selection);
+        let context = self.current_selection_context();
+        let selection = self
+            .app_model
+            .map_state_opt(|s| Some(&s.selection).filter(|s| s.context == context))?;
         Some(Box::new(selection))
     }
 }
@@ -167,8 +223,74 @@ impl SimpleHeaderBarModel for NowPlayingModel {
     }
 
     fn select_all(&self) {
-        let songs: Vec<SongDescription> = self.queue().songs().collect();
+        let queue = self.queue();
+        let mut songs: Vec<SongDescription> = queue.queued_songs().collect();
+        songs.append(&mut queue.songs().collect());
+        drop(queue);
         self.dispatcher
             .dispatch(SelectionAction::Select(songs).into());
     }
 }
+
+// The songs queued by hand, shown above the rest of the queue but otherwise handled the same way
+pub struct QueuedSongsModel(Rc<NowPlayingModel>);
+
+impl QueuedSongsModel {
+    pub fn new(model: Rc<NowPlayingModel>) -> Self {
+        Self(model)
+    }
+}
+
+impl PlaylistModel for QueuedSongsModel {
+    fn song_list_model(&self) -> SongListModel {
+        self.0.queue().queued_songs().clone()
+    }
+
+    fn is_paused(&self) -> bool {
+        self.0.is_paused()
+    }
+
+    fn song_columns(&self) -> SongColumns {
+        self.0.song_columns()
+    }
+
+    fn current_song_id(&self) -> Option<String> {
+        self.0.current_song_id()
+    }
+
+    fn play_song_at(&self, pos: usize, id: &str) {
+        self.0.play_song_at(pos, id)
+    }
+
+    fn autoscroll_to_playing(&self) -> bool {
+        false
+    }
+
+    fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
+        self.0.actions_for(id)
+    }
+
+    fn menu_for(&self, id: &str) -> Option<gio::MenuModel> {
+        self.0.menu_for(id)
+    }
+
+    fn select_song(&self, id: &str) {
+        self.0.select_song(id)
+    }
+
+    fn select_songs(&self, songs: Vec<SongDescription>) {
+        self.0.select_songs(songs)
+    }
+
+    fn deselect_song(&self, id: &str) {
+        self.0.deselect_song(id)
+    }
+
+    fn enable_selection(&self) -> bool {
+        self.0.enable_selection()
+    }
+
+    fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
+        self.0.selection()
+    }
+}
@@ -0,0 +1,64 @@
+use gio::prelude::*;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::app::components::EventListener;
+use crate::app::play_history;
+use crate::app::state::PlaybackEvent;
+use crate::app::{AppEvent, AppModel, Worker};
+
+// Skipping through tracks changes them in quick succession, no need to write after each one
+const SAVE_DELAY: Duration = Duration::from_secs(10);
+
+// Remembers when tracks were played, so that smart shuffle can favor the others
+pub struct PlayHistoryNotifier {
+    app_model: Rc<AppModel>,
+    worker: Worker,
+    // Shared with the shutdown handler
+    save_pending: Rc<Cell<bool>>,
+}
+
+impl PlayHistoryNotifier {
+    pub fn new(app_model: Rc<AppModel>, worker: Worker) -> Self {
+        let save_pending: Rc<Cell<bool>> = Default::default();
+        // Otherwise whatever was played since the last save would be lost on quit
+        if let Some(app) = gio::Application::default() {
+            app.connect_shutdown(clone!(@weak save_pending => move |_| {
+                if save_pending.replace(false) {
+                    play_history::save_blocking();
+                }
+            }));
+        }
+        Self {
+            app_model,
+            worker,
+            save_pending,
+        }
+    }
+
+    fn schedule_save(&self) {
+        if self.save_pending.replace(true) {
+            return;
+        }
+
+        let save_pending = Rc::clone(&self.save_pending);
+        let worker = self.worker.clone();
+        glib::timeout_add_local_once(SAVE_DELAY, move || {
+            if save_pending.replace(false) {
+                worker.send_task(play_history::save());
+            }
+        });
+    }
+}
+
+impl EventListener for PlayHistoryNotifier {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(id)) = event {
+            if self.app_model.get_state().settings.settings.smart_shuffle {
+                play_history::record_played(id);
+                self.schedule_save();
+            }
+        }
+    }
+}
@@ -1,9 +1,10 @@
+use glib::ObjectExt;
 use std::ops::Deref;
 use std::rc::Rc;
 
 use crate::app::components::EventListener;
 use crate::app::models::*;
-use crate::app::state::{PlaybackAction, PlaybackEvent, ScreenName, SelectionEvent};
+use crate::app::state::{PlaybackAction, PlaybackEvent, ScreenName, SelectionEvent, SettingsEvent};
 use crate::app::{
     ActionDispatcher, AppAction, AppEvent, AppModel, AppState, BrowserAction, Worker,
 };
@@ -45,6 +46,31 @@ impl PlaybackModel {
         self.app_model.get_state().playback.current_song()
     }
 
+    fn remaining_songs(&self) -> Option<(usize, Option<u64>)> {
+        self.state().playback.remaining_songs()
+    }
+
+    // How far back and ahead to skip in seconds, when an episode is playing
+    fn skip_intervals(&self) -> Option<(u32, u32)> {
+        self.current_song().filter(|song| song.is_episode())?;
+        let skip = self.state().settings.settings.episode_skip;
+        Some((skip.back, skip.forward))
+    }
+
+    fn skip_back(&self) {
+        if let Some((back, _)) = self.skip_intervals() {
+            self.dispatcher
+                .dispatch(PlaybackAction::SeekBy(-(back as i32)).into());
+        }
+    }
+
+    fn skip_forward(&self) {
+        if let Some((_, forward)) = self.skip_intervals() {
+            self.dispatcher
+                .dispatch(PlaybackAction::SeekBy(forward as i32).into());
+        }
+    }
+
     fn play_next_song(&self) {
         self.dispatcher.dispatch(PlaybackAction::Next.into());
     }
@@ -67,6 +93,10 @@ impl PlaybackModel {
             .dispatch(PlaybackAction::ToggleRepeat.into());
     }
 
+    fn is_current_song(&self, id: &str) -> bool {
+        self.state().playback.current_song_id().as_deref() == Some(id)
+    }
+
     fn seek_to(&self, position: u32) {
         self.dispatcher
             .dispatch(PlaybackAction::Seek(position).into());
@@ -86,6 +116,8 @@ impl PlaybackControl {
         widget.connect_play_pause(clone!(@weak model => move || model.toggle_playback() ));
         widget.connect_next(clone!(@weak model => move || model.play_next_song()));
         widget.connect_prev(clone!(@weak model => move || model.play_prev_song()));
+        widget.connect_skip_back(clone!(@weak model => move || model.skip_back()));
+        widget.connect_skip_forward(clone!(@weak model => move || model.skip_forward()));
         widget.connect_shuffle(clone!(@weak model => move || model.toggle_shuffle()));
         widget.connect_repeat(clone!(@weak model => move || model.toggle_repeat()));
         widget.connect_seek(clone!(@weak model => move |position| model.seek_to(position)));
@@ -116,12 +148,49 @@ impl PlaybackControl {
             self.widget
                 .set_title_and_artist(&song.title, &song.artists_name());
             self.widget.set_song_duration(Some(song.duration as f64));
+            self.widget.set_skip_intervals(self.model.skip_intervals());
+            self.update_chapters(&song);
             if let Some(url) = song.art {
-                self.widget.set_artwork_from_url(url, &self.worker);
+                self.widget
+                    .set_artwork_from_url(url.to_string(), &self.worker);
             }
         } else {
             self.widget.reset_info();
+            self.widget.set_skip_intervals(None);
+            self.widget.set_chapters(vec![]);
+        }
+    }
+
+    // Chapters are only found in the description of episodes, which songs don't carry
+    fn update_chapters(&self, song: &SongDescription) {
+        self.widget.set_chapters(vec![]);
+        if !song.is_episode() {
+            return;
         }
+        let api = self.model.app_model.get_spotify();
+        let id = song.id.clone();
+        let model = Rc::downgrade(&self.model);
+        let widget = self.widget.downgrade();
+        self.worker.send_local_task(async move {
+            let chapters = match api.get_episode(&id).await {
+                Ok(episode) => episode.chapters(),
+                Err(err) => {
+                    debug!("Could not get the chapters of {}: {}", id, err);
+                    return;
+                }
+            };
+            let (Some(model), Some(widget)) = (model.upgrade(), widget.upgrade()) else {
+                return;
+            };
+            // Another song might have started in the meantime
+            if model.is_current_song(&id) {
+                widget.set_chapters(chapters);
+            }
+        });
+    }
+
+    fn update_queue_summary(&self) {
+        self.widget.set_queue_summary(self.model.remaining_songs());
     }
 
     fn sync_seek(&self, pos: u32) {
@@ -141,18 +210,27 @@ impl EventListener for PlaybackControl {
             }
             AppEvent::PlaybackEvent(PlaybackEvent::ShuffleChanged(_)) => {
                 self.update_shuffled();
+                self.update_queue_summary();
             }
             AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) => {
                 self.update_current_info();
+                self.update_queue_summary();
             }
             AppEvent::PlaybackEvent(PlaybackEvent::PlaybackStopped) => {
                 self.update_playing();
                 self.update_current_info();
+                self.update_queue_summary();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaylistChanged) => {
+                self.update_queue_summary();
             }
             AppEvent::PlaybackEvent(PlaybackEvent::SeekSynced(pos))
             | AppEvent::PlaybackEvent(PlaybackEvent::TrackSeeked(pos)) => {
                 self.sync_seek(*pos);
             }
+            AppEvent::SettingsEvent(SettingsEvent::SettingsChanged) => {
+                self.widget.set_skip_intervals(self.model.skip_intervals());
+            }
             AppEvent::SelectionEvent(SelectionEvent::SelectionModeChanged(active)) => {
                 self.widget.set_seekbar_visible(!active);
             }
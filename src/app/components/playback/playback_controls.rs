@@ -3,6 +3,7 @@ use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::{glib, CompositeTemplate};
 
+use crate::app::components::labels;
 use crate::app::models::RepeatMode;
 
 mod imp {
@@ -21,6 +22,12 @@ mod imp {
         #[template_child]
         pub prev: TemplateChild<gtk::Button>,
 
+        #[template_child]
+        pub skip_back: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub skip_forward: TemplateChild<gtk::Button>,
+
         #[template_child]
         pub shuffle: TemplateChild<gtk::ToggleButton>,
 
@@ -70,6 +77,32 @@ impl PlaybackControlsWidget {
         let playback_control = self.imp();
         playback_control.play_pause.set_icon_name(playback_icon);
         playback_control.play_pause.set_tooltip_text(tooltip_text);
+        playback_control
+            .play_pause
+            .update_property(&[gtk::accessible::Property::Label(&translated_tooltip)]);
+    }
+
+    // Episodes get buttons to skip a few seconds instead of going to the previous or next track
+    pub fn set_skip_intervals(&self, intervals: Option<(u32, u32)>) {
+        let widget = self.imp();
+        widget.prev.set_visible(intervals.is_none());
+        widget.next.set_visible(intervals.is_none());
+        widget.skip_back.set_visible(intervals.is_some());
+        widget.skip_forward.set_visible(intervals.is_some());
+
+        if let Some((back, forward)) = intervals {
+            let back_label = labels::skip_back_label(back);
+            widget.skip_back.set_tooltip_text(Some(&back_label));
+            widget
+                .skip_back
+                .update_property(&[gtk::accessible::Property::Label(&back_label)]);
+
+            let forward_label = labels::skip_forward_label(forward);
+            widget.skip_forward.set_tooltip_text(Some(&forward_label));
+            widget
+                .skip_forward
+                .update_property(&[gtk::accessible::Property::Label(&forward_label)]);
+        }
     }
 
     pub fn set_shuffled(&self, shuffled: bool) {
@@ -83,7 +116,19 @@ impl PlaybackControlsWidget {
             RepeatMode::None => "media-playlist-consecutive-symbolic",
         };
 
-        self.imp().repeat.set_icon_name(repeat_mode_icon);
+        // Screen readers can't tell the icons apart
+        let repeat_mode_label = match mode {
+            // translators: Read by screen readers for the repeat button when the current song repeats
+            RepeatMode::Song => gettext("Repeat song"),
+            // translators: Read by screen readers for the repeat button when the whole queue repeats
+            RepeatMode::Playlist => gettext("Repeat playlist"),
+            // translators: Read by screen readers for the repeat button when nothing repeats
+            RepeatMode::None => gettext("Repeat off"),
+        };
+
+        let repeat = &self.imp().repeat;
+        repeat.set_icon_name(repeat_mode_icon);
+        repeat.update_property(&[gtk::accessible::Property::Label(&repeat_mode_label)]);
     }
 
     pub fn connect_play_pause<F>(&self, f: F)
@@ -107,6 +152,20 @@ impl PlaybackControlsWidget {
         self.imp().next.connect_clicked(move |_| f());
     }
 
+    pub fn connect_skip_back<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().skip_back.connect_clicked(move |_| f());
+    }
+
+    pub fn connect_skip_forward<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().skip_forward.connect_clicked(move |_| f());
+    }
+
     pub fn connect_shuffle<F>(&self, f: F)
     where
         F: Fn() + 'static,
@@ -3,6 +3,8 @@ use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::{glib, CompositeTemplate};
 
+use crate::app::components::labels;
+
 mod imp {
 
     use super::*;
@@ -38,24 +40,30 @@ mod imp {
 }
 
 glib::wrapper! {
-    pub struct PlaybackInfoWidget(ObjectSubclass<imp::PlaybackInfoWidget>) @extends gtk::Widget, gtk::Button;
+    pub struct PlaybackInfoWidget(ObjectSubclass<imp::PlaybackInfoWidget>) @extends gtk::Widget, gtk::Button, @implements gtk::Accessible;
 }
 
 impl PlaybackInfoWidget {
     pub fn set_title_and_artist(&self, title: &str, artist: &str) {
         let widget = self.imp();
-        let title = glib::markup_escape_text(title);
-        let artist = glib::markup_escape_text(artist);
-        let label = format!("<b>{}</b>\n{}", title.as_str(), artist.as_str());
+        let label = format!(
+            "<b>{}</b>\n{}",
+            glib::markup_escape_text(title).as_str(),
+            glib::markup_escape_text(artist).as_str()
+        );
         widget.current_song_info.set_label(&label[..]);
+        // Screen readers get the plain text
+        self.update_property(&[gtk::accessible::Property::Label(
+            &labels::now_playing_label(title, artist),
+        )]);
     }
 
     pub fn reset_info(&self) {
         let widget = self.imp();
-        widget
-            .current_song_info
-            // translators: Short text displayed instead of a song title when nothing plays
-            .set_label(&gettext("No song playing"));
+        // translators: Short text displayed instead of a song title when nothing plays
+        let label = gettext("No song playing");
+        widget.current_song_info.set_label(&label);
+        self.update_property(&[gtk::accessible::Property::Label(&label)]);
         widget
             .playing_image
             .set_from_icon_name(Some("emblem-music-symbolic"));
@@ -68,7 +76,7 @@ impl PlaybackInfoWidget {
         self.imp().current_song_info.set_visible(visible);
     }
 
-    pub fn set_artwork(&self, art: &gdk_pixbuf::Pixbuf) {
-        self.imp().playing_image.set_from_pixbuf(Some(art));
+    pub fn set_artwork(&self, art: &gdk::Texture) {
+        self.imp().playing_image.set_from_paintable(Some(art));
     }
 }
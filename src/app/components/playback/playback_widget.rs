@@ -2,10 +2,10 @@ use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::{glib, CompositeTemplate};
 
-use crate::app::components::display_add_css_provider;
 use crate::app::components::utils::{format_duration, Clock, Debouncer};
+use crate::app::components::{display_add_css_provider, labels};
 use crate::app::loader::ImageLoader;
-use crate::app::models::RepeatMode;
+use crate::app::models::{Chapter, RepeatMode};
 use crate::app::Worker;
 
 use super::playback_controls::PlaybackControlsWidget;
@@ -14,6 +14,7 @@ use super::playback_info::PlaybackInfoWidget;
 mod imp {
 
     use super::*;
+    use std::cell::RefCell;
 
     #[derive(Debug, Default, CompositeTemplate)]
     #[template(resource = "/dev/alextren/Spot/components/playback_widget.ui")]
@@ -33,6 +34,9 @@ mod imp {
         #[template_child]
         pub seek_bar: TemplateChild<gtk::Scale>,
 
+        #[template_child]
+        pub queue_summary: TemplateChild<gtk::Label>,
+
         #[template_child]
         pub track_position: TemplateChild<gtk::Label>,
 
@@ -40,6 +44,9 @@ mod imp {
         pub track_duration: TemplateChild<gtk::Label>,
 
         pub clock: Clock,
+
+        // Of the episode playing, if it has any
+        pub chapters: RefCell<Vec<Chapter>>,
     }
 
     #[glib::object_subclass]
@@ -63,6 +70,23 @@ mod imp {
             self.now_playing_mobile.set_info_visible(false);
             self.now_playing.set_info_visible(true);
             display_add_css_provider(resource!("/components/playback.css"));
+
+            let obj = (*self.obj()).clone();
+            self.seek_bar.connect_query_tooltip(
+                clone!(@weak obj => @default-return false, move |seek_bar, x, _, _, tooltip| {
+                    let adjustment = seek_bar.adjustment();
+                    let position = adjustment.upper() * x as f64 / seek_bar.width().max(1) as f64;
+                    let Some(chapter) = obj.chapter_at(position) else {
+                        return false;
+                    };
+                    tooltip.set_markup(Some(&format!(
+                        "{} · {}",
+                        format_duration(chapter.start as f64),
+                        chapter.title
+                    )));
+                    true
+                }),
+            );
         }
     }
 
@@ -78,6 +102,9 @@ impl PlaybackWidget {
     pub fn set_title_and_artist(&self, title: &str, artist: &str) {
         let widget = self.imp();
         widget.now_playing.set_title_and_artist(title, artist);
+        widget
+            .now_playing_mobile
+            .set_title_and_artist(title, artist);
     }
 
     pub fn reset_info(&self) {
@@ -87,7 +114,7 @@ impl PlaybackWidget {
         self.set_song_duration(None);
     }
 
-    fn set_artwork(&self, image: &gdk_pixbuf::Pixbuf) {
+    fn set_artwork(&self, image: &gdk::Texture) {
         let widget = self.imp();
         widget.now_playing.set_artwork(image);
         widget.now_playing_mobile.set_artwork(image);
@@ -97,7 +124,7 @@ impl PlaybackWidget {
         let weak_self = self.downgrade();
         worker.send_local_task(async move {
             let loader = ImageLoader::new();
-            let result = loader.load_remote(&url, "jpg", 48, 48).await;
+            let result = loader.load_texture(&url, 48).await;
             if let (Some(ref _self), Some(ref result)) = (weak_self.upgrade(), result) {
                 _self.set_artwork(result);
             }
@@ -125,6 +152,54 @@ impl PlaybackWidget {
         }
     }
 
+    // How many songs are left in the queue and for how long, if anything is playing
+    pub fn set_queue_summary(&self, remaining: Option<(usize, Option<u64>)>) {
+        let queue_summary = &self.imp().queue_summary;
+        match remaining {
+            Some((count, duration)) if count > 0 => {
+                queue_summary.set_text(&labels::queue_summary_label(count, duration));
+                queue_summary.set_visible(true);
+            }
+            _ => queue_summary.set_visible(false),
+        }
+    }
+
+    // Ticks where chapters start, their titles show up when hovering the seek bar
+    pub fn set_chapters(&self, chapters: Vec<Chapter>) {
+        let widget = self.imp();
+        widget.seek_bar.clear_marks();
+        for chapter in &chapters {
+            widget
+                .seek_bar
+                .add_mark(chapter.start as f64, gtk::PositionType::Bottom, None);
+        }
+        widget.seek_bar.set_has_tooltip(!chapters.is_empty());
+        widget.chapters.replace(chapters);
+    }
+
+    fn chapter_at(&self, position: f64) -> Option<Chapter> {
+        self.imp()
+            .chapters
+            .borrow()
+            .iter()
+            .rev()
+            .find(|chapter| chapter.start as f64 <= position)
+            .cloned()
+    }
+
+    // Seeking close enough to where a chapter starts goes right to it
+    fn snap_to_chapter(&self, position: f64) -> f64 {
+        let widget = self.imp();
+        let margin = widget.seek_bar.adjustment().upper() / 100.0;
+        widget
+            .chapters
+            .borrow()
+            .iter()
+            .map(|chapter| chapter.start as f64)
+            .find(|start| (start - position).abs() < margin)
+            .unwrap_or(position)
+    }
+
     pub fn set_seek_position(&self, pos: f64) {
         let widget = self.imp();
         widget.seek_bar.set_value(pos);
@@ -154,13 +229,20 @@ impl PlaybackWidget {
         let widget = self.imp();
         widget.seek_bar.set_increments(5_000.0, 10_000.0);
         widget.seek_bar.connect_change_value(
-            clone!(@weak self as _self => @default-return glib::signal::Inhibit(false), move |_, _, requested| {
+            clone!(@weak self as _self => @default-return glib::signal::Inhibit(false), move |seek_bar, scroll, requested| {
+                // Only for clicks and drags, stepping with the keyboard would get stuck on chapters
+                let requested = if scroll == gtk::ScrollType::Jump {
+                    _self.snap_to_chapter(requested)
+                } else {
+                    requested
+                };
+                seek_bar.set_value(requested);
                 _self.imp()
                     .track_position
                     .set_text(&format_duration(requested));
                 let seek = seek.clone();
                 debouncer.debounce(200, move || seek(requested as u32));
-                glib::signal::Inhibit(false)
+                glib::signal::Inhibit(true)
             }),
         );
     }
@@ -184,6 +266,12 @@ impl PlaybackWidget {
         widget.controls_mobile.set_repeat_mode(mode);
     }
 
+    pub fn set_skip_intervals(&self, intervals: Option<(u32, u32)>) {
+        let widget = self.imp();
+        widget.controls.set_skip_intervals(intervals);
+        widget.controls_mobile.set_skip_intervals(intervals);
+    }
+
     pub fn set_shuffled(&self, shuffled: bool) {
         let widget = self.imp();
         widget.controls.set_shuffled(shuffled);
@@ -222,6 +310,24 @@ impl PlaybackWidget {
         widget.controls_mobile.connect_next(f);
     }
 
+    pub fn connect_skip_back<F>(&self, f: F)
+    where
+        F: Fn() + Clone + 'static,
+    {
+        let widget = self.imp();
+        widget.controls.connect_skip_back(f.clone());
+        widget.controls_mobile.connect_skip_back(f);
+    }
+
+    pub fn connect_skip_forward<F>(&self, f: F)
+    where
+        F: Fn() + Clone + 'static,
+    {
+        let widget = self.imp();
+        widget.controls.connect_skip_forward(f.clone());
+        widget.controls_mobile.connect_skip_forward(f);
+    }
+
     pub fn connect_shuffle<F>(&self, f: F)
     where
         F: Fn() + Clone + 'static,
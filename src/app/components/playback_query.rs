@@ -0,0 +1,61 @@
+use gio::prelude::*;
+use std::rc::Rc;
+
+use crate::app::components::EventListener;
+use crate::app::state::PlaybackEvent;
+use crate::app::{AppEvent, AppModel};
+
+// Exposes what's playing as the state of the "query" action, which other instances
+// (spot --query) can read through GApplication
+pub struct PlaybackQuery {
+    app_model: Rc<AppModel>,
+    action: gio::SimpleAction,
+}
+
+impl PlaybackQuery {
+    pub fn new(app_model: Rc<AppModel>) -> Self {
+        let action = gio::SimpleAction::new_stateful("query", None, "".to_variant());
+        if let Some(app) = gio::Application::default() {
+            app.add_action(&action);
+        }
+        let query = Self { app_model, action };
+        query.update();
+        query
+    }
+
+    fn describe(&self) -> String {
+        let state = self.app_model.get_state();
+        let playback = &state.playback;
+        let status = match (playback.current_song_id(), playback.is_playing()) {
+            (None, _) => "Stopped",
+            (Some(_), true) => "Playing",
+            (Some(_), false) => "Paused",
+        };
+        let mut lines = vec![format!("status: {status}")];
+        if let Some(song) = playback.current_song() {
+            lines.push(format!("title: {}", song.title));
+            lines.push(format!("artist: {}", song.artists_name()));
+            lines.push(format!("album: {}", song.album.name));
+            lines.push(format!("uri: {}", song.uri));
+        }
+        lines.join("\n")
+    }
+
+    fn update(&self) {
+        self.action.set_state(self.describe().to_variant());
+    }
+}
+
+impl EventListener for PlaybackQuery {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::PlaybackEvent(
+            PlaybackEvent::TrackChanged(_)
+            | PlaybackEvent::PlaybackPaused
+            | PlaybackEvent::PlaybackResumed
+            | PlaybackEvent::PlaybackStopped,
+        ) = event
+        {
+            self.update();
+        }
+    }
+}
@@ -2,9 +2,10 @@ use std::ops::Deref;
 use std::rc::Rc;
 
 use futures::channel::mpsc::UnboundedSender;
-use librespot::core::spotify_id::SpotifyId;
+use librespot::core::spotify_id::{SpotifyAudioType, SpotifyId};
 
 use crate::app::components::EventListener;
+use crate::app::episode_progress;
 use crate::app::state::{
     Device, LoginAction, LoginEvent, LoginStartedEvent, PlaybackEvent, SettingsEvent,
 };
@@ -62,6 +63,13 @@ impl PlayerNotifier {
     fn currently_playing(&self) -> Option<CurrentlyPlaying> {
         let state = self.app_model.get_state();
         let song = state.playback.current_song_id()?;
+        // A queued song is played on its own, whatever comes next is sent once it's over
+        if state.playback.is_playing_queued() {
+            return Some(CurrentlyPlaying::Songs {
+                songs: vec![song],
+                offset: 0,
+            });
+        }
         let offset = state.playback.current_song_index()?;
         let source = state.playback.current_source().cloned();
         let result = match source {
@@ -78,6 +86,29 @@ impl PlayerNotifier {
         Some(result)
     }
 
+    // Episodes share the song queue but need their own kind of id
+    fn spotify_id(&self, id: &str) -> Option<SpotifyId> {
+        let song = self.app_model.get_state().playback.song(id);
+        match song.map(|s| s.uri) {
+            Some(uri) if uri.starts_with("spotify:episode:") => SpotifyId::from_uri(&uri).ok(),
+            _ => SpotifyId::from_base62(id).ok(),
+        }
+    }
+
+    // Episodes also pick up where they were left off
+    fn load_command(&self, id: &str, resume: bool) -> Option<Command> {
+        let track = self.spotify_id(id)?;
+        let position = match track.audio_type {
+            SpotifyAudioType::Podcast => episode_progress::resume_position(id),
+            _ => 0,
+        };
+        Some(Command::PlayerLoad {
+            track,
+            resume,
+            position,
+        })
+    }
+
     fn device(&self) -> impl Deref<Target = Device> + '_ {
         self.app_model.map_state(|s| s.playback.current_device())
     }
@@ -97,7 +128,11 @@ impl PlayerNotifier {
                 })
             }
             LoginEvent::FreshTokenRequested => Some(Command::RefreshToken),
-            LoginEvent::LogoutCompleted => Some(Command::Logout),
+            LoginEvent::LogoutCompleted | LoginEvent::AccountSwitched => Some(Command::Logout),
+            // The player was recreated, load the current track again (it was paused when the connection was lost)
+            LoginEvent::ConnectionRestored if matches!(*self.device(), Device::Local) => self
+                .currently_playing()
+                .and_then(|c| self.load_command(c.song_id(), false)),
             _ => None,
         };
 
@@ -153,24 +188,14 @@ impl PlayerNotifier {
             PlaybackEvent::PlaybackResumed => Some(Command::PlayerResume),
             PlaybackEvent::PlaybackStopped => Some(Command::PlayerStop),
             PlaybackEvent::VolumeSet(volume) => Some(Command::PlayerSetVolume(*volume)),
-            PlaybackEvent::TrackChanged(id) => {
-                SpotifyId::from_base62(id)
-                    .ok()
-                    .map(|track| Command::PlayerLoad {
-                        track,
-                        resume: true,
-                    })
-            }
+            PlaybackEvent::TrackChanged(id) => self.load_command(id, true),
             PlaybackEvent::SourceChanged => {
                 let resume = self.is_playing();
                 self.currently_playing()
-                    .and_then(|c| SpotifyId::from_base62(c.song_id()).ok())
-                    .map(|track| Command::PlayerLoad { track, resume })
+                    .and_then(|c| self.load_command(c.song_id(), resume))
             }
             PlaybackEvent::TrackSeeked(position) => Some(Command::PlayerSeek(*position)),
-            PlaybackEvent::Preload(id) => {
-                SpotifyId::from_base62(id).ok().map(Command::PlayerPreload)
-            }
+            PlaybackEvent::Preload(id) => self.spotify_id(id).map(Command::PlayerPreload),
             _ => None,
         };
 
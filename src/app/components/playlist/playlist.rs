@@ -1,13 +1,31 @@
 use gio::prelude::*;
 use gtk::prelude::*;
+use std::cell::{Cell, RefCell};
 use std::ops::Deref;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
-use crate::app::components::utils::{ancestor, AnimatorDefault};
+use crate::app::components::utils::{ancestor, is_horizontal_swipe, AnimatorDefault};
 use crate::app::components::{Component, EventListener, SongWidget};
-use crate::app::models::{SongListModel, SongModel, SongState};
-use crate::app::state::{PlaybackEvent, SelectionEvent, SelectionState};
-use crate::app::{AppEvent, Worker};
+use crate::app::models::{SongDescription, SongListModel, SongModel, SongState};
+use crate::app::state::{PlaybackEvent, SelectionEvent, SelectionState, SettingsEvent};
+use crate::app::{AppEvent, BrowserEvent, Worker};
+
+// The optional columns of a song row, each screen picks the ones that make sense there
+#[derive(Clone, Copy, Debug)]
+pub struct SongColumns {
+    pub album: bool,
+    pub duration: bool,
+}
+
+impl Default for SongColumns {
+    fn default() -> Self {
+        Self {
+            album: false,
+            duration: true,
+        }
+    }
+}
 
 pub trait PlaylistModel {
     fn is_paused(&self) -> bool;
@@ -26,6 +44,19 @@ pub trait PlaylistModel {
         true
     }
 
+    fn date_added_label(&self, _song: &SongDescription) -> Option<String> {
+        None
+    }
+
+    fn song_columns(&self) -> SongColumns {
+        SongColumns::default()
+    }
+
+    // A header shown above the song at that position, to group songs together
+    fn header_at(&self, _position: usize) -> Option<String> {
+        None
+    }
+
     fn actions_for(&self, _id: &str) -> Option<gio::ActionGroup> {
         None
     }
@@ -34,6 +65,8 @@ pub trait PlaylistModel {
     }
 
     fn select_song(&self, _id: &str) {}
+    // Used to select a range of songs at once (shift+click)
+    fn select_songs(&self, _songs: Vec<SongDescription>) {}
     fn deselect_song(&self, _id: &str) {}
     fn enable_selection(&self) -> bool {
         false
@@ -49,11 +82,14 @@ pub trait PlaylistModel {
             .unwrap_or(false)
     }
 
-    fn song_state(&self, id: &str) -> SongState {
-        let is_playing = self.current_song_id().map(|s| s.eq(id)).unwrap_or(false);
+    fn song_state(&self, song: &SongDescription) -> SongState {
+        let is_playing = self
+            .current_song_id()
+            .map(|id| song.has_id(&id))
+            .unwrap_or(false);
         let is_selected = self
             .selection()
-            .map(|s| s.is_song_selected(id))
+            .map(|s| s.is_song_selected(&song.id))
             .unwrap_or(false);
         SongState {
             is_selected,
@@ -72,10 +108,47 @@ pub trait PlaylistModel {
     }
 }
 
+// Typing a few letters quickly jumps to the first song whose title starts with them
+#[derive(Default)]
+struct TypeAhead {
+    prefix: String,
+    last_key: Option<Instant>,
+}
+
+impl TypeAhead {
+    const TIMEOUT: Duration = Duration::from_millis(1000);
+
+    fn is_typing(&self) -> bool {
+        self.last_key
+            .map(|t| t.elapsed() <= Self::TIMEOUT)
+            .unwrap_or(false)
+    }
+
+    fn push(&mut self, c: char) -> &str {
+        if !self.is_typing() {
+            self.prefix.clear();
+        }
+        self.last_key = Some(Instant::now());
+        self.prefix.extend(c.to_lowercase());
+        &self.prefix
+    }
+
+    fn find_in(list_model: &SongListModel, prefix: &str) -> Option<usize> {
+        (0..list_model.partial_len()).find(|i| {
+            list_model
+                .index_continuous(*i)
+                .map(|song| song.description().title.to_lowercase().starts_with(prefix))
+                .unwrap_or(false)
+        })
+    }
+}
+
 pub struct Playlist<Model> {
     animator: AnimatorDefault,
     listview: gtk::ListView,
     model: Rc<Model>,
+    // Position of the last song (de)selected, a shift+click selects everything from there
+    selection_anchor: Rc<Cell<Option<usize>>>,
 }
 
 impl<Model> Playlist<Model>
@@ -105,10 +178,14 @@ where
         factory.connect_bind(clone!(@weak model => move |_, item| {
             let item = item.downcast_ref::<gtk::ListItem>().unwrap();
             let song_model = item.item().unwrap().downcast::<SongModel>().unwrap();
-            song_model.set_state(model.song_state(&song_model.get_id()));
+            song_model.set_state(model.song_state(&song_model.description()));
 
             let widget = item.child().unwrap().downcast::<SongWidget>().unwrap();
             widget.bind(&song_model, worker.clone(), model.show_song_covers());
+            widget.set_list_item(item);
+            widget.set_columns(&song_model, model.song_columns());
+            widget.set_date_added(model.date_added_label(&song_model.description()).as_deref());
+            widget.set_header(model.header_at(item.position() as usize).as_deref());
 
             let id = &song_model.get_id();
             widget.set_actions(model.actions_for(id).as_ref());
@@ -119,34 +196,173 @@ where
             let item = item.downcast_ref::<gtk::ListItem>().unwrap();
             let song_model = item.item().unwrap().downcast::<SongModel>().unwrap();
             song_model.unbind_all();
+
+            let widget = item.child().unwrap().downcast::<SongWidget>().unwrap();
+            widget.unbind();
         });
 
-        listview.connect_activate(clone!(@weak list_model, @weak model => move |_, position| {
-            let song = list_model.index_continuous(position as usize).expect("attempt to access invalid index");
-            let song = song.description();
+        // We only get the position in the activate signal, so we keep track of the modifiers here
+        let shift_pressed = Rc::new(Cell::new(false));
+        let click_gesture = gtk::GestureClick::new();
+        click_gesture.set_propagation_phase(gtk::PropagationPhase::Capture);
+        click_gesture.connect_pressed(clone!(@weak shift_pressed => move |gesture, _, _, _| {
+            let state = gesture.current_event_state();
+            shift_pressed.set(state.contains(gtk::gdk::ModifierType::SHIFT_MASK));
+        }));
+        listview.add_controller(click_gesture);
+
+        let selection_anchor: Rc<Cell<Option<usize>>> = Default::default();
+        listview.connect_activate(clone!(@weak list_model, @weak model, @weak selection_anchor => move |_, position| {
+            let position = position as usize;
+            let song = list_model.index_continuous(position).expect("attempt to access invalid index");
+            let id = song.get_id();
             let selection_enabled = model.is_selection_enabled();
-            if selection_enabled {
-                model.toggle_select(&song.id);
-            } else {
-                model.play_song_at(position as usize, &song.id);
+            if !selection_enabled {
+                model.play_song_at(position, &id);
+                return;
             }
+            let extend_selection = shift_pressed.take();
+            match selection_anchor.get().filter(|_| extend_selection) {
+                Some(anchor) => {
+                    let songs = (anchor.min(position)..=anchor.max(position))
+                        .filter_map(|i| list_model.index_continuous(i))
+                        .map(|song| song.description().clone())
+                        .collect();
+                    model.select_songs(songs);
+                }
+                None => model.toggle_select(&id),
+            }
+            selection_anchor.set(Some(position));
         }));
 
+        // On touchscreens, a long press opens the menu instead, there's no right click there
         let press_gesture = gtk::GestureLongPress::new();
         press_gesture.set_touch_only(false);
         press_gesture.set_propagation_phase(gtk::PropagationPhase::Capture);
-        press_gesture.connect_pressed(clone!(@weak model => move |_, _, _| {
-            model.enable_selection();
+        press_gesture.connect_pressed(clone!(@weak model => move |gesture, x, y| {
+            let is_touch = gesture
+                .device()
+                .map(|device| device.source() == gtk::gdk::InputSource::Touchscreen)
+                .unwrap_or(false);
+            let song = Self::song_at(gesture.widget(), x, y).filter(|_| is_touch);
+            if !song.map(|song| song.popup_menu()).unwrap_or(false) {
+                model.enable_selection();
+            }
         }));
         listview.add_controller(press_gesture);
 
+        // Swiping a song to the left adds it to the queue
+        let swipe_start: Rc<Cell<Option<(f64, f64)>>> = Default::default();
+        let swipe_gesture = gtk::GestureSwipe::new();
+        swipe_gesture.set_touch_only(true);
+        swipe_gesture.connect_begin(clone!(@weak swipe_start => move |gesture, sequence| {
+            swipe_start.set(gesture.point(sequence));
+        }));
+        swipe_gesture.connect_swipe(move |gesture, velocity_x, velocity_y| {
+            let start = swipe_start.take();
+            if !is_horizontal_swipe(velocity_x, velocity_y) || velocity_x > 0.0 {
+                return;
+            }
+            let song = start.and_then(|(x, y)| Self::song_at(gesture.widget(), x, y));
+            if let Some(song) = song {
+                song.activate_action("song.queue", None).ok();
+            }
+        });
+        listview.add_controller(swipe_gesture);
+
+        // Arrow keys and Enter are already handled by the list view itself
+        let type_ahead = RefCell::new(TypeAhead::default());
+        let key_controller = gtk::EventControllerKey::new();
+        key_controller.connect_key_pressed(
+            clone!(@weak list_model => @default-return gtk::Inhibit(false), move |controller, key, _, modifiers| {
+                let Some(listview) = controller.widget().downcast::<gtk::ListView>().ok() else {
+                    return gtk::Inhibit(false);
+                };
+                let modifiers = modifiers & gtk::accelerator_get_default_mod_mask();
+                let opens_menu = key == gtk::gdk::Key::Menu
+                    || (key == gtk::gdk::Key::F10 && modifiers == gtk::gdk::ModifierType::SHIFT_MASK);
+                if opens_menu {
+                    return gtk::Inhibit(Self::popup_focused_menu(&listview));
+                }
+
+                let typed = key.to_unicode().filter(|c| {
+                    !c.is_control() && (modifiers - gtk::gdk::ModifierType::SHIFT_MASK).is_empty()
+                });
+                let Some(c) = typed else {
+                    return gtk::Inhibit(false);
+                };
+                let mut type_ahead = type_ahead.borrow_mut();
+                // A lone space is left for the list view to handle
+                if c == ' ' && !type_ahead.is_typing() {
+                    return gtk::Inhibit(false);
+                }
+                let prefix = type_ahead.push(c);
+                if let Some(position) = TypeAhead::find_in(&list_model, prefix) {
+                    Self::focus_row(&listview, position as u32);
+                }
+                gtk::Inhibit(true)
+            }),
+        );
+        listview.add_controller(key_controller);
+
         Self {
             animator: AnimatorDefault::ease_in_out_animator(),
             listview,
             model,
+            selection_anchor,
         }
     }
 
+    fn song_at(listview: gtk::Widget, x: f64, y: f64) -> Option<SongWidget> {
+        listview
+            .pick(x, y, gtk::PickFlags::DEFAULT)?
+            .ancestor(SongWidget::static_type())?
+            .downcast::<SongWidget>()
+            .ok()
+    }
+
+    // The rows currently bound, along with the song widget in each
+    fn rows(listview: &gtk::ListView) -> impl Iterator<Item = (gtk::Widget, SongWidget)> {
+        std::iter::successors(listview.first_child(), |row| row.next_sibling()).filter_map(|row| {
+            let song = row.first_child()?.downcast::<SongWidget>().ok()?;
+            Some((row, song))
+        })
+    }
+
+    // Scrolling to the row makes sure it has a widget, which can then take the keyboard focus
+    fn focus_row(listview: &gtk::ListView, position: u32) {
+        listview
+            .activate_action("list.scroll-to-item", Some(&position.to_variant()))
+            .ok();
+        glib::idle_add_local_once(clone!(@weak listview => move || {
+            let row = Self::rows(&listview).find(|(_, song)| song.position() == Some(position));
+            if let Some((row, _)) = row {
+                row.grab_focus();
+            }
+        }));
+    }
+
+    // Headers depend on the songs around them, so bound rows need theirs again when those change
+    fn update_headers(&self) {
+        let model = Rc::clone(&self.model);
+        glib::idle_add_local_once(clone!(@weak self.listview as listview => move || {
+            for (_, song) in Self::rows(&listview) {
+                if let Some(position) = song.position() {
+                    song.set_header(model.header_at(position as usize).as_deref());
+                }
+            }
+        }));
+    }
+
+    fn popup_focused_menu(listview: &gtk::ListView) -> bool {
+        listview
+            .focus_child()
+            .and_then(|row| row.first_child())
+            .and_then(|child| child.downcast::<SongWidget>().ok())
+            .map(|song| song.popup_menu())
+            .unwrap_or(false)
+    }
+
     fn autoscroll_to_playing(&self, index: usize) {
         let len = self.model.song_list_model().partial_len() as f64;
         let scrolled_window: Option<gtk::ScrolledWindow> = ancestor(&self.listview);
@@ -175,7 +391,7 @@ where
         let is_selection_enabled = self.model.is_selection_enabled();
 
         self.model.song_list_model().for_each(|i, model_song| {
-            let state = self.model.song_state(&model_song.get_id());
+            let state = self.model.song_state(&model_song.description());
             model_song.set_state(state);
             if state.is_playing && autoscroll_to_playing && !is_selection_enabled {
                 self.autoscroll_to_playing(i);
@@ -233,9 +449,19 @@ where
                 Self::set_paused(&self.listview, self.model.is_paused());
             }
             AppEvent::SelectionEvent(SelectionEvent::SelectionModeChanged(_)) => {
+                self.selection_anchor.set(None);
                 Self::set_selection_active(&self.listview, self.model.is_selection_enabled());
                 self.update_list();
             }
+            // Which songs can be played changed
+            AppEvent::SettingsEvent(SettingsEvent::OfflineModeChanged(_)) => {
+                self.model.song_list_model().refresh();
+            }
+            // Songs were added or the grouping was turned on or off
+            AppEvent::BrowserEvent(BrowserEvent::SavedTracksUpdated)
+            | AppEvent::SettingsEvent(SettingsEvent::SettingsChanged) => {
+                self.update_headers();
+            }
             _ => {}
         }
     }
@@ -1,7 +1,9 @@
-use crate::app::components::display_add_css_provider;
-use crate::app::loader::ImageLoader;
-use crate::app::models::SongModel;
-use crate::app::Worker;
+use crate::app::components::utils::format_duration;
+use crate::app::components::{display_add_css_provider, labels, SongColumns};
+use crate::app::loader::PendingImage;
+use crate::app::models::{Restriction, SongModel};
+use crate::app::{offline_tracks, Worker};
+use gettextrs::gettext;
 use gio::MenuModel;
 use glib::subclass::InitializingObject;
 
@@ -12,6 +14,7 @@ use gtk::CompositeTemplate;
 mod imp {
 
     use super::*;
+    use std::cell::RefCell;
 
     const SONG_CLASS: &str = "song--playing";
 
@@ -36,11 +39,28 @@ mod imp {
         #[template_child]
         pub song_length: TemplateChild<gtk::Label>,
 
+        #[template_child]
+        pub song_album: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub song_added: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub song_header: TemplateChild<gtk::Label>,
+
         #[template_child]
         pub menu_btn: TemplateChild<gtk::MenuButton>,
 
         #[template_child]
         pub song_cover: TemplateChild<gtk::Image>,
+
+        pub cover: PendingImage,
+
+        // What gets dragged out of the row, e.g. onto a playlist in the sidebar
+        pub uri: RefCell<String>,
+
+        // The list item it's bound to, which knows where the row is in the list
+        pub list_item: glib::WeakRef<gtk::ListItem>,
     }
 
     #[glib::object_subclass]
@@ -76,10 +96,16 @@ mod imp {
                     let is_playing = value
                         .get()
                         .expect("type conformity checked by `Object::set_property`");
+                    let obj = self.obj();
                     if is_playing {
-                        self.obj().add_css_class(SONG_CLASS);
+                        obj.add_css_class(SONG_CLASS);
+                        // translators: Read by screen readers after the song that is currently playing
+                        obj.update_property(&[gtk::accessible::Property::Description(&gettext(
+                            "Playing",
+                        ))]);
                     } else {
-                        self.obj().remove_css_class(SONG_CLASS);
+                        obj.remove_css_class(SONG_CLASS);
+                        obj.reset_property(gtk::AccessibleProperty::Description);
                     }
                 }
                 "selected" => {
@@ -103,9 +129,26 @@ mod imp {
         fn constructed(&self) {
             self.parent_constructed();
             self.song_checkbox.set_sensitive(false);
+
+            let obj = (*self.obj()).clone();
+            let drag_source = gtk::DragSource::new();
+            drag_source.set_actions(gdk::DragAction::COPY);
+            drag_source.connect_prepare(clone!(@weak obj => @default-return None, move |_, _, _| {
+                let uri = obj.imp().uri.borrow();
+                if uri.is_empty() {
+                    return None;
+                }
+                Some(gdk::ContentProvider::for_value(&uri.to_value()))
+            }));
+            drag_source.connect_drag_begin(clone!(@weak obj => move |source, _| {
+                let paintable = gtk::WidgetPaintable::new(Some(&obj));
+                source.set_icon(Some(&paintable), 0, 0);
+            }));
+            obj.add_controller(drag_source);
         }
 
         fn dispose(&self) {
+            self.cover.cancel();
             while let Some(child) = self.obj().first_child() {
                 child.unparent();
             }
@@ -117,7 +160,7 @@ mod imp {
 }
 
 glib::wrapper! {
-    pub struct SongWidget(ObjectSubclass<imp::SongWidget>) @extends gtk::Widget, gtk::Grid;
+    pub struct SongWidget(ObjectSubclass<imp::SongWidget>) @extends gtk::Widget, gtk::Grid, @implements gtk::Accessible;
 }
 
 impl SongWidget {
@@ -138,6 +181,16 @@ impl SongWidget {
         }
     }
 
+    // Opens the context menu, when there is one
+    pub fn popup_menu(&self) -> bool {
+        let menu_btn = &self.imp().menu_btn;
+        let has_menu = menu_btn.menu_model().is_some();
+        if has_menu {
+            menu_btn.popup();
+        }
+        has_menu
+    }
+
     fn set_show_cover(&self, show_cover: bool) {
         let song_class = "song--cover";
         if show_cover {
@@ -147,23 +200,87 @@ impl SongWidget {
         }
     }
 
-    fn set_image(&self, pixbuf: Option<&gdk_pixbuf::Pixbuf>) {
-        self.imp().song_cover.set_from_pixbuf(pixbuf);
+    fn set_image(&self, texture: Option<&gdk::Texture>) {
+        let song_cover = &self.imp().song_cover;
+        song_cover.set_from_paintable(texture);
+        if texture.is_some() {
+            song_cover.remove_css_class("song__cover--placeholder");
+        } else {
+            song_cover.add_css_class("song__cover--placeholder");
+        }
     }
 
-    pub fn set_art(&self, model: &SongModel, worker: Worker) {
-        if let Some(url) = model.description().art.clone() {
-            let _self = self.downgrade();
-            worker.send_local_task(async move {
-                if let Some(_self) = _self.upgrade() {
-                    let loader = ImageLoader::new();
-                    let result = loader.load_remote(&url, "jpg", 100, 100).await;
-                    _self.set_image(result.as_ref());
-                }
-            });
+    // Unplayable songs are dimmed, with the reason in a tooltip
+    fn set_restriction(&self, restriction: Option<Restriction>, offline_unavailable: bool) {
+        let unplayable_class = "song--unplayable";
+        if let Some(restriction) = restriction {
+            self.add_css_class(unplayable_class);
+            self.set_tooltip_text(Some(&labels::unplayable_label(restriction)));
+        } else if offline_unavailable {
+            self.add_css_class(unplayable_class);
+            self.set_tooltip_text(Some(&labels::NOT_AVAILABLE_OFFLINE));
+        } else {
+            self.remove_css_class(unplayable_class);
+            self.set_tooltip_text(None);
         }
     }
 
+    // The album column has a fixed width so that it lines up from one row to the next
+    pub fn set_columns(&self, model: &SongModel, columns: SongColumns) {
+        let widget = self.imp();
+        widget.song_album.set_visible(columns.album);
+        if columns.album {
+            widget.song_album.set_text(&model.description().album.name);
+        }
+        widget.song_length.set_visible(columns.duration);
+    }
+
+    // When the song was saved, only shown in liked songs
+    pub fn set_date_added(&self, label: Option<&str>) {
+        let song_added = &self.imp().song_added;
+        song_added.set_text(label.unwrap_or_default());
+        song_added.set_visible(label.is_some());
+    }
+
+    // Shown above the first song of a group
+    pub fn set_header(&self, header: Option<&str>) {
+        let song_header = &self.imp().song_header;
+        song_header.set_text(header.unwrap_or_default());
+        song_header.set_visible(header.is_some());
+    }
+
+    pub fn set_art(&self, model: &SongModel, worker: Worker) {
+        // Rows get recycled, the cover of the previous song shouldn't linger while the new one loads
+        self.set_image(None);
+        let widget = self.imp();
+        let Some(url) = model.description().art.as_deref().map(String::from) else {
+            widget.cover.cancel();
+            return;
+        };
+        let _self = self.downgrade();
+        widget.cover.load(&worker, url, 100, move |texture| {
+            if let Some(_self) = _self.upgrade() {
+                _self.set_image(texture.as_ref());
+            }
+        });
+    }
+
+    // The row scrolled out of view, no need to finish loading its cover
+    pub fn unbind(&self) {
+        self.imp().cover.cancel();
+        self.imp().list_item.set(None);
+    }
+
+    pub fn set_list_item(&self, item: &gtk::ListItem) {
+        self.imp().list_item.set(Some(item));
+    }
+
+    // Follows the songs inserted or removed before it
+    pub fn position(&self) -> Option<u32> {
+        Some(self.imp().list_item.upgrade()?.position())
+            .filter(|&position| position != gtk::INVALID_LIST_POSITION)
+    }
+
     pub fn bind(&self, model: &SongModel, worker: Worker, show_cover: bool) {
         let widget = self.imp();
 
@@ -173,6 +290,19 @@ impl SongWidget {
         model.bind_playing(self, "playing");
         model.bind_selected(self, "selected");
 
+        let description = model.description();
+        widget.uri.replace(description.uri.clone());
+        self.update_property(&[gtk::accessible::Property::Label(&labels::song_row_label(
+            &description.artists_name(),
+            &description.title,
+            &format_duration(description.duration.into()),
+        ))]);
+        self.set_restriction(
+            description.restriction,
+            offline_tracks::is_unavailable(&description.id),
+        );
+        drop(description);
+
         self.set_show_cover(show_cover);
         if show_cover {
             self.set_art(model, worker);
@@ -1,9 +1,56 @@
 use gdk::prelude::*;
+use gettextrs::gettext;
 use gio::SimpleAction;
+use std::sync::Arc;
 
-use crate::app::models::SongDescription;
+use crate::api::SpotifyApiClient;
+use crate::app::models::{SongBatch, SongDescription, SongListModel};
 use crate::app::state::{AppAction, PlaybackAction};
-use crate::app::ActionDispatcher;
+use crate::app::{offline_tracks, ActionDispatcher, BatchLoader, BatchQuery, SongsSource};
+
+// Replaces the queue with the songs of a radio, and plays it from the start
+pub fn play_radio_actions(songs: Vec<SongDescription>) -> Vec<AppAction> {
+    let Some(first) = songs.first().map(|song| song.id.clone()) else {
+        // translators: This notification shows up when Spotify has no songs to suggest for a radio
+        return vec![AppAction::ShowNotification(gettext("No radio available"))];
+    };
+    vec![
+        PlaybackAction::LoadSongs(songs, 0).into(),
+        PlaybackAction::Load(first).into(),
+    ]
+}
+
+// Shuffles a paged source from a random track of its first batch, the rest of it is added as it loads
+pub fn shuffle_play(
+    dispatcher: &dyn ActionDispatcher,
+    loader: BatchLoader,
+    source: SongsSource,
+    first_batch: SongBatch,
+) {
+    let next_query = BatchQuery {
+        source: source.clone(),
+        batch: first_batch.batch,
+    }
+    .next();
+    dispatcher.dispatch_many(vec![
+        PlaybackAction::LoadPagedSongs(source, first_batch, 0).into(),
+        PlaybackAction::PlayShuffled.into(),
+    ]);
+    let Some(query) = next_query else {
+        return;
+    };
+    // It's playing, so it should keep loading after navigating away
+    dispatcher
+        .unscoped()
+        .dispatch_many_async(Box::pin(async move {
+            loader
+                .query_all(query, |source, batch| {
+                    PlaybackAction::AppendPagedSongs(source, batch).into()
+                })
+                .await
+                .unwrap_or_else(|error| error.into_iter().collect())
+        }));
+}
 
 impl SongDescription {
     pub fn make_queue_action(
@@ -32,8 +79,64 @@ impl SongDescription {
         dequeue
     }
 
+    // Plays the list from this song on, the songs above it are left out of the queue
+    pub fn make_play_from_here_action(
+        &self,
+        songs: SongListModel,
+        source: Option<SongsSource>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        name: Option<&str>,
+    ) -> SimpleAction {
+        let play_from_here = SimpleAction::new(name.unwrap_or("play_from_here"), None);
+        let id = self.id.clone();
+        play_from_here.connect_activate(move |_, _| {
+            let Some(position) = songs.find_index(&id) else {
+                return;
+            };
+            let load = match source.clone() {
+                Some(source) => songs
+                    .song_batch_for(position)
+                    .map(|batch| PlaybackAction::LoadPagedSongs(source, batch, position)),
+                None => Some(PlaybackAction::LoadSongs(songs.collect(), position)),
+            };
+            if let Some(load) = load {
+                dispatcher.dispatch(load.into());
+                dispatcher.dispatch(PlaybackAction::Load(id.clone()).into());
+            }
+        });
+        play_from_here
+    }
+
+    // Takes an unscoped dispatcher, the radio should start even if the user navigated away in the meantime
+    pub fn make_radio_action(
+        &self,
+        api: Arc<dyn SpotifyApiClient + Send + Sync>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        name: Option<&str>,
+    ) -> SimpleAction {
+        let radio = SimpleAction::new(name.unwrap_or("radio"), None);
+        let song = self.clone();
+        radio.connect_activate(move |_, _| {
+            let api = Arc::clone(&api);
+            let song = song.clone();
+            dispatcher.call_spotify_and_dispatch_many(move || async move {
+                let similar = api
+                    .get_recommendations(vec![], vec![song.id.clone()])
+                    .await?;
+                // Like in the official client, the radio starts with the song it was made from
+                let songs = std::iter::once(song.clone())
+                    .chain(similar.into_iter().filter(|s| s.id != song.id))
+                    .collect();
+                Ok(play_radio_actions(songs))
+            });
+        });
+        // Recommendations need the API
+        radio.set_enabled(!offline_tracks::is_offline());
+        radio
+    }
+
     pub fn make_link_action(&self, name: Option<&str>) -> SimpleAction {
-        let track_id = self.id.clone();
+        let track_id = self.original_id().to_string();
         let copy_link = SimpleAction::new(name.unwrap_or("copy_link"), None);
         copy_link.connect_activate(move |_, _| {
             let link = format!("https://open.spotify.com/track/{track_id}");
@@ -53,11 +156,25 @@ impl SongDescription {
         let album_id = self.album.id.clone();
         let view_album = SimpleAction::new(name.unwrap_or("view_album"), None);
         view_album.connect_activate(move |_, _| {
-            dispatcher.dispatch(AppAction::ViewAlbum(album_id.clone()));
+            dispatcher.dispatch(AppAction::ViewAlbum(album_id.to_string()));
         });
         view_album
     }
 
+    // Episodes keep their show where songs keep their album
+    pub fn make_show_action(
+        &self,
+        dispatcher: Box<dyn ActionDispatcher>,
+        name: Option<&str>,
+    ) -> SimpleAction {
+        let show_id = self.album.id.clone();
+        let view_show = SimpleAction::new(name.unwrap_or("view_show"), None);
+        view_show.connect_activate(move |_, _| {
+            dispatcher.dispatch(AppAction::ViewShow(show_id.to_string()));
+        });
+        view_show
+    }
+
     pub fn make_artist_actions(
         &self,
         dispatcher: Box<dyn ActionDispatcher>,
@@ -73,7 +190,7 @@ impl SongDescription {
                 );
                 let dispatcher = dispatcher.box_clone();
                 view_artist.connect_activate(move |_, _| {
-                    dispatcher.dispatch(AppAction::ViewArtist(id.clone()));
+                    dispatcher.dispatch(AppAction::ViewArtist(id.to_string()));
                 });
                 view_artist
             })
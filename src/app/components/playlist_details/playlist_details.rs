@@ -12,7 +12,7 @@ use crate::app::components::{
 };
 use crate::app::dispatch::Worker;
 use crate::app::loader::ImageLoader;
-use crate::app::state::{PlaybackEvent, SelectionEvent};
+use crate::app::state::{NetworkEvent, PlaybackEvent, SelectionEvent, SettingsEvent};
 use crate::app::{AppEvent, BrowserEvent};
 use libadwaita::subclass::prelude::BinImpl;
 
@@ -126,6 +126,11 @@ impl PlaylistDetailsWidget {
         self.imp().headerbar.set_editable(editing);
     }
 
+    fn set_share_uri(&self, uri: &str) {
+        self.imp().header_widget.set_share_uri(uri);
+        self.imp().header_mobile.set_share_uri(uri);
+    }
+
     fn set_info(&self, playlist: &str, owner: &str) {
         self.imp().header_widget.set_info(playlist, owner);
         self.imp().header_mobile.set_info(playlist, owner);
@@ -137,7 +142,7 @@ impl PlaylistDetailsWidget {
         self.imp().header_mobile.set_playing(is_playing);
     }
 
-    fn set_artwork(&self, art: &gdk_pixbuf::Pixbuf) {
+    fn set_artwork(&self, art: &gdk::Texture) {
         self.imp().header_widget.set_artwork(art);
         self.imp().header_mobile.set_artwork(art);
     }
@@ -178,6 +183,14 @@ impl PlaylistDetailsWidget {
         self.imp().header_mobile.connect_play(f);
     }
 
+    pub fn connect_shuffle<F>(&self, f: F)
+    where
+        F: Fn() + Clone + 'static,
+    {
+        self.imp().header_widget.connect_shuffle(f.clone());
+        self.imp().header_mobile.connect_shuffle(f);
+    }
+
     pub fn connect_done<F>(&self, f: F)
     where
         F: Fn(String) + 'static,
@@ -219,6 +232,7 @@ impl PlaylistDetails {
         ));
 
         widget.set_editable(model.is_playlist_editable());
+        widget.set_share_uri(&format!("spotify:playlist:{}", model.id));
 
         widget.connect_header();
 
@@ -240,6 +254,8 @@ impl PlaylistDetails {
 
         widget.connect_play(clone!(@weak model => move || model.toggle_play_playlist()));
 
+        widget.connect_shuffle(clone!(@weak model => move || model.shuffle_play_playlist()));
+
         widget.connect_go_back(clone!(@weak model => move || model.go_back()));
 
         Self {
@@ -261,11 +277,20 @@ impl PlaylistDetails {
             if let Some(art_url) = art_url.cloned() {
                 let widget = self.widget.downgrade();
                 self.worker.send_local_task(async move {
-                    let pixbuf = ImageLoader::new()
-                        .load_remote(&art_url[..], "jpg", 320, 320)
-                        .await;
-                    if let (Some(widget), Some(ref pixbuf)) = (widget.upgrade(), pixbuf) {
-                        widget.set_artwork(pixbuf);
+                    let texture = ImageLoader::new().load_texture(&art_url, 320).await;
+                    if let (Some(widget), Some(ref texture)) = (widget.upgrade(), texture) {
+                        widget.set_artwork(texture);
+                        widget.set_loaded();
+                    }
+                });
+            } else if let Some(mosaic) = self.model.mosaic_art() {
+                let widget = self.widget.downgrade();
+                self.worker.send_local_task(async move {
+                    let texture = ImageLoader::new().load_mosaic(&mosaic, 320).await;
+                    if let (Some(widget), Some(ref texture)) = (widget.upgrade(), texture) {
+                        widget.set_artwork(texture);
+                    }
+                    if let Some(widget) = widget.upgrade() {
                         widget.set_loaded();
                     }
                 });
@@ -310,8 +335,8 @@ impl EventListener for PlaylistDetails {
                 self.update_details();
                 self.update_playing(true);
             }
-            AppEvent::SelectionEvent(SelectionEvent::SelectionModeChanged(editing)) => {
-                self.set_editing(*editing);
+            AppEvent::SelectionEvent(SelectionEvent::SelectionModeChanged(_)) => {
+                self.set_editing(self.model.is_selection_enabled());
             }
             AppEvent::PlaybackEvent(PlaybackEvent::PlaybackPaused) => {
                 self.update_playing(false);
@@ -319,6 +344,14 @@ impl EventListener for PlaylistDetails {
             AppEvent::PlaybackEvent(PlaybackEvent::PlaybackResumed) => {
                 self.update_playing(true);
             }
+            AppEvent::SettingsEvent(SettingsEvent::GuestModeChanged(_)) => {
+                self.widget.set_editable(self.model.is_playlist_editable());
+            }
+            AppEvent::NetworkEvent(NetworkEvent::BackOnline)
+                if self.model.get_playlist_info().is_none() =>
+            {
+                self.model.load_playlist_info();
+            }
             _ => {}
         }
         self.broadcast_event(event);
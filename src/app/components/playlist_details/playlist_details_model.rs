@@ -6,7 +6,7 @@ use std::ops::Deref;
 use std::rc::Rc;
 
 use crate::api::SpotifyApiError;
-use crate::app::components::{labels, PlaylistModel};
+use crate::app::components::{labels, shuffle_play, PlaylistModel, SongColumns};
 use crate::app::models::*;
 use crate::app::state::SelectionContext;
 use crate::app::state::{BrowserAction, PlaybackAction, SelectionAction, SelectionState};
@@ -32,9 +32,19 @@ impl PlaylistDetailsModel {
         self.app_model.get_state()
     }
 
+    fn selection_context(&self) -> SelectionContext {
+        if self.is_playlist_editable() {
+            SelectionContext::EditablePlaylist(self.id.clone())
+        } else {
+            SelectionContext::PlaylistDetails(self.id.clone())
+        }
+    }
+
+    // Guests can't touch the host's playlists
     pub fn is_playlist_editable(&self) -> bool {
         let state = self.app_model.get_state();
-        state.logged_user.playlists.iter().any(|p| p.id == self.id)
+        !state.settings.settings.guest_mode
+            && state.logged_user.playlists.iter().any(|p| p.id == self.id)
     }
 
     pub fn get_playlist_info(&self) -> Option<impl Deref<Target = PlaylistDescription> + '_> {
@@ -46,6 +56,31 @@ impl PlaylistDetailsModel {
         })
     }
 
+    // The covers of the first four albums in the playlist, which stand in for a cover of its own
+    pub fn mosaic_art(&self) -> Option<Vec<String>> {
+        let songs = self.song_list_model();
+        let mut albums = vec![];
+        let mut art = vec![];
+        for i in 0..songs.partial_len() {
+            let Some(song) = songs.index_continuous(i) else {
+                break;
+            };
+            let song = song.description();
+            if let Some(url) = song
+                .art
+                .as_ref()
+                .filter(|_| !albums.contains(&song.album.id))
+            {
+                albums.push(song.album.id.clone());
+                art.push(url.to_string());
+            }
+            if art.len() == 4 {
+                return Some(art);
+            }
+        }
+        None
+    }
+
     pub fn is_playing(&self) -> bool {
         self.state().playback.is_playing()
     }
@@ -110,6 +145,15 @@ impl PlaylistDetailsModel {
             });
     }
 
+    pub fn shuffle_play_playlist(&self) {
+        let Some(first_batch) = self.song_list_model().song_batch_for(0) else {
+            return;
+        };
+        let source = SongsSource::Playlist(self.id.clone());
+        let loader = self.app_model.get_batch_loader();
+        shuffle_play(self.dispatcher.as_ref(), loader, source, first_batch);
+    }
+
     pub fn load_more_tracks(&self) -> Option<()> {
         let last_batch = self.song_list_model().last_batch()?;
         let query = BatchQuery {
@@ -136,7 +180,9 @@ impl PlaylistDetailsModel {
     pub fn update_playlist_details(&self, title: String) {
         let api = self.app_model.get_spotify();
         let id = self.id.clone();
+        // Should go through even if the screen is closed in the meantime
         self.dispatcher
+            .unscoped()
             .call_spotify_and_dispatch(move || async move {
                 let playlist = api.update_playlist_details(&id, title.clone()).await;
                 match playlist {
@@ -178,6 +224,13 @@ impl PlaylistModel for PlaylistDetailsModel {
         !self.state().playback.is_playing()
     }
 
+    fn song_columns(&self) -> SongColumns {
+        SongColumns {
+            album: true,
+            ..Default::default()
+        }
+    }
+
     fn current_song_id(&self) -> Option<String> {
         self.state().playback.current_song_id()
     }
@@ -187,23 +240,44 @@ impl PlaylistModel for PlaylistDetailsModel {
         let batch = self.song_list_model().song_batch_for(pos);
         if let Some(batch) = batch {
             self.dispatcher
-                .dispatch(PlaybackAction::LoadPagedSongs(source, batch).into());
+                .dispatch(PlaybackAction::LoadPagedSongs(source, batch, 0).into());
             self.dispatcher
                 .dispatch(PlaybackAction::Load(id.to_string()).into());
         }
     }
 
+    fn autoscroll_to_playing(&self) -> bool {
+        self.app_model
+            .get_state()
+            .settings
+            .settings
+            .autoscroll
+            .playlists
+    }
+
     fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
         let song = self.song_list_model().get(id)?;
         let song = song.description();
 
         let group = SimpleActionGroup::new();
 
+        group.add_action(&song.make_play_from_here_action(
+            self.song_list_model(),
+            Some(SongsSource::Playlist(self.id.clone())),
+            self.dispatcher.box_clone(),
+            None,
+        ));
+
         for view_artist in song.make_artist_actions(self.dispatcher.box_clone(), None) {
             group.add_action(&view_artist);
         }
         group.add_action(&song.make_album_action(self.dispatcher.box_clone(), None));
         group.add_action(&song.make_link_action(None));
+        group.add_action(&song.make_radio_action(
+            self.app_model.get_spotify(),
+            self.dispatcher.unscoped(),
+            None,
+        ));
         group.add_action(&song.make_queue_action(self.dispatcher.box_clone(), None));
 
         Some(group.upcast())
@@ -214,6 +288,7 @@ impl PlaylistModel for PlaylistDetailsModel {
         let song = song.description();
 
         let menu = gio::Menu::new();
+        menu.append(Some(&*labels::PLAY_FROM_HERE), Some("song.play_from_here"));
         menu.append(Some(&*labels::VIEW_ALBUM), Some("song.view_album"));
         for artist in song.artists.iter() {
             menu.append(
@@ -222,7 +297,14 @@ impl PlaylistModel for PlaylistDetailsModel {
             );
         }
 
+        if !song.is_episode() {
+            menu.append(Some(&*labels::SONG_RADIO), Some("song.radio"));
+        }
         menu.append(Some(&*labels::COPY_LINK), Some("song.copy_link"));
+        menu.append(
+            Some(&*labels::SHARE),
+            Some(&format!("app.share::{}", song.uri)),
+        );
         menu.append(Some(&*labels::ADD_TO_QUEUE), Some("song.queue"));
 
         Some(menu.upcast())
@@ -236,6 +318,11 @@ impl PlaylistModel for PlaylistDetailsModel {
         }
     }
 
+    fn select_songs(&self, songs: Vec<SongDescription>) {
+        self.dispatcher
+            .dispatch(SelectionAction::Select(songs).into());
+    }
+
     fn deselect_song(&self, id: &str) {
         self.dispatcher
             .dispatch(SelectionAction::Deselect(vec![id.to_string()]).into());
@@ -243,15 +330,15 @@ impl PlaylistModel for PlaylistDetailsModel {
 
     fn enable_selection(&self) -> bool {
         self.dispatcher
-            .dispatch(AppAction::EnableSelection(if self.is_playlist_editable() {
-                SelectionContext::EditablePlaylist(self.id.clone())
-            } else {
-                SelectionContext::Playlist
-            }));
+            .dispatch(AppAction::EnableSelection(self.selection_context()));
         true
     }
 
     fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
-        Some(Box::new(self.app_model.map_state(|s| &s.selection)))
+        let context = self.selection_context();
+        let selection = self
+            .app_model
+            .map_state_opt(|s| Some(&s.selection).filter(|s| s.context == context))?;
+        Some(Box::new(selection))
     }
 }
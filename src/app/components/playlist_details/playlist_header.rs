@@ -38,6 +38,12 @@ mod imp {
         #[template_child]
         pub play_button: TemplateChild<gtk::Button>,
 
+        #[template_child]
+        pub shuffle_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub share_button: TemplateChild<gtk::Button>,
+
         #[property(get, set, name = "original-entry-text")]
         pub original_entry_text: RefCell<String>,
     }
@@ -106,6 +112,13 @@ impl PlaylistHeaderWidget {
         self.imp().play_button.connect_clicked(move |_| f());
     }
 
+    pub fn connect_shuffle<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().shuffle_button.connect_clicked(move |_| f());
+    }
+
     pub fn reset_playlist_name(&self) {
         self.imp()
             .playlist_label_entry
@@ -116,8 +129,14 @@ impl PlaylistHeaderWidget {
         self.imp().playlist_label_entry.text().to_string()
     }
 
-    pub fn set_artwork(&self, art: &gdk_pixbuf::Pixbuf) {
-        self.imp().playlist_art.set_from_pixbuf(Some(art));
+    pub fn set_artwork(&self, art: &gdk::Texture) {
+        self.imp().playlist_art.set_from_paintable(Some(art));
+    }
+
+    pub fn set_share_uri(&self, uri: &str) {
+        self.imp()
+            .share_button
+            .set_action_target_value(Some(&uri.to_variant()));
     }
 
     pub fn set_info(&self, playlist: &str, owner: &str) {
@@ -0,0 +1,5 @@
+mod saved_episodes;
+pub use saved_episodes::*;
+
+mod saved_episodes_model;
+pub use saved_episodes_model::*;
@@ -0,0 +1,55 @@
+use gtk::prelude::*;
+use std::rc::Rc;
+
+use super::SavedEpisodesModel;
+use crate::app::components::{Component, EventListener, Playlist, SavedTracksWidget};
+use crate::app::state::{LoginEvent, NetworkEvent};
+use crate::app::{AppEvent, Worker};
+
+pub struct SavedEpisodes {
+    widget: SavedTracksWidget,
+    model: Rc<SavedEpisodesModel>,
+    children: Vec<Box<dyn EventListener>>,
+}
+
+impl SavedEpisodes {
+    pub fn new(model: Rc<SavedEpisodesModel>, worker: Worker) -> Self {
+        let widget = SavedTracksWidget::new();
+
+        widget.connect_bottom_edge(clone!(@weak model => move || {
+            model.load_more();
+        }));
+
+        let playlist = Playlist::new(widget.song_list_widget().clone(), model.clone(), worker);
+
+        Self {
+            widget,
+            model,
+            children: vec![Box::new(playlist)],
+        }
+    }
+}
+
+impl Component for SavedEpisodes {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        self.widget.upcast_ref()
+    }
+
+    fn get_children(&mut self) -> Option<&mut Vec<Box<dyn EventListener>>> {
+        Some(&mut self.children)
+    }
+}
+
+impl EventListener for SavedEpisodes {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started
+            | AppEvent::LoginEvent(LoginEvent::LoginCompleted(_))
+            | AppEvent::NetworkEvent(NetworkEvent::BackOnline) => {
+                self.model.load_initial();
+            }
+            _ => {}
+        }
+        self.broadcast_event(event);
+    }
+}
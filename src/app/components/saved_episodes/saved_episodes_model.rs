@@ -0,0 +1,128 @@
+use gio::prelude::*;
+use gio::SimpleActionGroup;
+use std::rc::Rc;
+
+use crate::app::components::{labels, PlaylistModel, SongColumns};
+use crate::app::models::*;
+use crate::app::state::PlaybackAction;
+use crate::app::{ActionDispatcher, AppModel, BatchQuery, BrowserAction, SongsSource};
+
+// Episodes saved on their own, listed and played like saved tracks
+pub struct SavedEpisodesModel {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl SavedEpisodesModel {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    pub fn load_initial(&self) {
+        let loader = self.app_model.get_batch_loader();
+        let query = BatchQuery {
+            source: SongsSource::SavedEpisodes,
+            batch: Batch::first_of_size(50),
+        };
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            loader
+                .query(query, |_s, song_batch| {
+                    BrowserAction::SetSavedEpisodes(Box::new(song_batch)).into()
+                })
+                .await
+        }));
+    }
+
+    pub fn load_more(&self) -> Option<()> {
+        let loader = self.app_model.get_batch_loader();
+        let last_batch = self.song_list_model().last_batch()?.next()?;
+        let query = BatchQuery {
+            source: SongsSource::SavedEpisodes,
+            batch: last_batch,
+        };
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            loader
+                .query(query, |_s, song_batch| {
+                    BrowserAction::AppendSavedEpisodes(Box::new(song_batch)).into()
+                })
+                .await
+        }));
+        Some(())
+    }
+}
+
+impl PlaylistModel for SavedEpisodesModel {
+    fn song_list_model(&self) -> SongListModel {
+        self.app_model
+            .get_state()
+            .browser
+            .home_state()
+            .expect("illegal attempt to read home_state")
+            .saved_episodes
+            .clone()
+    }
+
+    fn is_paused(&self) -> bool {
+        !self.app_model.get_state().playback.is_playing()
+    }
+
+    // The album column holds the podcast
+    fn song_columns(&self) -> SongColumns {
+        SongColumns {
+            album: true,
+            ..Default::default()
+        }
+    }
+
+    fn current_song_id(&self) -> Option<String> {
+        self.app_model.get_state().playback.current_song_id()
+    }
+
+    fn play_song_at(&self, pos: usize, id: &str) {
+        let source = SongsSource::SavedEpisodes;
+        let batch = self.song_list_model().song_batch_for(pos);
+        if let Some(batch) = batch {
+            self.dispatcher
+                .dispatch(PlaybackAction::LoadPagedSongs(source, batch, 0).into());
+            self.dispatcher
+                .dispatch(PlaybackAction::Load(id.to_string()).into());
+        }
+    }
+
+    fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
+        let song = self.song_list_model().get(id)?;
+        let song = song.description();
+
+        let group = SimpleActionGroup::new();
+
+        group.add_action(&song.make_play_from_here_action(
+            self.song_list_model(),
+            Some(SongsSource::SavedEpisodes),
+            self.dispatcher.box_clone(),
+            None,
+        ));
+        group.add_action(&song.make_queue_action(self.dispatcher.box_clone(), None));
+        group.add_action(&song.make_show_action(self.dispatcher.box_clone(), None));
+
+        Some(group.upcast())
+    }
+
+    fn menu_for(&self, id: &str) -> Option<gio::MenuModel> {
+        let song = self.song_list_model().get(id)?;
+        let song = song.description();
+
+        let menu = gio::Menu::new();
+        menu.append(Some(&*labels::PLAY_FROM_HERE), Some("song.play_from_here"));
+        menu.append(Some(&*labels::ADD_TO_QUEUE), Some("song.queue"));
+        menu.append(Some(&*labels::VIEW_SHOW), Some("song.view_show"));
+        menu.append(
+            Some(&*labels::SHARE),
+            Some(&format!("app.share::{}", song.uri)),
+        );
+
+        Some(menu.upcast())
+    }
+}
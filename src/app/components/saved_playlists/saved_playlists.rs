@@ -7,7 +7,7 @@ use super::SavedPlaylistsModel;
 use crate::app::components::{AlbumWidget, Component, EventListener};
 use crate::app::dispatch::Worker;
 use crate::app::models::AlbumModel;
-use crate::app::state::LoginEvent;
+use crate::app::state::{LoginEvent, NetworkEvent};
 use crate::app::{AppEvent, BrowserEvent, ListStore};
 
 mod imp {
@@ -134,7 +134,8 @@ impl EventListener for SavedPlaylists {
                 let _ = self.model.refresh_saved_playlists();
                 self.bind_flowbox();
             }
-            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => {
+            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_))
+            | AppEvent::NetworkEvent(NetworkEvent::BackOnline) => {
                 let _ = self.model.refresh_saved_playlists();
             }
             AppEvent::BrowserEvent(BrowserEvent::SavedPlaylistsUpdated) => {
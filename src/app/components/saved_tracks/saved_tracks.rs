@@ -5,7 +5,7 @@ use std::rc::Rc;
 
 use super::SavedTracksModel;
 use crate::app::components::{Component, EventListener, Playlist};
-use crate::app::state::LoginEvent;
+use crate::app::state::{LoginEvent, NetworkEvent};
 use crate::app::{AppEvent, Worker};
 use libadwaita::subclass::prelude::BinImpl;
 
@@ -47,12 +47,13 @@ glib::wrapper! {
     pub struct SavedTracksWidget(ObjectSubclass<imp::SavedTracksWidget>) @extends gtk::Widget, libadwaita::Bin;
 }
 
+// Also used for saved episodes, which are listed the same way
 impl SavedTracksWidget {
-    fn new() -> Self {
+    pub fn new() -> Self {
         glib::Object::new()
     }
 
-    fn connect_bottom_edge<F>(&self, f: F)
+    pub fn connect_bottom_edge<F>(&self, f: F)
     where
         F: Fn() + 'static,
     {
@@ -65,7 +66,7 @@ impl SavedTracksWidget {
             });
     }
 
-    fn song_list_widget(&self) -> &gtk::ListView {
+    pub fn song_list_widget(&self) -> &gtk::ListView {
         self.imp().song_list.as_ref()
     }
 }
@@ -107,7 +108,9 @@ impl Component for SavedTracks {
 impl EventListener for SavedTracks {
     fn on_event(&mut self, event: &AppEvent) {
         match event {
-            AppEvent::Started | AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => {
+            AppEvent::Started
+            | AppEvent::LoginEvent(LoginEvent::LoginCompleted(_))
+            | AppEvent::NetworkEvent(NetworkEvent::BackOnline) => {
                 self.model.load_initial();
             }
             _ => {}
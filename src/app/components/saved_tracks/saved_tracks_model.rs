@@ -3,12 +3,53 @@ use gio::SimpleActionGroup;
 use std::ops::Deref;
 use std::rc::Rc;
 
-use crate::app::components::{labels, PlaylistModel};
+use crate::app::components::{labels, PlaylistModel, SongColumns};
 use crate::app::models::*;
 use crate::app::state::SelectionContext;
 use crate::app::state::{PlaybackAction, SelectionAction, SelectionState};
 use crate::app::{ActionDispatcher, AppAction, AppModel, BatchQuery, BrowserAction, SongsSource};
 
+// Liked songs can be grouped by when they were saved
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AddedGroup {
+    ThisWeek,
+    ThisMonth,
+    Earlier,
+}
+
+impl AddedGroup {
+    fn label(self) -> String {
+        match self {
+            Self::ThisWeek => labels::ADDED_THIS_WEEK.clone(),
+            Self::ThisMonth => labels::ADDED_THIS_MONTH.clone(),
+            Self::Earlier => labels::ADDED_EARLIER.clone(),
+        }
+    }
+}
+
+fn added_at(song: &SongDescription) -> Option<glib::DateTime> {
+    glib::DateTime::from_iso8601(song.added_at.as_deref()?, None)
+        .ok()?
+        .to_local()
+        .ok()
+}
+
+fn days_between(from: &glib::DateTime, to: &glib::DateTime) -> u32 {
+    (to.to_unix() - from.to_unix()).max(0) as u32 / (24 * 3600)
+}
+
+fn added_group(song: &SongDescription, now: &glib::DateTime) -> AddedGroup {
+    match added_at(song) {
+        // Songs saved from here don't come with a date, but they were just saved
+        None => AddedGroup::ThisWeek,
+        Some(added) if days_between(&added, now) < 7 => AddedGroup::ThisWeek,
+        Some(added) if (added.year(), added.month()) == (now.year(), now.month()) => {
+            AddedGroup::ThisMonth
+        }
+        Some(_) => AddedGroup::Earlier,
+    }
+}
+
 pub struct SavedTracksModel {
     app_model: Rc<AppModel>,
     dispatcher: Box<dyn ActionDispatcher>,
@@ -70,6 +111,13 @@ impl PlaylistModel for SavedTracksModel {
         !self.app_model.get_state().playback.is_playing()
     }
 
+    fn song_columns(&self) -> SongColumns {
+        SongColumns {
+            album: true,
+            ..Default::default()
+        }
+    }
+
     fn current_song_id(&self) -> Option<String> {
         self.app_model.get_state().playback.current_song_id()
     }
@@ -79,13 +127,49 @@ impl PlaylistModel for SavedTracksModel {
         let batch = self.song_list_model().song_batch_for(pos);
         if let Some(batch) = batch {
             self.dispatcher
-                .dispatch(PlaybackAction::LoadPagedSongs(source, batch).into());
+                .dispatch(PlaybackAction::LoadPagedSongs(source, batch, 0).into());
             self.dispatcher
                 .dispatch(PlaybackAction::Load(id.to_string()).into());
         }
     }
     fn autoscroll_to_playing(&self) -> bool {
-        true
+        self.app_model
+            .get_state()
+            .settings
+            .settings
+            .autoscroll
+            .saved_tracks
+    }
+
+    // How long ago for recent songs, the date for older ones
+    fn date_added_label(&self, song: &SongDescription) -> Option<String> {
+        let added = added_at(song)?;
+        let days = days_between(&added, &glib::DateTime::now_local().ok()?);
+        if days < 28 {
+            Some(labels::added_ago_label(days))
+        } else {
+            added.format("%x").ok().map(String::from)
+        }
+    }
+
+    fn header_at(&self, position: usize) -> Option<String> {
+        let grouped = self
+            .app_model
+            .get_state()
+            .settings
+            .settings
+            .group_saved_tracks_by_date;
+        if !grouped {
+            return None;
+        }
+        let now = glib::DateTime::now_local().ok()?;
+        let list = self.song_list_model();
+        let group = added_group(&list.index_continuous(position)?.description(), &now);
+        let previous = position
+            .checked_sub(1)
+            .and_then(|i| list.index_continuous(i))
+            .map(|song| added_group(&song.description(), &now));
+        Some(group.label()).filter(|_| previous != Some(group))
     }
 
     fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
@@ -94,11 +178,23 @@ impl PlaylistModel for SavedTracksModel {
 
         let group = SimpleActionGroup::new();
 
+        group.add_action(&song.make_play_from_here_action(
+            self.song_list_model(),
+            Some(SongsSource::SavedTracks),
+            self.dispatcher.box_clone(),
+            None,
+        ));
+
         for view_artist in song.make_artist_actions(self.dispatcher.box_clone(), None) {
             group.add_action(&view_artist);
         }
         group.add_action(&song.make_album_action(self.dispatcher.box_clone(), None));
         group.add_action(&song.make_link_action(None));
+        group.add_action(&song.make_radio_action(
+            self.app_model.get_spotify(),
+            self.dispatcher.unscoped(),
+            None,
+        ));
 
         Some(group.upcast())
     }
@@ -108,6 +204,7 @@ impl PlaylistModel for SavedTracksModel {
         let song = song.description();
 
         let menu = gio::Menu::new();
+        menu.append(Some(&*labels::PLAY_FROM_HERE), Some("song.play_from_here"));
         menu.append(Some(&*labels::VIEW_ALBUM), Some("song.view_album"));
         for artist in song.artists.iter() {
             menu.append(
@@ -116,7 +213,14 @@ impl PlaylistModel for SavedTracksModel {
             );
         }
 
+        if !song.is_episode() {
+            menu.append(Some(&*labels::SONG_RADIO), Some("song.radio"));
+        }
         menu.append(Some(&*labels::COPY_LINK), Some("song.copy_link"));
+        menu.append(
+            Some(&*labels::SHARE),
+            Some(&format!("app.share::{}", song.uri)),
+        );
 
         Some(menu.upcast())
     }
@@ -129,6 +233,11 @@ impl PlaylistModel for SavedTracksModel {
         }
     }
 
+    fn select_songs(&self, songs: Vec<SongDescription>) {
+        self.dispatcher
+            .dispatch(SelectionAction::Select(songs).into());
+    }
+
     fn deselect_song(&self, id: &str) {
         self.dispatcher
             .dispatch(SelectionAction::Deselect(vec![id.to_string()]).into());
@@ -141,7 +250,9 @@ impl PlaylistModel for SavedTracksModel {
     }
 
     fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
-        let selection = self.app_model.map_state(|s| &s.selection);
+        let selection = self.app_model.map_state_opt(|s| {
+            Some(&s.selection).filter(|s| s.context == SelectionContext::SavedTracks)
+        })?;
         Some(Box::new(selection))
     }
 }
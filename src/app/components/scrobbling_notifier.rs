@@ -0,0 +1,153 @@
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::app::components::EventListener;
+use crate::app::models::SongDescription;
+use crate::app::state::PlaybackEvent;
+use crate::app::{AppEvent, AppModel, Worker};
+use crate::scrobbling::{
+    enabled_scrobblers, scrobble_or_enqueue, should_scrobble, submit_pending, ScrobbledTrack,
+};
+
+// What we know about the track being played
+struct PlayingTrack {
+    track: ScrobbledTrack,
+    // Unix timestamp
+    started_at: u64,
+    // Until it was last paused
+    played: Duration,
+    // Set while playing
+    resumed_at: Option<Instant>,
+    scrobbled: bool,
+}
+
+impl PlayingTrack {
+    fn new(song: SongDescription, is_playing: bool) -> Self {
+        let started_at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            track: ScrobbledTrack {
+                artist: song.artists_name(),
+                title: song.title,
+                album: song.album.name.to_string(),
+                duration: Duration::from_millis(song.duration as u64),
+            },
+            started_at,
+            played: Duration::ZERO,
+            resumed_at: Some(Instant::now()).filter(|_| is_playing),
+            scrobbled: false,
+        }
+    }
+
+    fn played(&self) -> Duration {
+        self.played + self.resumed_at.map(|t| t.elapsed()).unwrap_or_default()
+    }
+
+    fn pause(&mut self) {
+        if let Some(resumed_at) = self.resumed_at.take() {
+            self.played += resumed_at.elapsed();
+        }
+    }
+
+    fn resume(&mut self) {
+        self.resumed_at.get_or_insert_with(Instant::now);
+    }
+}
+
+// Tells the scrobbling services enabled in the preferences what is being listened to
+pub struct ScrobblingNotifier {
+    app_model: Rc<AppModel>,
+    worker: Worker,
+    current: Option<PlayingTrack>,
+}
+
+impl ScrobblingNotifier {
+    pub fn new(app_model: Rc<AppModel>, worker: Worker) -> Self {
+        Self {
+            app_model,
+            worker,
+            current: None,
+        }
+    }
+
+    fn start_track(&mut self) {
+        self.finish_track();
+
+        let state = self.app_model.get_state();
+        let is_playing = state.playback.is_playing();
+        self.current = state
+            .playback
+            .current_song()
+            .map(|song| PlayingTrack::new(song, is_playing));
+        drop(state);
+
+        let Some(current) = self.current.as_ref() else {
+            return;
+        };
+        for scrobbler in enabled_scrobblers() {
+            let track = current.track.clone();
+            self.worker.send_task(async move {
+                match scrobbler.now_playing(track).await {
+                    // Back online, maybe
+                    Ok(_) => submit_pending(vec![scrobbler]).await,
+                    Err(err) => {
+                        warn!("Could not update {} now playing: {}", scrobbler.name(), err)
+                    }
+                }
+            });
+        }
+    }
+
+    // Scrobbles as soon as the track has been played long enough
+    fn check_played(&mut self) {
+        let Some(current) = self.current.as_mut() else {
+            return;
+        };
+        if current.scrobbled || !should_scrobble(current.track.duration, current.played()) {
+            return;
+        }
+        current.scrobbled = true;
+
+        for scrobbler in enabled_scrobblers() {
+            let track = current.track.clone();
+            let started_at = current.started_at;
+            self.worker.send_task(async move {
+                if scrobble_or_enqueue(scrobbler.clone(), track, started_at).await {
+                    submit_pending(vec![scrobbler]).await;
+                }
+            });
+        }
+    }
+
+    fn finish_track(&mut self) {
+        self.check_played();
+        self.current = None;
+    }
+}
+
+impl EventListener for ScrobblingNotifier {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) => self.start_track(),
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackPaused) => {
+                self.check_played();
+                if let Some(current) = self.current.as_mut() {
+                    current.pause();
+                }
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackResumed) => {
+                if let Some(current) = self.current.as_mut() {
+                    current.resume();
+                }
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackStopped) => self.finish_track(),
+            // Sent regularly while playing, a good time to check
+            AppEvent::PlaybackEvent(
+                PlaybackEvent::SeekSynced(_) | PlaybackEvent::TrackSeeked(_),
+            ) => self.check_played(),
+            _ => {}
+        }
+    }
+}
@@ -203,7 +203,7 @@ impl SearchResults {
                 self.album_results_model.append(&AlbumModel::new(
                     &album.artists_name(),
                     &album.title,
-                    album.year(),
+                    album.release_date,
                     album.art.as_ref(),
                     &album.id,
                 ));
@@ -3,10 +3,12 @@ use gtk::prelude::*;
 use std::ops::Deref;
 use std::rc::Rc;
 
-use crate::app::components::{Component, EventListener};
-use crate::app::models::PlaylistSummary;
+use crate::api::{SpotifyApiError, SAVED_TRACKS_MAX_IDS};
+use crate::app::components::{labels, Component, EventListener};
+use crate::app::models::{PlaylistSummary, SongDescription};
 use crate::app::state::{
-    LoginEvent, SelectionAction, SelectionContext, SelectionEvent, SelectionState,
+    LibraryMutation, LoginEvent, SelectionAction, SelectionContext, SelectionEvent, SelectionState,
+    SettingsEvent, Undoable,
 };
 use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel, BrowserAction};
 
@@ -52,50 +54,132 @@ impl SelectionToolbarModel {
 
     pub fn save_selection(&self) {
         let api = self.app_model.get_spotify();
-        let ids: Vec<String> = self
-            .selection()
-            .peek_selection()
-            .map(|s| &s.id)
-            .cloned()
-            .collect();
-        self.dispatcher
-            .call_spotify_and_dispatch_many(move || async move {
+        let songs: Vec<SongDescription> = self.selection().peek_selection().cloned().collect();
+        let ids: Vec<String> = songs.iter().map(|s| s.original_id().to_string()).collect();
+        self.dispatcher.call_spotify_and_mutate(
+            LibraryMutation::SaveTracks(songs),
+            move || async move {
                 api.save_tracks(ids).await?;
-                Ok(vec![
-                    AppAction::SaveSelection,
-                    AppAction::ShowNotification(gettext("Tracks saved!")),
-                ])
-            })
+                Ok(vec![AppAction::ShowNotification(gettext("Tracks saved!"))])
+            },
+        );
+        self.dispatcher.dispatch(AppAction::CancelSelection);
     }
 
+    // One mutation per request, so that a failure partway only takes back the songs that are still saved
     fn remove_saved_tracks(&self) {
         let api = self.app_model.get_spotify();
-        let ids: Vec<String> = self
+        let chunks: Vec<Vec<SongDescription>> = self
             .selection()
             .peek_selection()
-            .map(|s| &s.id)
             .cloned()
+            .collect::<Vec<_>>()
+            .chunks(SAVED_TRACKS_MAX_IDS)
+            .map(<[SongDescription]>::to_vec)
             .collect();
+        for chunk in &chunks {
+            self.dispatcher.dispatch(
+                BrowserAction::ApplyMutation(LibraryMutation::RemoveSavedTracks(chunk.clone()))
+                    .into(),
+            );
+        }
         self.dispatcher
             .call_spotify_and_dispatch_many(move || async move {
-                api.remove_saved_tracks(ids).await?;
-                Ok(vec![AppAction::UnsaveSelection])
-            })
+                let mut actions: Vec<AppAction> = vec![];
+                let mut removed: Vec<SongDescription> = vec![];
+                let mut chunks = chunks.into_iter();
+                while let Some(chunk) = chunks.next() {
+                    let ids = chunk.iter().map(|s| s.original_id().to_string()).collect();
+                    match api.remove_saved_tracks(ids).await {
+                        Ok(()) => {
+                            actions.push(
+                                BrowserAction::ConfirmMutation(LibraryMutation::RemoveSavedTracks(
+                                    chunk.clone(),
+                                ))
+                                .into(),
+                            );
+                            removed.extend(chunk);
+                        }
+                        // Removing is idempotent, so it's all done again once the token is refreshed
+                        Err(SpotifyApiError::InvalidToken) => {
+                            return Err(SpotifyApiError::InvalidToken)
+                        }
+                        Err(err) => {
+                            error!("Spotify API error: {}", err);
+                            actions.extend(std::iter::once(chunk).chain(chunks).map(|chunk| {
+                                BrowserAction::RevertMutation(LibraryMutation::RemoveSavedTracks(
+                                    chunk,
+                                ))
+                                .into()
+                            }));
+                            actions.push(AppAction::ShowNotification(
+                                labels::LIBRARY_UPDATE_FAILED.clone(),
+                            ));
+                            break;
+                        }
+                    }
+                }
+                if !removed.is_empty() {
+                    let message = labels::n_songs_removed_label(removed.len());
+                    actions.push(AppAction::PushUndoable(
+                        Undoable::UnsavedTracks(removed),
+                        message,
+                    ));
+                }
+                Ok(actions)
+            });
+        self.dispatcher.dispatch(AppAction::CancelSelection);
+    }
+
+    // One link per line, so that they can be pasted as a list
+    pub fn copy_links(&self, clipboard: gdk::Clipboard) {
+        let links = self
+            .selection()
+            .peek_selection()
+            .map(|s| format!("https://open.spotify.com/track/{}", s.original_id()))
+            .collect::<Vec<String>>()
+            .join("\n");
+        clipboard.set_text(&links);
+        self.dispatcher
+            .dispatch(AppAction::ShowNotification(labels::n_links_copied_label(
+                self.selected_count(),
+            )));
     }
 
     fn selection(&self) -> impl Deref<Target = SelectionState> + '_ {
         self.app_model.map_state(|s| &s.selection)
     }
 
+    fn is_guest_mode(&self) -> bool {
+        self.app_model.get_state().settings.settings.guest_mode
+    }
+
+    fn is_offline(&self) -> bool {
+        self.app_model.get_state().settings.settings.offline_mode
+    }
+
     fn selected_count(&self) -> usize {
         self.selection().count()
     }
 
+    fn selected_duration(&self) -> u64 {
+        self.selection()
+            .peek_selection()
+            .map(|s| s.duration as u64)
+            .sum()
+    }
+
     fn user_playlists(&self) -> impl Deref<Target = Vec<PlaylistSummary>> + '_ {
         self.app_model.map_state(|s| &s.logged_user.playlists)
     }
 
     fn add_to_playlist(&self, id: &str) {
+        let title = self
+            .user_playlists()
+            .iter()
+            .find(|p| p.id == id)
+            .map(|p| p.title.clone())
+            .unwrap_or_default();
         let id = id.to_string();
         let api = self.app_model.get_spotify();
         let uris: Vec<String> = self
@@ -105,15 +189,34 @@ impl SelectionToolbarModel {
             .cloned()
             .collect();
         self.dispatcher
-            .call_spotify_and_dispatch(move || async move {
+            .call_spotify_and_dispatch_many(move || async move {
+                let count = uris.len();
                 api.add_to_playlist(&id, uris).await?;
-                Ok(SelectionAction::Clear.into())
+                Ok(vec![
+                    SelectionAction::Clear.into(),
+                    AppAction::ShowNotification(labels::n_songs_added_to_playlist_label(
+                        count, &title,
+                    )),
+                ])
             })
     }
 
     fn remove_from_playlist(&self, id: &str) {
         let api = self.app_model.get_spotify();
         let id = id.to_string();
+        // Positions are kept around so that the songs can be put back where they were
+        let songs: Vec<(usize, SongDescription)> = {
+            let state = self.app_model.get_state();
+            let playlist = state.browser.playlist_details_state(&id);
+            state
+                .selection
+                .peek_selection()
+                .filter_map(|song| {
+                    let position = playlist?.songs.find_index(&song.id)?;
+                    Some((position, song.clone()))
+                })
+                .collect()
+        };
         let uris: Vec<String> = self
             .selection()
             .peek_selection()
@@ -123,9 +226,11 @@ impl SelectionToolbarModel {
         self.dispatcher
             .call_spotify_and_dispatch_many(move || async move {
                 api.remove_from_playlist(&id, uris.clone()).await?;
+                let message = labels::n_songs_removed_label(uris.len());
                 Ok(vec![
-                    BrowserAction::RemoveTracksFromPlaylist(id, uris).into(),
+                    BrowserAction::RemoveTracksFromPlaylist(id.clone(), uris).into(),
                     SelectionAction::Clear.into(),
+                    AppAction::PushUndoable(Undoable::RemovedFromPlaylist(id, songs), message),
                 ])
             })
     }
@@ -144,13 +249,26 @@ impl SelectionToolbar {
         widget.connect_queue(clone!(@weak model => move || model.queue_selection()));
         widget.connect_remove(clone!(@weak model => move || model.remove_selection()));
         widget.connect_save(clone!(@weak model => move || model.save_selection()));
+        widget.connect_copy_links(
+            clone!(@weak model => move |clipboard| model.copy_links(clipboard)),
+        );
         Self { model, widget }
     }
 
     fn update_active_tools(&self) {
         let count = self.model.selected_count();
+        let summary = Some(labels::selection_summary_label(
+            count,
+            self.model.selected_duration(),
+        ))
+        .filter(|_| count > 0);
+        self.widget.set_summary(summary.as_deref());
+        self.widget
+            .set_copy_links(SelectionToolState::Visible(count > 0));
         match self.model.selection().context {
-            SelectionContext::Default => {
+            SelectionContext::Default
+            | SelectionContext::AlbumDetails(_)
+            | SelectionContext::ArtistDetails(_) => {
                 self.widget.set_move(SelectionToolState::Hidden);
                 self.widget
                     .set_queue(SelectionToolState::Visible(count > 0));
@@ -183,7 +301,7 @@ impl SelectionToolbar {
                     .set_remove(SelectionToolState::Visible(count > 0));
                 self.widget.set_save(SelectionToolState::Visible(count > 0));
             }
-            SelectionContext::Playlist => {
+            SelectionContext::PlaylistDetails(_) => {
                 self.widget.set_move(SelectionToolState::Hidden);
                 self.widget
                     .set_queue(SelectionToolState::Visible(count > 0));
@@ -201,6 +319,22 @@ impl SelectionToolbar {
                 self.widget.set_save(SelectionToolState::Hidden);
             }
         };
+        // Guests may only (de)queue songs
+        if self.model.is_guest_mode() {
+            self.widget.set_add(SelectionToolState::Hidden);
+            self.widget.set_save(SelectionToolState::Hidden);
+            if !matches!(self.model.selection().context, SelectionContext::Queue) {
+                self.widget.set_remove(SelectionToolState::Hidden);
+            }
+        }
+        // Same offline, only the queue doesn't need the API
+        if self.model.is_offline() {
+            self.widget.set_add(SelectionToolState::Hidden);
+            self.widget.set_save(SelectionToolState::Hidden);
+            if !matches!(self.model.selection().context, SelectionContext::Queue) {
+                self.widget.set_remove(SelectionToolState::Hidden);
+            }
+        }
     }
 }
 
@@ -217,7 +351,8 @@ impl EventListener for SelectionToolbar {
                 self.widget.set_visible(*active);
                 self.update_active_tools();
             }
-            AppEvent::SelectionEvent(SelectionEvent::SelectionChanged) => {
+            AppEvent::SelectionEvent(SelectionEvent::SelectionChanged)
+            | AppEvent::SettingsEvent(SettingsEvent::OfflineModeChanged(_)) => {
                 self.update_active_tools();
             }
             AppEvent::LoginEvent(LoginEvent::UserPlaylistsLoaded) => {
@@ -1,10 +1,11 @@
-use gio::prelude::ActionMapExt;
-use gio::{SimpleAction, SimpleActionGroup};
+use gettextrs::gettext;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
+use std::convert::TryFrom;
+use std::rc::Rc;
 
-use crate::app::components::{display_add_css_provider, labels};
+use crate::app::components::display_add_css_provider;
 use crate::app::models::PlaylistSummary;
 
 mod imp {
@@ -23,6 +24,9 @@ mod imp {
         #[template_child]
         pub move_down: TemplateChild<gtk::Button>,
 
+        #[template_child]
+        pub summary: TemplateChild<gtk::Label>,
+
         #[template_child]
         pub add: TemplateChild<gtk::MenuButton>,
 
@@ -34,6 +38,9 @@ mod imp {
 
         #[template_child]
         pub save: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub copy_links: TemplateChild<gtk::Button>,
     }
 
     #[glib::object_subclass]
@@ -111,6 +118,16 @@ impl SelectionToolbarWidget {
         self.imp().save.connect_clicked(move |_| f());
     }
 
+    // Hands out the clipboard of the display the toolbar is on
+    pub fn connect_copy_links<F>(&self, f: F)
+    where
+        F: Fn(gdk::Clipboard) + 'static,
+    {
+        self.imp()
+            .copy_links
+            .connect_clicked(move |button| f(button.clipboard()));
+    }
+
     pub fn connect_remove<F>(&self, f: F)
     where
         F: Fn() + 'static,
@@ -145,39 +162,83 @@ impl SelectionToolbarWidget {
         self.imp().save.set_visible(state.visible());
     }
 
+    pub fn set_copy_links(&self, state: SelectionToolState) {
+        self.imp().copy_links.set_sensitive(state.sensitive());
+        self.imp().copy_links.set_visible(state.visible());
+    }
+
+    pub fn set_summary(&self, summary: Option<&str>) {
+        let label = &self.imp().summary;
+        label.set_text(summary.unwrap_or_default());
+        label.set_visible(summary.is_some());
+    }
+
     pub fn set_visible(&self, visible: bool) {
         gtk::Widget::set_visible(self.upcast_ref(), visible);
         self.imp().action_bar.set_revealed(visible);
     }
 
+    // A searchable list of the user's playlists, to pick the one to add the selection to
     pub fn connect_playlists<F>(&self, playlists: &[PlaylistSummary], on_playlist_selected: F)
     where
-        F: Fn(&str) + Clone + 'static,
+        F: Fn(&str) + 'static,
     {
-        let menu = gio::Menu::new();
-        let action_group = SimpleActionGroup::new();
-
-        for PlaylistSummary { title, id } in playlists {
-            let action_name = format!("playlist_{id}");
-
-            action_group.add_action(&{
-                let id = id.clone();
-                let action = SimpleAction::new(&action_name, None);
-                let f = on_playlist_selected.clone();
-                action.connect_activate(move |_, _| f(&id));
-                action
-            });
-
-            menu.append(
-                Some(&labels::add_to_playlist_label(title)),
-                Some(&format!("add_to.{action_name}")),
-            );
+        let playlists: Rc<Vec<PlaylistSummary>> = Rc::new(playlists.to_vec());
+
+        let search = gtk::SearchEntry::new();
+        // translators: Placeholder of the search field in the list of playlists the selection can be added to
+        search.set_placeholder_text(Some(&gettext("Search playlists")));
+
+        let list = gtk::ListBox::new();
+        list.set_selection_mode(gtk::SelectionMode::None);
+        list.add_css_class("navigation-sidebar");
+        for PlaylistSummary { title, .. } in playlists.iter() {
+            let label = gtk::Label::new(Some(title));
+            label.set_halign(gtk::Align::Start);
+            label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+            list.append(&label);
         }
 
-        let popover = gtk::PopoverMenu::from_model(Some(&menu));
+        list.set_filter_func(
+            clone!(@weak search, @strong playlists => @default-return true, move |row| {
+                let query = search.text().to_lowercase();
+                usize::try_from(row.index())
+                    .ok()
+                    .and_then(|i| playlists.get(i))
+                    .map(|p| p.title.to_lowercase().contains(&query))
+                    .unwrap_or(false)
+            }),
+        );
+        search.connect_search_changed(clone!(@weak list => move |_| list.invalidate_filter()));
+
+        let scrolled_window = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .propagate_natural_height(true)
+            .max_content_height(400)
+            .child(&list)
+            .build();
+
+        let content = gtk::Box::new(gtk::Orientation::Vertical, 6);
+        content.append(&search);
+        content.append(&scrolled_window);
+
+        let popover = gtk::Popover::new();
+        popover.set_child(Some(&content));
+        popover.connect_show(clone!(@weak search => move |_| {
+            search.set_text("");
+            search.grab_focus();
+        }));
+
+        list.connect_row_activated(clone!(@weak popover => move |_, row| {
+            popover.popdown();
+            let playlist = usize::try_from(row.index())
+                .ok()
+                .and_then(|i| playlists.get(i));
+            if let Some(playlist) = playlist {
+                on_playlist_selected(&playlist.id);
+            }
+        }));
+
         self.imp().add.set_popover(Some(&popover));
-        self.imp()
-            .add
-            .insert_action_group("add_to", Some(&action_group));
     }
 }
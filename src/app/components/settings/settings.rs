@@ -1,16 +1,56 @@
-use crate::app::components::EventListener;
+use crate::api::CacheCategory;
+use crate::app::components::{labels, EventListener};
+use crate::app::credentials::{self, Secret};
 use crate::app::AppEvent;
 use crate::settings::SpotSettings;
 
+use gettextrs::*;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
 use libadwaita::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
 
 use super::SettingsModel;
 
 const SETTINGS: &str = "dev.alextren.Spot";
 
+// Actions that can be bound to a keyboard shortcut, along with how they're shown to the user
+fn shortcut_actions() -> [(&'static str, String); 6] {
+    [
+        // translators: This is an action that can be bound to a keyboard shortcut in preferences
+        ("app.toggle_playback", gettext("Play/Pause")),
+        // translators: This is an action that can be bound to a keyboard shortcut in preferences
+        ("app.player_prev", gettext("Previous track")),
+        // translators: This is an action that can be bound to a keyboard shortcut in preferences
+        ("app.player_next", gettext("Next track")),
+        // translators: This is an action that can be bound to a keyboard shortcut in preferences
+        ("app.search", gettext("Search")),
+        // translators: This is an action that can be bound to a keyboard shortcut in preferences
+        ("app.nav_pop", gettext("Go back")),
+        // translators: This is an action that can be bound to a keyboard shortcut in preferences
+        ("app.quit", gettext("Quit")),
+    ]
+}
+
+fn get_shortcuts(settings: &gio::Settings) -> HashMap<String, String> {
+    crate::settings::shortcuts(settings)
+}
+
+fn set_shortcut(settings: &gio::Settings, action: &str, trigger: &str) {
+    let mut shortcuts = get_shortcuts(settings);
+    shortcuts.insert(action.to_string(), trigger.to_string());
+    let _ = settings.set_value("shortcuts", &shortcuts.to_variant());
+}
+
+fn show_shortcut(label: &gtk::ShortcutLabel, trigger: &str) {
+    // translators: Shown in preferences in place of a keyboard shortcut that was disabled
+    label.set_disabled_text(&gettext("Disabled"));
+    label.set_accelerator(trigger);
+}
+
 mod imp {
 
     use super::*;
@@ -34,11 +74,188 @@ mod imp {
         #[template_child]
         pub gapless_playback: TemplateChild<libadwaita::ActionRow>,
 
+        #[template_child]
+        pub smart_shuffle: TemplateChild<libadwaita::ActionRow>,
+
         #[template_child]
         pub ap_port: TemplateChild<gtk::Entry>,
 
         #[template_child]
         pub theme: TemplateChild<libadwaita::ComboRow>,
+
+        #[template_child]
+        pub compact_mode: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub window_title: TemplateChild<libadwaita::ComboRow>,
+
+        #[template_child]
+        pub track_change_notifications: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub notification_detail: TemplateChild<libadwaita::ComboRow>,
+
+        #[template_child]
+        pub up_next_toast: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub inhibit_suspend: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub pause_on_lock: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub resume_on_unlock: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub run_in_background: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub tray_icon: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub autoscroll_to_playing: TemplateChild<libadwaita::ExpanderRow>,
+
+        #[template_child]
+        pub autoscroll_albums: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub autoscroll_playlists: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub autoscroll_artists: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub autoscroll_saved_tracks: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub credentials_in_keyring: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub clear_cache_on_logout: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub group_saved_tracks: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub library_export: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub export_library: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub lastfm_enabled: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub lastfm_api_key: TemplateChild<gtk::Entry>,
+
+        #[template_child]
+        pub lastfm_api_key_row: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub lastfm_api_secret: TemplateChild<gtk::PasswordEntry>,
+
+        #[template_child]
+        pub lastfm_api_secret_row: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub lastfm_account: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub lastfm_connect: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub listenbrainz_enabled: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub listenbrainz_token: TemplateChild<gtk::PasswordEntry>,
+
+        #[template_child]
+        pub listenbrainz_token_row: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub remote_control_enabled: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub remote_control_port: TemplateChild<gtk::Entry>,
+
+        #[template_child]
+        pub remote_control_port_row: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub remote_control_token: TemplateChild<gtk::PasswordEntry>,
+
+        #[template_child]
+        pub remote_control_token_row: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub debug_logs: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub episode_skip_back: TemplateChild<gtk::Entry>,
+
+        #[template_child]
+        pub episode_skip_forward: TemplateChild<gtk::Entry>,
+
+        #[template_child]
+        pub cache_dir: TemplateChild<gtk::Entry>,
+
+        #[template_child]
+        pub cache_max_size: TemplateChild<gtk::Entry>,
+
+        #[template_child]
+        pub cache_usage: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub clear_cache: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub images_cache_usage: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub clear_images_cache: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub api_cache_usage: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub clear_api_cache: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub audio_cache_usage: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub clear_audio_cache: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub proxy_type: TemplateChild<libadwaita::ComboRow>,
+
+        #[template_child]
+        pub proxy_host: TemplateChild<gtk::Entry>,
+
+        #[template_child]
+        pub proxy_host_row: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub proxy_port: TemplateChild<gtk::Entry>,
+
+        #[template_child]
+        pub proxy_port_row: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub proxy_username: TemplateChild<gtk::Entry>,
+
+        #[template_child]
+        pub proxy_username_row: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub proxy_password: TemplateChild<gtk::PasswordEntry>,
+
+        #[template_child]
+        pub proxy_password_row: TemplateChild<libadwaita::ActionRow>,
+
+        #[template_child]
+        pub shortcuts_group: TemplateChild<libadwaita::PreferencesGroup>,
     }
 
     #[glib::object_subclass]
@@ -72,7 +289,13 @@ impl SettingsWindow {
         let window: Self = glib::Object::new();
 
         window.bind_backend_and_device();
+        window.bind_proxy_rows();
+        window.bind_scrobbling_rows();
+        window.bind_remote_control_rows();
+        window.bind_notification_rows();
+        window.bind_lock_rows();
         window.bind_settings();
+        window.build_shortcut_editor();
         window.connect_theme_select();
         window
     }
@@ -99,6 +322,100 @@ impl SettingsWindow {
         }
     }
 
+    // Proxy details are only relevant if a proxy is used at all
+    fn bind_proxy_rows(&self) {
+        let widget = self.imp();
+        let proxy_type = widget
+            .proxy_type
+            .downcast_ref::<libadwaita::ComboRow>()
+            .unwrap();
+        let rows = [
+            &widget.proxy_host_row,
+            &widget.proxy_port_row,
+            &widget.proxy_username_row,
+            &widget.proxy_password_row,
+        ];
+        for row in rows {
+            proxy_type
+                .bind_property("selected", &**row, "visible")
+                .transform_to(|_, value: u32| Some(value != 0))
+                .sync_create()
+                .build();
+        }
+    }
+
+    // Same for the details of scrobbling services, only relevant if they are enabled
+    fn bind_scrobbling_rows(&self) {
+        let widget = self.imp();
+        let enabled = widget.lastfm_enabled.activatable_widget().unwrap();
+        let rows = [
+            &widget.lastfm_api_key_row,
+            &widget.lastfm_api_secret_row,
+            &widget.lastfm_account,
+        ];
+        for row in rows {
+            enabled
+                .bind_property("active", &**row, "visible")
+                .sync_create()
+                .build();
+        }
+
+        widget
+            .listenbrainz_enabled
+            .activatable_widget()
+            .unwrap()
+            .bind_property("active", &*widget.listenbrainz_token_row, "visible")
+            .sync_create()
+            .build();
+    }
+
+    fn bind_remote_control_rows(&self) {
+        let widget = self.imp();
+        let enabled = widget.remote_control_enabled.activatable_widget().unwrap();
+        for row in [
+            &widget.remote_control_port_row,
+            &widget.remote_control_token_row,
+        ] {
+            enabled
+                .bind_property("active", &**row, "visible")
+                .sync_create()
+                .build();
+        }
+
+        // The server won't start without a token, so one is made up if needed
+        let token = widget.remote_control_token.get();
+        enabled.connect_notify_local(Some("active"), move |enabled, _| {
+            let active = enabled.property::<bool>("active");
+            if active && token.text().is_empty() {
+                token.set_text(&crate::remote::new_token());
+            }
+        });
+    }
+
+    // Details only matter if notifications are shown
+    fn bind_notification_rows(&self) {
+        let widget = self.imp();
+        widget
+            .track_change_notifications
+            .activatable_widget()
+            .unwrap()
+            .bind_property("active", &*widget.notification_detail, "sensitive")
+            .sync_create()
+            .build();
+    }
+
+    // Resuming only applies to what the lock paused
+    fn bind_lock_rows(&self) {
+        let widget = self.imp();
+        widget
+            .pause_on_lock
+            .activatable_widget()
+            .unwrap()
+            .bind_property("active", &*widget.resume_on_unlock, "sensitive")
+            .sync_create()
+            .build();
+    }
+
     fn bind_settings(&self) {
         let widget = self.imp();
         let settings = gio::Settings::new(SETTINGS);
@@ -178,6 +495,18 @@ impl SettingsWindow {
             )
             .build();
 
+        let smart_shuffle = widget
+            .smart_shuffle
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "smart-shuffle",
+                &smart_shuffle.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
         let ap_port = widget.ap_port.downcast_ref::<gtk::Entry>().unwrap();
         settings
             .bind("ap-port", ap_port, "text")
@@ -211,6 +540,490 @@ impl SettingsWindow {
                 })
             })
             .build();
+
+        let compact_mode = widget
+            .compact_mode
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "compact-mode",
+                &compact_mode.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        settings
+            .bind("window-title", &*widget.window_title, "selected")
+            .mapping(|variant, _| {
+                variant.str().map(|s| {
+                    match s {
+                        "track" => 1u32,
+                        "elapsed" => 2,
+                        _ => 0,
+                    }
+                    .to_value()
+                })
+            })
+            .set_mapping(|value, _| {
+                value.get::<u32>().ok().map(|u| {
+                    match u {
+                        1 => "track",
+                        2 => "elapsed",
+                        _ => "app",
+                    }
+                    .to_variant()
+                })
+            })
+            .build();
+
+        let track_change_notifications = widget
+            .track_change_notifications
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "track-change-notifications",
+                &track_change_notifications.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        settings
+            .bind(
+                "track-change-notifications-detail",
+                &*widget.notification_detail,
+                "selected",
+            )
+            .mapping(|variant, _| {
+                variant.str().map(|s| {
+                    match s {
+                        "title" => 0u32,
+                        "cover" => 1,
+                        _ => 2,
+                    }
+                    .to_value()
+                })
+            })
+            .set_mapping(|value, _| {
+                value.get::<u32>().ok().map(|u| {
+                    match u {
+                        0 => "title",
+                        1 => "cover",
+                        _ => "controls",
+                    }
+                    .to_variant()
+                })
+            })
+            .build();
+
+        let up_next_toast = widget
+            .up_next_toast
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "up-next-toast",
+                &up_next_toast.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let inhibit_suspend = widget
+            .inhibit_suspend
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "inhibit-suspend",
+                &inhibit_suspend.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let pause_on_lock = widget
+            .pause_on_lock
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "pause-on-lock",
+                &pause_on_lock.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let resume_on_unlock = widget
+            .resume_on_unlock
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "resume-on-unlock",
+                &resume_on_unlock.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let run_in_background = widget
+            .run_in_background
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "run-in-background",
+                &run_in_background.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let tray_icon = widget
+            .tray_icon
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "tray-icon",
+                &tray_icon.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let autoscroll_to_playing = widget
+            .autoscroll_to_playing
+            .downcast_ref::<libadwaita::ExpanderRow>()
+            .unwrap();
+        settings
+            .bind(
+                "autoscroll-to-playing",
+                autoscroll_to_playing,
+                "enable-expansion",
+            )
+            .build();
+
+        let autoscroll_albums = widget
+            .autoscroll_albums
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "autoscroll-albums",
+                &autoscroll_albums.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let autoscroll_playlists = widget
+            .autoscroll_playlists
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "autoscroll-playlists",
+                &autoscroll_playlists.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let autoscroll_artists = widget
+            .autoscroll_artists
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "autoscroll-artists",
+                &autoscroll_artists.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let autoscroll_saved_tracks = widget
+            .autoscroll_saved_tracks
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "autoscroll-saved-tracks",
+                &autoscroll_saved_tracks.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let credentials_in_keyring = widget
+            .credentials_in_keyring
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "credentials-in-keyring",
+                &credentials_in_keyring.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let clear_cache_on_logout = widget
+            .clear_cache_on_logout
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "clear-cache-on-logout",
+                &clear_cache_on_logout.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let group_saved_tracks = widget
+            .group_saved_tracks
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "group-saved-tracks-by-date",
+                &group_saved_tracks.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let lastfm_enabled = widget
+            .lastfm_enabled
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "lastfm-enabled",
+                &lastfm_enabled.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let lastfm_api_key = widget.lastfm_api_key.downcast_ref::<gtk::Entry>().unwrap();
+        settings
+            .bind("lastfm-api-key", lastfm_api_key, "text")
+            .build();
+
+        let lastfm_api_secret = widget
+            .lastfm_api_secret
+            .downcast_ref::<gtk::PasswordEntry>()
+            .unwrap();
+        lastfm_api_secret.set_text(&credentials::secret(Secret::LastfmApi));
+        lastfm_api_secret.connect_changed(|entry| {
+            credentials::set_secret(Secret::LastfmApi, &entry.text());
+        });
+
+        let listenbrainz_enabled = widget
+            .listenbrainz_enabled
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "listenbrainz-enabled",
+                &listenbrainz_enabled.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let listenbrainz_token = widget
+            .listenbrainz_token
+            .downcast_ref::<gtk::PasswordEntry>()
+            .unwrap();
+        listenbrainz_token.set_text(&credentials::secret(Secret::ListenbrainzToken));
+        listenbrainz_token.connect_changed(|entry| {
+            credentials::set_secret(Secret::ListenbrainzToken, &entry.text());
+        });
+
+        let remote_control_enabled = widget
+            .remote_control_enabled
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "remote-control-enabled",
+                &remote_control_enabled.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let remote_control_port = widget
+            .remote_control_port
+            .downcast_ref::<gtk::Entry>()
+            .unwrap();
+        settings
+            .bind("remote-control-port", remote_control_port, "text")
+            .mapping(|variant, _| variant.get::<u32>().map(|port| port.to_string().to_value()))
+            .set_mapping(|value, _| {
+                let port = value.get::<String>().ok()?.parse::<u32>().ok()?;
+                Some(port.to_variant())
+            })
+            .build();
+
+        let remote_control_token = widget
+            .remote_control_token
+            .downcast_ref::<gtk::PasswordEntry>()
+            .unwrap();
+        settings
+            .bind("remote-control-token", remote_control_token, "text")
+            .build();
+
+        let debug_logs = widget
+            .debug_logs
+            .downcast_ref::<libadwaita::ActionRow>()
+            .unwrap();
+        settings
+            .bind(
+                "debug-logs",
+                &debug_logs.activatable_widget().unwrap(),
+                "active",
+            )
+            .build();
+
+        let proxy_type = widget
+            .proxy_type
+            .downcast_ref::<libadwaita::ComboRow>()
+            .unwrap();
+        settings
+            .bind("proxy-type", proxy_type, "selected")
+            .mapping(|variant, _| {
+                variant.str().map(|s| {
+                    match s {
+                        "none" => 0,
+                        "http" => 1,
+                        "socks5" => 2,
+                        _ => unreachable!(),
+                    }
+                    .to_value()
+                })
+            })
+            .set_mapping(|value, _| {
+                value.get::<u32>().ok().map(|u| {
+                    match u {
+                        0 => "none",
+                        1 => "http",
+                        2 => "socks5",
+                        _ => unreachable!(),
+                    }
+                    .to_variant()
+                })
+            })
+            .build();
+
+        let proxy_host = widget.proxy_host.downcast_ref::<gtk::Entry>().unwrap();
+        settings.bind("proxy-host", proxy_host, "text").build();
+
+        let proxy_port = widget.proxy_port.downcast_ref::<gtk::Entry>().unwrap();
+        settings
+            .bind("proxy-port", proxy_port, "text")
+            .mapping(|variant, _| variant.get::<u32>().map(|port| port.to_string().to_value()))
+            .set_mapping(|value, _| {
+                let port = value.get::<String>().ok()?.trim().parse::<u32>().ok()?;
+                Some(port.to_variant())
+            })
+            .build();
+
+        let proxy_username = widget.proxy_username.downcast_ref::<gtk::Entry>().unwrap();
+        settings
+            .bind("proxy-username", proxy_username, "text")
+            .build();
+
+        let proxy_password = widget
+            .proxy_password
+            .downcast_ref::<gtk::PasswordEntry>()
+            .unwrap();
+        // Kept in the keyring rather than in the settings
+        proxy_password.set_text(&credentials::secret(Secret::ProxyPassword));
+        proxy_password.connect_changed(|entry| {
+            credentials::set_secret(Secret::ProxyPassword, &entry.text());
+        });
+
+        for (key, entry) in [
+            ("episode-skip-back", &widget.episode_skip_back),
+            ("episode-skip-forward", &widget.episode_skip_forward),
+        ] {
+            settings
+                .bind(key, &**entry, "text")
+                .mapping(|variant, _| variant.get::<u32>().map(|s| s.to_string().to_value()))
+                .set_mapping(|value, _| {
+                    let text = value.get::<String>().ok()?;
+                    text.trim().parse::<u32>().ok().map(|u| u.to_variant())
+                })
+                .build();
+        }
+
+        let cache_dir = widget.cache_dir.downcast_ref::<gtk::Entry>().unwrap();
+        settings.bind("cache-dir", cache_dir, "text").build();
+
+        let cache_max_size = widget.cache_max_size.downcast_ref::<gtk::Entry>().unwrap();
+        settings
+            .bind("cache-max-size", cache_max_size, "text")
+            .mapping(|variant, _| variant.get::<u32>().map(|size| size.to_string().to_value()))
+            .set_mapping(|value, _| {
+                let size = value.get::<String>().ok()?.trim().parse::<u32>().ok()?;
+                Some(size.to_variant())
+            })
+            .build();
+    }
+
+    fn build_shortcut_editor(&self) {
+        let settings = gio::Settings::new(SETTINGS);
+        let shortcuts = get_shortcuts(&settings);
+        // The action being edited, and the label showing its shortcut
+        let editing: Rc<RefCell<Option<(&'static str, gtk::ShortcutLabel)>>> = Default::default();
+
+        for (action, title) in shortcut_actions() {
+            let label = gtk::ShortcutLabel::new("");
+            label.set_valign(gtk::Align::Center);
+            show_shortcut(
+                &label,
+                shortcuts.get(action).map(|s| s.as_str()).unwrap_or(""),
+            );
+
+            let row = libadwaita::ActionRow::builder()
+                .title(title)
+                .activatable(true)
+                .build();
+            row.add_suffix(&label);
+            row.connect_activated(
+                clone!(@strong editing, @strong settings, @weak label => move |_| {
+                    // Only one shortcut can be edited at a time
+                    if let Some((previous_action, previous)) = editing.take() {
+                        let shortcuts = get_shortcuts(&settings);
+                        let trigger = shortcuts.get(previous_action).map(|s| s.as_str());
+                        show_shortcut(&previous, trigger.unwrap_or(""));
+                    }
+                    // translators: Shown in preferences while waiting for the user to press a new keyboard shortcut
+                    label.set_disabled_text(&gettext("New shortcut…"));
+                    label.set_accelerator("");
+                    editing.replace(Some((action, label)));
+                }),
+            );
+            self.imp().shortcuts_group.add(&row);
+        }
+
+        let controller = gtk::EventControllerKey::new();
+        controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+        controller.connect_key_pressed(move |_, key, _, modifiers| {
+            let Some((action, label)) = editing.take() else {
+                return gtk::Inhibit(false);
+            };
+            let modifiers = modifiers & gtk::accelerator_get_default_mod_mask();
+            let trigger = match key {
+                gdk::Key::Escape => get_shortcuts(&settings)
+                    .get(action)
+                    .cloned()
+                    .unwrap_or_default(),
+                gdk::Key::BackSpace => String::new(),
+                // Modifiers alone, wait for the actual key
+                _ if !gtk::accelerator_valid(key, modifiers) => {
+                    editing.replace(Some((action, label)));
+                    return gtk::Inhibit(true);
+                }
+                _ => gtk::accelerator_name(key, modifiers).to_string(),
+            };
+            set_shortcut(&settings, action, &trigger);
+            show_shortcut(&label, &trigger);
+            gtk::Inhibit(true)
+        });
+        self.add_controller(controller);
     }
 
     fn connect_theme_select(&self) {
@@ -230,6 +1043,108 @@ impl SettingsWindow {
         });
     }
 
+    fn set_cache_usage(&self, usage: u64) {
+        self.imp()
+            .cache_usage
+            .set_subtitle(&labels::cache_usage(usage));
+    }
+
+    fn connect_clear_cache<F>(&self, on_clear: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp().clear_cache.connect_clicked(move |_| on_clear());
+    }
+
+    fn cache_category_widgets(
+        &self,
+        category: CacheCategory,
+    ) -> (&libadwaita::ActionRow, &gtk::Button) {
+        let widget = self.imp();
+        match category {
+            CacheCategory::Images => (&*widget.images_cache_usage, &*widget.clear_images_cache),
+            CacheCategory::ApiResponses => (&*widget.api_cache_usage, &*widget.clear_api_cache),
+            CacheCategory::Audio => (&*widget.audio_cache_usage, &*widget.clear_audio_cache),
+        }
+    }
+
+    fn set_cache_category_usage(&self, category: CacheCategory, usage: u64) {
+        let (row, _) = self.cache_category_widgets(category);
+        row.set_subtitle(&labels::cache_usage(usage));
+    }
+
+    fn connect_clear_cache_category<F>(&self, on_clear: F)
+    where
+        F: Fn(CacheCategory) + Clone + 'static,
+    {
+        for category in CacheCategory::ALL {
+            let (_, button) = self.cache_category_widgets(category);
+            let on_clear = on_clear.clone();
+            button.connect_clicked(move |_| on_clear(category));
+        }
+    }
+
+    fn set_library_export_status(&self, status: &str, exporting: bool) {
+        let widget = self.imp();
+        widget.library_export.set_subtitle(status);
+        widget.export_library.set_sensitive(!exporting);
+    }
+
+    fn connect_export_library<F>(&self, on_export: F)
+    where
+        F: Fn(gio::File) + 'static,
+    {
+        let on_export = Rc::new(on_export);
+        self.imp()
+            .export_library
+            .connect_clicked(clone!(@weak self as _self => move |_| {
+                let dialog = gtk::FileDialog::builder()
+                    // translators: Title of the dialog to pick where to export the library
+                    .title(gettext("Export Library"))
+                    .initial_name("spot-library.json")
+                    .modal(true)
+                    .build();
+                let on_export = Rc::clone(&on_export);
+                dialog.save(Some(&_self), gio::Cancellable::NONE, move |result| {
+                    if let Ok(file) = result {
+                        on_export(file);
+                    }
+                });
+            }));
+    }
+
+    // The connected account if any, or whether we're waiting for the user to authorize Spot
+    fn set_lastfm_account(&self, username: Option<&str>, connecting: bool) {
+        let widget = self.imp();
+        let subtitle = match (username, connecting) {
+            (_, true) => {
+                // translators: Shown in preferences while the user authorizes Spot on the Last.fm website
+                gettext("Waiting for authorization in your browser…")
+            }
+            (Some(username), false) => username.to_string(),
+            // translators: Shown in preferences when no Last.fm account is connected
+            (None, false) => gettext("Not connected"),
+        };
+        widget.lastfm_account.set_subtitle(&subtitle);
+        widget.lastfm_connect.set_sensitive(!connecting);
+        widget.lastfm_connect.set_label(&if username.is_some() {
+            // translators: Button to forget the Last.fm account, in preferences
+            gettext("Disconnect")
+        } else {
+            // translators: Button to connect a Last.fm account, in preferences
+            gettext("Connect")
+        });
+    }
+
+    fn connect_lastfm_connect<F>(&self, on_click: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp()
+            .lastfm_connect
+            .connect_clicked(move |_| on_click());
+    }
+
     fn connect_close<F>(&self, on_close: F)
     where
         F: Fn() + 'static,
@@ -248,23 +1163,92 @@ impl SettingsWindow {
 pub struct Settings {
     parent: gtk::Window,
     settings_window: SettingsWindow,
+    model: Rc<SettingsModel>,
 }
 
 impl Settings {
     pub fn new(parent: gtk::Window, model: SettingsModel) -> Self {
+        let model = Rc::new(model);
         let settings_window = SettingsWindow::new();
 
-        settings_window.connect_close(move || {
+        settings_window.connect_close(clone!(@weak model => move || {
             let new_settings = SpotSettings::new_from_gsettings().unwrap_or_default();
             if model.settings().player_settings != new_settings.player_settings {
                 model.stop_player();
             }
+            model.limit_cache(new_settings.cache_max_size);
             model.set_settings();
-        });
+        }));
+
+        settings_window.connect_clear_cache(clone!(@weak model, @weak settings_window => move || {
+            model.clear_cache(clone!(@weak model, @weak settings_window => move || {
+                refresh_cache_usage(&model, &settings_window);
+            }));
+        }));
+
+        settings_window.connect_clear_cache_category(
+            clone!(@weak model, @weak settings_window => move |category| {
+                model.clear_cache_category(category, clone!(@weak model, @weak settings_window => move || {
+                    refresh_cache_usage(&model, &settings_window);
+                }));
+            }),
+        );
+
+        settings_window.connect_export_library(
+            clone!(@weak model, @weak settings_window => move |file| {
+                let progress_window = settings_window.downgrade();
+                let done_window = settings_window.downgrade();
+                // translators: Shown in preferences when the library export starts
+                settings_window.set_library_export_status(&gettext("Exporting…"), true);
+                model.export_library(
+                    file,
+                    move |progress| {
+                        if let Some(settings_window) = progress_window.upgrade() {
+                            settings_window.set_library_export_status(
+                                &labels::library_export_progress(progress),
+                                true,
+                            );
+                        }
+                    },
+                    move |success| {
+                        if let Some(settings_window) = done_window.upgrade() {
+                            let status = if success {
+                                // translators: Shown in preferences once the library was exported
+                                gettext("Library exported")
+                            } else {
+                                // translators: Shown in preferences when the library could not be exported
+                                gettext("Export failed")
+                            };
+                            settings_window.set_library_export_status(&status, false);
+                        }
+                    },
+                );
+            }),
+        );
+
+        let username = lastfm_username();
+        settings_window.set_lastfm_account(username.as_deref(), false);
+        settings_window.connect_lastfm_connect(
+            clone!(@weak model, @weak settings_window => move || {
+                if lastfm_username().is_some() {
+                    model.disconnect_lastfm();
+                    settings_window.set_lastfm_account(None, false);
+                    return;
+                }
+                settings_window.set_lastfm_account(None, true);
+                let window = settings_window.downgrade();
+                model.connect_lastfm(move |username| {
+                    if let Some(settings_window) = window.upgrade() {
+                        settings_window.set_lastfm_account(username.as_deref(), false);
+                    }
+                });
+            }),
+        );
 
         Self {
             parent,
             settings_window,
+            model,
         }
     }
 
@@ -276,6 +1260,30 @@ impl Settings {
         self.window().set_transient_for(Some(&self.parent));
         self.window().set_modal(true);
         self.window().set_visible(true);
+        refresh_cache_usage(&self.model, &self.settings_window);
+    }
+}
+
+fn lastfm_username() -> Option<String> {
+    let settings = gio::Settings::new(SETTINGS);
+    Some(settings.string("lastfm-username").to_string()).filter(|s| !s.is_empty())
+}
+
+// The total, and how it breaks down
+fn refresh_cache_usage(model: &SettingsModel, settings_window: &SettingsWindow) {
+    let window = settings_window.downgrade();
+    model.get_cache_usage(move |usage| {
+        if let Some(settings_window) = window.upgrade() {
+            settings_window.set_cache_usage(usage);
+        }
+    });
+    for category in CacheCategory::ALL {
+        let window = settings_window.downgrade();
+        model.get_cache_category_usage(category, move |usage| {
+            if let Some(settings_window) = window.upgrade() {
+                settings_window.set_cache_category_usage(category, usage);
+            }
+        });
     }
 }
 
@@ -1,18 +1,38 @@
+use crate::api::library_export::{export_library, ExportFormat, ExportProgress};
+use crate::api::{
+    cache_category_usage, cache_usage, clear_cache_category, clear_caches, limit_caches,
+    trim_caches, CacheCategory,
+};
+use crate::app::credentials::{self, Secret};
 use crate::app::state::{PlaybackAction, SettingsAction};
-use crate::app::{ActionDispatcher, AppModel};
+use crate::app::{ActionDispatcher, AppModel, Worker};
+use crate::scrobbling::LastfmClient;
 use crate::settings::SpotSettings;
+use gio::prelude::*;
 use std::rc::Rc;
 
+const SETTINGS: &str = "dev.alextren.Spot";
+
+// How often, and how many times, we check whether the user authorized Spot on Last.fm
+const LASTFM_POLL_INTERVAL_SECS: u32 = 3;
+const LASTFM_POLL_ATTEMPTS: u32 = 100;
+
 pub struct SettingsModel {
     app_model: Rc<AppModel>,
     dispatcher: Box<dyn ActionDispatcher>,
+    worker: Worker,
 }
 
 impl SettingsModel {
-    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+    pub fn new(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        worker: Worker,
+    ) -> Self {
         Self {
             app_model,
             dispatcher,
+            worker,
         }
     }
 
@@ -29,4 +49,128 @@ impl SettingsModel {
         let state = self.app_model.get_state();
         state.settings.settings.clone()
     }
+
+    pub fn get_cache_usage(&self, on_done: impl FnOnce(u64) + 'static) {
+        self.worker.send_local_task(async move {
+            if let Some(usage) = cache_usage().await {
+                on_done(usage);
+            }
+        });
+    }
+
+    pub fn clear_cache(&self, on_done: impl FnOnce() + 'static) {
+        self.worker.send_local_task(async move {
+            let _ = clear_caches().await;
+            on_done();
+        });
+    }
+
+    pub fn get_cache_category_usage(
+        &self,
+        category: CacheCategory,
+        on_done: impl FnOnce(u64) + 'static,
+    ) {
+        self.worker.send_local_task(async move {
+            if let Some(usage) = cache_category_usage(category).await {
+                on_done(usage);
+            }
+        });
+    }
+
+    pub fn clear_cache_category(&self, category: CacheCategory, on_done: impl FnOnce() + 'static) {
+        self.worker.send_local_task(async move {
+            let _ = clear_cache_category(category).await;
+            on_done();
+        });
+    }
+
+    // A lower limit applies right away
+    pub fn limit_cache(&self, max_size: Option<u64>) {
+        limit_caches(max_size);
+        if let Some(max_size) = max_size {
+            self.worker.send_task(async move {
+                let _ = trim_caches(max_size).await;
+            });
+        }
+    }
+
+    // Authorizes Spot with the API account entered in the preferences, which the user confirms in a browser
+    pub fn connect_lastfm(&self, on_done: impl FnOnce(Option<String>) + 'static) {
+        let settings = gio::Settings::new(SETTINGS);
+        let client = LastfmClient::new(
+            settings.string("lastfm-api-key").to_string(),
+            credentials::secret(Secret::LastfmApi),
+            None,
+        );
+        self.worker.send_local_task(async move {
+            let token = match client.get_token().await {
+                Ok(token) => token,
+                Err(err) => {
+                    warn!("Could not connect to Last.fm: {}", err);
+                    on_done(None);
+                    return;
+                }
+            };
+            let url = client.auth_url(&token);
+            if let Err(err) =
+                gio::AppInfo::launch_default_for_uri(&url, None::<&gio::AppLaunchContext>)
+            {
+                warn!("Could not open {}: {}", url, err);
+            }
+
+            for _ in 0..LASTFM_POLL_ATTEMPTS {
+                glib::timeout_future_seconds(LASTFM_POLL_INTERVAL_SECS).await;
+                if let Ok(session) = client.get_session(&token).await {
+                    credentials::set_secret(Secret::LastfmSessionKey, &session.key);
+                    let _ = settings.set_string("lastfm-username", &session.name);
+                    on_done(Some(session.name));
+                    return;
+                }
+            }
+            on_done(None);
+        });
+    }
+
+    pub fn disconnect_lastfm(&self) {
+        let settings = gio::Settings::new(SETTINGS);
+        credentials::set_secret(Secret::LastfmSessionKey, "");
+        let _ = settings.set_string("lastfm-username", "");
+    }
+
+    // Writes the whole library to the file, in a format picked from its name
+    pub fn export_library(
+        &self,
+        file: gio::File,
+        on_progress: impl Fn(ExportProgress) + 'static,
+        on_done: impl FnOnce(bool) + 'static,
+    ) {
+        let api = self.app_model.get_spotify();
+        let format = file
+            .basename()
+            .map(|name| ExportFormat::from_file_name(&name.to_string_lossy()))
+            .unwrap_or(ExportFormat::Json);
+        // Not on the worker, which would hold up every other task until the export is done
+        glib::MainContext::default().spawn_local(async move {
+            let export = match export_library(api, on_progress).await {
+                Ok(export) => export,
+                Err(err) => {
+                    warn!("Could not export library: {}", err);
+                    on_done(false);
+                    return;
+                }
+            };
+            let result = file
+                .replace_contents_future(
+                    export.serialize(format),
+                    None,
+                    false,
+                    gio::FileCreateFlags::REPLACE_DESTINATION,
+                )
+                .await;
+            if let Err((_, err)) = &result {
+                warn!("Could not write library export: {}", err);
+            }
+            on_done(result.is_ok());
+        });
+    }
 }
@@ -0,0 +1,122 @@
+use gettextrs::gettext;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use qrcode::{Color, QrCode};
+
+use crate::app::state::parse_spotify_uri;
+
+// Size of a single square of the QR code, in pixels
+const MODULE_SIZE: usize = 6;
+// Blank space around the code, in squares, so that it can be read
+const QUIET_ZONE: usize = 4;
+
+mod imp {
+
+    use super::*;
+    use libadwaita::subclass::prelude::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/dev/alextren/Spot/components/share.ui")]
+    pub struct ShareWindow {
+        #[template_child]
+        pub toast_overlay: TemplateChild<libadwaita::ToastOverlay>,
+
+        #[template_child]
+        pub qr_code: TemplateChild<gtk::Picture>,
+
+        #[template_child]
+        pub link: TemplateChild<gtk::Entry>,
+
+        #[template_child]
+        pub copy_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ShareWindow {
+        const NAME: &'static str = "ShareWindow";
+        type Type = super::ShareWindow;
+        type ParentType = libadwaita::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ShareWindow {}
+    impl WidgetImpl for ShareWindow {}
+    impl WindowImpl for ShareWindow {}
+    impl AdwWindowImpl for ShareWindow {}
+}
+
+glib::wrapper! {
+    pub struct ShareWindow(ObjectSubclass<imp::ShareWindow>) @extends gtk::Widget, gtk::Window, libadwaita::Window;
+}
+
+// The open.spotify.com page for a Spotify URI (spotify:album:xyz...)
+fn web_link(uri: &str) -> Option<String> {
+    let (kind, id) = parse_spotify_uri(uri)?;
+    Some(format!("https://open.spotify.com/{kind}/{id}"))
+}
+
+// Black squares on a white background, scaled up so that it doesn't need to be scaled when displayed
+fn qr_code_texture(link: &str) -> Option<gdk::Texture> {
+    let code = QrCode::new(link.as_bytes()).ok()?;
+    let modules = code.width();
+    let colors = code.to_colors();
+
+    let size = (modules + 2 * QUIET_ZONE) * MODULE_SIZE;
+    let mut pixels = vec![0xffu8; size * size * 3];
+    for (i, color) in colors.iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let x0 = (i % modules + QUIET_ZONE) * MODULE_SIZE;
+        let y0 = (i / modules + QUIET_ZONE) * MODULE_SIZE;
+        for y in y0..y0 + MODULE_SIZE {
+            let row = y * size * 3;
+            pixels[row + x0 * 3..row + (x0 + MODULE_SIZE) * 3].fill(0);
+        }
+    }
+
+    let bytes = glib::Bytes::from_owned(pixels);
+    let texture = gdk::MemoryTexture::new(
+        size as i32,
+        size as i32,
+        gdk::MemoryFormat::R8g8b8,
+        &bytes,
+        size * 3,
+    );
+    Some(texture.upcast())
+}
+
+impl ShareWindow {
+    // None if the URI isn't one we know how to link to
+    pub fn for_uri(uri: &str) -> Option<Self> {
+        let link = web_link(uri)?;
+        let window: Self = glib::Object::new();
+        let widget = window.imp();
+
+        widget.link.set_text(&link);
+        widget
+            .qr_code
+            .set_paintable(qr_code_texture(&link).as_ref());
+        widget
+            .copy_button
+            .connect_clicked(clone!(@weak window => move |_| window.copy_link()));
+
+        Some(window)
+    }
+
+    fn copy_link(&self) {
+        let widget = self.imp();
+        self.clipboard().set_text(&widget.link.text());
+        // translators: Notification shown in the share window after copying the link
+        let toast = libadwaita::Toast::new(&gettext("Link copied"));
+        widget.toast_overlay.add_toast(toast);
+    }
+}
@@ -0,0 +1,342 @@
+use gettextrs::gettext;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::CompositeTemplate;
+use libadwaita::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::app::components::utils::format_duration;
+use crate::app::components::{display_add_css_provider, labels, Component, EventListener};
+use crate::app::loader::ImageLoader;
+use crate::app::markup;
+use crate::app::models::EpisodeDescription;
+use crate::app::state::{NetworkEvent, PlaybackEvent};
+use crate::app::{AppEvent, BrowserEvent, Worker};
+
+use super::ShowDetailsModel;
+
+mod imp {
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/dev/alextren/Spot/components/show_details.ui")]
+    pub struct ShowDetailsWidget {
+        #[template_child]
+        pub scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+
+        #[template_child]
+        pub show_art: TemplateChild<gtk::Image>,
+
+        #[template_child]
+        pub show_title: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub show_publisher: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub show_description: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub episodes: TemplateChild<gtk::ListBox>,
+
+        pub episode_rows: RefCell<Vec<EpisodeRow>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ShowDetailsWidget {
+        const NAME: &'static str = "ShowDetailsWidget";
+        type Type = super::ShowDetailsWidget;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ShowDetailsWidget {}
+    impl WidgetImpl for ShowDetailsWidget {}
+    impl BoxImpl for ShowDetailsWidget {}
+}
+
+// An episode in the list, which can be marked as played from its menu
+#[derive(Debug)]
+pub struct EpisodeRow {
+    id: String,
+    row: libadwaita::ActionRow,
+    playing_icon: gtk::Image,
+    played_icon: gtk::Image,
+    played_action: gio::SimpleAction,
+}
+
+impl EpisodeRow {
+    // Release date and duration, then the beginning of the description
+    fn new(episode: &EpisodeDescription, model: &Rc<ShowDetailsModel>) -> Self {
+        let details = [
+            episode.release_date.clone(),
+            Some(format_duration(episode.duration.into())),
+        ]
+        .iter()
+        .flatten()
+        .cloned()
+        .collect::<Vec<String>>()
+        .join(" · ");
+        let subtitle = format!("{}\n{}", markup::escape(&details), episode.description);
+
+        let row = libadwaita::ActionRow::builder()
+            .title(glib::markup_escape_text(&episode.title).as_str())
+            .subtitle(subtitle.as_str())
+            .subtitle_lines(3)
+            .activatable(true)
+            .build();
+
+        // A shape rather than just a color, so that the episode playing stands out for everyone
+        let playing_icon = gtk::Image::from_icon_name("audio-volume-high-symbolic");
+        playing_icon.add_css_class("show__episode-playing-icon");
+        playing_icon.set_visible(false);
+        row.add_prefix(&playing_icon);
+
+        let played_icon = gtk::Image::from_icon_name("object-select-symbolic");
+        played_icon.set_tooltip_text(Some(&*labels::PLAYED));
+        row.add_prefix(&played_icon);
+
+        let menu = gio::Menu::new();
+        menu.append(Some(&*labels::PLAYED), Some("episode.played"));
+        let menu_button = gtk::MenuButton::builder()
+            .icon_name("view-more-symbolic")
+            .menu_model(&menu)
+            .valign(gtk::Align::Center)
+            .tooltip_text(&*labels::EPISODE_MENU)
+            .build();
+        menu_button.add_css_class("flat");
+        row.add_suffix(&menu_button);
+        row.add_suffix(&gtk::Image::from_icon_name("media-playback-start-symbolic"));
+
+        let played_action = gio::SimpleAction::new_stateful("played", None, false.to_variant());
+        let actions = gio::SimpleActionGroup::new();
+        actions.add_action(&played_action);
+        row.insert_action_group("episode", Some(&actions));
+
+        let episode_row = Self {
+            id: episode.id.clone(),
+            row,
+            playing_icon,
+            played_icon,
+            played_action,
+        };
+        episode_row.set_played(model.is_episode_played(&episode.id));
+
+        let id = episode.id.clone();
+        let (row, played_icon) = (episode_row.row.clone(), episode_row.played_icon.clone());
+        episode_row.played_action.connect_activate(
+            clone!(@weak model, @weak row, @weak played_icon => move |action, _| {
+                let played = !action.state().and_then(|s| s.get::<bool>()).unwrap_or(false);
+                model.set_episode_played(&id, played);
+                action.set_state(played.to_variant());
+                Self::show_played(&row, &played_icon, played);
+            }),
+        );
+
+        episode_row
+    }
+
+    fn show_played(row: &libadwaita::ActionRow, played_icon: &gtk::Image, played: bool) {
+        played_icon.set_visible(played);
+        if played {
+            row.add_css_class("show__episode--played");
+        } else {
+            row.remove_css_class("show__episode--played");
+        }
+    }
+
+    fn set_played(&self, played: bool) {
+        self.played_action.set_state(played.to_variant());
+        Self::show_played(&self.row, &self.played_icon, played);
+    }
+
+    fn set_playing(&self, playing: bool) {
+        self.playing_icon.set_visible(playing);
+        if playing {
+            self.row.add_css_class("show__episode--playing");
+            // translators: Read by screen readers after the episode that is currently playing
+            self.row
+                .update_property(&[gtk::accessible::Property::Description(&gettext("Playing"))]);
+        } else {
+            self.row.remove_css_class("show__episode--playing");
+            self.row
+                .reset_property(gtk::AccessibleProperty::Description);
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct ShowDetailsWidget(ObjectSubclass<imp::ShowDetailsWidget>) @extends gtk::Widget, gtk::Box;
+}
+
+impl ShowDetailsWidget {
+    fn new() -> Self {
+        display_add_css_provider(resource!("/components/show_details.css"));
+        glib::Object::new()
+    }
+
+    fn set_header(&self, title: &str, publisher: &str, description: &str) {
+        self.add_css_class("show__loaded");
+        let widget = self.imp();
+        widget.show_title.set_text(title);
+        widget.show_publisher.set_text(publisher);
+        widget.show_description.set_markup(description);
+    }
+
+    fn set_artwork(&self, art: &gdk::Texture) {
+        self.imp().show_art.set_from_paintable(Some(art));
+    }
+
+    fn episode_count(&self) -> usize {
+        self.imp().episode_rows.borrow().len()
+    }
+
+    fn append_episodes(&self, episodes: &[EpisodeDescription], model: &Rc<ShowDetailsModel>) {
+        let widget = self.imp();
+        for episode in episodes {
+            let row = EpisodeRow::new(episode, model);
+            widget.episodes.append(&row.row);
+            widget.episode_rows.borrow_mut().push(row);
+        }
+    }
+
+    fn update_played(&self, model: &ShowDetailsModel) {
+        for row in self.imp().episode_rows.borrow().iter() {
+            row.set_played(model.is_episode_played(&row.id));
+        }
+    }
+
+    fn update_playing(&self, playing_id: Option<&str>) {
+        for row in self.imp().episode_rows.borrow().iter() {
+            row.set_playing(playing_id == Some(row.id.as_str()));
+        }
+    }
+
+    fn connect_episode_activated<F>(&self, f: F)
+    where
+        F: Fn(usize) + 'static,
+    {
+        self.imp()
+            .episodes
+            .connect_row_activated(move |_, row| f(row.index() as usize));
+    }
+
+    fn connect_bottom_edge<F>(&self, f: F)
+    where
+        F: Fn() + 'static,
+    {
+        self.imp()
+            .scrolled_window
+            .connect_edge_reached(move |_, pos| {
+                if let gtk::PositionType::Bottom = pos {
+                    f()
+                }
+            });
+    }
+}
+
+pub struct ShowDetails {
+    model: Rc<ShowDetailsModel>,
+    widget: ShowDetailsWidget,
+    worker: Worker,
+}
+
+impl ShowDetails {
+    pub fn new(model: ShowDetailsModel, worker: Worker) -> Self {
+        model.load_show_details();
+
+        let widget = ShowDetailsWidget::new();
+        let model = Rc::new(model);
+
+        widget.connect_bottom_edge(clone!(@weak model => move || {
+            model.load_more();
+        }));
+
+        widget.connect_episode_activated(clone!(@weak model => move |pos| {
+            model.play_episode_at(pos);
+        }));
+
+        Self {
+            model,
+            widget,
+            worker,
+        }
+    }
+
+    fn update_details(&self) {
+        let Some(show) = self.model.get_show() else {
+            return;
+        };
+        self.widget
+            .set_header(&show.title, &show.publisher, &show.description);
+        let art = show.art.clone();
+        drop(show);
+        self.append_episodes();
+        self.update_playing();
+
+        if let Some(url) = art {
+            let widget = self.widget.downgrade();
+            self.worker.send_local_task(async move {
+                let loader = ImageLoader::new();
+                let result = loader.load_texture(&url, 120).await;
+                if let (Some(widget), Some(texture)) = (widget.upgrade(), result) {
+                    widget.set_artwork(&texture);
+                }
+            });
+        }
+    }
+
+    fn append_episodes(&self) {
+        if let Some(show) = self.model.get_show() {
+            let loaded = self.widget.episode_count();
+            self.widget
+                .append_episodes(show.episodes.get(loaded..).unwrap_or_default(), &self.model);
+        }
+    }
+
+    fn update_playing(&self) {
+        self.widget
+            .update_playing(self.model.current_song_id().as_deref());
+    }
+}
+
+impl Component for ShowDetails {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        self.widget.as_ref()
+    }
+}
+
+impl EventListener for ShowDetails {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::BrowserEvent(BrowserEvent::ShowDetailsLoaded(id)) if id == &self.model.id => {
+                self.update_details();
+            }
+            AppEvent::BrowserEvent(BrowserEvent::ShowEpisodesAppended(id))
+                if id == &self.model.id =>
+            {
+                self.append_episodes();
+                self.update_playing();
+            }
+            // Episodes listened to the end just got marked as played
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_))
+            | AppEvent::PlaybackEvent(PlaybackEvent::PlaybackStopped) => {
+                self.widget.update_played(&self.model);
+                self.update_playing();
+            }
+            AppEvent::NetworkEvent(NetworkEvent::BackOnline) if self.model.get_show().is_none() => {
+                self.model.load_show_details();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::ops::Deref;
+use std::rc::Rc;
+
+use crate::api::SpotifyApiError;
+use crate::app::episode_progress;
+use crate::app::models::*;
+use crate::app::state::{BrowserAction, PlaybackAction};
+use crate::app::{ActionDispatcher, AppModel};
+
+pub struct ShowDetailsModel {
+    pub id: String,
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl ShowDetailsModel {
+    pub fn new(id: String, app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            id,
+            app_model,
+            dispatcher,
+        }
+    }
+
+    pub fn get_show(&self) -> Option<impl Deref<Target = ShowDescription> + '_> {
+        self.app_model
+            .map_state_opt(|s| s.browser.show_details_state(&self.id)?.show.as_ref())
+    }
+
+    pub fn load_show_details(&self) {
+        let id = self.id.clone();
+        let api = self.app_model.get_spotify();
+        self.dispatcher
+            .call_spotify_and_dispatch(move || async move {
+                match api.get_show(&id).await {
+                    Ok(show) => {
+                        episode_progress::sync_with_remote(&show.episodes);
+                        Ok(BrowserAction::SetShowDetails(Box::new(show)).into())
+                    }
+                    Err(SpotifyApiError::BadStatus(400, _))
+                    | Err(SpotifyApiError::BadStatus(404, _)) => {
+                        Ok(BrowserAction::NavigationPop.into())
+                    }
+                    Err(e) => Err(e),
+                }
+            });
+    }
+
+    pub fn load_more(&self) -> Option<()> {
+        let api = self.app_model.get_spotify();
+        let state = self.app_model.get_state();
+        let next_page = &state.browser.show_details_state(&self.id)?.next_page;
+
+        let id = next_page.data.clone();
+        let batch_size = next_page.batch_size;
+        let offset = next_page.next_offset?;
+        self.dispatcher
+            .call_spotify_and_dispatch(move || async move {
+                let batch = api.get_show_episodes(&id, offset, batch_size).await?;
+                episode_progress::sync_with_remote(&batch.episodes);
+                Ok(BrowserAction::AppendShowEpisodes(id, Box::new(batch)).into())
+            });
+
+        Some(())
+    }
+
+    pub fn current_song_id(&self) -> Option<String> {
+        self.app_model.get_state().playback.current_song_id()
+    }
+
+    pub fn is_episode_played(&self, id: &str) -> bool {
+        episode_progress::is_played(id)
+    }
+
+    pub fn set_episode_played(&self, id: &str, played: bool) {
+        episode_progress::set_played(id, played);
+    }
+
+    // The loaded episodes are queued, starting from the one picked
+    pub fn play_episode_at(&self, pos: usize) {
+        let Some(show) = self.get_show() else {
+            return;
+        };
+        let Some(episode) = show.episodes.get(pos) else {
+            return;
+        };
+        let id = episode.id.clone();
+        let songs = show
+            .episodes
+            .iter()
+            .map(|episode| show.episode_song(episode))
+            .collect();
+        drop(show);
+
+        self.dispatcher
+            .dispatch(PlaybackAction::LoadSongs(songs, 0).into());
+        self.dispatcher.dispatch(PlaybackAction::Load(id).into());
+    }
+}
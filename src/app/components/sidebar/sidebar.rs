@@ -1,25 +1,34 @@
 use gettextrs::gettext;
 use gtk::prelude::*;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use super::create_playlist::CreatePlaylistPopover;
 use super::{
-    sidebar_row::SidebarRow, SidebarDestination, SidebarItem, CREATE_PLAYLIST_ITEM,
-    SAVED_PLAYLISTS_SECTION,
+    sidebar_row::SidebarRow, SidebarDestination, SidebarItem, CREATE_PLAYLIST_ITEM, PINNED_SECTION,
+    PLAYLIST_FILTER_ITEM, SAVED_PLAYLISTS_SECTION,
 };
+use crate::app::components::labels;
 use crate::app::models::{AlbumModel, PlaylistSummary};
-use crate::app::state::ScreenName;
+use crate::app::state::{ScreenName, SettingsAction, SettingsEvent};
 use crate::app::{
     ActionDispatcher, AppAction, AppEvent, AppModel, BrowserAction, BrowserEvent, Component,
     EventListener,
 };
+use crate::settings::PinnedItem;
 
-const NUM_FIXED_ENTRIES: u32 = 6;
+const NUM_FIXED_ENTRIES: u32 = 7;
+// Pinned items go right after the library, saved tracks and now playing entries
+const PINNED_OFFSET: u32 = 3;
 const NUM_PLAYLISTS: usize = 20;
 
 pub struct SidebarModel {
     app_model: Rc<AppModel>,
     dispatcher: Box<dyn ActionDispatcher>,
+    // Lowercased text typed in the filter entry above the playlists
+    filter: RefCell<String>,
+    // Offset of the page of playlists last asked for, so that it's only asked for once
+    requested_offset: Cell<Option<usize>>,
 }
 
 impl SidebarModel {
@@ -27,10 +36,43 @@ impl SidebarModel {
         Self {
             app_model,
             dispatcher,
+            filter: Default::default(),
+            requested_offset: Default::default(),
         }
     }
 
+    fn set_filter(&self, text: &str) {
+        self.filter.replace(text.to_lowercase());
+        self.load_remaining_playlists();
+    }
+
+    // The filter should find any playlist, not just those loaded so far: while filtering,
+    // the remaining pages are loaded one after the other
+    fn load_remaining_playlists(&self) -> Option<()> {
+        if self.filter.borrow().is_empty() {
+            self.requested_offset.set(None);
+            return None;
+        }
+        let state = self.app_model.get_state();
+        let next_page = &state.browser.home_state()?.next_playlists_page;
+        let batch_size = next_page.batch_size;
+        let offset = next_page.next_offset?;
+        if self.requested_offset.replace(Some(offset)) == Some(offset) {
+            return None;
+        }
+
+        let api = self.app_model.get_spotify();
+        self.dispatcher
+            .call_spotify_and_dispatch(move || async move {
+                api.get_saved_playlists(offset, batch_size)
+                    .await
+                    .map(|playlists| BrowserAction::AppendPlaylistsContent(playlists).into())
+            });
+        Some(())
+    }
+
     fn get_playlists(&self) -> Vec<SidebarDestination> {
+        let filter = self.filter.borrow();
         self.app_model
             .get_state()
             .browser
@@ -38,11 +80,61 @@ impl SidebarModel {
             .expect("expected HomeState to be available")
             .playlists
             .iter()
+            .filter(|a| filter.is_empty() || a.album().to_lowercase().contains(filter.as_str()))
             .take(NUM_PLAYLISTS)
             .map(Self::map_to_destination)
             .collect()
     }
 
+    fn get_pinned(&self) -> Vec<SidebarDestination> {
+        self.app_model
+            .get_state()
+            .settings
+            .settings
+            .pinned
+            .iter()
+            .cloned()
+            .map(SidebarDestination::Pinned)
+            .collect()
+    }
+
+    fn is_pinned(&self, uri: &str) -> bool {
+        self.app_model
+            .get_state()
+            .settings
+            .settings
+            .pinned
+            .iter()
+            .any(|p| p.uri == uri)
+    }
+
+    fn toggle_pinned(&self, uri: String, title: String) {
+        self.dispatcher
+            .dispatch(SettingsAction::TogglePinned(PinnedItem { uri, title }).into());
+    }
+
+    fn is_guest_mode(&self) -> bool {
+        self.app_model.get_state().settings.settings.guest_mode
+    }
+
+    // Only the user's own playlists can have songs added to them, and not by guests
+    fn is_user_playlist(&self, id: &str) -> bool {
+        let state = self.app_model.get_state();
+        !state.settings.settings.guest_mode
+            && state.logged_user.playlists.iter().any(|p| p.id == id)
+    }
+
+    fn add_to_playlist(&self, id: String, title: String, uri: String) {
+        let api = self.app_model.get_spotify();
+        self.dispatcher
+            .call_spotify_and_dispatch(move || async move {
+                api.add_to_playlist(&id, vec![uri]).await?;
+                Ok(AppAction::ShowNotification(
+                    labels::n_songs_added_to_playlist_label(1, &title),
+                ))
+            });
+    }
+
     fn map_to_destination(a: AlbumModel) -> SidebarDestination {
         let title = Some(a.album())
             .filter(|s| !s.is_empty())
@@ -66,6 +158,7 @@ impl SidebarModel {
         let actions = match dest {
             SidebarDestination::Library
             | SidebarDestination::SavedTracks
+            | SidebarDestination::SavedEpisodes
             | SidebarDestination::NowPlaying
             | SidebarDestination::SavedPlaylists => {
                 vec![
@@ -76,6 +169,9 @@ impl SidebarModel {
             SidebarDestination::Playlist(PlaylistSummary { id, .. }) => {
                 vec![AppAction::ViewPlaylist(id)]
             }
+            SidebarDestination::Pinned(PinnedItem { uri, .. }) => {
+                AppAction::OpenURI(uri).into_iter().collect()
+            }
         };
         self.dispatcher.dispatch_many(actions);
     }
@@ -84,6 +180,8 @@ impl SidebarModel {
 pub struct Sidebar {
     listbox: gtk::ListBox,
     list_store: gio::ListStore,
+    // The pinned section's header and items, if any
+    pinned_entries: Rc<Cell<u32>>,
     model: Rc<SidebarModel>,
 }
 
@@ -98,6 +196,9 @@ impl Sidebar {
         list_store.append(&SidebarItem::from_destination(
             SidebarDestination::SavedTracks,
         ));
+        list_store.append(&SidebarItem::from_destination(
+            SidebarDestination::SavedEpisodes,
+        ));
         list_store.append(&SidebarItem::from_destination(
             SidebarDestination::NowPlaying,
         ));
@@ -106,17 +207,35 @@ impl Sidebar {
         list_store.append(&SidebarItem::from_destination(
             SidebarDestination::SavedPlaylists,
         ));
+        list_store.append(&SidebarItem::playlist_filter_item());
+
+        let pinned_entries = Rc::new(Cell::new(0));
+
+        let filter_entry = gtk::SearchEntry::builder()
+            // translators: Placeholder of the entry narrowing down the playlists listed in the sidebar.
+            .placeholder_text(gettext("Filter playlists"))
+            .margin_start(6)
+            .margin_end(6)
+            .build();
+        filter_entry.connect_search_changed(
+            clone!(@weak list_store, @weak model, @weak pinned_entries => move |entry| {
+                model.set_filter(&entry.text());
+                Self::splice_playlists(&list_store, &model, pinned_entries.get());
+            }),
+        );
+        filter_entry.connect_stop_search(|entry| entry.set_text(""));
 
         listbox.bind_model(
             Some(&list_store),
-            clone!(@weak popover => @default-panic, move |obj| {
+            clone!(@weak popover, @strong filter_entry => @default-panic, move |obj| {
                 let item = obj.downcast_ref::<SidebarItem>().unwrap();
                 if item.navigatable() {
                     Self::make_navigatable(item)
                 } else {
                     match item.id().as_str() {
-                        SAVED_PLAYLISTS_SECTION => Self::make_section_label(item),
+                        PINNED_SECTION | SAVED_PLAYLISTS_SECTION => Self::make_section_label(item),
                         CREATE_PLAYLIST_ITEM => Self::make_create_playlist(item, popover),
+                        PLAYLIST_FILTER_ITEM => Self::make_filter_entry(filter_entry.clone()),
                         _ => unimplemented!(),
                     }
                 }
@@ -129,6 +248,7 @@ impl Sidebar {
                     model.navigate(dest);
                 } else {
                     match row.item().id().as_str() {
+                        CREATE_PLAYLIST_ITEM if model.is_guest_mode() => {}
                         CREATE_PLAYLIST_ITEM => popover.popup(),
                         _ => unimplemented!()
                     }
@@ -136,11 +256,112 @@ impl Sidebar {
             }
         }));
 
-        Self {
+        Self::setup_pin_menu(&listbox, &model);
+        Self::setup_drop_target(&listbox, &model);
+
+        let sidebar = Self {
             listbox,
             list_store,
+            pinned_entries,
             model,
-        }
+        };
+        sidebar.update_pinned_in_sidebar();
+        sidebar
+    }
+
+    // Right-clicking (or long-pressing) a playlist lets users pin it to the top, or unpin it
+    fn setup_pin_menu(listbox: &gtk::ListBox, model: &Rc<SidebarModel>) {
+        let toggle_pin =
+            gio::SimpleAction::new("toggle-pin", Some(glib::VariantTy::new("(ss)").unwrap()));
+        toggle_pin.connect_activate(clone!(@weak model => move |_, param| {
+            if let Some((uri, title)) = param.and_then(|p| p.get::<(String, String)>()) {
+                model.toggle_pinned(uri, title);
+            }
+        }));
+        let actions = gio::SimpleActionGroup::new();
+        actions.add_action(&toggle_pin);
+        listbox.insert_action_group("sidebar", Some(&actions));
+
+        let popover = gtk::PopoverMenu::from_model(None::<&gio::MenuModel>);
+        popover.set_has_arrow(false);
+        popover.set_parent(listbox);
+
+        let show_menu = clone!(@weak listbox, @weak popover, @weak model => move |x: f64, y: f64| {
+            let Some(item) = listbox
+                .row_at_y(y as i32)
+                .and_then(|row| row.downcast::<SidebarRow>().ok())
+                .and_then(|row| row.item().destination())
+                .and_then(|dest| dest.pinned_item()) else {
+                return;
+            };
+            let label = if model.is_pinned(&item.uri) {
+                &*labels::UNPIN_FROM_SIDEBAR
+            } else {
+                &*labels::PIN_TO_SIDEBAR
+            };
+            let menu_item = gio::MenuItem::new(Some(label), None);
+            menu_item.set_action_and_target_value(
+                Some("sidebar.toggle-pin"),
+                Some(&(item.uri, item.title).to_variant()),
+            );
+            let menu = gio::Menu::new();
+            menu.append_item(&menu_item);
+            popover.set_menu_model(Some(&menu));
+            popover.set_pointing_to(Some(&gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+            popover.popup();
+        });
+
+        let click = gtk::GestureClick::builder()
+            .button(gtk::gdk::BUTTON_SECONDARY)
+            .build();
+        click.connect_pressed(clone!(@strong show_menu => move |_, _, x, y| show_menu(x, y)));
+        listbox.add_controller(click);
+
+        let long_press = gtk::GestureLongPress::builder().touch_only(true).build();
+        long_press.connect_pressed(move |_, x, y| show_menu(x, y));
+        listbox.add_controller(long_press);
+    }
+
+    // Songs can be dragged onto the user's playlists to add them there
+    fn setup_drop_target(listbox: &gtk::ListBox, model: &Rc<SidebarModel>) {
+        let playlist_at = clone!(@weak listbox, @weak model => @default-return None, move |y: f64| {
+            let row = listbox.row_at_y(y as i32)?;
+            let dest = row.downcast_ref::<SidebarRow>()?.item().destination()?;
+            match dest {
+                SidebarDestination::Playlist(playlist) if model.is_user_playlist(&playlist.id) => {
+                    Some((row, playlist))
+                }
+                _ => None,
+            }
+        });
+
+        let drop_target = gtk::DropTarget::new(String::static_type(), gtk::gdk::DragAction::COPY);
+        drop_target.connect_motion(clone!(@weak listbox, @strong playlist_at => @default-return gtk::gdk::DragAction::empty(), move |_, _, y| {
+            match playlist_at(y) {
+                Some((row, _)) => {
+                    listbox.drag_highlight_row(&row);
+                    gtk::gdk::DragAction::COPY
+                }
+                None => {
+                    listbox.drag_unhighlight_row();
+                    gtk::gdk::DragAction::empty()
+                }
+            }
+        }));
+        drop_target.connect_leave(clone!(@weak listbox => move |_| {
+            listbox.drag_unhighlight_row();
+        }));
+        drop_target.connect_drop(
+            clone!(@weak listbox, @weak model => @default-return false, move |_, value, _, y| {
+                listbox.drag_unhighlight_row();
+                let (Ok(uri), Some((_, playlist))) = (value.get::<String>(), playlist_at(y)) else {
+                    return false;
+                };
+                model.add_to_playlist(playlist.id, playlist.title, uri);
+                true
+            }),
+        );
+        listbox.add_controller(drop_target);
     }
 
     fn make_navigatable(item: &SidebarItem) -> gtk::Widget {
@@ -170,18 +391,41 @@ impl Sidebar {
         row.upcast()
     }
 
-    fn update_playlists_in_sidebar(&self) {
-        let playlists: Vec<SidebarItem> = self
-            .model
+    fn make_filter_entry(entry: gtk::SearchEntry) -> gtk::Widget {
+        let row = gtk::ListBoxRow::builder()
+            .activatable(false)
+            .selectable(false)
+            .child(&entry)
+            .build();
+        row.upcast()
+    }
+
+    fn splice_playlists(list_store: &gio::ListStore, model: &SidebarModel, pinned_entries: u32) {
+        let playlists: Vec<SidebarItem> = model
             .get_playlists()
             .into_iter()
             .map(SidebarItem::from_destination)
             .collect();
-        self.list_store.splice(
-            NUM_FIXED_ENTRIES,
-            self.list_store.n_items() - NUM_FIXED_ENTRIES,
-            playlists.as_slice(),
-        );
+        let offset = NUM_FIXED_ENTRIES + pinned_entries;
+        list_store.splice(offset, list_store.n_items() - offset, playlists.as_slice());
+    }
+
+    fn update_playlists_in_sidebar(&self) {
+        Self::splice_playlists(&self.list_store, &self.model, self.pinned_entries.get());
+    }
+
+    fn update_pinned_in_sidebar(&self) {
+        let pinned = self.model.get_pinned();
+        let entries: Vec<SidebarItem> = if pinned.is_empty() {
+            vec![]
+        } else {
+            std::iter::once(SidebarItem::pinned_section())
+                .chain(pinned.into_iter().map(SidebarItem::from_destination))
+                .collect()
+        };
+        self.list_store
+            .splice(PINNED_OFFSET, self.pinned_entries.get(), entries.as_slice());
+        self.pinned_entries.set(entries.len() as u32);
     }
 }
 
@@ -193,8 +437,17 @@ impl Component for Sidebar {
 
 impl EventListener for Sidebar {
     fn on_event(&mut self, event: &AppEvent) {
-        if let AppEvent::BrowserEvent(BrowserEvent::SavedPlaylistsUpdated) = event {
-            self.update_playlists_in_sidebar();
+        match event {
+            AppEvent::BrowserEvent(BrowserEvent::SavedPlaylistsUpdated) => {
+                self.update_playlists_in_sidebar();
+                self.model.load_remaining_playlists();
+            }
+            AppEvent::SettingsEvent(
+                SettingsEvent::PinnedChanged | SettingsEvent::SettingsChanged,
+            ) => {
+                self.update_pinned_in_sidebar();
+            }
+            _ => {}
         }
     }
 }
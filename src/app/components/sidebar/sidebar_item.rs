@@ -4,22 +4,29 @@ use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 
 use crate::app::models::PlaylistSummary;
+use crate::settings::PinnedItem;
 
 const LIBRARY: &str = "library";
 const SAVED_TRACKS: &str = "saved_tracks";
+const SAVED_EPISODES: &str = "saved_episodes";
 const NOW_PLAYING: &str = "now_playing";
 const SAVED_PLAYLISTS: &str = "saved_playlists";
 const PLAYLIST: &str = "playlist";
+const PINNED: &str = "pinned";
+pub const PINNED_SECTION: &str = "pinned_section";
 pub const SAVED_PLAYLISTS_SECTION: &str = "saved_playlists_section";
 pub const CREATE_PLAYLIST_ITEM: &str = "create_playlist";
+pub const PLAYLIST_FILTER_ITEM: &str = "playlist_filter";
 
 #[derive(Debug)]
 pub enum SidebarDestination {
     Library,
     SavedTracks,
+    SavedEpisodes,
     NowPlaying,
     SavedPlaylists,
     Playlist(PlaylistSummary),
+    Pinned(PinnedItem),
 }
 
 impl SidebarDestination {
@@ -27,9 +34,11 @@ impl SidebarDestination {
         match self {
             Self::Library => LIBRARY,
             Self::SavedTracks => SAVED_TRACKS,
+            Self::SavedEpisodes => SAVED_EPISODES,
             Self::NowPlaying => NOW_PLAYING,
             Self::SavedPlaylists => SAVED_PLAYLISTS,
             Self::Playlist(_) => PLAYLIST,
+            Self::Pinned(_) => PINNED,
         }
     }
 
@@ -39,11 +48,26 @@ impl SidebarDestination {
             Self::Library => gettext("Library"),
             // translators: This is a sidebar entry to browse to saved tracks.
             Self::SavedTracks => gettext("Saved tracks"),
+            // translators: This is a sidebar entry to browse to podcast episodes saved on their own.
+            Self::SavedEpisodes => gettext("Saved episodes"),
             // translators: This is a sidebar entry to browse to saved playlists.
             Self::NowPlaying => gettext("Now playing"),
             // translators: This is a sidebar entry that marks that the entries below are playlists.
             Self::SavedPlaylists => gettext("Playlists"),
             Self::Playlist(PlaylistSummary { title, .. }) => title.clone(),
+            Self::Pinned(PinnedItem { title, .. }) => title.clone(),
+        }
+    }
+
+    // The pages of the home screen, which exclude playlists
+    pub fn home_page(id: &str) -> Option<Self> {
+        match id {
+            LIBRARY => Some(Self::Library),
+            SAVED_TRACKS => Some(Self::SavedTracks),
+            SAVED_EPISODES => Some(Self::SavedEpisodes),
+            NOW_PLAYING => Some(Self::NowPlaying),
+            SAVED_PLAYLISTS => Some(Self::SavedPlaylists),
+            _ => None,
         }
     }
 
@@ -51,9 +75,26 @@ impl SidebarDestination {
         match self {
             Self::Library => "library-music-symbolic",
             Self::SavedTracks => "starred-symbolic",
+            Self::SavedEpisodes => "audio-input-microphone-symbolic",
             Self::NowPlaying => "music-queue-symbolic",
             Self::SavedPlaylists => "view-app-grid-symbolic",
             Self::Playlist(_) => "playlist2-symbolic",
+            Self::Pinned(PinnedItem { uri, .. }) if uri.starts_with("spotify:album:") => {
+                "media-optical-symbolic"
+            }
+            Self::Pinned(_) => "playlist2-symbolic",
+        }
+    }
+
+    // What pinning this destination would add to the sidebar, if it can be pinned at all
+    pub fn pinned_item(&self) -> Option<PinnedItem> {
+        match self {
+            Self::Playlist(PlaylistSummary { id, title }) => Some(PinnedItem {
+                uri: format!("spotify:playlist:{id}"),
+                title: title.clone(),
+            }),
+            Self::Pinned(item) => Some(item.clone()),
+            _ => None,
         }
     }
 }
@@ -64,6 +105,7 @@ impl SidebarItem {
             SidebarDestination::Playlist(PlaylistSummary { id, title }) => {
                 (PLAYLIST, Some(id), title)
             }
+            SidebarDestination::Pinned(PinnedItem { uri, title }) => (PINNED, Some(uri), title),
             _ => (dest.id(), None, dest.title()),
         };
         glib::Object::builder()
@@ -74,6 +116,16 @@ impl SidebarItem {
             .build()
     }
 
+    pub fn pinned_section() -> Self {
+        glib::Object::builder()
+            .property("id", PINNED_SECTION)
+            .property("data", String::new())
+            // translators: This is a sidebar section above the albums and playlists pinned by the user.
+            .property("title", gettext("Pinned"))
+            .property("navigatable", false)
+            .build()
+    }
+
     pub fn playlists_section() -> Self {
         glib::Object::builder()
             .property("id", SAVED_PLAYLISTS_SECTION)
@@ -92,6 +144,15 @@ impl SidebarItem {
             .build()
     }
 
+    pub fn playlist_filter_item() -> Self {
+        glib::Object::builder()
+            .property("id", PLAYLIST_FILTER_ITEM)
+            .property("data", &String::new())
+            .property("title", &String::new())
+            .property("navigatable", false)
+            .build()
+    }
+
     pub fn destination(&self) -> Option<SidebarDestination> {
         let navigatable = self.property::<bool>("navigatable");
         if navigatable {
@@ -99,15 +160,12 @@ impl SidebarItem {
             let data = self.property::<String>("data");
             let title = self.title();
             match id.as_str() {
-                LIBRARY => Some(SidebarDestination::Library),
-                SAVED_TRACKS => Some(SidebarDestination::SavedTracks),
-                NOW_PLAYING => Some(SidebarDestination::NowPlaying),
-                SAVED_PLAYLISTS => Some(SidebarDestination::SavedPlaylists),
                 PLAYLIST => Some(SidebarDestination::Playlist(PlaylistSummary {
                     id: data,
                     title,
                 })),
-                _ => None,
+                PINNED => Some(SidebarDestination::Pinned(PinnedItem { uri: data, title })),
+                id => SidebarDestination::home_page(id),
             }
         } else {
             None
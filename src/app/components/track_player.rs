@@ -0,0 +1,53 @@
+use std::rc::Rc;
+
+use crate::app::components::EventListener;
+use crate::app::state::{LoginEvent, PlaybackAction};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
+
+// Plays single tracks opened from links, which might be opened before we're even logged in
+pub struct TrackPlayer {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    pending: Option<String>,
+}
+
+impl TrackPlayer {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            pending: None,
+        }
+    }
+
+    fn is_logged_in(&self) -> bool {
+        self.app_model.get_state().logged_user.user.is_some()
+    }
+
+    fn play(&self, id: String) {
+        let api = self.app_model.get_spotify();
+        self.dispatcher
+            .call_spotify_and_dispatch_many(move || async move {
+                let song = api.get_track(&id).await?;
+                Ok(vec![
+                    AppAction::from(PlaybackAction::LoadSongs(vec![song], 0)),
+                    AppAction::from(PlaybackAction::Load(id)),
+                ])
+            });
+    }
+}
+
+impl EventListener for TrackPlayer {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::TrackRequested(id) if self.is_logged_in() => self.play(id.clone()),
+            AppEvent::TrackRequested(id) => self.pending = Some(id.clone()),
+            AppEvent::LoginEvent(LoginEvent::LoginCompleted(_)) => {
+                if let Some(id) = self.pending.take() {
+                    self.play(id);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::rc::Rc;
+
+use crate::app::components::EventListener;
+use crate::app::models::SongDescription;
+use crate::app::state::Undoable;
+use crate::app::{ActionDispatcher, AppEvent, AppModel, BrowserAction};
+
+// Reverts destructive actions, remotely first and then locally
+pub struct Undo {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl Undo {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    fn undo(&self, undoable: Undoable) {
+        let api = self.app_model.get_spotify();
+        match undoable {
+            Undoable::UnsavedTracks(songs) => {
+                self.dispatcher
+                    .call_spotify_and_dispatch(move || async move {
+                        let ids = songs.iter().map(|s| s.original_id().to_string()).collect();
+                        api.save_tracks(ids).await?;
+                        Ok(BrowserAction::SaveTracks(songs).into())
+                    });
+            }
+            Undoable::UnsavedAlbum(id) => {
+                self.dispatcher
+                    .call_spotify_and_dispatch(move || async move {
+                        let album = api.save_album(&id).await?;
+                        Ok(BrowserAction::SaveAlbum(Box::new(album)).into())
+                    });
+            }
+            Undoable::RemovedFromPlaylist(id, songs) => {
+                self.dispatcher
+                    .call_spotify_and_dispatch(move || async move {
+                        // Going in order, each run of songs ends up where it was before
+                        for (position, run) in contiguous_runs(&songs) {
+                            let uris = run.into_iter().map(|s| s.uri.clone()).collect();
+                            api.insert_into_playlist(&id, uris, position).await?;
+                        }
+                        Ok(BrowserAction::InsertTracksInPlaylist(id, songs).into())
+                    });
+            }
+        }
+    }
+}
+
+// Groups songs with consecutive positions, so that they can be inserted in one go
+fn contiguous_runs(songs: &[(usize, SongDescription)]) -> Vec<(usize, Vec<&SongDescription>)> {
+    let mut sorted: Vec<&(usize, SongDescription)> = songs.iter().collect();
+    sorted.sort_by_key(|(position, _)| *position);
+
+    let mut runs: Vec<(usize, Vec<&SongDescription>)> = vec![];
+    for (position, song) in sorted {
+        match runs.last_mut() {
+            Some((start, run)) if *start + run.len() == *position => run.push(song),
+            _ => runs.push((*position, vec![song])),
+        }
+    }
+    runs
+}
+
+impl EventListener for Undo {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::UndoRequested(undoable) = event {
+            self.undo(undoable.clone());
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::rc::Rc;
+
+use crate::app::components::utils::{Debouncer, TrackPosition};
+use crate::app::components::{labels, EventListener};
+use crate::app::state::{PlaybackEvent, SettingsEvent};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
+
+// How long before the end of a track the next one gets announced (in milliseconds)
+const LEAD_TIME_MS: u32 = 10_000;
+
+// Shows a toast with the next track shortly before the current one ends
+pub struct UpNextNotifier {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    position: TrackPosition,
+    timeout: Debouncer,
+}
+
+impl UpNextNotifier {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            position: Default::default(),
+            timeout: Debouncer::new(),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.app_model.get_state().settings.settings.up_next_toast
+    }
+
+    fn seek(&mut self, position: u32) {
+        let playing = self.app_model.get_state().playback.is_playing();
+        self.position.seek(position, playing);
+    }
+
+    // Called whenever the position jumps or playback resumes
+    fn schedule(&self) {
+        self.timeout.cancel();
+        if !self.position.is_running() || !self.is_enabled() {
+            return;
+        }
+        let state = self.app_model.get_state();
+        let Some(duration) = state.playback.current_song().map(|s| s.duration) else {
+            return;
+        };
+        drop(state);
+        // Too late (or the track is too short) for the toast to be of any use
+        let remaining = duration.saturating_sub(self.position.current());
+        if remaining <= LEAD_TIME_MS {
+            return;
+        }
+
+        let app_model = Rc::downgrade(&self.app_model);
+        let dispatcher = self.dispatcher.box_clone();
+        self.timeout.debounce(remaining - LEAD_TIME_MS, move || {
+            let Some(app_model) = app_model.upgrade() else {
+                return;
+            };
+            let Some(next) = app_model.get_state().playback.next_song() else {
+                return;
+            };
+            let message = labels::up_next_label(&next.title, &next.artists_name());
+            dispatcher.dispatch(AppAction::ShowNotification(message));
+        });
+    }
+}
+
+impl EventListener for UpNextNotifier {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) => {
+                self.seek(0);
+                self.schedule();
+            }
+            AppEvent::PlaybackEvent(
+                PlaybackEvent::SeekSynced(pos) | PlaybackEvent::TrackSeeked(pos),
+            ) => {
+                self.seek(*pos);
+                self.schedule();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackPaused) => {
+                self.position.pause();
+                self.schedule();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackResumed) => {
+                self.position.resume();
+                self.schedule();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackStopped) => {
+                self.position.seek(0, false);
+                self.timeout.cancel();
+            }
+            AppEvent::SettingsEvent(SettingsEvent::SettingsChanged) => self.schedule(),
+            _ => {}
+        }
+    }
+}
@@ -1,10 +1,14 @@
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::CompositeTemplate;
+use libadwaita::prelude::*;
+use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::app::components::utils::wrap_flowbox_item;
-use crate::app::components::{display_add_css_provider, AlbumWidget, Component, EventListener};
+use crate::app::components::{
+    display_add_css_provider, labels, AlbumWidget, Component, EventListener,
+};
 use crate::app::{models::*, ListStore};
 use crate::app::{AppEvent, BrowserEvent, Worker};
 
@@ -23,8 +27,16 @@ mod imp {
         #[template_child]
         pub user_name: TemplateChild<gtk::Label>,
 
+        #[template_child]
+        pub user_followers: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub playlist_visibility: TemplateChild<libadwaita::PreferencesGroup>,
+
         #[template_child]
         pub user_playlists: TemplateChild<gtk::FlowBox>,
+
+        pub visibility_rows: RefCell<Vec<libadwaita::ActionRow>>,
     }
 
     #[glib::object_subclass]
@@ -62,6 +74,53 @@ impl UserDetailsWidget {
         self.imp().user_name.set_text(name);
     }
 
+    fn set_followers(&self, followers: Option<u32>, following: Option<u32>) {
+        let text = [
+            followers.map(labels::n_followers_label),
+            following.map(labels::n_following_label),
+        ]
+        .iter()
+        .flatten()
+        .cloned()
+        .collect::<Vec<String>>()
+        .join(" · ");
+        let label = &self.imp().user_followers;
+        label.set_visible(!text.is_empty());
+        label.set_text(&text);
+    }
+
+    // A switch for each playlist, to make it public or private
+    fn set_owned_playlists<F>(&self, playlists: &[PlaylistDescription], on_toggled: F)
+    where
+        F: Fn(String, bool) + Clone + 'static,
+    {
+        let widget = self.imp();
+        let group = &widget.playlist_visibility;
+        for row in widget.visibility_rows.take() {
+            group.remove(&row);
+        }
+
+        for playlist in playlists {
+            let switch = gtk::Switch::builder()
+                .active(playlist.public.unwrap_or(false))
+                .valign(gtk::Align::Center)
+                .build();
+            let row = libadwaita::ActionRow::builder()
+                .title(glib::markup_escape_text(&playlist.title).as_str())
+                .activatable_widget(&switch)
+                .build();
+            row.add_suffix(&switch);
+
+            let id = playlist.id.clone();
+            let f = on_toggled.clone();
+            switch.connect_active_notify(move |switch| f(id.clone(), switch.is_active()));
+
+            group.add(&row);
+            widget.visibility_rows.borrow_mut().push(row);
+        }
+        group.set_visible(!playlists.is_empty());
+    }
+
     fn connect_bottom_edge<F>(&self, f: F)
     where
         F: Fn() + 'static,
@@ -102,6 +161,9 @@ pub struct UserDetails {
 impl UserDetails {
     pub fn new(model: UserDetailsModel, worker: Worker) -> Self {
         model.load_user_details(model.id.clone());
+        if model.is_own_profile() {
+            model.load_following();
+        }
 
         let widget = UserDetailsWidget::new();
         let model = Rc::new(model);
@@ -127,6 +189,20 @@ impl UserDetails {
         if let Some(name) = self.model.get_user_name() {
             self.widget.set_user_name(&name);
         }
+        self.widget
+            .set_followers(self.model.get_followers(), self.model.get_following());
+
+        if self.model.is_own_profile() {
+            if let Some(playlists) = self.model.get_owned_playlists() {
+                let model = Rc::downgrade(&self.model);
+                self.widget
+                    .set_owned_playlists(&playlists, move |id, public| {
+                        if let Some(model) = model.upgrade() {
+                            model.set_playlist_public(id, public);
+                        }
+                    });
+            }
+        }
     }
 }
 
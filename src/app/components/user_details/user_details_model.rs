@@ -1,8 +1,9 @@
 use std::ops::Deref;
 use std::rc::Rc;
 
+use crate::api::SpotifyApiError;
 use crate::app::models::*;
-use crate::app::state::BrowserAction;
+use crate::app::state::{BrowserAction, LibraryMutation};
 use crate::app::{ActionDispatcher, AppAction, AppModel, ListStore};
 
 pub struct UserDetailsModel {
@@ -24,6 +25,34 @@ impl UserDetailsModel {
             .map_state_opt(|s| s.browser.user_state(&self.id)?.user.as_ref())
     }
 
+    // The profile of the logged in user, who can manage their playlists from there
+    pub fn is_own_profile(&self) -> bool {
+        self.app_model.get_state().logged_user.user.as_ref() == Some(&self.id)
+    }
+
+    pub fn get_following(&self) -> Option<u32> {
+        self.app_model
+            .get_state()
+            .browser
+            .user_state(&self.id)?
+            .following
+    }
+
+    pub fn get_owned_playlists(
+        &self,
+    ) -> Option<impl Deref<Target = Vec<PlaylistDescription>> + '_> {
+        self.app_model
+            .map_state_opt(|s| Some(&s.browser.user_state(&self.id)?.owned_playlists))
+    }
+
+    pub fn get_followers(&self) -> Option<u32> {
+        self.app_model
+            .get_state()
+            .browser
+            .user_state(&self.id)?
+            .followers
+    }
+
     pub fn get_list_store(&self) -> Option<impl Deref<Target = ListStore<AlbumModel>> + '_> {
         self.app_model
             .map_state_opt(|s| Some(&s.browser.user_state(&self.id)?.playlists))
@@ -39,6 +68,37 @@ impl UserDetailsModel {
             });
     }
 
+    // The API only tells us about followed artists, and only for the logged in user
+    pub fn load_following(&self) {
+        let id = self.id.clone();
+        let api = self.app_model.get_spotify();
+        self.dispatcher
+            .call_spotify_and_dispatch_many(move || async move {
+                match api.get_followed_artists_count().await {
+                    Ok(count) => Ok(vec![BrowserAction::SetUserFollowing(id, count).into()]),
+                    // Sessions from before the user-follow-read scope was asked for can't read it, until the next login
+                    Err(SpotifyApiError::BadStatus(403, _)) => {
+                        info!("Not allowed to read followed artists, log in again to see them");
+                        Ok(vec![])
+                    }
+                    Err(err) => Err(err),
+                }
+            });
+    }
+
+    // Unscoped, so that the change still goes through if the user leaves the page
+    // The switch is flipped back (from the state) if Spotify refuses the change
+    pub fn set_playlist_public(&self, id: String, public: bool) {
+        let api = self.app_model.get_spotify();
+        self.dispatcher.unscoped().call_spotify_and_mutate(
+            LibraryMutation::SetPlaylistPublic(id.clone(), public),
+            move || async move {
+                api.set_playlist_public(&id, public).await?;
+                Ok(vec![])
+            },
+        );
+    }
+
     pub fn open_playlist(&self, id: String) {
         self.dispatcher.dispatch(AppAction::ViewPlaylist(id));
     }
@@ -1,14 +1,19 @@
 use gettextrs::*;
 use gio::{prelude::ActionMapExt, SimpleAction, SimpleActionGroup};
 use gtk::prelude::*;
+use libadwaita::prelude::*;
 use std::rc::Rc;
 
 use super::UserMenuModel;
-use crate::app::components::{EventListener, Settings};
-use crate::app::{state::LoginEvent, AppEvent};
+use crate::app::components::{EventListener, Inspector, Logs, Settings};
+use crate::app::state::{LoginEvent, NetworkEvent, SettingsEvent};
+use crate::app::AppEvent;
 
 pub struct UserMenu {
     user_button: gtk::MenuButton,
+    offline_action: SimpleAction,
+    car_mode_action: SimpleAction,
+    inspector: Rc<Inspector>,
     model: Rc<UserMenuModel>,
 }
 
@@ -16,10 +21,13 @@ impl UserMenu {
     pub fn new(
         user_button: gtk::MenuButton,
         settings: Settings,
+        logs: Logs,
+        inspector: Inspector,
         about: libadwaita::AboutWindow,
         model: UserMenuModel,
     ) -> Self {
         let model = Rc::new(model);
+        let inspector = Rc::new(inspector);
 
         about.connect_close_request(
             clone!(@weak about => @default-return gtk::Inhibit(false), move |_| {
@@ -30,6 +38,14 @@ impl UserMenu {
 
         let action_group = SimpleActionGroup::new();
 
+        action_group.add_action(&{
+            let profile = SimpleAction::new("profile", None);
+            profile.connect_activate(clone!(@weak model => move |_, _| {
+                model.view_profile();
+            }));
+            profile
+        });
+
         action_group.add_action(&{
             let logout = SimpleAction::new("logout", None);
             logout.connect_activate(clone!(@weak model => move |_, _| {
@@ -38,6 +54,63 @@ impl UserMenu {
             logout
         });
 
+        action_group.add_action(&{
+            let switch_account = SimpleAction::new("switch_account", Some(glib::VariantTy::STRING));
+            switch_account.connect_activate(clone!(@weak model => move |_, parameter| {
+                let username = parameter.and_then(|p| p.get::<String>());
+                model.switch_account(username);
+            }));
+            switch_account
+        });
+
+        action_group.add_action(&{
+            let add_account = SimpleAction::new("add_account", None);
+            add_account.connect_activate(clone!(@weak model => move |_, _| {
+                model.switch_account(None);
+            }));
+            add_account
+        });
+
+        let offline_action =
+            SimpleAction::new_stateful("offline", None, model.is_offline().to_variant());
+        offline_action.connect_change_state(clone!(@weak model => move |action, state| {
+            if let Some(offline) = state.and_then(|s| s.get::<bool>()) {
+                action.set_state(offline.to_variant());
+                model.set_offline_mode(offline);
+            }
+        }));
+        action_group.add_action(&offline_action);
+
+        let car_mode_action =
+            SimpleAction::new_stateful("car_mode", None, model.is_car_mode().to_variant());
+        car_mode_action.connect_change_state(clone!(@weak model => move |action, state| {
+            if let Some(car_mode) = state.and_then(|s| s.get::<bool>()) {
+                action.set_state(car_mode.to_variant());
+                model.set_car_mode(car_mode);
+            }
+        }));
+        action_group.add_action(&car_mode_action);
+
+        action_group.add_action(&{
+            let guest_mode_action = SimpleAction::new("guest_mode", None);
+            guest_mode_action.connect_activate(
+                clone!(@weak model, @weak user_button => move |_, _| {
+                    let parent = user_button
+                        .root()
+                        .and_then(|root| root.downcast::<gtk::Window>().ok());
+                    let locking = !model.is_guest_mode();
+                    show_guest_mode_dialog(parent.as_ref(), locking, move |pin| {
+                        if locking {
+                            model.lock_guest_mode(pin);
+                        } else {
+                            model.unlock_guest_mode(pin);
+                        }
+                    });
+                }),
+            );
+            guest_mode_action
+        });
+
         action_group.add_action(&{
             let settings_action = SimpleAction::new("settings", None);
             settings_action.connect_activate(clone!(@weak model => move |_, _| {
@@ -46,6 +119,22 @@ impl UserMenu {
             settings_action
         });
 
+        action_group.add_action(&{
+            let logs_action = SimpleAction::new("logs", None);
+            logs_action.connect_activate(move |_, _| {
+                logs.show_self();
+            });
+            logs_action
+        });
+
+        action_group.add_action(&{
+            let inspector_action = SimpleAction::new("inspector", None);
+            inspector_action.connect_activate(clone!(@weak inspector => move |_, _| {
+                inspector.show_self();
+            }));
+            inspector_action
+        });
+
         action_group.add_action(&{
             let about_action = SimpleAction::new("about", None);
             about_action.connect_activate(clone!(@weak about => move |_, _| {
@@ -56,13 +145,40 @@ impl UserMenu {
 
         user_button.insert_action_group("menu", Some(&action_group));
 
-        Self { user_button, model }
+        Self {
+            user_button,
+            offline_action,
+            car_mode_action,
+            inspector,
+            model,
+        }
     }
 
     fn update_menu(&self) {
         let menu = gio::Menu::new();
+        // translators: This is a menu entry (a toggle), for a simplified layout with big buttons meant for touch screens in cars.
+        menu.append(Some(&gettext("Car Mode")), Some("menu.car_mode"));
+        // Nothing that could change the library, the account or the preferences while guests are around
+        if self.model.is_guest_mode() {
+            // translators: This is a menu entry, it asks for the PIN chosen when guest mode was turned on.
+            menu.append(Some(&gettext("Leave Guest Mode…")), Some("menu.guest_mode"));
+            // translators: This is a menu entry.
+            menu.append(Some(&gettext("About")), Some("menu.about"));
+            self.user_button.set_menu_model(Some(&menu));
+            return;
+        }
+        // translators: This is a menu entry (a toggle), to only use what was previously cached.
+        menu.append(Some(&gettext("Offline Mode")), Some("menu.offline"));
+        // translators: This is a menu entry, it locks the app so that guests can only search and queue songs.
+        menu.append(Some(&gettext("Guest Mode…")), Some("menu.guest_mode"));
         // translators: This is a menu entry.
         menu.append(Some(&gettext("Preferences")), Some("menu.settings"));
+        if crate::logging::is_capturing() {
+            // translators: This is a menu entry, only shown when debugging.
+            menu.append(Some(&gettext("Logs")), Some("menu.logs"));
+            // translators: This is a menu entry, only shown when debugging. It opens a window showing the app's internal state.
+            menu.append(Some(&gettext("State Inspector")), Some("menu.inspector"));
+        }
         // translators: This is a menu entry.
         menu.append(Some(&gettext("About")), Some("menu.about"));
         // translators: This is a menu entry.
@@ -70,8 +186,25 @@ impl UserMenu {
 
         if let Some(username) = self.model.username() {
             let user_menu = gio::Menu::new();
+            // translators: This is a menu entry, it opens the profile page of the logged in user.
+            user_menu.append(Some(&gettext("Profile")), Some("menu.profile"));
             // translators: This is a menu entry.
             user_menu.append(Some(&gettext("Log out")), Some("menu.logout"));
+
+            let accounts_menu = gio::Menu::new();
+            for account in self.model.other_accounts() {
+                let item = gio::MenuItem::new(Some(&account), None);
+                item.set_action_and_target_value(
+                    Some("menu.switch_account"),
+                    Some(&account.to_variant()),
+                );
+                accounts_menu.append_item(&item);
+            }
+            // translators: This is a menu entry, to log in with another Spotify account.
+            accounts_menu.append(Some(&gettext("Add Account…")), Some("menu.add_account"));
+            // translators: This is the title of a menu section listing the other Spotify accounts the user can switch to.
+            user_menu.append_submenu(Some(&gettext("Switch Account")), &accounts_menu);
+
             menu.insert_section(0, Some(&username), &user_menu);
         }
 
@@ -86,7 +219,73 @@ impl EventListener for UserMenu {
                 self.update_menu();
                 self.model.fetch_user_playlists();
             }
+            AppEvent::NetworkEvent(NetworkEvent::BackOnline) => {
+                self.model.fetch_user_playlists();
+            }
+            AppEvent::LoginEvent(LoginEvent::AccountSwitched | LoginEvent::LogoutCompleted) => {
+                self.update_menu();
+            }
+            AppEvent::SettingsEvent(SettingsEvent::OfflineModeChanged(offline)) => {
+                self.offline_action.set_state(offline.to_variant());
+            }
+            AppEvent::SettingsEvent(SettingsEvent::CarModeChanged(car_mode)) => {
+                self.car_mode_action.set_state(car_mode.to_variant());
+            }
+            AppEvent::SettingsEvent(SettingsEvent::GuestModeChanged(_)) => {
+                self.update_menu();
+            }
             _ => {}
         }
+        self.inspector.on_state_changed();
     }
 }
+
+// Locking asks for a new PIN, unlocking for the one chosen then
+fn show_guest_mode_dialog<F>(parent: Option<&gtk::Window>, locking: bool, on_pin: F)
+where
+    F: Fn(&str) + 'static,
+{
+    let (heading, body, response) = if locking {
+        (
+            // translators: Title of the dialog shown before turning guest mode on
+            gettext("Lock Guest Mode"),
+            // translators: Text of the dialog shown before turning guest mode on
+            gettext("Guests will be able to search and queue songs, but not to change your library or preferences. Choose a PIN to leave guest mode later."),
+            // translators: Button of the dialog shown before turning guest mode on
+            gettext("Lock"),
+        )
+    } else {
+        (
+            // translators: Title of the dialog shown before turning guest mode off
+            gettext("Leave Guest Mode"),
+            // translators: Text of the dialog shown before turning guest mode off
+            gettext("Enter the PIN chosen when guest mode was turned on."),
+            // translators: Button of the dialog shown before turning guest mode off
+            gettext("Unlock"),
+        )
+    };
+
+    let dialog = libadwaita::MessageDialog::new(parent, Some(&heading), Some(&body));
+    // translators: Button of the guest mode dialogs
+    dialog.add_response("cancel", &gettext("Cancel"));
+    dialog.add_response("confirm", &response);
+    dialog.set_response_appearance("confirm", libadwaita::ResponseAppearance::Suggested);
+    dialog.set_response_enabled("confirm", false);
+    dialog.set_default_response(Some("confirm"));
+    dialog.set_close_response("cancel");
+
+    let entry = gtk::PasswordEntry::builder()
+        .show_peek_icon(true)
+        .activates_default(true)
+        .build();
+    entry.connect_changed(clone!(@weak dialog => move |entry| {
+        dialog.set_response_enabled("confirm", !entry.text().is_empty());
+    }));
+    dialog.set_extra_child(Some(&entry));
+
+    dialog.connect_response(
+        Some("confirm"),
+        clone!(@weak entry => move |_, _| on_pin(&entry.text())),
+    );
+    dialog.present();
+}
@@ -1,10 +1,14 @@
-use crate::api::clear_user_cache;
-use crate::app::credentials::Credentials;
-use crate::app::state::{LoginAction, PlaybackAction};
-use crate::app::{ActionDispatcher, AppModel};
+use crate::api::{clear_caches, clear_user_cache};
+use crate::app::credentials::{self, Credentials};
+use crate::app::state::{LoginAction, PlaybackAction, SettingsAction};
+use crate::app::{ActionDispatcher, AppAction, AppModel};
+use gettextrs::gettext;
+use gio::prelude::SettingsExt;
 use std::ops::Deref;
 use std::rc::Rc;
 
+const SETTINGS: &str = "dev.alextren.Spot";
+
 pub struct UserMenuModel {
     app_model: Rc<AppModel>,
     dispatcher: Box<dyn ActionDispatcher>,
@@ -23,15 +27,96 @@ impl UserMenuModel {
             .map_state_opt(|s| s.logged_user.user.as_ref())
     }
 
+    pub fn view_profile(&self) {
+        let username = self.username().map(|u| u.clone());
+        if let Some(username) = username {
+            self.dispatcher.dispatch(AppAction::ViewUser(username));
+        }
+    }
+
     pub fn logout(&self) {
+        let clear_all_caches = self
+            .app_model
+            .get_state()
+            .settings
+            .settings
+            .clear_cache_on_logout;
         self.dispatcher.dispatch(PlaybackAction::Stop.into());
-        self.dispatcher.dispatch_async(Box::pin(async {
-            let _ = Credentials::logout().await;
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            // Before logging out, while the cache is still the account's
             let _ = clear_user_cache().await;
+            let _ = Credentials::logout().await;
+            if clear_all_caches {
+                let _ = clear_caches().await;
+            }
             Some(LoginAction::Logout.into())
         }));
     }
 
+    // Other accounts the user can switch to
+    pub fn other_accounts(&self) -> Vec<String> {
+        let current = self.username().map(|u| u.clone());
+        credentials::known_accounts()
+            .into_iter()
+            .filter(|a| Some(a) != current.as_ref())
+            .collect()
+    }
+
+    pub fn switch_account(&self, username: Option<String>) {
+        self.dispatcher.dispatch(PlaybackAction::Stop.into());
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            // No account means the login window will show up, so that another one can be added
+            credentials::set_current_account(username.as_deref());
+            Some(LoginAction::SwitchAccount.into())
+        }));
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.app_model.get_state().settings.settings.offline_mode
+    }
+
+    pub fn set_offline_mode(&self, offline: bool) {
+        // Remembered across restarts
+        let settings = gio::Settings::new(SETTINGS);
+        if let Err(err) = settings.set_boolean("offline-mode", offline) {
+            warn!("Could not save offline mode: {}", err);
+        }
+        self.dispatcher
+            .dispatch(SettingsAction::SetOfflineMode(offline).into());
+    }
+
+    pub fn is_car_mode(&self) -> bool {
+        self.app_model.get_state().settings.settings.car_mode
+    }
+
+    pub fn set_car_mode(&self, car_mode: bool) {
+        crate::settings::save_car_mode(car_mode);
+        self.dispatcher
+            .dispatch(SettingsAction::SetCarMode(car_mode).into());
+    }
+
+    pub fn is_guest_mode(&self) -> bool {
+        self.app_model.get_state().settings.settings.guest_mode
+    }
+
+    pub fn lock_guest_mode(&self, pin: &str) {
+        crate::settings::save_guest_mode(Some(pin));
+        self.dispatcher
+            .dispatch(SettingsAction::SetGuestMode(true).into());
+    }
+
+    pub fn unlock_guest_mode(&self, pin: &str) {
+        if !crate::settings::is_guest_mode_pin(pin) {
+            // translators: This notification shows up when the PIN entered to leave guest mode is not the right one
+            self.dispatcher
+                .dispatch(AppAction::ShowNotification(gettext("Wrong PIN")));
+            return;
+        }
+        crate::settings::save_guest_mode(None);
+        self.dispatcher
+            .dispatch(SettingsAction::SetGuestMode(false).into());
+    }
+
     pub fn fetch_user_playlists(&self) {
         let api = self.app_model.get_spotify();
         if let Some(current_user) = self.username() {
@@ -1,7 +1,7 @@
 use gtk::prelude::*;
 use std::cell::Cell;
 use std::rc::Rc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[derive(Clone)]
 pub struct Clock {
@@ -74,6 +74,49 @@ impl Debouncer {
             previous_source.remove();
         }
     }
+
+    pub fn cancel(&self) {
+        if let Some(source) = self.0.take() {
+            source.remove();
+        }
+    }
+}
+
+// Where we are in the current track, for components that can't wait for the player to tell them
+#[derive(Debug, Clone, Default)]
+pub struct TrackPosition {
+    // Until it was last paused or seeked (in milliseconds)
+    position: u32,
+    // Set while playing
+    resumed_at: Option<Instant>,
+}
+
+impl TrackPosition {
+    pub fn current(&self) -> u32 {
+        let elapsed = self
+            .resumed_at
+            .map(|t| t.elapsed().as_millis() as u32)
+            .unwrap_or(0);
+        self.position.saturating_add(elapsed)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.resumed_at.is_some()
+    }
+
+    pub fn seek(&mut self, position: u32, playing: bool) {
+        self.position = position;
+        self.resumed_at = Some(Instant::now()).filter(|_| playing);
+    }
+
+    pub fn pause(&mut self) {
+        self.position = self.current();
+        self.resumed_at = None;
+    }
+
+    pub fn resume(&mut self) {
+        self.resumed_at.get_or_insert_with(Instant::now);
+    }
 }
 
 pub struct Animator<EasingFn> {
@@ -152,6 +195,11 @@ pub fn wrap_flowbox_item<
     child.upcast::<gtk::Widget>()
 }
 
+// Fast enough and mostly sideways, so that scrolling a list doesn't count
+pub fn is_horizontal_swipe(velocity_x: f64, velocity_y: f64) -> bool {
+    velocity_x.abs() > 500.0 && velocity_x.abs() > 2.0 * velocity_y.abs()
+}
+
 pub fn format_duration(duration: f64) -> String {
     let seconds = (duration / 1000.0) as i32;
     let hours = seconds.div_euclid(3600);
@@ -1,10 +1,13 @@
+use gettextrs::*;
 use gtk::prelude::*;
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use crate::app::components::utils::{format_duration, Clock, TrackPosition};
 use crate::app::components::EventListener;
+use crate::app::state::{NetworkEvent, PlaybackEvent, SettingsEvent};
 use crate::app::{AppEvent, AppModel};
-use crate::settings::WindowGeometry;
+use crate::settings::{WindowGeometry, WindowTitle};
 
 thread_local! {
     static WINDOW_GEOMETRY: RefCell<WindowGeometry> = RefCell::new(WindowGeometry {
@@ -14,7 +17,17 @@ thread_local! {
 
 pub struct MainWindow {
     initial_window_geometry: WindowGeometry,
+    app_model: Rc<AppModel>,
     window: libadwaita::ApplicationWindow,
+    shortcuts: gtk::ShortcutController,
+    offline_banner: gtk::Revealer,
+    // Cookie of the active suspend inhibitor, if any
+    inhibit_cookie: Option<u32>,
+    // The window stays hidden until asked for (see AppAction::Raise)
+    headless: bool,
+    // To show the elapsed time in the title, see the window title preference
+    position: TrackPosition,
+    title_clock: Clock,
 }
 
 impl MainWindow {
@@ -22,11 +35,16 @@ impl MainWindow {
         initial_window_geometry: WindowGeometry,
         app_model: Rc<AppModel>,
         window: libadwaita::ApplicationWindow,
+        shortcuts: gtk::ShortcutController,
+        offline_banner: gtk::Revealer,
+        headless: bool,
     ) -> Self {
         window.connect_close_request(
             clone!(@weak app_model => @default-return gtk::Inhibit(false), move |window| {
                 let state = app_model.get_state();
-                if state.playback.is_playing() {
+                // Hiding the window keeps the app (player, MPRIS...) running, activating the app brings it back
+                let keep_running = state.playback.is_playing() || state.settings.settings.run_in_background;
+                if headless || keep_running {
                     window.set_visible(false);
                     gtk::Inhibit(true)
                 } else {
@@ -46,7 +64,14 @@ impl MainWindow {
 
         Self {
             initial_window_geometry,
+            app_model,
             window,
+            shortcuts,
+            offline_banner,
+            inhibit_cookie: None,
+            headless,
+            position: Default::default(),
+            title_clock: Clock::default(),
         }
     }
 
@@ -58,13 +83,134 @@ impl MainWindow {
         if self.initial_window_geometry.is_maximized {
             self.window.maximize();
         }
-        self.window.present();
+        self.update_shortcuts();
+        self.update_offline_banner();
+        self.update_density();
+        self.update_title();
+        if !self.headless {
+            self.window.present();
+        }
+    }
+
+    // (Re)create the keyboard shortcuts from the settings
+    fn update_shortcuts(&self) {
+        while let Some(shortcut) = self
+            .shortcuts
+            .item(0)
+            .and_then(|o| o.downcast::<gtk::Shortcut>().ok())
+        {
+            self.shortcuts.remove_shortcut(&shortcut);
+        }
+
+        let state = self.app_model.get_state();
+        for (action, trigger) in state.settings.settings.shortcuts.iter() {
+            // An empty trigger means the shortcut is disabled
+            if trigger.is_empty() {
+                continue;
+            }
+            let Some(trigger) = gtk::ShortcutTrigger::parse_string(trigger) else {
+                warn!("Invalid shortcut for {}: {}", action, trigger);
+                continue;
+            };
+            let action = gtk::NamedAction::new(action);
+            self.shortcuts
+                .add_shortcut(gtk::Shortcut::new(Some(trigger), Some(action)));
+        }
+    }
+
+    fn update_offline_banner(&self) {
+        let state = self.app_model.get_state();
+        let offline = state.settings.settings.offline_mode || !state.network.connected;
+        self.offline_banner.set_reveal_child(offline);
+    }
+
+    // Styles for the compact mode are scoped under this class
+    fn update_density(&self) {
+        let compact = self.app_model.get_state().settings.settings.compact_mode;
+        if compact {
+            self.window.add_css_class("compact");
+        } else {
+            self.window.remove_css_class("compact");
+        }
+    }
+
+    // "Spot — Artist – Title (1:23)", depending on the preference
+    fn title(app_model: &AppModel, position: &TrackPosition) -> String {
+        let state = app_model.get_state();
+        let detail = state.settings.settings.window_title;
+        match (detail, state.playback.current_song()) {
+            (WindowTitle::App, _) | (_, None) => "Spot".to_string(),
+            (WindowTitle::Track, Some(song)) => {
+                format!("Spot — {} – {}", song.artists_name(), song.title)
+            }
+            (WindowTitle::Elapsed, Some(song)) => format!(
+                "Spot — {} – {} ({})",
+                song.artists_name(),
+                song.title,
+                format_duration(position.current().into())
+            ),
+        }
+    }
+
+    fn update_title(&self) {
+        self.window
+            .set_title(Some(&Self::title(&self.app_model, &self.position)));
+
+        let ticking = self.position.is_running()
+            && self.app_model.get_state().settings.settings.window_title == WindowTitle::Elapsed;
+        if !ticking {
+            self.title_clock.stop();
+            return;
+        }
+        // Good until the next seek or pause, which restart the clock
+        let position = self.position.clone();
+        let window = self.window.downgrade();
+        let app_model = Rc::downgrade(&self.app_model);
+        self.title_clock.start(move || {
+            if let (Some(window), Some(app_model)) = (window.upgrade(), app_model.upgrade()) {
+                window.set_title(Some(&Self::title(&app_model, &position)));
+            }
+        });
+    }
+
+    fn sync_position(&mut self, position: u32) {
+        let playing = self.app_model.get_state().playback.is_playing();
+        self.position.seek(position, playing);
+        self.update_title();
     }
 
     fn raise(&self) {
         self.window.present();
     }
 
+    // Keep the session from suspending while we're playing music
+    fn update_inhibitor(&mut self) {
+        let state = self.app_model.get_state();
+        let should_inhibit = state.playback.is_playing() && state.settings.settings.inhibit_suspend;
+        drop(state);
+
+        let Some(app) = self.window.application() else {
+            return;
+        };
+        match (should_inhibit, self.inhibit_cookie) {
+            (true, None) => {
+                let cookie = app.inhibit(
+                    Some(&self.window),
+                    gtk::ApplicationInhibitFlags::SUSPEND | gtk::ApplicationInhibitFlags::IDLE,
+                    // translators: This is shown by the desktop when Spot prevents the computer from going to sleep
+                    Some(&gettext("Playing music")),
+                );
+                // A zero cookie means the request failed
+                self.inhibit_cookie = Some(cookie).filter(|&c| c != 0);
+            }
+            (false, Some(cookie)) => {
+                app.uninhibit(cookie);
+                self.inhibit_cookie = None;
+            }
+            _ => {}
+        }
+    }
+
     fn save_window_geometry<W: GtkWindowExt>(window: &W) {
         let (width, height) = window.default_size();
         let is_maximized = window.is_maximized();
@@ -84,6 +230,34 @@ impl EventListener for MainWindow {
         match event {
             AppEvent::Started => self.start(),
             AppEvent::Raised => self.raise(),
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackResumed) => {
+                self.update_inhibitor();
+                self.position.resume();
+                self.update_title();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackPaused) => {
+                self.update_inhibitor();
+                self.position.pause();
+                self.update_title();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackStopped) => {
+                self.update_inhibitor();
+                self.sync_position(0);
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) => self.sync_position(0),
+            AppEvent::PlaybackEvent(
+                PlaybackEvent::SeekSynced(pos) | PlaybackEvent::TrackSeeked(pos),
+            ) => self.sync_position(*pos),
+            AppEvent::SettingsEvent(SettingsEvent::SettingsChanged) => {
+                self.update_inhibitor();
+                self.update_shortcuts();
+                self.update_density();
+                self.update_title();
+            }
+            AppEvent::SettingsEvent(SettingsEvent::OfflineModeChanged(_))
+            | AppEvent::NetworkEvent(NetworkEvent::ConnectivityChanged) => {
+                self.update_offline_banner();
+            }
             _ => {}
         }
     }
@@ -1,16 +1,113 @@
-use secret_service::{EncryptionType, Error, SecretService};
+use async_std::fs;
+use gio::prelude::{SettingsExt, SettingsExtManual};
+use secret_service::{Collection, EncryptionType, Error, Item, SecretService};
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
 use std::{collections::HashMap, time::SystemTime};
 
 static SPOT_ATTR: &str = "spot_credentials";
+static USERNAME_ATTR: &str = "username";
+static SECRETS_ATTR: &str = "spot_secrets";
+static SECRET_NAME_ATTR: &str = "name";
+
+// Typing a secret would otherwise write every keystroke to the keyring
+const SECRET_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+const SETTINGS: &str = "dev.alextren.Spot";
+
+// Users without a keyring can opt out of it, in which case credentials are written to a file only they can read
+fn use_keyring() -> bool {
+    gio::Settings::new(SETTINGS).boolean("credentials-in-keyring")
+}
+
+fn credentials_file(username: &str) -> PathBuf {
+    glib::user_config_dir()
+        .join("spot")
+        .join("accounts")
+        .join(format!("{username}.json"))
+}
+
+fn secrets_file() -> PathBuf {
+    glib::user_config_dir().join("spot").join("secrets.json")
+}
+
+// Where credentials were written before we supported multiple accounts
+fn legacy_credentials_file() -> PathBuf {
+    glib::user_config_dir()
+        .join("spot")
+        .join("credentials.json")
+}
 
 // I'm not sure this is the right way to make credentials identifiable, but hey, it works
-fn make_attributes() -> HashMap<&'static str, &'static str> {
+fn make_attributes(username: Option<&str>) -> HashMap<&str, &str> {
     let mut attributes = HashMap::new();
     attributes.insert(SPOT_ATTR, "yes");
+    if let Some(username) = username {
+        attributes.insert(USERNAME_ATTR, username);
+    }
     attributes
 }
 
+// The accounts we have credentials for, in the order they were added
+pub fn known_accounts() -> Vec<String> {
+    gio::Settings::new(SETTINGS)
+        .strv("accounts")
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+// The account to log in with, None if the user should be asked
+pub fn current_account() -> Option<String> {
+    Some(
+        gio::Settings::new(SETTINGS)
+            .string("current-account")
+            .to_string(),
+    )
+    .filter(|s| !s.is_empty())
+}
+
+pub fn set_current_account(username: Option<&str>) {
+    crate::api::cache::set_cache_account(username);
+    let settings = gio::Settings::new(SETTINGS);
+    if let Err(err) = settings.set_string("current-account", username.unwrap_or_default()) {
+        warn!("Could not save current account: {}", err);
+    }
+}
+
+fn remember_account(username: &str) {
+    let mut accounts = known_accounts();
+    if !accounts.iter().any(|a| a == username) {
+        accounts.push(username.to_string());
+    }
+    let settings = gio::Settings::new(SETTINGS);
+    let accounts: Vec<&str> = accounts.iter().map(|a| a.as_str()).collect();
+    if let Err(err) = settings.set_strv("accounts", accounts.as_slice()) {
+        warn!("Could not save accounts: {}", err);
+    }
+    set_current_account(Some(username));
+}
+
+fn forget_account(username: &str) {
+    let accounts = known_accounts();
+    let accounts: Vec<&str> = accounts
+        .iter()
+        .map(|a| a.as_str())
+        .filter(|a| *a != username)
+        .collect();
+    let settings = gio::Settings::new(SETTINGS);
+    if let Err(err) = settings.set_strv("accounts", accounts.as_slice()) {
+        warn!("Could not save accounts: {}", err);
+    }
+    if current_account().as_deref() == Some(username) {
+        set_current_account(None);
+    }
+}
+
 // A (statically accessed) wrapper around the DBUS Secret Service
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Credentials {
@@ -19,6 +116,9 @@ pub struct Credentials {
     pub token: String,
     pub token_expiry_time: Option<SystemTime>,
     pub country: String,
+    // Only set when logging in through the browser (OAuth)
+    #[serde(default)]
+    pub refresh_token: Option<String>,
 }
 
 impl Credentials {
@@ -29,24 +129,105 @@ impl Credentials {
         }
     }
 
+    // Credentials of the current account
     pub async fn retrieve() -> Result<Self, Error> {
-        let service = SecretService::connect(EncryptionType::Dh).await?;
+        match current_account() {
+            Some(username) => Self::retrieve_account(Some(&username)).await,
+            None => {
+                // Credentials saved before we had accounts: save them again to make them the current account
+                let credentials = Self::retrieve_account(None).await?;
+                credentials.save().await?;
+                Ok(credentials)
+            }
+        }
+    }
+
+    // A username of None refers to credentials saved before we supported multiple accounts
+    async fn retrieve_account(username: Option<&str>) -> Result<Self, Error> {
+        if !use_keyring() {
+            return Self::retrieve_from_file(username).await.map(|(c, _)| c);
+        }
+        match Self::retrieve_from_keyring(username).await {
+            // Credentials might have been saved to a file while the keyring was turned off
+            Err(Error::NoResult) => Self::migrate_file_to_keyring(username).await,
+            result => result,
+        }
+    }
+
+    async fn keyring_collection<'a>(
+        service: &'a SecretService<'a>,
+    ) -> Result<Collection<'a>, Error> {
         let collection = service.get_default_collection().await?;
         if collection.is_locked().await? {
             collection.unlock().await?;
         }
-        let items = collection.search_items(make_attributes()).await?;
+        Ok(collection)
+    }
+
+    // Our items for the given account, or the legacy ones (without a username attribute) for None
+    async fn keyring_items<'a>(
+        collection: &'a Collection<'a>,
+        username: Option<&str>,
+    ) -> Result<Vec<Item<'a>>, Error> {
+        let items = collection.search_items(make_attributes(username)).await?;
+        if username.is_some() {
+            return Ok(items);
+        }
+        let mut legacy_items = vec![];
+        for item in items {
+            if !item.get_attributes().await?.contains_key(USERNAME_ATTR) {
+                legacy_items.push(item);
+            }
+        }
+        Ok(legacy_items)
+    }
+
+    async fn retrieve_from_keyring(username: Option<&str>) -> Result<Self, Error> {
+        let service = SecretService::connect(EncryptionType::Dh).await?;
+        let collection = Self::keyring_collection(&service).await?;
+        let items = Self::keyring_items(&collection, username).await?;
         let item = items.get(0).ok_or(Error::NoResult)?.get_secret().await?;
         serde_json::from_slice(&item).map_err(|_| Error::Unavailable)
     }
 
-    // Try to clear the credentials
+    async fn retrieve_from_file(username: Option<&str>) -> Result<(Self, PathBuf), Error> {
+        let path = username
+            .map(credentials_file)
+            .unwrap_or_else(legacy_credentials_file);
+        let content = fs::read(&path).await.map_err(|_| Error::NoResult)?;
+        let credentials = serde_json::from_slice(&content).map_err(|_| Error::Unavailable)?;
+        Ok((credentials, path))
+    }
+
+    async fn migrate_file_to_keyring(username: Option<&str>) -> Result<Self, Error> {
+        let (credentials, path) = Self::retrieve_from_file(username).await?;
+        credentials.save_to_keyring().await?;
+        let _ = fs::remove_file(path).await;
+        info!("Moved credentials to the keyring");
+        Ok(credentials)
+    }
+
+    // Try to clear the credentials of the current account
     pub async fn logout() -> Result<(), Error> {
+        let username = current_account();
+        if let Some(username) = username.as_deref() {
+            forget_account(username);
+        }
+
+        // Whatever the current setting, don't leave anything behind
+        if let Some(username) = username.as_deref() {
+            let _ = fs::remove_file(credentials_file(username)).await;
+        }
+        let _ = fs::remove_file(legacy_credentials_file()).await;
+        if !use_keyring() {
+            return Ok(());
+        }
+
         let service = SecretService::connect(EncryptionType::Dh).await?;
         let collection = service.get_default_collection().await?;
         if !collection.is_locked().await? {
-            let result = collection.search_items(make_attributes()).await?;
-            let item = result.get(0).ok_or(Error::NoResult)?;
+            let items = Self::keyring_items(&collection, username.as_deref()).await?;
+            let item = items.first().ok_or(Error::NoResult)?;
             item.delete().await
         } else {
             warn!("Keyring is locked -- not clearing credentials");
@@ -54,18 +235,48 @@ impl Credentials {
         }
     }
 
+    // Saving credentials also makes their account the current one
     pub async fn save(&self) -> Result<(), Error> {
+        if use_keyring() {
+            self.save_to_keyring().await?;
+        } else {
+            self.save_to_file().await?;
+        }
+        remember_account(&self.username);
+        Ok(())
+    }
+
+    async fn save_to_file(&self) -> Result<(), Error> {
+        let path = credentials_file(&self.username);
+        let encoded = serde_json::to_vec(&self).unwrap();
+        let write = async {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            fs::write(&path, &encoded).await?;
+            fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).await
+        };
+        write.await.map_err(|e| {
+            warn!("Could not write credentials file: {}", e);
+            Error::Unavailable
+        })?;
+        let _ = fs::remove_file(legacy_credentials_file()).await;
+        Ok(())
+    }
+
+    async fn save_to_keyring(&self) -> Result<(), Error> {
         let service = SecretService::connect(EncryptionType::Dh).await?;
-        let collection = service.get_default_collection().await?;
-        if collection.is_locked().await? {
-            collection.unlock().await?;
+        let collection = Self::keyring_collection(&service).await?;
+        // Replaced by the item we're about to create
+        for item in Self::keyring_items(&collection, None).await? {
+            item.delete().await?;
         }
         // We simply write our stuct as JSON and send it
         let encoded = serde_json::to_vec(&self).unwrap();
         collection
             .create_item(
                 "Spotify Credentials",
-                make_attributes(),
+                make_attributes(Some(&self.username)),
                 &encoded,
                 true,
                 "text/plain",
@@ -74,3 +285,190 @@ impl Credentials {
         Ok(())
     }
 }
+
+// Secrets other than the account credentials, kept along with them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Secret {
+    ProxyPassword,
+    LastfmApi,
+    LastfmSessionKey,
+    ListenbrainzToken,
+}
+
+impl Secret {
+    const ALL: [Self; 4] = [
+        Self::ProxyPassword,
+        Self::LastfmApi,
+        Self::LastfmSessionKey,
+        Self::ListenbrainzToken,
+    ];
+
+    // Also the settings key where it used to be stored in plain text
+    fn name(self) -> &'static str {
+        match self {
+            Self::ProxyPassword => "proxy-password",
+            Self::LastfmApi => "lastfm-api-secret",
+            Self::LastfmSessionKey => "lastfm-session-key",
+            Self::ListenbrainzToken => "listenbrainz-token",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::ProxyPassword => "Spot Proxy Password",
+            Self::LastfmApi => "Spot Last.fm API Secret",
+            Self::LastfmSessionKey => "Spot Last.fm Session Key",
+            Self::ListenbrainzToken => "Spot ListenBrainz Token",
+        }
+    }
+
+    fn attributes(self) -> HashMap<&'static str, &'static str> {
+        HashMap::from([(SECRETS_ATTR, "yes"), (SECRET_NAME_ATTR, self.name())])
+    }
+}
+
+lazy_static! {
+    // Loaded once on startup, so that they can be read along with the settings without waiting on the keyring
+    static ref SECRETS: Mutex<HashMap<Secret, String>> = Default::default();
+}
+
+thread_local! {
+    static PENDING_SECRET_SAVES: RefCell<HashMap<Secret, glib::SourceId>> = Default::default();
+}
+
+pub fn secret(secret: Secret) -> String {
+    SECRETS
+        .lock()
+        .unwrap()
+        .get(&secret)
+        .cloned()
+        .unwrap_or_default()
+}
+
+// Takes effect right away, but is only saved after a short delay (from the main thread)
+pub fn set_secret(secret: Secret, value: &str) {
+    SECRETS.lock().unwrap().insert(secret, value.to_string());
+    PENDING_SECRET_SAVES.with(|pending| {
+        if let Some(source) = pending.borrow_mut().remove(&secret) {
+            source.remove();
+        }
+        let source = glib::timeout_add_local_once(SECRET_SAVE_DELAY, move || {
+            PENDING_SECRET_SAVES.with(|pending| pending.borrow_mut().remove(&secret));
+            let value = self::secret(secret);
+            glib::MainContext::default().spawn_local(async move {
+                if let Err(err) = save_secret(secret, &value).await {
+                    warn!("Could not save {}: {}", secret.name(), err);
+                }
+            });
+        });
+        pending.borrow_mut().insert(secret, source);
+    });
+}
+
+// Called on startup. Secrets that used to be stored in the settings in plain text are moved out of there.
+pub fn load_secrets() {
+    let settings = gio::Settings::new(SETTINGS);
+    let secrets = async_std::task::block_on(async {
+        let mut secrets = match retrieve_secrets().await {
+            Ok(secrets) => secrets,
+            Err(Error::NoResult) => HashMap::new(),
+            Err(err) => {
+                warn!("Could not read secrets: {}", err);
+                HashMap::new()
+            }
+        };
+        for secret in Secret::ALL {
+            let legacy_value = settings.string(secret.name()).to_string();
+            if legacy_value.is_empty() {
+                continue;
+            }
+            match save_secret(secret, &legacy_value).await {
+                Ok(_) => {
+                    let _ = settings.set_string(secret.name(), "");
+                    info!("Moved {} out of the settings", secret.name());
+                }
+                Err(err) => warn!("Could not save {}: {}", secret.name(), err),
+            }
+            secrets.insert(secret, legacy_value);
+        }
+        secrets
+    });
+    *SECRETS.lock().unwrap() = secrets;
+}
+
+async fn read_secrets_file() -> HashMap<String, String> {
+    match fs::read(secrets_file()).await {
+        Ok(content) => serde_json::from_slice(&content).unwrap_or_default(),
+        Err(_) => HashMap::new(),
+    }
+}
+
+async fn retrieve_secrets() -> Result<HashMap<Secret, String>, Error> {
+    let named = |name: &str| Secret::ALL.iter().copied().find(|s| s.name() == name);
+    if !use_keyring() {
+        let stored = read_secrets_file().await;
+        return Ok(stored
+            .into_iter()
+            .filter_map(|(name, value)| Some((named(&name)?, value)))
+            .collect());
+    }
+    let service = SecretService::connect(EncryptionType::Dh).await?;
+    let collection = Credentials::keyring_collection(&service).await?;
+    let items = collection
+        .search_items(HashMap::from([(SECRETS_ATTR, "yes")]))
+        .await?;
+    let mut secrets = HashMap::new();
+    for item in items {
+        let attributes = item.get_attributes().await?;
+        let Some(secret) = attributes.get(SECRET_NAME_ATTR).and_then(|n| named(n)) else {
+            continue;
+        };
+        let value = String::from_utf8(item.get_secret().await?).map_err(|_| Error::Unavailable)?;
+        secrets.insert(secret, value);
+    }
+    Ok(secrets)
+}
+
+// An empty value removes the secret
+async fn save_secret(secret: Secret, value: &str) -> Result<(), Error> {
+    if !use_keyring() {
+        let mut stored = read_secrets_file().await;
+        if value.is_empty() {
+            stored.remove(secret.name());
+        } else {
+            stored.insert(secret.name().to_string(), value.to_string());
+        }
+        let path = secrets_file();
+        let encoded = serde_json::to_vec(&stored).unwrap();
+        let write = async {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            fs::write(&path, &encoded).await?;
+            fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).await
+        };
+        return write.await.map_err(|e| {
+            warn!("Could not write secrets file: {}", e);
+            Error::Unavailable
+        });
+    }
+
+    let service = SecretService::connect(EncryptionType::Dh).await?;
+    let collection = Credentials::keyring_collection(&service).await?;
+    if value.is_empty() {
+        for item in collection.search_items(secret.attributes()).await? {
+            item.delete().await?;
+        }
+        return Ok(());
+    }
+    collection
+        .create_item(
+            secret.label(),
+            secret.attributes(),
+            value.as_bytes(),
+            true,
+            "text/plain",
+        )
+        .await?;
+    Ok(())
+}
@@ -1,11 +1,17 @@
 use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::channel::oneshot;
 use futures::future::BoxFuture;
 use futures::future::Future;
+use futures::future::{select, Either, FutureExt, Shared};
 use futures::stream::StreamExt;
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
 
 use super::AppAction;
 
+// So that the UI still gets a chance to update when actions keep coming in
+const MAX_BATCH_SIZE: usize = 64;
+
 // A wrapper around an MPSC sender to send AppActions synchronously or asynchronously
 // It is a trait because I guess I wanted to be able to stub it, but see how that went...
 pub trait ActionDispatcher {
@@ -15,6 +21,10 @@ pub trait ActionDispatcher {
     fn dispatch_many_async(&self, actions: BoxFuture<'static, Vec<AppAction>>);
     // Can't have impl Clone easily so there you go
     fn box_clone(&self) -> Box<dyn ActionDispatcher>;
+    // For things that should complete no matter what (see ScopedActionDispatcher)
+    fn unscoped(&self) -> Box<dyn ActionDispatcher> {
+        self.box_clone()
+    }
 }
 
 #[derive(Clone)]
@@ -63,6 +73,101 @@ impl ActionDispatcher for ActionDispatcherImpl {
     }
 }
 
+// Dropping the sender resolves all the receivers, which is how the scope signals cancellation
+struct Scope {
+    _sender: oneshot::Sender<()>,
+    cancelled: Shared<oneshot::Receiver<()>>,
+}
+
+// A dispatcher tied to the lifetime of a component (typically a screen that can be popped):
+// once it and all its clones are dropped, pending async tasks are aborted and won't dispatch anything
+pub struct ScopedActionDispatcher {
+    inner: Box<dyn ActionDispatcher>,
+    scope: Arc<Scope>,
+}
+
+impl ScopedActionDispatcher {
+    pub fn new(inner: Box<dyn ActionDispatcher>) -> Self {
+        let (sender, receiver) = oneshot::channel();
+        let scope = Scope {
+            _sender: sender,
+            cancelled: receiver.shared(),
+        };
+        Self {
+            inner,
+            scope: Arc::new(scope),
+        }
+    }
+
+    fn abortable<T: Default + Send + 'static>(
+        &self,
+        task: BoxFuture<'static, T>,
+    ) -> BoxFuture<'static, T> {
+        let cancelled = self.scope.cancelled.clone();
+        Box::pin(async move {
+            match select(task, cancelled).await {
+                Either::Left((result, _)) => result,
+                Either::Right(_) => T::default(),
+            }
+        })
+    }
+}
+
+impl ActionDispatcher for ScopedActionDispatcher {
+    fn dispatch(&self, action: AppAction) {
+        self.inner.dispatch(action);
+    }
+
+    fn dispatch_many(&self, actions: Vec<AppAction>) {
+        self.inner.dispatch_many(actions);
+    }
+
+    fn dispatch_async(&self, action: BoxFuture<'static, Option<AppAction>>) {
+        self.inner.dispatch_async(self.abortable(action));
+    }
+
+    fn dispatch_many_async(&self, actions: BoxFuture<'static, Vec<AppAction>>) {
+        self.inner.dispatch_many_async(self.abortable(actions));
+    }
+
+    fn box_clone(&self) -> Box<dyn ActionDispatcher> {
+        Box::new(Self {
+            inner: self.inner.box_clone(),
+            scope: Arc::clone(&self.scope),
+        })
+    }
+
+    fn unscoped(&self) -> Box<dyn ActionDispatcher> {
+        self.inner.unscoped()
+    }
+}
+
+// A failed request that can be made again, yielding the actions to dispatch
+// (a Mutex so that the closure only has to be Send, as actions are)
+#[derive(Clone)]
+pub struct Retry(Arc<Mutex<Box<RetryFn>>>);
+
+type RetryFn = dyn Fn() -> BoxFuture<'static, Vec<AppAction>> + Send;
+
+impl Retry {
+    pub fn new<F>(retry: F) -> Self
+    where
+        F: Fn() -> BoxFuture<'static, Vec<AppAction>> + Send + 'static,
+    {
+        Self(Arc::new(Mutex::new(Box::new(retry))))
+    }
+
+    pub fn run(&self) -> BoxFuture<'static, Vec<AppAction>> {
+        (self.0.lock().unwrap())()
+    }
+}
+
+impl std::fmt::Debug for Retry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Retry")
+    }
+}
+
 // Funky name for a mere wrapper around an MPSC send/recv pair
 pub struct DispatchLoop {
     receiver: UnboundedReceiver<AppAction>,
@@ -79,10 +184,12 @@ impl DispatchLoop {
         self.sender.clone()
     }
 
-    pub async fn attach(self, mut handler: impl FnMut(AppAction)) {
+    // Actions are handled in batches of whatever was sent since the last iteration of the main loop
+    pub async fn attach(self, mut handler: impl FnMut(Vec<AppAction>)) {
         self.receiver
-            .for_each(|action| {
-                handler(action);
+            .ready_chunks(MAX_BATCH_SIZE)
+            .for_each(|actions| {
+                handler(actions);
                 async {}
             })
             .await;
@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::app::models::EpisodeDescription;
+
+// Stopping this close to the end counts as finishing the episode, which then starts over
+const END_MARGIN_MS: u32 = 15_000;
+// Positions change on every pause and seek, writes are grouped
+const SAVE_DELAY: Duration = Duration::from_secs(5);
+
+// Where an episode was left off
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct EpisodeProgress {
+    // In milliseconds
+    position: u32,
+    // The resume point Spotify gave us last time, to notice when the episode was played on another device
+    remote_position: Option<u32>,
+    // Listened to the end, or marked as such
+    #[serde(default)]
+    played: bool,
+}
+
+type Progress = HashMap<String, EpisodeProgress>;
+
+lazy_static! {
+    // Read from disk the first time it's needed
+    static ref PROGRESS: Mutex<Option<Progress>> = Mutex::new(None);
+}
+
+// Set while changes are waiting to be written
+static SAVE_PENDING: AtomicBool = AtomicBool::new(false);
+
+fn progress_path() -> PathBuf {
+    glib::user_data_dir().join("spot").join("episodes.json")
+}
+
+fn read_progress() -> Progress {
+    std::fs::read(progress_path())
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn serialize_progress() -> Option<Vec<u8>> {
+    let progress = PROGRESS.lock().unwrap();
+    serde_json::to_vec(progress.as_ref()?)
+        .map_err(|err| warn!("Could not save episode progress: {}", err))
+        .ok()
+}
+
+async fn save() {
+    if !SAVE_PENDING.swap(false, Ordering::Relaxed) {
+        return;
+    }
+    let Some(content) = serialize_progress() else {
+        return;
+    };
+    let path = progress_path();
+    if let Some(parent) = path.parent() {
+        let _ = async_std::fs::create_dir_all(parent).await;
+    }
+    if let Err(err) = async_std::fs::write(&path, content).await {
+        warn!("Could not save episode progress: {}", err);
+    }
+}
+
+// When quitting, the pending save would never run
+pub fn save_blocking() {
+    if !SAVE_PENDING.swap(false, Ordering::Relaxed) {
+        return;
+    }
+    let Some(content) = serialize_progress() else {
+        return;
+    };
+    let path = progress_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Err(err) = std::fs::write(&path, content) {
+        warn!("Could not save episode progress: {}", err);
+    }
+}
+
+fn schedule_save() {
+    if SAVE_PENDING.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    glib::timeout_add_once(SAVE_DELAY, || {
+        glib::MainContext::default().spawn(save());
+    });
+}
+
+fn with_progress<T>(f: impl FnOnce(&mut Progress) -> T) -> T {
+    let mut progress = PROGRESS.lock().unwrap();
+    f(progress.get_or_insert_with(read_progress))
+}
+
+// Returns whether anything changed
+fn sync_entry(progress: &mut Progress, id: &str, remote: u32) -> bool {
+    // No need to keep track of episodes that were never started
+    if remote == 0 && !progress.contains_key(id) {
+        return false;
+    }
+    let entry = progress.entry(id.to_string()).or_default();
+    if entry.remote_position == Some(remote) {
+        return false;
+    }
+    // A resume point we haven't seen before only wins if we have nothing better
+    if entry.remote_position.is_some() || entry.position == 0 {
+        entry.position = remote;
+    }
+    entry.remote_position = Some(remote);
+    true
+}
+
+// Where to start playing an episode from (in milliseconds)
+pub fn resume_position(id: &str) -> u32 {
+    with_progress(|progress| progress.get(id).map(|p| p.position).unwrap_or(0))
+}
+
+pub fn is_played(id: &str) -> bool {
+    with_progress(|progress| progress.get(id).map(|p| p.played).unwrap_or(false))
+}
+
+// Either way, the episode will start from the beginning next time
+pub fn set_played(id: &str, played: bool) {
+    with_progress(|progress| {
+        let entry = progress.entry(id.to_string()).or_default();
+        entry.position = 0;
+        entry.played = played;
+        schedule_save();
+    });
+}
+
+// Remembers where playback of an episode stopped, episodes played to the end are marked as played
+pub fn save_position(id: &str, position: u32, duration: u32) {
+    let finished = position.saturating_add(END_MARGIN_MS) >= duration;
+    let position = if finished { 0 } else { position };
+    with_progress(|progress| {
+        let entry = progress.entry(id.to_string()).or_default();
+        if entry.position != position || (finished && !entry.played) {
+            entry.position = position;
+            entry.played |= finished;
+            schedule_save();
+        }
+    });
+}
+
+// Spotify doesn't let us update its resume points, but we can at least pick up those from other devices
+pub fn sync_with_remote(episodes: &[EpisodeDescription]) {
+    with_progress(|progress| {
+        let mut changed = false;
+        for episode in episodes {
+            if let Some(remote) = episode.resume_position {
+                changed |= sync_entry(progress, &episode.id, remote);
+            }
+        }
+        if changed {
+            schedule_save();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync_entry() {
+        let mut progress = Progress::new();
+
+        assert!(!sync_entry(&mut progress, "unplayed", 0));
+        assert!(progress.is_empty());
+
+        progress.insert(
+            "local".to_string(),
+            EpisodeProgress {
+                position: 5000,
+                ..Default::default()
+            },
+        );
+        assert!(sync_entry(&mut progress, "local", 0));
+        assert_eq!(progress["local"].position, 5000);
+
+        // Played elsewhere since
+        assert!(sync_entry(&mut progress, "local", 8000));
+        assert_eq!(progress["local"].position, 8000);
+
+        assert!(!sync_entry(&mut progress, "local", 8000));
+    }
+}
@@ -1,32 +1,106 @@
 use crate::api::cache::*;
+use crate::api::http_client;
+use crate::app::Worker;
+use futures::future::{AbortHandle, Abortable, LocalBoxFuture, Shared};
+use futures::FutureExt;
 use gdk_pixbuf::traits::PixbufLoaderExt;
-use gdk_pixbuf::{Pixbuf, PixbufLoader};
-use isahc::config::Configurable;
-use isahc::{AsyncBody, AsyncReadResponseExt, HttpClient, Response};
+use gdk_pixbuf::{Colorspace, Pixbuf, PixbufLoader};
+use gtk::prelude::*;
+use isahc::{AsyncBody, AsyncReadResponseExt, Response};
+use std::cell::RefCell;
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::future::Future;
 use std::hash::Hasher;
-use std::io::{Error, ErrorKind, Write};
+use std::path::PathBuf;
 
-// A wrapper to be able to implement the Write trait on a PixbufLoader
-struct LocalPixbufLoader<'a>(&'a PixbufLoader);
+type TextureKey = (String, i32);
 
-impl<'a> Write for LocalPixbufLoader<'a> {
-    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
-        self.0
-            .write(buf)
-            .map_err(|e| Error::new(ErrorKind::Other, format!("glib error: {e}")))?;
-        Ok(buf.len())
+// Below that, there's no point going through the whole map to prune it
+const MIN_TEXTURES_PRUNED: usize = 64;
+
+thread_local! {
+    // Textures currently in use, so that the same cover shown in several places at the same size is only
+    // decoded (and kept in memory) once
+    static TEXTURES: RefCell<TextureCache> = Default::default();
+    // Textures being loaded, so that rows asking for the same cover at the same time share the download
+    static PENDING_TEXTURES: RefCell<HashMap<TextureKey, Shared<LocalBoxFuture<'static, Option<gdk::Texture>>>>> =
+        Default::default();
+}
+
+#[derive(Default)]
+struct TextureCache {
+    textures: HashMap<TextureKey, glib::WeakRef<gdk::Texture>>,
+    // The ones nothing uses anymore are forgotten once the map grows to that size
+    prune_at: usize,
+}
+
+impl TextureCache {
+    fn get(&self, key: &TextureKey) -> Option<gdk::Texture> {
+        self.textures.get(key)?.upgrade()
     }
 
-    fn flush(&mut self) -> Result<(), Error> {
-        self.0
-            .close()
-            .map_err(|e| Error::new(ErrorKind::Other, format!("glib error: {e}")))?;
-        Ok(())
+    fn insert(&mut self, key: TextureKey, texture: &gdk::Texture) {
+        if self.textures.len() >= self.prune_at {
+            self.textures
+                .retain(|_, texture| texture.upgrade().is_some());
+            self.prune_at = usize::max(2 * self.textures.len(), MIN_TEXTURES_PRUNED);
+        }
+        self.textures.insert(key, texture.downgrade());
+    }
+}
+
+// Whoever asks first loads the texture, the others wait for it
+async fn shared_texture<F>(key: TextureKey, load: F) -> Option<gdk::Texture>
+where
+    F: Future<Output = Option<gdk::Texture>> + 'static,
+{
+    let cached = TEXTURES.with(|textures| textures.borrow().get(&key));
+    if cached.is_some() {
+        return cached;
+    }
+
+    let pending = PENDING_TEXTURES.with(|pending| {
+        pending
+            .borrow_mut()
+            .entry(key.clone())
+            .or_insert_with(|| load.boxed_local().shared())
+            .clone()
+    });
+    let texture = pending.await;
+    PENDING_TEXTURES.with(|pending| pending.borrow_mut().remove(&key));
+    if let Some(texture) = texture.as_ref() {
+        TEXTURES.with(|textures| textures.borrow_mut().insert(key, texture));
+    }
+    texture
+}
+
+// Pixbufs can't be sent across threads, so decoded images come back from the worker as raw pixels
+struct DecodedImage {
+    pixels: glib::Bytes,
+    has_alpha: bool,
+    bits_per_sample: i32,
+    width: i32,
+    height: i32,
+    rowstride: i32,
+}
+
+impl DecodedImage {
+    fn into_pixbuf(self) -> Pixbuf {
+        Pixbuf::from_bytes(
+            &self.pixels,
+            Colorspace::Rgb,
+            self.has_alpha,
+            self.bits_per_sample,
+            self.width,
+            self.height,
+            self.rowstride,
+        )
     }
 }
 
 // A helper to load remote images, with simple cache management
+#[derive(Clone)]
 pub struct ImageLoader {
     cache: CacheManager,
 }
@@ -47,12 +121,24 @@ impl ImageLoader {
     }
 
     async fn get_image(url: &str) -> Option<Response<AsyncBody>> {
-        let mut builder = HttpClient::builder();
-        if cfg!(debug_assertions) {
-            builder = builder.ssl_options(isahc::config::SslOption::DANGER_ACCEPT_INVALID_CERTS);
-        }
-        let client = builder.build().unwrap();
-        client.get_async(url).await.ok()
+        http_client().get_async(url).await.ok()
+    }
+
+    // Runs on a separate thread, decoding large images takes a while
+    fn decode(buffer: &[u8], width: i32, height: i32) -> Option<DecodedImage> {
+        let pixbuf_loader = PixbufLoader::new();
+        pixbuf_loader.set_size(width, height);
+        pixbuf_loader.write(buffer).ok()?;
+        pixbuf_loader.close().ok()?;
+        let pixbuf = pixbuf_loader.pixbuf()?;
+        Some(DecodedImage {
+            pixels: pixbuf.read_pixel_bytes(),
+            has_alpha: pixbuf.has_alpha(),
+            bits_per_sample: pixbuf.bits_per_sample(),
+            width: pixbuf.width(),
+            height: pixbuf.height(),
+            rowstride: pixbuf.rowstride(),
+        })
     }
 
     pub async fn load_remote(
@@ -63,38 +149,136 @@ impl ImageLoader {
         height: i32,
     ) -> Option<Pixbuf> {
         let resource = Self::resource_for(url, ext);
-        let pixbuf_loader = PixbufLoader::new();
-        pixbuf_loader.set_size(width, height);
-        let mut loader = LocalPixbufLoader(&pixbuf_loader);
 
         // Try to read from cache first, ignoring possible expiry
-        match self
+        let buffer = match self
+            .cache
+            .read_cache_file(&resource[..], CachePolicy::IgnoreExpiry)
+            .await
+        {
+            Ok(CacheFile::Fresh(buffer, _)) => buffer,
+            // Otherwise, get image over HTTP
+            _ => {
+                let mut resp = Self::get_image(url).await?;
+                let mut buffer = vec![];
+                resp.copy_to(&mut buffer).await.ok()?;
+                // ...but also save it to cache
+                self.cache
+                    .write_cache_file(&resource[..], &buffer[..], CacheExpiry::Never)
+                    .await
+                    .ok()?;
+                buffer
+            }
+        };
+
+        async_std::task::spawn_blocking(move || Self::decode(&buffer, width, height))
+            .await
+            .map(DecodedImage::into_pixbuf)
+    }
+
+    // A square texture for a cover, shared with whatever else shows it at that size
+    pub async fn load_texture(&self, url: &str, size: i32) -> Option<gdk::Texture> {
+        let loader = self.clone();
+        let url = url.to_string();
+        shared_texture((url.clone(), size), async move {
+            let pixbuf = loader.load_remote(&url, "jpg", size, size).await?;
+            Some(gdk::Texture::for_pixbuf(&pixbuf))
+        })
+        .await
+    }
+
+    // Four covers in a 2×2 grid, for playlists without a cover of their own
+    pub async fn load_mosaic(&self, urls: &[String], size: i32) -> Option<gdk::Texture> {
+        let loader = self.clone();
+        let urls = urls.to_vec();
+        shared_texture((urls.join(" "), size), async move {
+            loader.load_mosaic_uncached(&urls, size).await
+        })
+        .await
+    }
+
+    async fn load_mosaic_uncached(&self, urls: &[String], size: i32) -> Option<gdk::Texture> {
+        let resource = Self::resource_for(&urls.join(" "), "png");
+        let pixbuf = match self
             .cache
             .read_cache_file(&resource[..], CachePolicy::IgnoreExpiry)
             .await
         {
-            // Write content of cache file to the pixbuf loader if the cache contained something
             Ok(CacheFile::Fresh(buffer, _)) => {
-                loader.write_all(&buffer[..]).ok()?;
+                async_std::task::spawn_blocking(move || Self::decode(&buffer, size, size))
+                    .await?
+                    .into_pixbuf()
             }
-            // Otherwise, get image over HTTP
             _ => {
-                if let Some(mut resp) = Self::get_image(url).await {
-                    let mut buffer = vec![];
-                    // Copy the image to a buffer...
-                    resp.copy_to(&mut buffer).await.ok()?;
-                    // ... copy the buffer to the loader...
-                    loader.write_all(&buffer[..]).ok()?;
-                    // ... but also save that buffer to cache
-                    self.cache
-                        .write_cache_file(&resource[..], &buffer[..], CacheExpiry::Never)
-                        .await
-                        .ok()?;
-                }
+                let pixbuf = self.compose_mosaic(urls, size).await?;
+                let buffer = pixbuf.save_to_bufferv("png", &[]).ok()?;
+                self.cache
+                    .write_cache_file(&resource[..], &buffer[..], CacheExpiry::Never)
+                    .await
+                    .ok()?;
+                pixbuf
             }
         };
 
-        pixbuf_loader.close().ok()?;
-        pixbuf_loader.pixbuf()
+        Some(gdk::Texture::for_pixbuf(&pixbuf))
+    }
+
+    async fn compose_mosaic(&self, urls: &[String], size: i32) -> Option<Pixbuf> {
+        let tile = size / 2;
+        let mosaic = Pixbuf::new(gdk_pixbuf::Colorspace::Rgb, false, 8, tile * 2, tile * 2)?;
+        for (i, url) in urls.iter().take(4).enumerate() {
+            let cover = self.load_remote(url, "jpg", tile, tile).await?;
+            let (x, y) = ((i as i32 % 2) * tile, (i as i32 / 2) * tile);
+            cover.copy_area(0, 0, tile, tile, &mosaic, x, y);
+        }
+        Some(mosaic)
+    }
+
+    // Makes sure a remote image is in the cache, and returns where it lives on disk
+    pub async fn load_remote_to_file(&self, url: &str, ext: &str) -> Option<PathBuf> {
+        let resource = Self::resource_for(url, ext);
+        let path = self.cache.cache_path(&resource[..]);
+        if !path.exists().await {
+            let mut resp = Self::get_image(url).await?;
+            let mut buffer = vec![];
+            resp.copy_to(&mut buffer).await.ok()?;
+            self.cache
+                .write_cache_file(&resource[..], &buffer[..], CacheExpiry::Never)
+                .await
+                .ok()?;
+        }
+        Some(path.into())
+    }
+}
+
+// An image being loaded for a widget, which can be given up on when the widget doesn't need it anymore,
+// like list rows that get recycled as they scroll out of view
+#[derive(Debug, Default)]
+pub struct PendingImage(RefCell<Option<AbortHandle>>);
+
+impl PendingImage {
+    // Cancels whatever was loading before
+    pub fn load<F>(&self, worker: &Worker, url: String, size: i32, on_loaded: F)
+    where
+        F: FnOnce(Option<gdk::Texture>) + 'static,
+    {
+        self.cancel();
+        let (handle, registration) = AbortHandle::new_pair();
+        self.0.replace(Some(handle));
+
+        let task = Abortable::new(
+            async move {
+                let loader = ImageLoader::new();
+                on_loaded(loader.load_texture(&url, size).await);
+            },
+            registration,
+        );
+        worker.send_local_task(task.map(|_| ()));
+    }
+
+    pub fn cancel(&self) {
+        if let Some(handle) = self.0.take() {
+            handle.abort();
+        }
     }
 }
@@ -0,0 +1,184 @@
+// Spotify describes shows and episodes with a bit of HTML, which labels can't show as is.
+// Only what Pango markup can express is kept (paragraphs, lists, emphasis, links), everything else is dropped.
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => {
+            let code = entity.strip_prefix('#')?;
+            let code = match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+// Stray ampersands are left as they are
+fn decode(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        let entity = rest.find(';').map(|end| &rest[..end]);
+        match entity.and_then(|entity| Some((entity, decode_entity(entity)?))) {
+            Some((entity, c)) => {
+                decoded.push(c);
+                rest = &rest[entity.len() + 1..];
+            }
+            None => decoded.push('&'),
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+// Text between tags, decoded and then escaped again for Pango
+fn push_text(markup: &mut String, text: &str) {
+    markup.push_str(&escape(&decode(text)));
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag.find(&format!("{name}="))? + name.len() + 1;
+    let value = &tag[start..];
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let value = &value[1..];
+    value.find(quote).map(|end| &value[..end])
+}
+
+fn break_paragraph(markup: &mut String) {
+    if !markup.is_empty() && !markup.ends_with("\n\n") {
+        markup.push_str(if markup.ends_with('\n') { "\n" } else { "\n\n" });
+    }
+}
+
+pub fn html_to_markup(html: &str) -> String {
+    let mut markup = String::with_capacity(html.len());
+    // The Pango tags left open, so that they all get closed in the right order
+    let mut open: Vec<&'static str> = vec![];
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        push_text(&mut markup, &rest[..start]);
+        // A lone "<", as in "1 < 2"
+        if !rest[start + 1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/') {
+            markup.push_str("&lt;");
+            rest = &rest[start + 1..];
+            continue;
+        }
+        let Some(end) = rest[start..].find('>') else {
+            push_text(&mut markup, &rest[start..]);
+            rest = "";
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        rest = &rest[start + end + 1..];
+
+        let closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let pango = match name.as_str() {
+            "b" | "strong" => Some("b"),
+            "i" | "em" => Some("i"),
+            "u" => Some("u"),
+            "a" => Some("a"),
+            "p" | "div" | "ul" | "ol" => {
+                break_paragraph(&mut markup);
+                None
+            }
+            "br" => {
+                markup.push('\n');
+                None
+            }
+            "li" if !closing => {
+                if !markup.is_empty() && !markup.ends_with('\n') {
+                    markup.push('\n');
+                }
+                markup.push_str("• ");
+                None
+            }
+            _ => None,
+        };
+        let Some(pango) = pango else {
+            continue;
+        };
+
+        if closing {
+            // Tags closed out of order are dropped rather than breaking the markup
+            if open.last() == Some(&pango) {
+                open.pop();
+                markup.push_str(&format!("</{pango}>"));
+            }
+        } else if pango == "a" {
+            // Links that don't go to a web page aren't kept, their text is
+            let href = attribute(tag, "href")
+                .filter(|href| href.starts_with("https://") || href.starts_with("http://"));
+            if let Some(href) = href {
+                markup.push_str(&format!("<a href=\"{}\">", escape(&decode(href))));
+                open.push(pango);
+            }
+        } else {
+            markup.push_str(&format!("<{pango}>"));
+            open.push(pango);
+        }
+    }
+    push_text(&mut markup, rest);
+
+    while let Some(pango) = open.pop() {
+        markup.push_str(&format!("</{pango}>"));
+    }
+    markup.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paragraphs_and_links() {
+        let html = r#"<p>First &amp; <b>bold</b></p><p>See <a href="https://example.com/?a=1&amp;b=2">this</a></p>"#;
+        assert_eq!(
+            html_to_markup(html),
+            "First &amp; <b>bold</b>\n\nSee <a href=\"https://example.com/?a=1&amp;b=2\">this</a>"
+        );
+    }
+
+    #[test]
+    fn test_unsafe_markup_is_dropped() {
+        let html =
+            r#"<span class="x">A</span> <a href="javascript:alert(1)">link</a> 1 < 2 <i>open"#;
+        assert_eq!(html_to_markup(html), "A link 1 &lt; 2 <i>open</i>");
+    }
+
+    #[test]
+    fn test_lists() {
+        let html = "Topics:<ul><li>One</li><li>Two</li></ul>";
+        assert_eq!(html_to_markup(html), "Topics:\n\n• One\n• Two");
+    }
+}
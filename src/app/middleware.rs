@@ -0,0 +1,136 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use super::{AppAction, AppEvent};
+
+// How many actions we keep around for the state inspector
+const MAX_RECORDED_ACTIONS: usize = 500;
+
+// Something that sits between the dispatch loop and the state, and gets to see (and possibly alter)
+// every action on its way in, and every resulting event on its way out
+pub trait Middleware {
+    // Return None to drop the action altogether
+    fn before(&mut self, action: AppAction) -> Option<AppAction> {
+        Some(action)
+    }
+
+    fn after(&mut self, _events: &[AppEvent]) {}
+}
+
+// Middlewares are applied in the order they were added
+#[derive(Default)]
+pub struct MiddlewareChain {
+    middlewares: Vec<Box<dyn Middleware>>,
+}
+
+impl MiddlewareChain {
+    pub fn push(&mut self, middleware: Box<dyn Middleware>) {
+        self.middlewares.push(middleware);
+    }
+
+    // Runs the action through the chain, then through the update function if it survived
+    pub fn process<F>(&mut self, action: AppAction, update: F) -> Vec<AppEvent>
+    where
+        F: FnOnce(AppAction) -> Vec<AppEvent>,
+    {
+        let action = self
+            .middlewares
+            .iter_mut()
+            .try_fold(action, |action, middleware| middleware.before(action));
+        let Some(action) = action else {
+            return vec![];
+        };
+
+        let events = update(action);
+        for middleware in self.middlewares.iter_mut() {
+            middleware.after(&events);
+        }
+        events
+    }
+}
+
+// Traces every action and event, handy when debugging
+pub struct LoggingMiddleware;
+
+impl Middleware for LoggingMiddleware {
+    fn before(&mut self, action: AppAction) -> Option<AppAction> {
+        trace!("action: {:?}", &action);
+        Some(action)
+    }
+
+    fn after(&mut self, events: &[AppEvent]) {
+        for event in events {
+            trace!("event: {:?}", event);
+        }
+    }
+}
+
+// The most recent actions, oldest first
+pub type ActionLog = Rc<RefCell<VecDeque<(SystemTime, AppAction)>>>;
+
+// Keeps the most recent actions in memory, so that they can be inspected (and replayed)
+pub struct RecordingMiddleware {
+    log: ActionLog,
+}
+
+impl RecordingMiddleware {
+    pub fn new(log: ActionLog) -> Self {
+        Self { log }
+    }
+}
+
+impl Middleware for RecordingMiddleware {
+    fn before(&mut self, action: AppAction) -> Option<AppAction> {
+        // Those carry credentials, which we don't want to show up in an exported trace
+        if matches!(action, AppAction::LoginAction(_)) {
+            return Some(action);
+        }
+        let mut log = self.log.borrow_mut();
+        if log.len() >= MAX_RECORDED_ACTIONS {
+            log.pop_front();
+        }
+        log.push_back((SystemTime::now(), action.clone()));
+        Some(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct DropRaise;
+
+    impl Middleware for DropRaise {
+        fn before(&mut self, action: AppAction) -> Option<AppAction> {
+            Some(action).filter(|a| !matches!(a, AppAction::Raise))
+        }
+    }
+
+    struct CountEvents(Rc<RefCell<usize>>);
+
+    impl Middleware for CountEvents {
+        fn after(&mut self, events: &[AppEvent]) {
+            *self.0.borrow_mut() += events.len();
+        }
+    }
+
+    #[test]
+    fn test_chain() {
+        let count = Rc::new(RefCell::new(0));
+        let mut chain = MiddlewareChain::default();
+        chain.push(Box::new(DropRaise));
+        chain.push(Box::new(CountEvents(Rc::clone(&count))));
+
+        let events = chain.process(AppAction::Raise, |_| vec![AppEvent::Raised]);
+        assert!(events.is_empty());
+        assert_eq!(*count.borrow(), 0);
+
+        let events = chain.process(AppAction::ViewNowPlaying, |_| {
+            vec![AppEvent::NowPlayingShown]
+        });
+        assert_eq!(events.len(), 1);
+        assert_eq!(*count.borrow(), 1);
+    }
+}
@@ -5,13 +5,18 @@ use std::rc::Rc;
 use std::sync::Arc;
 
 pub mod dispatch;
-pub use dispatch::{ActionDispatcher, ActionDispatcherImpl, DispatchLoop, Worker};
+pub use dispatch::{
+    ActionDispatcher, ActionDispatcherImpl, DispatchLoop, Retry, ScopedActionDispatcher, Worker,
+};
 
 pub mod components;
 use components::*;
 
 pub mod models;
 
+pub mod middleware;
+use middleware::{ActionLog, LoggingMiddleware, MiddlewareChain, RecordingMiddleware};
+
 mod list_store;
 pub use list_store::*;
 
@@ -22,7 +27,11 @@ mod batch_loader;
 pub use batch_loader::*;
 
 pub mod credentials;
+pub mod episode_progress;
 pub mod loader;
+pub mod markup;
+pub mod offline_tracks;
+pub mod play_history;
 
 pub mod rng;
 pub use rng::LazyRandomIndex;
@@ -34,11 +43,17 @@ pub struct App {
     builder: gtk::Builder,
     // All the "components" that will be notified of things happening throughout the app
     components: Vec<Box<dyn EventListener>>,
+    // Get to look at all actions before they reach the model, and at the events they produce
+    middlewares: MiddlewareChain,
+    // Recent actions, only recorded when debugging
+    action_log: ActionLog,
     // Holds the app state
     model: Rc<AppModel>,
     // Allows sending actions that are handled by the model above
     sender: UnboundedSender<AppAction>,
     worker: Worker,
+    // Started without a window, which only shows up once the app is activated
+    headless: bool,
 }
 
 impl App {
@@ -47,8 +62,9 @@ impl App {
         builder: gtk::Builder,
         sender: UnboundedSender<AppAction>,
         worker: Worker,
+        headless: bool,
     ) -> Self {
-        let state = AppState::new();
+        let state = AppState::new(settings.clone());
         let spotify_client = Arc::new(CachedSpotifyClient::new());
         let model = Rc::new(AppModel::new(state, spotify_client));
 
@@ -61,15 +77,65 @@ impl App {
                 sender.clone(),
             ),
             App::make_dbus(Rc::clone(&model), sender.clone()),
+            App::make_remote_control(Rc::clone(&model), sender.clone()),
+            App::make_desktop_notifier(Rc::clone(&model), worker.clone()),
+            App::make_scrobbling_notifier(Rc::clone(&model), worker.clone()),
+            App::make_up_next_notifier(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+            ),
+            App::make_network_watcher(Box::new(ActionDispatcherImpl::new(
+                sender.clone(),
+                worker.clone(),
+            ))),
+            App::make_episode_progress_notifier(Rc::clone(&model)),
+            App::make_play_history_notifier(Rc::clone(&model), worker.clone()),
+            App::make_home_snapshots(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+                worker.clone(),
+            ),
+            App::make_undo(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+            ),
+            App::make_navigation_snapshot(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+            ),
+            App::make_track_player(
+                Rc::clone(&model),
+                Box::new(ActionDispatcherImpl::new(sender.clone(), worker.clone())),
+            ),
+            App::make_playback_query(Rc::clone(&model)),
         ];
 
+        // Cross-cutting concerns that don't need to be components
+        let mut middlewares = MiddlewareChain::default();
+        middlewares.push(Box::new(LoggingMiddleware));
+        let action_log = ActionLog::default();
+        if crate::logging::is_capturing() {
+            middlewares.push(Box::new(RecordingMiddleware::new(Rc::clone(&action_log))));
+        }
+
+        // Keep the caches under the limit set in the preferences
+        crate::api::limit_caches(settings.cache_max_size);
+
+        // Which tracks can still be played offline
+        worker.send_task(offline_tracks::load());
+        // When tracks were last played, for smart shuffle
+        worker.send_task(play_history::load());
+
         Self {
             settings,
             builder,
             components,
+            middlewares,
+            action_log,
             model,
             sender,
             worker,
+            headless,
         }
     }
 
@@ -88,7 +154,7 @@ impl App {
 
         // All components that will be available initially
         let mut components: Vec<Box<dyn EventListener>> = vec![
-            App::make_window(&self.settings, builder, Rc::clone(model)),
+            App::make_window(&self.settings, builder, Rc::clone(model), self.headless),
             App::make_selection_toolbar(builder, Rc::clone(model), dispatcher.box_clone()),
             App::make_playback(
                 builder,
@@ -104,8 +170,22 @@ impl App {
                 worker.clone(),
             ),
             App::make_search_button(builder, dispatcher.box_clone()),
-            App::make_user_menu(builder, Rc::clone(model), dispatcher),
-            App::make_notification(builder),
+            App::make_friend_activity(builder, Rc::clone(model), dispatcher.box_clone()),
+            App::make_car_mode(
+                builder,
+                Rc::clone(model),
+                dispatcher.box_clone(),
+                worker.clone(),
+            ),
+            App::make_user_menu(
+                builder,
+                Rc::clone(model),
+                dispatcher.box_clone(),
+                worker.clone(),
+                &self.action_log,
+            ),
+            App::make_library_import(builder, Rc::clone(model), dispatcher.box_clone()),
+            App::make_notification(builder, dispatcher),
         ];
 
         self.components.append(&mut components);
@@ -137,13 +217,105 @@ impl App {
         Box::new(crate::dbus::start_dbus_server(app_model, sender))
     }
 
+    // The optional HTTP server for home automation setups, see the remote control preferences
+    fn make_remote_control(
+        app_model: Rc<AppModel>,
+        sender: UnboundedSender<AppAction>,
+    ) -> Box<impl EventListener> {
+        Box::new(crate::remote::start_remote_server(app_model, sender))
+    }
+
+    // A component that sends desktop notifications
+    fn make_desktop_notifier(app_model: Rc<AppModel>, worker: Worker) -> Box<DesktopNotifier> {
+        Box::new(DesktopNotifier::new(app_model, worker))
+    }
+
+    // A component that reports what is being listened to to scrobbling services
+    fn make_scrobbling_notifier(
+        app_model: Rc<AppModel>,
+        worker: Worker,
+    ) -> Box<ScrobblingNotifier> {
+        Box::new(ScrobblingNotifier::new(app_model, worker))
+    }
+
+    // A component that announces the next track shortly before the current one ends
+    fn make_up_next_notifier(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<UpNextNotifier> {
+        Box::new(UpNextNotifier::new(app_model, dispatcher))
+    }
+
+    // A component that follows the network connectivity
+    fn make_network_watcher(dispatcher: Box<dyn ActionDispatcher>) -> Box<NetworkWatcher> {
+        Box::new(NetworkWatcher::new(dispatcher))
+    }
+
+    // A component that remembers where podcast episodes were left off
+    fn make_episode_progress_notifier(app_model: Rc<AppModel>) -> Box<EpisodeProgressNotifier> {
+        Box::new(EpisodeProgressNotifier::new(app_model))
+    }
+
+    // A component that remembers which tracks were played recently, for smart shuffle
+    fn make_play_history_notifier(
+        app_model: Rc<AppModel>,
+        worker: Worker,
+    ) -> Box<PlayHistoryNotifier> {
+        Box::new(PlayHistoryNotifier::new(app_model, worker))
+    }
+
+    // A component that keeps the home screen around between runs
+    fn make_home_snapshots(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        worker: Worker,
+    ) -> Box<HomeSnapshots> {
+        Box::new(HomeSnapshots::new(app_model, dispatcher, worker))
+    }
+
+    // A component that reverts destructive actions on demand
+    fn make_undo(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Box<Undo> {
+        Box::new(Undo::new(app_model, dispatcher))
+    }
+
+    // A component that reopens the screens that were open last time
+    fn make_navigation_snapshot(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<NavigationSnapshot> {
+        Box::new(NavigationSnapshot::new(app_model, dispatcher))
+    }
+
+    // A component that plays the tracks opened from links
+    fn make_track_player(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<TrackPlayer> {
+        Box::new(TrackPlayer::new(app_model, dispatcher))
+    }
+
+    // A component that lets other instances know what's playing
+    fn make_playback_query(app_model: Rc<AppModel>) -> Box<PlaybackQuery> {
+        Box::new(PlaybackQuery::new(app_model))
+    }
+
     fn make_window(
         settings: &SpotSettings,
         builder: &gtk::Builder,
         app_model: Rc<AppModel>,
+        headless: bool,
     ) -> Box<impl EventListener> {
         let window: libadwaita::ApplicationWindow = builder.object("window").unwrap();
-        Box::new(MainWindow::new(settings.window.clone(), app_model, window))
+        let shortcuts: gtk::ShortcutController = builder.object("shortcuts").unwrap();
+        let offline_banner: gtk::Revealer = builder.object("offline_banner").unwrap();
+        Box::new(MainWindow::new(
+            settings.window.clone(),
+            app_model,
+            window,
+            shortcuts,
+            offline_banner,
+            headless,
+        ))
     }
 
     fn make_navigation(
@@ -216,40 +388,98 @@ impl App {
         Box::new(SearchButton::new(model, search_button))
     }
 
+    fn make_friend_activity(
+        builder: &gtk::Builder,
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<FriendActivityPanel> {
+        let widget: FriendActivityWidget = builder.object("friend_activity").unwrap();
+        let model = FriendActivityModel::new(app_model, dispatcher);
+        Box::new(FriendActivityPanel::new(widget, model))
+    }
+
+    fn make_car_mode(
+        builder: &gtk::Builder,
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        worker: Worker,
+    ) -> Box<CarModePanel> {
+        let widget: CarModeWidget = builder.object("car_mode").unwrap();
+        let layouts: gtk::Stack = builder.object("layouts").unwrap();
+        let model = CarModeModel::new(app_model, dispatcher);
+        Box::new(CarModePanel::new(widget, layouts, model, worker))
+    }
+
     fn make_user_menu(
         builder: &gtk::Builder,
         app_model: Rc<AppModel>,
         dispatcher: Box<dyn ActionDispatcher>,
+        worker: Worker,
+        action_log: &ActionLog,
     ) -> Box<UserMenu> {
         let parent: gtk::Window = builder.object("window").unwrap();
-        let settings_model = SettingsModel::new(app_model.clone(), dispatcher.box_clone());
-        let settings = Settings::new(parent, settings_model);
+        let settings_model = SettingsModel::new(app_model.clone(), dispatcher.box_clone(), worker);
+        let settings = Settings::new(parent.clone(), settings_model);
+        let logs = Logs::new(parent.clone());
+        let inspector_model = InspectorModel::new(
+            Rc::clone(&app_model),
+            dispatcher.box_clone(),
+            Rc::clone(action_log),
+        );
+        let inspector = Inspector::new(parent, inspector_model);
 
         let button: gtk::MenuButton = builder.object("user").unwrap();
         let about: libadwaita::AboutWindow = builder.object("about").unwrap();
         let model = UserMenuModel::new(app_model, dispatcher);
-        let user_menu = UserMenu::new(button, settings, about, model);
+        let user_menu = UserMenu::new(button, settings, logs, inspector, about, model);
         Box::new(user_menu)
     }
 
-    fn make_notification(builder: &gtk::Builder) -> Box<Notification> {
+    // A component that imports library backups dropped on the window
+    fn make_library_import(
+        builder: &gtk::Builder,
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<LibraryImport> {
+        let window: gtk::Window = builder.object("window").unwrap();
+        Box::new(LibraryImport::new(app_model, dispatcher, window))
+    }
+
+    fn make_notification(
+        builder: &gtk::Builder,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<Notification> {
         let toast_overlay: libadwaita::ToastOverlay = builder.object("main").unwrap();
-        Box::new(Notification::new(toast_overlay))
+        Box::new(Notification::new(toast_overlay, dispatcher))
     }
 
     // Main handler called in a loop
-    fn handle(&mut self, action: AppAction) {
-        let starting = matches!(&action, &AppAction::Start);
+    fn handle(&mut self, actions: Vec<AppAction>) {
+        let mut starting = false;
+        let mut events = vec![];
+
+        for action in actions {
+            let is_start = matches!(&action, &AppAction::Start);
 
-        // Update the state based on an incoming action
-        // and obtain events representing what that mutation entailed...
-        let events = self.model.update_state(action);
+            // Update the state based on an incoming action (if no middleware dropped it)
+            // and obtain events representing what that mutation entailed...
+            let model = &self.model;
+            let mut new_events = self
+                .middlewares
+                .process(action, |action| model.update_state(action));
+
+            starting |= is_start && !new_events.is_empty();
+            events.append(&mut new_events);
+        }
 
         // (AppAction::Start is special and is used to setup the initial components)
-        if !events.is_empty() && starting {
+        if starting {
             self.add_ui_components();
         }
 
+        // Several pages appended in a row only need to be reported once
+        let events = AppEvent::coalesce(events);
+
         // ...and notify every component that we know.
         // They'll be responsible for passing down these events, if they feel like it.
         for event in events.iter() {
@@ -263,8 +493,8 @@ impl App {
     pub async fn attach(mut self, dispatch_loop: DispatchLoop) {
         let app = &mut self;
         dispatch_loop
-            .attach(move |action| {
-                app.handle(action);
+            .attach(move |actions| {
+                app.handle(actions);
             })
             .await;
     }
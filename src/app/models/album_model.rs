@@ -4,6 +4,8 @@ use gio::prelude::*;
 use glib::subclass::prelude::*;
 use glib::Properties;
 
+use super::ReleaseDate;
+
 // UI model!
 // Despite the name, it can represent a playlist as well
 glib::wrapper! {
@@ -14,19 +16,30 @@ impl AlbumModel {
     pub fn new(
         artist: &String,
         album: &String,
-        year: Option<u32>,
+        release_date: Option<ReleaseDate>,
         cover: Option<&String>,
         uri: &String,
     ) -> AlbumModel {
-        let year = &year.unwrap_or(0);
         glib::Object::builder()
             .property("artist", artist)
             .property("album", album)
-            .property("year", year)
+            .property("year", release_date.map(|d| d.year).unwrap_or(0))
+            .property("month", release_date.and_then(|d| d.month).unwrap_or(0))
+            .property("day", release_date.and_then(|d| d.day).unwrap_or(0))
             .property("cover", &cover)
             .property("uri", uri)
             .build()
     }
+
+    pub fn release_date(&self) -> Option<ReleaseDate> {
+        Some(self.year())
+            .filter(|y| *y > 0)
+            .map(|year| ReleaseDate {
+                year,
+                month: Some(self.month()).filter(|m| *m > 0),
+                day: Some(self.day()).filter(|d| *d > 0),
+            })
+    }
 }
 
 mod imp {
@@ -42,8 +55,16 @@ mod imp {
         album: RefCell<String>,
         #[property(get, set)]
         artist: RefCell<String>,
+        // The first artist of an album, or the owner of a playlist
+        #[property(get, set)]
+        artist_id: RefCell<String>,
         #[property(get, set)]
         year: Cell<u32>,
+        // 0 when unknown, like the year
+        #[property(get, set)]
+        month: Cell<u32>,
+        #[property(get, set)]
+        day: Cell<u32>,
         #[property(get, set)]
         cover: RefCell<Option<String>>,
         #[property(get, set)]
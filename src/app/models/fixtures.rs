@@ -0,0 +1,21 @@
+use super::{AlbumRef, SongDescription};
+
+// A song that only its id tells apart from the others, for tests
+pub fn song(id: &str) -> SongDescription {
+    SongDescription {
+        id: id.to_string(),
+        uri: "".to_string(),
+        title: "Title".to_string(),
+        artists: vec![],
+        album: AlbumRef {
+            id: "".into(),
+            name: "".into(),
+        },
+        duration: 1000,
+        art: None,
+        track_number: None,
+        restriction: None,
+        linked_from: None,
+        added_at: None,
+    }
+}
@@ -0,0 +1,78 @@
+// Artist names, album titles and cover URLs repeat across every song of a library: songs share a single copy
+use serde::{Deserialize, Deserializer};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+lazy_static! {
+    static ref POOL: Mutex<Pool> = Mutex::new(Pool::default());
+}
+
+#[derive(Default)]
+struct Pool {
+    strings: HashSet<Arc<str>>,
+    // Size of the pool after it was last pruned
+    pruned_len: usize,
+}
+
+impl Pool {
+    fn get(&mut self, s: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(s) {
+            return Arc::clone(interned);
+        }
+        // Strings only the pool still holds are dropped once it has doubled in size
+        if self.strings.len() > 2 * self.pruned_len.max(512) {
+            self.strings
+                .retain(|interned| Arc::strong_count(interned) > 1);
+            self.pruned_len = self.strings.len();
+        }
+        let interned: Arc<str> = Arc::from(s);
+        self.strings.insert(Arc::clone(&interned));
+        interned
+    }
+}
+
+pub fn intern(s: &str) -> Arc<str> {
+    POOL.lock().unwrap().get(s)
+}
+
+// For use with #[serde(deserialize_with)], so that deserialized songs share strings as well
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Arc<str>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    Ok(intern(&s))
+}
+
+pub fn deserialize_opt<'de, D>(deserializer: D) -> Result<Option<Arc<str>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = Option::<String>::deserialize(deserializer)?;
+    Ok(s.as_deref().map(intern))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_intern_shares_strings() {
+        let a = intern("Some Artist");
+        let b = intern(&String::from("Some Artist"));
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(&*a, "Some Artist");
+    }
+
+    #[test]
+    fn test_pool_prunes_unused_strings() {
+        let mut pool = Pool::default();
+        let kept = pool.get("kept");
+        for i in 0..2000 {
+            pool.get(&i.to_string());
+        }
+        assert!(pool.strings.len() < 2000);
+        assert!(Arc::ptr_eq(&kept, &pool.get("kept")));
+    }
+}
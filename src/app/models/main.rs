@@ -1,12 +1,16 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::{
     hash::{Hash, Hasher},
     str::FromStr,
+    sync::Arc,
 };
 
+use super::intern::intern;
 use crate::app::SongsSource;
 
 // A batch of whatever
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Batch {
     // What offset does the batch start at
     pub offset: usize,
@@ -43,22 +47,26 @@ impl Batch {
 
 // "Something"Ref models usually boil down to an ID/url + a display name
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct UserRef {
     pub id: String,
     pub display_name: String,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ArtistRef {
-    pub id: String,
-    pub name: String,
+    #[serde(deserialize_with = "super::intern::deserialize")]
+    pub id: Arc<str>,
+    #[serde(deserialize_with = "super::intern::deserialize")]
+    pub name: Arc<str>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AlbumRef {
-    pub id: String,
-    pub name: String,
+    #[serde(deserialize_with = "super::intern::deserialize")]
+    pub id: Arc<str>,
+    #[serde(deserialize_with = "super::intern::deserialize")]
+    pub name: Arc<str>,
 }
 
 #[derive(Clone, Debug)]
@@ -67,12 +75,41 @@ pub struct SearchResults {
     pub artists: Vec<ArtistSummary>,
 }
 
-#[derive(Clone, Debug)]
+// When something came out, only as precise as Spotify knows it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ReleaseDate {
+    pub year: u32,
+    pub month: Option<u32>,
+    pub day: Option<u32>,
+}
+
+impl ReleaseDate {
+    // A date such as "1973-03-01", along with its precision: "year", "month" or "day"
+    pub fn parse(date: &str, precision: Option<&str>) -> Option<Self> {
+        let mut parts = date.split('-').map(|part| u32::from_str(part).ok());
+        // Unknown dates come as "0000"
+        let year = parts.next().flatten().filter(|y| *y > 0)?;
+        let month = parts
+            .next()
+            .flatten()
+            .filter(|m| (1..=12).contains(m) && precision != Some("year"));
+        let day = parts
+            .next()
+            .flatten()
+            .filter(|d| month.is_some() && (1..=31).contains(d) && precision != Some("month"));
+        Some(Self { year, month, day })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AlbumDescription {
     pub id: String,
     pub title: String,
     pub artists: Vec<ArtistRef>,
-    pub release_date: Option<String>,
+    pub release_date: Option<ReleaseDate>,
+    // From 0 to 100, not known for every album
+    #[serde(default)]
+    pub popularity: Option<u32>,
     pub art: Option<String>,
     pub songs: SongBatch,
     pub is_liked: bool,
@@ -88,10 +125,7 @@ impl AlbumDescription {
     }
 
     pub fn year(&self) -> Option<u32> {
-        self.release_date
-            .as_ref()
-            .and_then(|date| date.split('-').next())
-            .and_then(|y| u32::from_str(y).ok())
+        self.release_date.map(|date| date.year)
     }
 }
 
@@ -108,13 +142,135 @@ pub struct AlbumReleaseDetails {
     pub total_tracks: usize,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PlaylistDescription {
     pub id: String,
     pub title: String,
     pub art: Option<String>,
     pub songs: SongBatch,
     pub owner: UserRef,
+    // Unknown for playlists that weren't fetched from the API
+    #[serde(default)]
+    pub public: Option<bool>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EpisodeDescription {
+    pub id: String,
+    pub uri: String,
+    pub title: String,
+    // Pango markup
+    pub description: String,
+    pub release_date: Option<String>,
+    pub duration: u32,
+    pub art: Option<String>,
+    // Where Spotify says the user stopped listening (in milliseconds)
+    pub resume_position: Option<u32>,
+}
+
+// A part of an episode, as announced in its description
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chapter {
+    // In milliseconds
+    pub start: u32,
+    // Pango markup
+    pub title: String,
+}
+
+lazy_static! {
+    static ref TIMESTAMP: Regex = Regex::new(r"\b(?:(\d{1,2}):)?(\d{1,2}):([0-5]\d)\b").unwrap();
+    static ref TAG: Regex = Regex::new(r"<[^>]*>").unwrap();
+}
+
+impl EpisodeDescription {
+    // Lines of the description starting with a timestamp, e.g. "12:34 - The interview"
+    pub fn chapters(&self) -> Vec<Chapter> {
+        let trim = |text: &str| {
+            text.trim_matches(|c: char| c.is_whitespace() || "-–—:|()[]•".contains(c))
+                .to_string()
+        };
+        let mut chapters: Vec<Chapter> = vec![];
+        for line in self.description.lines() {
+            let line = TAG.replace_all(line, "");
+            let Some(captures) = TIMESTAMP.captures(&line) else {
+                continue;
+            };
+            let timestamp = captures.get(0).unwrap();
+            if !trim(&line[..timestamp.start()]).is_empty() {
+                continue;
+            }
+            let number = |i| {
+                captures
+                    .get(i)
+                    .and_then(|m| m.as_str().parse::<u32>().ok())
+                    .unwrap_or(0)
+            };
+            let start = ((number(1) * 60 + number(2)) * 60 + number(3)) * 1000;
+            // Chapters come in order, other timestamps are mentioned in passing
+            let in_order = chapters.last().map(|c| c.start < start).unwrap_or(true);
+            if in_order && start < self.duration {
+                chapters.push(Chapter {
+                    start,
+                    title: trim(&line[timestamp.end()..]),
+                });
+            }
+        }
+        // A lone timestamp isn't much of a table of contents
+        if chapters.len() < 2 {
+            chapters.clear();
+        }
+        chapters
+    }
+}
+
+// A podcast
+#[derive(Clone, Debug)]
+pub struct ShowDescription {
+    pub id: String,
+    pub title: String,
+    pub publisher: String,
+    // Pango markup
+    pub description: String,
+    pub art: Option<String>,
+    pub episodes: Vec<EpisodeDescription>,
+    // The first page of episodes, if any
+    pub episodes_batch: Option<Batch>,
+}
+
+// A page of episodes; those missing from it still count in the batch
+#[derive(Clone, Debug)]
+pub struct EpisodeBatch {
+    pub episodes: Vec<EpisodeDescription>,
+    pub batch: Batch,
+}
+
+impl ShowDescription {
+    // Episodes go through the player as songs, with the show in place of the artist and album
+    pub fn episode_song(&self, episode: &EpisodeDescription) -> SongDescription {
+        SongDescription {
+            id: episode.id.clone(),
+            track_number: None,
+            uri: episode.uri.clone(),
+            title: episode.title.clone(),
+            artists: vec![ArtistRef {
+                id: intern(&self.id),
+                name: intern(&self.publisher),
+            }],
+            album: AlbumRef {
+                id: intern(&self.id),
+                name: intern(&self.title),
+            },
+            duration: episode.duration,
+            art: episode
+                .art
+                .as_ref()
+                .or(self.art.as_ref())
+                .map(|art| intern(art)),
+            restriction: None,
+            linked_from: None,
+            added_at: None,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -132,13 +288,39 @@ pub struct ConnectDevice {
     pub kind: ConnectDeviceKind,
 }
 
+// What a followed user is listening to, or last listened to
+#[derive(Clone, Debug)]
+pub struct FriendActivity {
+    pub user_name: String,
+    pub track_uri: String,
+    pub track_title: String,
+    pub artist_name: String,
+    // The playlist or album the track is played from
+    pub context_uri: Option<String>,
+    pub context_name: Option<String>,
+    // Milliseconds since the epoch
+    pub timestamp: u64,
+}
+
 #[derive(Clone, Debug)]
 pub struct PlaylistSummary {
     pub id: String,
     pub title: String,
 }
 
-#[derive(Clone, Debug)]
+// Why Spotify won't let a song be played
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Restriction {
+    // Not available in the user's country
+    Market,
+    // Not available with the user's subscription
+    Product,
+    // The user turned off explicit content
+    Explicit,
+    Other,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SongDescription {
     pub id: String,
     pub track_number: Option<u32>,
@@ -147,7 +329,16 @@ pub struct SongDescription {
     pub artists: Vec<ArtistRef>,
     pub album: AlbumRef,
     pub duration: u32,
-    pub art: Option<String>,
+    #[serde(default, deserialize_with = "super::intern::deserialize_opt")]
+    pub art: Option<Arc<str>>,
+    #[serde(default)]
+    pub restriction: Option<Restriction>,
+    // Id of the track originally requested, if Spotify relinked it to another one for our market
+    #[serde(default)]
+    pub linked_from: Option<String>,
+    // When the song was saved to the library (ISO 8601), for liked songs only
+    #[serde(default)]
+    pub added_at: Option<String>,
 }
 
 impl SongDescription {
@@ -158,6 +349,25 @@ impl SongDescription {
             .collect::<Vec<String>>()
             .join(", ")
     }
+
+    // Podcast episodes get queued like songs
+    pub fn is_episode(&self) -> bool {
+        self.uri.starts_with("spotify:episode:")
+    }
+
+    pub fn is_playable(&self) -> bool {
+        self.restriction.is_none()
+    }
+
+    // Whether the id refers to this song, relinked or not
+    pub fn has_id(&self, id: &str) -> bool {
+        self.id == id || self.linked_from.as_deref() == Some(id)
+    }
+
+    // The id to use when saving or sharing the song, which is the one the catalog knows
+    pub fn original_id(&self) -> &str {
+        self.linked_from.as_deref().unwrap_or(&self.id)
+    }
 }
 
 impl Hash for SongDescription {
@@ -173,7 +383,7 @@ pub struct SongState {
 }
 
 // A batch of SONGS
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SongBatch {
     pub songs: Vec<SongDescription>,
     pub batch: Batch,
@@ -243,6 +453,7 @@ pub struct ArtistSummary {
 pub struct UserDescription {
     pub id: String,
     pub name: String,
+    pub followers: Option<u32>,
     pub playlists: Vec<PlaylistDescription>,
 }
 
@@ -280,22 +491,7 @@ impl Default for ConnectPlayerState {
 mod tests {
 
     use super::*;
-
-    fn song(id: &str) -> SongDescription {
-        SongDescription {
-            id: id.to_string(),
-            uri: "".to_string(),
-            title: "Title".to_string(),
-            artists: vec![],
-            album: AlbumRef {
-                id: "".to_string(),
-                name: "".to_string(),
-            },
-            duration: 1000,
-            art: None,
-            track_number: None,
-        }
-    }
+    use crate::app::models::fixtures::song;
 
     #[test]
     fn resize_batch() {
@@ -309,4 +505,68 @@ mod tests {
         assert_eq!(&batches.get(0).unwrap().songs.get(0).unwrap().id, "1");
         assert_eq!(&batches.get(1).unwrap().songs.get(0).unwrap().id, "3");
     }
+
+    #[test]
+    fn test_song_without_art() {
+        let json = r#"{"id":"1","track_number":null,"uri":"","title":"Title","artists":[],"album":{"id":"","name":""},"duration":1000}"#;
+        let song: SongDescription = serde_json::from_str(json).unwrap();
+        assert!(song.art.is_none());
+    }
+
+    #[test]
+    fn test_release_date_precision() {
+        let date = |year, month, day| Some(ReleaseDate { year, month, day });
+        assert_eq!(
+            ReleaseDate::parse("1973", Some("year")),
+            date(1973, None, None)
+        );
+        assert_eq!(
+            ReleaseDate::parse("1973-03", Some("month")),
+            date(1973, Some(3), None)
+        );
+        assert_eq!(
+            ReleaseDate::parse("1973-03-01", Some("day")),
+            date(1973, Some(3), Some(1))
+        );
+        // The date is cut down to its precision
+        assert_eq!(
+            ReleaseDate::parse("1973-01-01", Some("year")),
+            date(1973, None, None)
+        );
+        assert_eq!(ReleaseDate::parse("0000", Some("year")), None);
+    }
+
+    #[test]
+    fn test_episode_chapters() {
+        let episode = |description: &str| EpisodeDescription {
+            id: "".to_string(),
+            uri: "".to_string(),
+            title: "".to_string(),
+            description: description.to_string(),
+            release_date: None,
+            duration: 2 * 3600 * 1000,
+            art: None,
+            resume_position: None,
+        };
+        let chapter = |start, title: &str| Chapter {
+            start,
+            title: title.to_string(),
+        };
+
+        let chapters = episode(
+            "Topics:\n\n• 0:00 Intro\n• <b>(12:34)</b> The interview\n1:02:03 - Q&amp;A\nSee you at 10:00",
+        )
+        .chapters();
+        assert_eq!(
+            chapters,
+            vec![
+                chapter(0, "Intro"),
+                chapter(754_000, "The interview"),
+                chapter(3_723_000, "Q&amp;A"),
+            ]
+        );
+
+        assert_eq!(episode("0:00 Intro").chapters(), vec![]);
+        assert_eq!(episode("0:00 Intro\n3:00:00 Too late").chapters(), vec![]);
+    }
 }
@@ -2,6 +2,12 @@
 mod main;
 pub use main::*;
 
+mod intern;
+pub use intern::intern;
+
+#[cfg(test)]
+pub mod fixtures;
+
 // UI models (GObject)
 mod songs;
 pub use songs::*;
@@ -14,13 +20,17 @@ pub use artist_model::*;
 
 impl From<&AlbumDescription> for AlbumModel {
     fn from(album: &AlbumDescription) -> Self {
-        AlbumModel::new(
+        let model = AlbumModel::new(
             &album.artists_name(),
             &album.title,
-            album.year(),
+            album.release_date,
             album.art.as_ref(),
             &album.id,
-        )
+        );
+        if let Some(artist) = album.artists.first() {
+            model.set_artist_id(artist.id.to_string());
+        }
+        model
     }
 }
 
@@ -32,14 +42,16 @@ impl From<AlbumDescription> for AlbumModel {
 
 impl From<&PlaylistDescription> for AlbumModel {
     fn from(playlist: &PlaylistDescription) -> Self {
-        AlbumModel::new(
+        let model = AlbumModel::new(
             &playlist.owner.display_name,
             &playlist.title,
             // Playlists do not have their released date since they are expected to be updated anytime.
             None,
             playlist.art.as_ref(),
             &playlist.id,
-        )
+        );
+        model.set_artist_id(playlist.owner.id.clone());
+        model
     }
 }
 
@@ -165,6 +165,11 @@ impl SongListModel {
         SongListModelPending::new(Some(change), self)
     }
 
+    pub fn insert(&mut self, songs: Vec<(usize, SongDescription)>) -> SongListModelPending {
+        let change = self.inner_mut().insert(songs);
+        SongListModelPending::new(Some(change), self)
+    }
+
     pub fn move_down(&mut self, a: usize) -> SongListModelPending {
         let swap = self.inner_mut().swap(a + 1, a);
         SongListModelPending::new(swap, self)
@@ -179,6 +184,12 @@ impl SongListModel {
         let removed = self.inner_mut().clear();
         SongListModelPending::new(Some(removed), self)
     }
+
+    // Have the views bind every row again, for changes that don't come from the songs themselves
+    pub fn refresh(&self) {
+        let len = self.partial_len() as i32;
+        self.notify_changes(Some(ListRangeUpdate(0, len, len)));
+    }
 }
 
 mod imp {
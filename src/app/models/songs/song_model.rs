@@ -245,6 +245,7 @@ mod imp {
                     .as_ref()
                     .expect("song set at constructor")
                     .art
+                    .as_deref()
                     .to_value(),
                 "playing" => self.state.get().is_playing.to_value(),
                 "selected" => self.state.get().is_selected.to_value(),
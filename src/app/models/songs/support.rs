@@ -190,10 +190,17 @@ impl SongList {
             .skip(i % batch_size)
     }
 
-    // Find the position of a song in the list
+    // Find the position of a song in the list, which might be known under the id it was relinked from
     pub fn find_index(&self, song_id: &str) -> Option<usize> {
+        let indexed_songs = &self.indexed_songs;
         self.iter_ids_from(0)
-            .find(|(_, id)| &id[..] == song_id)
+            .find(|(_, id)| {
+                &id[..] == song_id
+                    || indexed_songs
+                        .get(*id)
+                        .map(|s| s.description().has_id(song_id))
+                        .unwrap_or(false)
+            })
             .map(|(pos, _)| pos)
     }
 
@@ -249,6 +256,34 @@ impl SongList {
         ListRangeUpdate(0, len as i32, self.total_loaded as i32)
     }
 
+    // Puts songs (back) at the given positions
+    pub fn insert(&mut self, mut songs: Vec<(usize, SongDescription)>) -> ListRangeUpdate {
+        let len = self.total_loaded;
+        let mut ids: Vec<String> = self.iter_ids_from(0).map(|(_, id)| id.clone()).collect();
+        // Songs that are still around aren't inserted twice
+        songs.retain(|(_, song)| !ids.contains(&song.id));
+        let inserted = songs.len();
+
+        // Positions refer to the resulting list, so we go in order
+        songs.sort_by_key(|(position, _)| *position);
+        for (position, song) in songs {
+            ids.insert(usize::min(position, ids.len()), song.id.clone());
+            self.indexed_songs
+                .insert(song.id.clone(), SongModel::new(song));
+        }
+
+        // Inserting also requires redoing all the batches
+        let mut batches = HashMap::<usize, Vec<String>>::default();
+        for id in ids.iter() {
+            Self::batches_add(&mut batches, self.batch_size, id);
+        }
+        self.total = self.total.saturating_add(inserted);
+        self.total_loaded = self.total_loaded.saturating_add(inserted);
+        self.last_batch_key = batches.len().saturating_sub(1);
+        self.batches = batches;
+        ListRangeUpdate(0, len as i32, self.total_loaded as i32)
+    }
+
     pub fn append(&mut self, songs: Vec<SongDescription>) -> ListRangeUpdate {
         let songs_len = songs.len();
         // How many loaded/visible songs so far
@@ -445,6 +480,7 @@ impl SongList {
 mod tests {
 
     use super::*;
+    use crate::app::models::fixtures::song;
 
     const NO_CHANGE: ListRangeUpdate = ListRangeUpdate(0, 0, 0);
 
@@ -456,22 +492,6 @@ mod tests {
         }
     }
 
-    fn song(id: &str) -> SongDescription {
-        SongDescription {
-            id: id.to_string(),
-            uri: "".to_string(),
-            title: "Title".to_string(),
-            artists: vec![],
-            album: AlbumRef {
-                id: "".to_string(),
-                name: "".to_string(),
-            },
-            duration: 1000,
-            art: None,
-            track_number: None,
-        }
-    }
-
     fn batch(id: usize) -> SongBatch {
         let offset = id * 2;
         SongBatch {
@@ -605,6 +625,18 @@ mod tests {
         assert_eq!(index, Some(6));
     }
 
+    #[test]
+    fn test_find_relinked() {
+        let mut list = SongList::new_from_initial_batch(batch(0));
+        list.append(vec![SongDescription {
+            linked_from: Some("original".to_string()),
+            ..song("relinked")
+        }]);
+
+        assert_eq!(list.find_index("relinked"), Some(2));
+        assert_eq!(list.find_index("original"), Some(2));
+    }
+
     #[test]
     fn test_iter_non_contiguous() {
         let mut list = SongList::new_from_initial_batch(batch(0));
@@ -636,6 +668,24 @@ mod tests {
         assert!(list_iter.next().is_none());
     }
 
+    #[test]
+    fn test_insert() {
+        let mut list = SongList::new_from_initial_batch(batch(0));
+        list.add(batch(1));
+
+        list.remove(&["song0".to_string(), "song2".to_string()]);
+        list.insert(vec![(2, song("song2")), (0, song("song0"))]);
+
+        assert_eq!(list.partial_len(), 4);
+
+        let mut list_iter = list.iter();
+        assert_eq!(list_iter.next().unwrap().description().id, "song0");
+        assert_eq!(list_iter.next().unwrap().description().id, "song1");
+        assert_eq!(list_iter.next().unwrap().description().id, "song2");
+        assert_eq!(list_iter.next().unwrap().description().id, "song3");
+        assert!(list_iter.next().is_none());
+    }
+
     #[test]
     fn test_batch_for() {
         let mut list = SongList::new_from_initial_batch(batch(0));
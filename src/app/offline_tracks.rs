@@ -0,0 +1,65 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+use crate::api::cache::librespot_cache_root;
+
+// Tracks librespot downloaded to its audio cache (played to the end at least once), the ones that can still be
+// played in offline mode. Files librespot evicts from its cache aren't noticed, so this might claim a bit too much.
+lazy_static! {
+    static ref CACHED_TRACKS: RwLock<HashSet<String>> = Default::default();
+}
+
+// Mirrors the offline mode setting, for widgets that have no access to the state
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+fn index_path() -> PathBuf {
+    librespot_cache_root().join("cached_tracks.json")
+}
+
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+// Whether a track can't be played right now, because we're offline and it isn't cached
+pub fn is_unavailable(id: &str) -> bool {
+    is_offline() && !CACHED_TRACKS.read().unwrap().contains(id)
+}
+
+pub async fn load() {
+    let Ok(content) = async_std::fs::read(index_path()).await else {
+        return;
+    };
+    match serde_json::from_slice::<HashSet<String>>(&content) {
+        Ok(tracks) => *CACHED_TRACKS.write().unwrap() = tracks,
+        Err(err) => warn!("Could not read cached tracks: {}", err),
+    }
+}
+
+pub async fn record_cached(id: String) {
+    let content = {
+        let mut tracks = CACHED_TRACKS.write().unwrap();
+        if !tracks.insert(id) {
+            return;
+        }
+        serde_json::to_vec(&*tracks)
+    };
+    let result = match content {
+        Ok(content) => async_std::fs::write(index_path(), content).await,
+        Err(err) => Err(err.into()),
+    };
+    if let Err(err) = result {
+        warn!("Could not save cached tracks: {}", err);
+    }
+}
+
+// When the audio cache is cleared
+pub async fn forget_all() {
+    CACHED_TRACKS.write().unwrap().clear();
+    let _ = async_std::fs::remove_file(index_path()).await;
+}
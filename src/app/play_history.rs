@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Tracks played longer ago than that are as likely to come up as tracks never played
+const RECOVERY_SECS: u64 = 14 * 24 * 60 * 60;
+// Even a track that just played can come up again, if little else is left to pick from
+const MIN_WEIGHT: f64 = 0.05;
+
+// Track ids mapped to when they were last played (unix timestamps)
+type History = HashMap<String, u64>;
+
+lazy_static! {
+    // Loaded from disk at startup, until then all tracks are as likely to come up
+    static ref HISTORY: Mutex<History> = Default::default();
+}
+
+fn history_path() -> PathBuf {
+    glib::user_data_dir().join("spot").join("history.json")
+}
+
+pub async fn load() {
+    let Ok(content) = async_std::fs::read(history_path()).await else {
+        return;
+    };
+    match serde_json::from_slice::<History>(&content) {
+        Ok(loaded) => {
+            // Tracks played in the meantime are more recent
+            let mut history = HISTORY.lock().unwrap();
+            for (id, played_at) in loaded {
+                history.entry(id).or_insert(played_at);
+            }
+        }
+        Err(err) => warn!("Could not read play history: {}", err),
+    }
+}
+
+fn serialize_history() -> Option<Vec<u8>> {
+    serde_json::to_vec(&*HISTORY.lock().unwrap())
+        .map_err(|err| warn!("Could not save play history: {}", err))
+        .ok()
+}
+
+pub async fn save() {
+    let Some(content) = serialize_history() else {
+        return;
+    };
+    let path = history_path();
+    if let Some(parent) = path.parent() {
+        let _ = async_std::fs::create_dir_all(parent).await;
+    }
+    if let Err(err) = async_std::fs::write(&path, content).await {
+        warn!("Could not save play history: {}", err);
+    }
+}
+
+// When quitting, there's no worker left to do it
+pub fn save_blocking() {
+    let Some(content) = serialize_history() else {
+        return;
+    };
+    let path = history_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Err(err) = std::fs::write(&path, content) {
+        warn!("Could not save play history: {}", err);
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// How likely a track is to be picked by smart shuffle, from how long ago it was played (in seconds)
+fn recency_weight(played_ago: Option<u64>) -> f64 {
+    match played_ago {
+        Some(ago) if ago < RECOVERY_SECS => {
+            MIN_WEIGHT + (1.0 - MIN_WEIGHT) * ago as f64 / RECOVERY_SECS as f64
+        }
+        _ => 1.0,
+    }
+}
+
+pub fn record_played(id: &str) {
+    let now = now();
+    let mut history = HISTORY.lock().unwrap();
+    // Older entries make no difference anymore
+    history.retain(|_, played_at| now.saturating_sub(*played_at) < RECOVERY_SECS);
+    history.insert(id.to_string(), now);
+}
+
+pub fn shuffle_weight(id: &str) -> f64 {
+    let now = now();
+    let played_at = HISTORY.lock().unwrap().get(id).copied();
+    recency_weight(played_at.map(|played_at| now.saturating_sub(played_at)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recency_weight() {
+        assert_eq!(recency_weight(None), 1.0);
+        assert_eq!(recency_weight(Some(RECOVERY_SECS)), 1.0);
+        assert_eq!(recency_weight(Some(0)), MIN_WEIGHT);
+
+        let day = 24 * 60 * 60;
+        assert!(recency_weight(Some(day)) < recency_weight(Some(7 * day)));
+        assert!(recency_weight(Some(7 * day)) < 1.0);
+    }
+}
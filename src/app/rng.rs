@@ -37,6 +37,11 @@ impl LazyRandomIndex {
         }
     }
 
+    // Forget the mappings generated so far, the next ones will be picked anew
+    pub fn reset(&mut self) {
+        self.generated = 0;
+    }
+
     // Grow or shrink
     pub fn resize(&mut self, size: usize) {
         if size >= self.indices.len() {
@@ -108,6 +113,57 @@ impl LazyRandomIndex {
         Some(self.pick_next(next))
     }
 
+    // Same as next_until, with the mappings picked by next_weighted
+    pub fn next_weighted_until(
+        &mut self,
+        i: usize,
+        weight: impl Fn(usize) -> f64,
+    ) -> Option<usize> {
+        if i >= self.indices.len() {
+            return None;
+        }
+
+        loop {
+            if self.generated > i {
+                break Some(self.indices[i]);
+            }
+            self.next_weighted(&weight);
+        }
+    }
+
+    // Generate the next mapping, picking indices with a probability proportional to their weight
+    pub fn next_weighted(&mut self, weight: impl Fn(usize) -> f64) -> Option<usize> {
+        let last = self.generated;
+        if last >= self.indices.len() {
+            return None;
+        }
+
+        let weights = self.indices[last..]
+            .iter()
+            .map(|&index| weight(index).max(0.0))
+            .collect::<Vec<f64>>();
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return self.next();
+        }
+
+        // Same as in next, except that what is picked from [k, n[ is drawn from the weights
+        let mut target = (self.rng.next_u64() as f64 / u64::MAX as f64) * total;
+        let picked = weights
+            .iter()
+            .position(|&w| {
+                if target < w {
+                    true
+                } else {
+                    target -= w;
+                    false
+                }
+            })
+            // Rounding errors could leave us slightly past the end
+            .unwrap_or(weights.len() - 1);
+        Some(self.pick_next(last + picked))
+    }
+
     fn pick_next(&mut self, next: usize) -> usize {
         let last = self.generated;
         self.indices.swap(last, next);
@@ -179,6 +235,21 @@ mod tests {
         assert_eq!(values, same_values);
     }
 
+    #[test]
+    fn test_weighted() {
+        let mut index = LazyRandomIndex::from(rng_for_test());
+        index.grow(4);
+
+        // Only index 2 can be picked first, the others come after in any order
+        let weight = |i: usize| if i == 2 { 1.0 } else { 0.0 };
+        assert_eq!(index.next_weighted(weight), Some(2));
+        index.next_weighted_until(3, weight);
+
+        let mut values = (0..4).filter_map(|i| index.get(i)).collect::<Vec<usize>>();
+        values.sort();
+        assert_eq!(values, &[0, 1, 2, 3]);
+    }
+
     #[test]
     fn test_reset() {
         let mut index = LazyRandomIndex::from(rng_for_test());
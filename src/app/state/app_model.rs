@@ -1,5 +1,5 @@
 use crate::api::SpotifyApiClient;
-use crate::app::{state::*, BatchLoader};
+use crate::app::{offline_tracks, state::*, BatchLoader};
 use ref_filter_map::*;
 use std::cell::{Ref, RefCell};
 use std::sync::Arc;
@@ -18,6 +18,8 @@ pub struct AppModel {
 
 impl AppModel {
     pub fn new(state: AppState, spotify_api: Arc<dyn SpotifyApiClient + Send + Sync>) -> Self {
+        spotify_api.set_offline(state.settings.settings.offline_mode);
+        offline_tracks::set_offline(state.settings.settings.offline_mode);
         let services = AppServices {
             batch_loader: BatchLoader::new(Arc::clone(&spotify_api)),
             spotify_api,
@@ -69,6 +71,14 @@ impl AppModel {
             AppAction::LoginAction(LoginAction::SetRefreshedToken { token, .. }) => {
                 self.services.spotify_api.update_token(token.clone());
             }
+            // Same for the offline mode, which the API client enforces
+            AppAction::SettingsAction(SettingsAction::SetOfflineMode(offline)) => {
+                self.services.spotify_api.set_offline(*offline);
+                offline_tracks::set_offline(*offline);
+            }
+            AppAction::NetworkAction(NetworkAction::SetConnected(connected)) => {
+                self.services.spotify_api.set_connected(*connected);
+            }
             _ => {}
         }
 
@@ -1,14 +1,31 @@
 use std::borrow::Cow;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
-use crate::app::models::{PlaylistDescription, PlaylistSummary};
+use crate::app::models::{PlaylistDescription, PlaylistSummary, SongDescription};
 use crate::app::state::{
     browser_state::{BrowserAction, BrowserEvent, BrowserState},
     login_state::{LoginAction, LoginEvent, LoginState},
+    network_state::{NetworkAction, NetworkEvent, NetworkState},
     playback_state::{PlaybackAction, PlaybackEvent, PlaybackState},
     selection_state::{SelectionAction, SelectionContext, SelectionEvent, SelectionState},
     settings_state::{SettingsAction, SettingsEvent, SettingsState},
     ScreenName, UpdatableState,
 };
+use crate::app::Retry;
+use crate::settings::SpotSettings;
+
+// How long a destructive action can be reverted for
+pub const UNDO_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Destructive actions that can be reverted, with what's needed to do so
+#[derive(Clone, Debug)]
+pub enum Undoable {
+    // Playlist id, songs along with the positions they were removed from
+    RemovedFromPlaylist(String, Vec<(usize, SongDescription)>),
+    UnsavedTracks(Vec<SongDescription>),
+    UnsavedAlbum(String),
+}
 
 // It's a big one...
 // All possible actions!
@@ -22,46 +39,70 @@ pub enum AppAction {
     SelectionAction(SelectionAction),
     LoginAction(LoginAction),
     SettingsAction(SettingsAction),
+    NetworkAction(NetworkAction),
     Start,
     Raise,
     ShowNotification(String),
+    // A notification for a failed request, offering to make it again
+    ShowRetryableNotification(String, Retry),
     ViewNowPlaying,
     // Cross-state actions
     QueueSelection,
     DequeueSelection,
     MoveUpSelection,
     MoveDownSelection,
-    SaveSelection,
-    UnsaveSelection,
     EnableSelection(SelectionContext),
     CancelSelection,
     CreatePlaylist(PlaylistDescription),
     UpdatePlaylistName(PlaylistSummary),
+    // Remember an undoable action and notify the user with the given message
+    PushUndoable(Undoable, String),
+    Undo,
+    // Play a single track, given its id
+    PlayTrack(String),
+    // The desktop session got locked (or unlocked)
+    SetSessionLocked(bool),
+    // A library backup was dropped on the window or opened with Spot
+    ImportLibrary(PathBuf),
+}
+
+// The kind of item (album, track...) and its id, from either form of link
+pub fn parse_spotify_uri(uri: &str) -> Option<(&str, &str)> {
+    let web_link = uri
+        .strip_prefix("https://open.spotify.com/")
+        .or_else(|| uri.strip_prefix("http://open.spotify.com/"));
+    if let Some(path) = web_link {
+        // Drop the query (?si=...) and localized prefixes (/intl-fr/...)
+        let path = path.split(['?', '#']).next()?;
+        let mut parts = path
+            .split('/')
+            .filter(|p| !p.is_empty() && !p.starts_with("intl-"));
+        return Some((parts.next()?, parts.next()?));
+    }
+
+    let mut parts = uri.strip_prefix("spotify:")?.split(':');
+    // Might start with /// because of https://gitlab.gnome.org/GNOME/glib/-/issues/1886/
+    let kind = parts.next()?.trim_start_matches('/');
+    let id = parts.next()?;
+    Some((kind, id)).filter(|(kind, id)| !kind.is_empty() && !id.is_empty())
 }
 
 // Not actual actions, just neat wrappers
 impl AppAction {
-    // An action to open a Spotify URI
+    // An action to open a Spotify URI or an open.spotify.com link
     #[allow(non_snake_case)]
     pub fn OpenURI(uri: String) -> Option<Self> {
         debug!("parsing {}", &uri);
-        let mut parts = uri.split(':');
-        if parts.next()? != "spotify" {
-            return None;
-        }
+        let (kind, id) = parse_spotify_uri(&uri)?;
+        let id = id.to_string();
 
-        // Might start with /// because of https://gitlab.gnome.org/GNOME/glib/-/issues/1886/
-        let action = parts
-            .next()?
-            .strip_prefix("///")
-            .filter(|p| !p.is_empty())?;
-        let data = parts.next()?;
-
-        match action {
-            "album" => Some(Self::ViewAlbum(data.to_string())),
-            "artist" => Some(Self::ViewArtist(data.to_string())),
-            "playlist" => Some(Self::ViewPlaylist(data.to_string())),
-            "user" => Some(Self::ViewUser(data.to_string())),
+        match kind {
+            "album" => Some(Self::ViewAlbum(id)),
+            "artist" => Some(Self::ViewArtist(id)),
+            "playlist" => Some(Self::ViewPlaylist(id)),
+            "user" => Some(Self::ViewUser(id)),
+            "track" => Some(Self::PlayTrack(id)),
+            "show" => Some(Self::ViewShow(id)),
             _ => None,
         }
     }
@@ -86,6 +127,11 @@ impl AppAction {
         BrowserAction::NavigationPush(ScreenName::User(id)).into()
     }
 
+    #[allow(non_snake_case)]
+    pub fn ViewShow(id: String) -> Self {
+        BrowserAction::NavigationPush(ScreenName::ShowDetails(id)).into()
+    }
+
     #[allow(non_snake_case)]
     pub fn ViewSearch() -> Self {
         BrowserAction::NavigationPush(ScreenName::Search).into()
@@ -104,11 +150,63 @@ pub enum AppEvent {
     Raised,
     NotificationShown(String),
     PlaylistCreatedNotificationShown(String),
+    UndoableNotificationShown(String),
+    RetryableNotificationShown(String, Retry),
+    UndoRequested(Undoable),
+    TrackRequested(String),
+    LibraryImportRequested(PathBuf),
     NowPlayingShown,
     SettingsEvent(SettingsEvent),
+    NetworkEvent(NetworkEvent),
 }
 
-// The actual state, split five-ways
+impl AppEvent {
+    // Events that only tell that something was updated (the details being in the state)
+    fn is_update_notification(&self) -> bool {
+        matches!(
+            self,
+            Self::BrowserEvent(
+                BrowserEvent::LibraryUpdated
+                    | BrowserEvent::SavedPlaylistsUpdated
+                    | BrowserEvent::AlbumTracksAppended(_)
+                    | BrowserEvent::PlaylistTracksAppended(_)
+                    | BrowserEvent::SearchResultsUpdated
+                    | BrowserEvent::ArtistDetailsUpdated(_)
+                    | BrowserEvent::UserDetailsUpdated(_)
+                    | BrowserEvent::SavedTracksUpdated
+                    | BrowserEvent::SavedEpisodesUpdated
+            ) | Self::PlaybackEvent(PlaybackEvent::PlaylistChanged)
+        )
+    }
+
+    fn is_same_notification(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::BrowserEvent(a), Self::BrowserEvent(b)) => a == b,
+            (
+                Self::PlaybackEvent(PlaybackEvent::PlaylistChanged),
+                Self::PlaybackEvent(PlaybackEvent::PlaylistChanged),
+            ) => true,
+            _ => false,
+        }
+    }
+
+    // When a bunch of actions are handled at once, components only need to hear about each update once
+    // (we keep the last one, so that it comes after anything it might depend on, like a screen being pushed)
+    pub fn coalesce(events: Vec<Self>) -> Vec<Self> {
+        let mut coalesced: Vec<Self> = vec![];
+        for event in events.into_iter().rev() {
+            let is_duplicate = event.is_update_notification()
+                && coalesced.iter().any(|e| e.is_same_notification(&event));
+            if !is_duplicate {
+                coalesced.push(event);
+            }
+        }
+        coalesced.reverse();
+        coalesced
+    }
+}
+
+// The actual state, split six-ways
 pub struct AppState {
     started: bool,
     pub playback: PlaybackState,
@@ -116,17 +214,24 @@ pub struct AppState {
     pub selection: SelectionState,
     pub logged_user: LoginState,
     pub settings: SettingsState,
+    pub network: NetworkState,
+    undo_stack: Vec<(Instant, Undoable)>,
+    // Whether playback was paused because the session got locked
+    paused_by_lock: bool,
 }
 
 impl AppState {
-    pub fn new() -> Self {
+    pub fn new(settings: SpotSettings) -> Self {
         Self {
             started: false,
-            playback: Default::default(),
+            playback: PlaybackState::new(settings.smart_shuffle),
             browser: BrowserState::new(),
             selection: Default::default(),
             logged_user: Default::default(),
-            settings: Default::default(),
+            settings: SettingsState { settings },
+            network: Default::default(),
+            undo_stack: vec![],
+            paused_by_lock: false,
         }
     }
 
@@ -139,8 +244,12 @@ impl AppState {
             // Couple of actions that don't mutate the state (not intested in keeping track of what they change)
             // they're here just to have a consistent way of doing things (always an Action)
             AppAction::ShowNotification(c) => vec![AppEvent::NotificationShown(c)],
+            AppAction::ShowRetryableNotification(c, retry) => {
+                vec![AppEvent::RetryableNotificationShown(c, retry)]
+            }
             AppAction::ViewNowPlaying => vec![AppEvent::NowPlayingShown],
             AppAction::Raise => vec![AppEvent::Raised],
+            AppAction::ImportLibrary(path) => vec![AppEvent::LibraryImportRequested(path)],
             // Cross-state actions: multiple "substates" are affected by these actions, that's why they're handled here
             // Might need some clean-up
             AppAction::QueueSelection => {
@@ -182,29 +291,6 @@ impl AppState {
                     .map(|_| vec![PlaybackEvent::PlaylistChanged.into()])
                     .unwrap_or_default()
             }
-            AppAction::SaveSelection => {
-                let tracks = self.selection.take_selection();
-                let mut events: Vec<AppEvent> = forward_action(
-                    BrowserAction::SaveTracks(tracks),
-                    self.browser.home_state_mut().unwrap(),
-                );
-                events.push(SelectionEvent::SelectionModeChanged(false).into());
-                events
-            }
-            AppAction::UnsaveSelection => {
-                let tracks: Vec<String> = self
-                    .selection
-                    .take_selection()
-                    .into_iter()
-                    .map(|s| s.id)
-                    .collect();
-                let mut events: Vec<AppEvent> = forward_action(
-                    BrowserAction::RemoveSavedTracks(tracks),
-                    self.browser.home_state_mut().unwrap(),
-                );
-                events.push(SelectionEvent::SelectionModeChanged(false).into());
-                events
-            }
             AppAction::EnableSelection(context) => {
                 if let Some(active) = self.selection.set_mode(Some(context)) {
                     vec![SelectionEvent::SelectionModeChanged(active).into()]
@@ -243,15 +329,100 @@ impl AppState {
                 events.append(&mut more_events);
                 events
             }
+            AppAction::PushUndoable(undoable, message) => {
+                self.undo_stack
+                    .retain(|(pushed, _)| pushed.elapsed() < UNDO_TIMEOUT);
+                self.undo_stack.push((Instant::now(), undoable));
+                vec![AppEvent::UndoableNotificationShown(message)]
+            }
+            AppAction::Undo => match self.undo_stack.pop() {
+                Some((pushed, undoable)) if pushed.elapsed() < UNDO_TIMEOUT => {
+                    vec![AppEvent::UndoRequested(undoable)]
+                }
+                // Anything older is expired too
+                _ => {
+                    self.undo_stack.clear();
+                    vec![]
+                }
+            },
+            // The track needs to be fetched first
+            AppAction::PlayTrack(id) => vec![AppEvent::TrackRequested(id)],
+            AppAction::SetSessionLocked(true) => {
+                if !self.settings.settings.pause_on_lock || !self.playback.is_playing() {
+                    return vec![];
+                }
+                self.paused_by_lock = true;
+                forward_action(PlaybackAction::Pause, &mut self.playback)
+            }
+            // Only what the lock paused gets resumed
+            AppAction::SetSessionLocked(false) => {
+                if !std::mem::take(&mut self.paused_by_lock)
+                    || !self.settings.settings.resume_on_unlock
+                {
+                    return vec![];
+                }
+                forward_action(PlaybackAction::Play, &mut self.playback)
+            }
+            // Whatever was playing stopped with the connection
+            AppAction::LoginAction(LoginAction::SetConnectionLost) => {
+                let mut events = forward_action(PlaybackAction::Pause, &mut self.playback);
+                events.append(&mut forward_action(
+                    LoginAction::SetConnectionLost,
+                    &mut self.logged_user,
+                ));
+                events
+            }
+            // Nothing from the previous account should survive a switch or a logout
+            AppAction::LoginAction(action @ (LoginAction::SwitchAccount | LoginAction::Logout)) => {
+                let mut events = self.reset_for_account_change();
+                events.append(&mut forward_action(action, &mut self.logged_user));
+                events
+            }
             // As for all other actions, we forward them to the substates :)
             AppAction::PlaybackAction(a) => forward_action(a, &mut self.playback),
             AppAction::BrowserAction(a) => forward_action(a, &mut self.browser),
             AppAction::SelectionAction(a) => forward_action(a, &mut self.selection),
             AppAction::LoginAction(a) => forward_action(a, &mut self.logged_user),
-            AppAction::SettingsAction(a) => forward_action(a, &mut self.settings),
+            AppAction::NetworkAction(a) => {
+                let mut events = forward_action(a, &mut self.network);
+                if !events.is_empty()
+                    && self.network.connected
+                    && !self.settings.settings.offline_mode
+                {
+                    events.push(NetworkEvent::BackOnline.into());
+                }
+                events
+            }
+            AppAction::SettingsAction(a) => {
+                let events = forward_action(a, &mut self.settings);
+                self.playback
+                    .set_smart_shuffle(self.settings.settings.smart_shuffle);
+                events
+            }
             _ => vec![],
         }
     }
+
+    fn reset_for_account_change(&mut self) -> Vec<AppEvent> {
+        self.undo_stack.clear();
+        let mut events = forward_action(PlaybackAction::Stop, &mut self.playback);
+        events.append(&mut forward_action(
+            PlaybackAction::LoadSongs(vec![], 0),
+            &mut self.playback,
+        ));
+        events.append(&mut forward_action(
+            PlaybackAction::ClearQueue,
+            &mut self.playback,
+        ));
+        if let Some(active) = self.selection.set_mode(None) {
+            events.push(SelectionEvent::SelectionModeChanged(active).into());
+        }
+        events.append(&mut forward_action(
+            BrowserAction::NavigationPopTo(ScreenName::Home),
+            &mut self.browser,
+        ));
+        events
+    }
 }
 
 fn forward_action<A, E>(
@@ -268,3 +439,133 @@ where
         .map(|e| e.into())
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::models::fixtures::song;
+
+    #[test]
+    fn test_coalesce_events() {
+        let events = vec![
+            BrowserEvent::PlaylistTracksAppended("a".to_string()).into(),
+            BrowserEvent::NavigationPushed(ScreenName::Search).into(),
+            BrowserEvent::PlaylistTracksAppended("a".to_string()).into(),
+            BrowserEvent::PlaylistTracksAppended("b".to_string()).into(),
+            BrowserEvent::NavigationPushed(ScreenName::Search).into(),
+        ];
+
+        let coalesced: Vec<BrowserEvent> = AppEvent::coalesce(events)
+            .into_iter()
+            .filter_map(|e| match e {
+                AppEvent::BrowserEvent(e) => Some(e),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            coalesced,
+            vec![
+                BrowserEvent::NavigationPushed(ScreenName::Search),
+                BrowserEvent::PlaylistTracksAppended("a".to_string()),
+                BrowserEvent::PlaylistTracksAppended("b".to_string()),
+                BrowserEvent::NavigationPushed(ScreenName::Search),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_spotify_uri() {
+        assert_eq!(
+            parse_spotify_uri("spotify:track:abc"),
+            Some(("track", "abc"))
+        );
+        assert_eq!(
+            parse_spotify_uri("spotify:///album:abc"),
+            Some(("album", "abc"))
+        );
+        assert_eq!(
+            parse_spotify_uri("https://open.spotify.com/intl-fr/playlist/abc?si=xyz"),
+            Some(("playlist", "abc"))
+        );
+        assert_eq!(
+            parse_spotify_uri("https://open.spotify.com/artist/abc"),
+            Some(("artist", "abc"))
+        );
+        assert_eq!(parse_spotify_uri("https://example.com/track/abc"), None);
+        assert_eq!(parse_spotify_uri("spotify:track"), None);
+    }
+
+    fn playing_state(settings: SpotSettings) -> AppState {
+        let mut state = AppState::new(settings);
+        state.update_state(PlaybackAction::LoadSongs(vec![song("1")], 0).into());
+        state.update_state(PlaybackAction::Load("1".to_string()).into());
+        state
+    }
+
+    #[test]
+    fn test_pause_on_lock() {
+        let mut state = playing_state(SpotSettings {
+            pause_on_lock: true,
+            ..Default::default()
+        });
+
+        state.update_state(AppAction::SetSessionLocked(true));
+        assert!(!state.playback.is_playing());
+
+        state.update_state(AppAction::SetSessionLocked(false));
+        assert!(state.playback.is_playing());
+    }
+
+    #[test]
+    fn test_unlock_keeps_user_pause() {
+        let mut state = playing_state(SpotSettings {
+            pause_on_lock: true,
+            ..Default::default()
+        });
+        state.update_state(PlaybackAction::Pause.into());
+
+        state.update_state(AppAction::SetSessionLocked(true));
+        state.update_state(AppAction::SetSessionLocked(false));
+        assert!(!state.playback.is_playing());
+    }
+
+    #[test]
+    fn test_back_online() {
+        let mut state = AppState::new(Default::default());
+        assert!(state
+            .update_state(NetworkAction::SetConnected(true).into())
+            .is_empty());
+
+        state.update_state(NetworkAction::SetConnected(false).into());
+        let events = state.update_state(NetworkAction::SetConnected(true).into());
+        assert!(matches!(
+            events.as_slice(),
+            [
+                AppEvent::NetworkEvent(NetworkEvent::ConnectivityChanged),
+                AppEvent::NetworkEvent(NetworkEvent::BackOnline)
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_no_refresh_in_offline_mode() {
+        let mut state = AppState::new(SpotSettings {
+            offline_mode: true,
+            ..Default::default()
+        });
+        state.update_state(NetworkAction::SetConnected(false).into());
+        let events = state.update_state(NetworkAction::SetConnected(true).into());
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, AppEvent::NetworkEvent(NetworkEvent::BackOnline))));
+    }
+
+    #[test]
+    fn test_lock_ignored_by_default() {
+        let mut state = playing_state(Default::default());
+
+        state.update_state(AppAction::SetSessionLocked(true));
+        assert!(state.playback.is_playing());
+    }
+}
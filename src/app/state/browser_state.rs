@@ -1,9 +1,10 @@
 use super::{
-    AppAction, AppEvent, ArtistState, DetailsState, HomeState, PlaylistDetailsState, ScreenName,
-    SearchState, UpdatableState, UserState,
+    AppAction, AppEvent, ArtistState, DetailsState, HomeState, PlaylistDetailsState, ReleaseSort,
+    ScreenName, SearchState, ShowDetailsState, UpdatableState, UserState,
 };
 use crate::app::models::*;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::iter::Iterator;
 
 // Actions that affect any "screen" that we push over time
@@ -22,21 +23,92 @@ pub enum BrowserAction {
     SetPlaylistDetails(Box<PlaylistDescription>, Box<SongBatch>),
     UpdatePlaylistName(PlaylistSummary),
     AppendPlaylistTracks(String, Box<SongBatch>),
+    // Songs along with their positions in the playlist
+    InsertTracksInPlaylist(String, Vec<(usize, SongDescription)>),
     Search(String),
     SetSearchResults(Box<SearchResults>),
     SetArtistDetails(Box<ArtistDescription>),
     AppendArtistReleases(String, Vec<AlbumDescription>),
+    SetArtistReleaseSort(String, ReleaseSort),
+    SetShowDetails(Box<ShowDescription>),
+    AppendShowEpisodes(String, Box<EpisodeBatch>),
     NavigationPush(ScreenName),
     NavigationPop,
     NavigationPopTo(ScreenName),
+    // Where a screen was scrolled to when we navigated away from it
+    SetScrollOffset(ScreenName, f64),
     SaveAlbum(Box<AlbumDescription>),
     UnsaveAlbum(String),
     SetUserDetails(Box<UserDescription>),
     AppendUserPlaylists(String, Vec<PlaylistDescription>),
+    // User id, number of artists followed
+    SetUserFollowing(String, u32),
+    SetPlaylistPublic(String, bool),
     SetSavedTracks(Box<SongBatch>),
     AppendSavedTracks(Box<SongBatch>),
     SaveTracks(Vec<SongDescription>),
     RemoveSavedTracks(Vec<String>),
+    SetSavedEpisodes(Box<SongBatch>),
+    AppendSavedEpisodes(Box<SongBatch>),
+    // Show a change to the library before Spotify confirmed it...
+    ApplyMutation(LibraryMutation),
+    // ...then either forget about it once it went through,
+    ConfirmMutation(LibraryMutation),
+    // or take it back if it didn't
+    RevertMutation(LibraryMutation),
+}
+
+// A change to the library, along with what's needed to take it back
+#[derive(Clone, Debug)]
+pub enum LibraryMutation {
+    SaveAlbum(Box<AlbumDescription>),
+    UnsaveAlbum(Box<AlbumDescription>),
+    SaveTracks(Vec<SongDescription>),
+    RemoveSavedTracks(Vec<SongDescription>),
+    // Playlist id, whether it's made public
+    SetPlaylistPublic(String, bool),
+}
+
+impl LibraryMutation {
+    // Ids of the albums or songs affected
+    fn ids(&self) -> Vec<&str> {
+        match self {
+            Self::SaveAlbum(album) | Self::UnsaveAlbum(album) => vec![&album.id],
+            Self::SaveTracks(songs) | Self::RemoveSavedTracks(songs) => {
+                songs.iter().map(|s| s.id.as_str()).collect()
+            }
+            Self::SetPlaylistPublic(id, _) => vec![id],
+        }
+    }
+
+    fn action(&self) -> BrowserAction {
+        match self {
+            Self::SaveAlbum(album) => BrowserAction::SaveAlbum(album.clone()),
+            Self::UnsaveAlbum(album) => BrowserAction::UnsaveAlbum(album.id.clone()),
+            Self::SaveTracks(songs) => BrowserAction::SaveTracks(songs.clone()),
+            Self::RemoveSavedTracks(songs) => {
+                BrowserAction::RemoveSavedTracks(songs.iter().map(|s| s.id.clone()).collect())
+            }
+            Self::SetPlaylistPublic(id, public) => {
+                BrowserAction::SetPlaylistPublic(id.clone(), *public)
+            }
+        }
+    }
+
+    fn reverse_action(&self) -> BrowserAction {
+        match self {
+            Self::SaveAlbum(album) => BrowserAction::UnsaveAlbum(album.id.clone()),
+            Self::UnsaveAlbum(album) => BrowserAction::SaveAlbum(album.clone()),
+            Self::SaveTracks(songs) => {
+                BrowserAction::RemoveSavedTracks(songs.iter().map(|s| s.id.clone()).collect())
+            }
+            // They come back at the top rather than where they were
+            Self::RemoveSavedTracks(songs) => BrowserAction::SaveTracks(songs.clone()),
+            Self::SetPlaylistPublic(id, public) => {
+                BrowserAction::SetPlaylistPublic(id.clone(), !public)
+            }
+        }
+    }
 }
 
 impl From<BrowserAction> for AppAction {
@@ -56,16 +128,21 @@ pub enum BrowserEvent {
     PlaylistDetailsLoaded(String),
     PlaylistTracksAppended(String),
     PlaylistTracksRemoved(String),
+    PlaylistTracksInserted(String),
     SearchUpdated,
     SearchResultsUpdated,
     ArtistDetailsUpdated(String),
+    ShowDetailsLoaded(String),
+    ShowEpisodesAppended(String),
     NavigationPushed(ScreenName),
     NavigationPopped,
     NavigationPoppedTo(ScreenName),
+    ScrollOffsetChanged(ScreenName),
     AlbumSaved(String),
     AlbumUnsaved(String),
     UserDetailsUpdated(String),
     SavedTracksUpdated,
+    SavedEpisodesUpdated,
 }
 
 impl From<BrowserEvent> for AppEvent {
@@ -82,6 +159,7 @@ pub enum BrowserScreen {
     Artist(Box<ArtistState>),
     PlaylistDetails(Box<PlaylistDetailsState>),
     User(Box<UserState>),
+    ShowDetails(Box<ShowDetailsState>),
 }
 
 impl BrowserScreen {
@@ -99,6 +177,9 @@ impl BrowserScreen {
                 BrowserScreen::PlaylistDetails(Box::new(PlaylistDetailsState::new(id.to_string())))
             }
             ScreenName::User(id) => BrowserScreen::User(Box::new(UserState::new(id.to_string()))),
+            ScreenName::ShowDetails(id) => {
+                BrowserScreen::ShowDetails(Box::new(ShowDetailsState::new(id.to_string())))
+            }
         }
     }
 
@@ -111,6 +192,7 @@ impl BrowserScreen {
             Self::Artist(state) => &mut **state,
             Self::PlaylistDetails(state) => &mut **state,
             Self::User(state) => &mut **state,
+            Self::ShowDetails(state) => &mut **state,
         }
     }
 }
@@ -126,6 +208,7 @@ impl NamedScreen for BrowserScreen {
             Self::Artist(state) => &state.name,
             Self::PlaylistDetails(state) => &state.name,
             Self::User(state) => &state.name,
+            Self::ShowDetails(state) => &state.name,
         }
     }
 }
@@ -158,6 +241,10 @@ where
         self.0.iter_mut().rev()
     }
 
+    fn iter(&self) -> impl Iterator<Item = &Screen> {
+        self.0.iter()
+    }
+
     fn iter_rev(&self) -> impl Iterator<Item = &Screen> {
         self.0.iter().rev()
     }
@@ -218,6 +305,10 @@ where
 pub struct BrowserState {
     navigation_hidden: bool,
     navigation: NavStack<BrowserScreen>,
+    // Keyed by screen identifier, only for screens in the stack
+    scroll_offsets: HashMap<String, f64>,
+    // Changes to the library already shown, that Spotify hasn't confirmed yet
+    pending_mutations: Vec<LibraryMutation>,
 }
 
 macro_rules! extract_state {
@@ -243,6 +334,8 @@ impl BrowserState {
         Self {
             navigation_hidden: false,
             navigation: NavStack::new(BrowserScreen::Home(Default::default())),
+            scroll_offsets: HashMap::new(),
+            pending_mutations: vec![],
         }
     }
 
@@ -258,15 +351,46 @@ impl BrowserState {
         self.navigation.count()
     }
 
-    pub fn home_state(&self) -> Option<&HomeState> {
-        extract_state!(self, BrowserScreen::Home(s) => s)
+    pub fn scroll_offset(&self, name: &ScreenName) -> Option<f64> {
+        self.scroll_offsets.get(name.identifier().as_ref()).copied()
     }
 
-    pub fn home_state_mut(&mut self) -> Option<&mut HomeState> {
-        self.navigation.iter_mut().find_map(|screen| match screen {
-            BrowserScreen::Home(s) => Some(&mut **s),
-            _ => None,
-        })
+    pub fn scroll_offsets(&self) -> &HashMap<String, f64> {
+        &self.scroll_offsets
+    }
+
+    // Whether an album or song is being saved or removed from the library
+    pub fn is_mutation_pending(&self, id: &str) -> bool {
+        self.pending_mutations
+            .iter()
+            .any(|mutation| mutation.ids().contains(&id))
+    }
+
+    fn take_pending_mutation(&mut self, mutation: &LibraryMutation) -> Option<LibraryMutation> {
+        let position = self
+            .pending_mutations
+            .iter()
+            .position(|pending| pending.ids() == mutation.ids())?;
+        Some(self.pending_mutations.remove(position))
+    }
+
+    // Offsets of screens that were popped are of no use anymore
+    fn prune_scroll_offsets(&mut self) {
+        let identifiers: Vec<String> = self
+            .screen_names()
+            .map(|name| name.identifier().into_owned())
+            .collect();
+        self.scroll_offsets
+            .retain(|identifier, _| identifiers.contains(identifier));
+    }
+
+    // From the bottom of the stack to the top
+    pub fn screen_names(&self) -> impl Iterator<Item = &ScreenName> {
+        self.navigation.iter().map(|s| s.name())
+    }
+
+    pub fn home_state(&self) -> Option<&HomeState> {
+        extract_state!(self, BrowserScreen::Home(s) => s)
     }
 
     pub fn details_state(&self, id: &str) -> Option<&DetailsState> {
@@ -289,6 +413,10 @@ impl BrowserState {
         extract_state!(self, BrowserScreen::User(state) if state.id == id => state)
     }
 
+    pub fn show_details_state(&self, id: &str) -> Option<&ShowDetailsState> {
+        extract_state!(self, BrowserScreen::ShowDetails(state) if state.id == id => state)
+    }
+
     // If a screen we want to push is already in the stack
     // we just pop all the way back to it
     fn push_if_needed(&mut self, name: &ScreenName) -> Vec<BrowserEvent> {
@@ -334,16 +462,35 @@ impl UpdatableState for BrowserState {
             BrowserAction::NavigationPush(name) => self.push_if_needed(name),
             BrowserAction::NavigationPopTo(name) => {
                 self.navigation.pop_to(name);
+                self.prune_scroll_offsets();
                 vec![BrowserEvent::NavigationPoppedTo(name.clone())]
             }
             BrowserAction::NavigationPop if can_pop => {
                 self.navigation.pop();
+                self.prune_scroll_offsets();
                 vec![BrowserEvent::NavigationPopped]
             }
+            BrowserAction::SetScrollOffset(name, offset) => {
+                self.scroll_offsets
+                    .insert(name.identifier().into_owned(), *offset);
+                vec![BrowserEvent::ScrollOffsetChanged(name.clone())]
+            }
             BrowserAction::NavigationPop if self.navigation_hidden => {
                 self.navigation_hidden = false;
                 vec![BrowserEvent::NavigationHidden(false)]
             }
+            BrowserAction::ApplyMutation(mutation) => {
+                self.pending_mutations.push(mutation.clone());
+                self.update_with(Cow::Owned(mutation.action()))
+            }
+            BrowserAction::ConfirmMutation(mutation) => {
+                self.take_pending_mutation(mutation);
+                vec![]
+            }
+            BrowserAction::RevertMutation(mutation) => match self.take_pending_mutation(mutation) {
+                Some(pending) => self.update_with(Cow::Owned(pending.reverse_action())),
+                None => vec![],
+            },
             // Besides navigation actions, we just forward actions to each dedicated reducer
             _ => self
                 .navigation
@@ -358,6 +505,7 @@ impl UpdatableState for BrowserState {
 pub mod tests {
 
     use super::*;
+    use crate::app::models::fixtures::song;
 
     #[test]
     fn test_navigation_push() {
@@ -376,6 +524,46 @@ pub mod tests {
         assert_eq!(state.artist_state("some_id").is_some(), true);
     }
 
+    #[test]
+    fn test_screen_names() {
+        let mut state = BrowserState::new();
+        let new_screen = ScreenName::Artist("some_id".to_string());
+        state.update_with(Cow::Owned(BrowserAction::NavigationPush(
+            new_screen.clone(),
+        )));
+
+        let names: Vec<ScreenName> = state.screen_names().cloned().collect();
+        assert_eq!(names, vec![ScreenName::Home, new_screen.clone()]);
+        assert_eq!(
+            ScreenName::from_identifier(&new_screen.identifier()),
+            Some(new_screen)
+        );
+    }
+
+    #[test]
+    fn test_scroll_offsets() {
+        let mut state = BrowserState::new();
+        let new_screen = ScreenName::Artist("some_id".to_string());
+        state.update_with(Cow::Owned(BrowserAction::SetScrollOffset(
+            ScreenName::Home,
+            120.0,
+        )));
+        state.update_with(Cow::Owned(BrowserAction::NavigationPush(
+            new_screen.clone(),
+        )));
+        state.update_with(Cow::Owned(BrowserAction::SetScrollOffset(
+            new_screen.clone(),
+            40.0,
+        )));
+
+        assert_eq!(state.scroll_offset(&ScreenName::Home), Some(120.0));
+        assert_eq!(state.scroll_offset(&new_screen), Some(40.0));
+
+        state.update_with(Cow::Owned(BrowserAction::NavigationPop));
+        assert_eq!(state.scroll_offset(&ScreenName::Home), Some(120.0));
+        assert_eq!(state.scroll_offset(&new_screen), None);
+    }
+
     #[test]
     fn test_navigation_pop() {
         let mut state = BrowserState::new();
@@ -437,4 +625,34 @@ pub mod tests {
         assert_eq!(state.count(), 2);
         assert_eq!(events, vec![BrowserEvent::NavigationPoppedTo(new_screen)]);
     }
+
+    #[test]
+    fn test_revert_mutation() {
+        let mut state = BrowserState::new();
+        let mutation = LibraryMutation::SaveTracks(vec![song("1")]);
+
+        state.update_with(Cow::Owned(BrowserAction::ApplyMutation(mutation.clone())));
+        assert!(state.is_mutation_pending("1"));
+        assert_eq!(state.home_state().unwrap().saved_tracks.partial_len(), 1);
+
+        let events = state.update_with(Cow::Owned(BrowserAction::RevertMutation(mutation)));
+        assert!(!state.is_mutation_pending("1"));
+        assert_eq!(state.home_state().unwrap().saved_tracks.partial_len(), 0);
+        assert_eq!(events, vec![BrowserEvent::SavedTracksUpdated]);
+    }
+
+    #[test]
+    fn test_confirm_mutation() {
+        let mut state = BrowserState::new();
+        let mutation = LibraryMutation::SaveTracks(vec![song("1")]);
+
+        state.update_with(Cow::Owned(BrowserAction::ApplyMutation(mutation.clone())));
+        state.update_with(Cow::Owned(BrowserAction::ConfirmMutation(mutation.clone())));
+        assert!(!state.is_mutation_pending("1"));
+
+        // Nothing left to take back
+        let events = state.update_with(Cow::Owned(BrowserAction::RevertMutation(mutation)));
+        assert_eq!(state.home_state().unwrap().saved_tracks.partial_len(), 1);
+        assert_eq!(events, vec![]);
+    }
 }
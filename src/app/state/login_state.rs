@@ -3,7 +3,7 @@ use std::borrow::Cow;
 use std::time::SystemTime;
 
 use crate::app::credentials::Credentials;
-use crate::app::models::PlaylistSummary;
+use crate::app::models::{FriendActivity, PlaylistSummary};
 use crate::app::state::{AppAction, AppEvent, UpdatableState};
 
 #[derive(Clone, Debug)]
@@ -26,6 +26,7 @@ pub enum LoginAction {
     SetUserPlaylists(Vec<PlaylistSummary>),
     UpdateUserPlaylist(PlaylistSummary),
     PrependUserPlaylist(Vec<PlaylistSummary>),
+    SetFriendActivity(Vec<FriendActivity>),
     SetLoginFailure,
     RefreshToken,
     SetRefreshedToken {
@@ -33,6 +34,10 @@ pub enum LoginAction {
         token_expiry_time: SystemTime,
     },
     Logout,
+    SetConnectionLost,
+    SetConnectionRestored,
+    // The current account was changed in the settings, log in with it
+    SwitchAccount,
 }
 
 impl From<LoginAction> for AppAction {
@@ -59,6 +64,7 @@ pub enum LoginEvent {
     LoginStarted(LoginStartedEvent),
     LoginCompleted(LoginCompletedEvent),
     UserPlaylistsLoaded,
+    FriendActivityUpdated,
     LoginFailed,
     FreshTokenRequested,
     RefreshTokenCompleted {
@@ -66,6 +72,9 @@ pub enum LoginEvent {
         token_expiry_time: SystemTime,
     },
     LogoutCompleted,
+    AccountSwitched,
+    ConnectionLost,
+    ConnectionRestored,
 }
 
 impl From<LoginEvent> for AppEvent {
@@ -80,6 +89,8 @@ pub struct LoginState {
     pub user: Option<String>,
     // Playlists owned by the logged in user
     pub playlists: Vec<PlaylistSummary>,
+    // What followed users are listening to
+    pub friend_activity: Vec<FriendActivity>,
 }
 
 impl UpdatableState for LoginState {
@@ -125,7 +136,30 @@ impl UpdatableState for LoginState {
             }
             LoginAction::Logout => {
                 self.user = None;
-                vec![LoginEvent::LogoutCompleted.into()]
+                self.friend_activity.clear();
+                vec![
+                    LoginEvent::FriendActivityUpdated.into(),
+                    LoginEvent::LogoutCompleted.into(),
+                ]
+            }
+            LoginAction::SetConnectionLost => {
+                // translators: This notification is shown when the connection to Spotify is lost (network issues...). Spot keeps trying to connect again.
+                vec![
+                    AppEvent::NotificationShown(gettext("Connection lost, reconnecting…")),
+                    LoginEvent::ConnectionLost.into(),
+                ]
+            }
+            // The "Connection restored" notification comes with the fresh token that follows
+            LoginAction::SetConnectionRestored => vec![LoginEvent::ConnectionRestored.into()],
+            LoginAction::SwitchAccount => {
+                self.user = None;
+                self.playlists.clear();
+                self.friend_activity.clear();
+                vec![
+                    LoginEvent::UserPlaylistsLoaded.into(),
+                    LoginEvent::FriendActivityUpdated.into(),
+                    LoginEvent::AccountSwitched.into(),
+                ]
             }
             LoginAction::SetUserPlaylists(playlists) => {
                 self.playlists = playlists;
@@ -142,6 +176,10 @@ impl UpdatableState for LoginState {
                 self.playlists = summaries;
                 vec![LoginEvent::UserPlaylistsLoaded.into()]
             }
+            LoginAction::SetFriendActivity(activity) => {
+                self.friend_activity = activity;
+                vec![LoginEvent::FriendActivityUpdated.into()]
+            }
         }
     }
 }
@@ -2,6 +2,7 @@ mod app_model;
 mod app_state;
 mod browser_state;
 mod login_state;
+mod network_state;
 mod pagination;
 mod playback_state;
 mod screen_states;
@@ -12,6 +13,7 @@ pub use app_model::AppModel;
 pub use app_state::*;
 pub use browser_state::*;
 pub use login_state::*;
+pub use network_state::*;
 pub use pagination::*;
 pub use playback_state::*;
 pub use screen_states::*;
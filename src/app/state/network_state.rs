@@ -0,0 +1,52 @@
+use crate::app::state::{AppAction, AppEvent, UpdatableState};
+
+#[derive(Clone, Debug)]
+pub enum NetworkAction {
+    SetConnected(bool),
+}
+
+impl From<NetworkAction> for AppAction {
+    fn from(network_action: NetworkAction) -> Self {
+        Self::NetworkAction(network_action)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum NetworkEvent {
+    // The network came or went, see NetworkState
+    ConnectivityChanged,
+    // Connected again (and not in offline mode): whatever failed to load can be tried again
+    BackOnline,
+}
+
+impl From<NetworkEvent> for AppEvent {
+    fn from(network_event: NetworkEvent) -> Self {
+        Self::NetworkEvent(network_event)
+    }
+}
+
+pub struct NetworkState {
+    pub connected: bool,
+}
+
+impl Default for NetworkState {
+    // Assumed until told otherwise
+    fn default() -> Self {
+        Self { connected: true }
+    }
+}
+
+impl UpdatableState for NetworkState {
+    type Action = NetworkAction;
+    type Event = NetworkEvent;
+
+    fn update_with(&mut self, action: std::borrow::Cow<Self::Action>) -> Vec<Self::Event> {
+        match action.into_owned() {
+            NetworkAction::SetConnected(connected) if connected != self.connected => {
+                self.connected = connected;
+                vec![NetworkEvent::ConnectivityChanged]
+            }
+            NetworkAction::SetConnected(_) => vec![],
+        }
+    }
+}
@@ -1,35 +1,104 @@
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::time::Instant;
 
 use crate::app::models::*;
+use crate::app::play_history;
 use crate::app::state::{AppAction, AppEvent, UpdatableState};
 use crate::app::{BatchQuery, LazyRandomIndex, SongsSource};
 
+// What's left to play in one section of the queue, kept up to date as it changes
+#[derive(Clone, Copy, Debug, Default)]
+struct QueueSummary {
+    count: usize,
+    // Of the songs that are loaded
+    duration: u64,
+    // Songs counted that aren't loaded yet, whose duration we don't know
+    unloaded: usize,
+}
+
+impl QueueSummary {
+    fn add(&mut self, duration: Option<u32>) {
+        self.count += 1;
+        match duration {
+            Some(duration) => self.duration += duration as u64,
+            None => self.unloaded += 1,
+        }
+    }
+
+    fn remove(&mut self, duration: Option<u32>) {
+        self.count = self.count.saturating_sub(1);
+        match duration {
+            Some(duration) => self.duration = self.duration.saturating_sub(duration as u64),
+            None => self.unloaded = self.unloaded.saturating_sub(1),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct PlaybackState {
     available_devices: Vec<ConnectDevice>,
     current_device: Device,
     // A mapping of indices for shuffled playback
     index: LazyRandomIndex,
-    // The actual list like thing backing the currently playing tracks
+    // The actual list like thing backing the currently playing tracks ("Next from <source>")
     songs: SongListModel,
+    // Songs queued by hand ("Next in queue"), played before the rest of the source
+    queued: SongListModel,
+    // The queued song being played, taken out of the queue: the source picks up where it was left after it
+    playing_queued: Option<SongDescription>,
     list_position: Option<usize>,
+    // Songs of a paged source before that position were left out of the queue ("play from here")
+    start: usize,
     seek_position: PositionMillis,
     source: Option<SongsSource>,
     repeat: RepeatMode,
     is_playing: bool,
     is_shuffled: bool,
+    // Shuffling favors tracks that weren't played recently
+    smart_shuffle: bool,
+    // What's left of each section (see remaining_songs)
+    queued_summary: QueueSummary,
+    source_summary: QueueSummary,
 }
 
 // Most mutatings methods shouldn't be pub
 // If they are, they probably are only used by the app state
 impl PlaybackState {
+    pub fn new(smart_shuffle: bool) -> Self {
+        Self {
+            smart_shuffle,
+            ..Default::default()
+        }
+    }
+
     pub fn songs(&self) -> &SongListModel {
         &self.songs
     }
 
+    pub fn queued_songs(&self) -> &SongListModel {
+        &self.queued
+    }
+
+    // A song from either section of the queue
+    pub fn song(&self, id: &str) -> Option<SongDescription> {
+        self.playing_queued
+            .clone()
+            .filter(|song| song.id == id)
+            .or_else(|| Some(self.queued.get(id)?.into_description()))
+            .or_else(|| Some(self.songs.get(id)?.into_description()))
+    }
+
+    pub fn is_playing_queued(&self) -> bool {
+        self.playing_queued.is_some()
+    }
+
+    fn has_current_song(&self) -> bool {
+        self.list_position.is_some() || self.playing_queued.is_some()
+    }
+
     pub fn is_playing(&self) -> bool {
-        self.is_playing && self.list_position.is_some()
+        self.is_playing && self.has_current_song()
     }
 
     pub fn is_shuffled(&self) -> bool {
@@ -66,6 +135,21 @@ impl PlaybackState {
         Some(song?.into_description())
     }
 
+    // Songs that aren't loaded yet are assumed to be playable, songs left out of the queue never are
+    fn is_playable_at(&self, i: usize) -> bool {
+        let position = if self.is_shuffled {
+            self.index.get(i)
+        } else {
+            Some(i)
+        };
+        position.map(|p| p >= self.start).unwrap_or(true)
+            && self.index(i).map(|song| song.is_playable()).unwrap_or(true)
+    }
+
+    pub fn queue_start(&self) -> usize {
+        self.start
+    }
+
     pub fn current_source(&self) -> Option<&SongsSource> {
         self.source.as_ref()
     }
@@ -75,22 +159,120 @@ impl PlaybackState {
     }
 
     pub fn current_song_id(&self) -> Option<String> {
-        Some(self.index(self.list_position?)?.id)
+        Some(self.current_song()?.id)
     }
 
     pub fn current_song(&self) -> Option<SongDescription> {
-        self.index(self.list_position?)
+        match self.playing_queued.as_ref() {
+            Some(song) => Some(song.clone()),
+            None => self.index(self.list_position?),
+        }
     }
 
     fn next_id(&self) -> Option<String> {
-        self.next_index()
-            .and_then(|i| Some(self.songs().index(i)?.description().id.clone()))
+        if self.repeat == RepeatMode::Song {
+            return self.current_song_id();
+        }
+        let queued = self
+            .queued
+            .index(0)
+            .map(|song| song.description().id.clone());
+        queued.or_else(|| {
+            self.next_index()
+                .and_then(|i| Some(self.songs().index(i)?.description().id.clone()))
+        })
+    }
+
+    // What plays once the current track ends, not counting repeats of that track
+    pub fn next_song(&self) -> Option<SongDescription> {
+        let id = self.next_id()?;
+        Some(id)
+            .filter(|id| Some(id) != self.current_song_id().as_ref())
+            .and_then(|id| self.song(&id))
+    }
+
+    // How many songs are left after the current one, and how long they last if they're all loaded
+    pub fn remaining_songs(&self) -> Option<(usize, Option<u64>)> {
+        if !self.has_current_song() {
+            return None;
+        }
+        let (queued, source) = (self.queued_summary, self.source_summary);
+        let duration = Some(queued.duration + source.duration).filter(|_| source.unloaded == 0);
+        Some((queued.count + source.count, duration))
+    }
+
+    // Where in the (possibly shuffled) source the songs that are yet to come start
+    fn next_position(&self) -> usize {
+        self.list_position.map(|p| p + 1).unwrap_or(0)
+    }
+
+    // Songs left out of the queue and unplayable ones never count
+    fn count_source_song(&mut self, j: usize, counted: bool) {
+        if j < self.start {
+            return;
+        }
+        let duration = match self.songs.index(j) {
+            Some(song) if !song.description().is_playable() => return,
+            Some(song) => Some(song.description().duration),
+            None => None,
+        };
+        if counted {
+            self.source_summary.add(duration);
+        } else {
+            self.source_summary.remove(duration);
+        }
+    }
+
+    // When the source itself changes, as opposed to the position in it
+    fn recount_source(&mut self) {
+        self.source_summary = Default::default();
+        // The order doesn't matter here, which is just as well: shuffled tracks aren't picked until they're needed
+        let next = self.next_position();
+        let unplayed: Vec<usize> = if self.is_shuffled {
+            let played: HashSet<usize> = (0..next).filter_map(|i| self.index.get(i)).collect();
+            (0..self.songs.len())
+                .filter(|j| !played.contains(j))
+                .collect()
+        } else {
+            (next..self.songs.len()).collect()
+        };
+        for j in unplayed {
+            self.count_source_song(j, true);
+        }
+    }
+
+    // Only the songs between the old and the new position go from ahead to behind, or the other way around
+    fn set_list_position(&mut self, position: Option<usize>) {
+        let before = self.next_position();
+        self.list_position = position;
+        let after = self.next_position();
+        for k in before.min(after)..before.max(after) {
+            let j = if self.is_shuffled {
+                self.index.get(k)
+            } else {
+                Some(k)
+            };
+            if let Some(j) = j.filter(|&j| j < self.songs.len()) {
+                self.count_source_song(j, after < before);
+            }
+        }
+    }
+
+    // Whether there's anything to skip to or back to, in either section
+    pub fn has_next(&self) -> bool {
+        self.queued.len() > 0 || self.next_index().is_some()
+    }
+
+    pub fn has_prev(&self) -> bool {
+        (self.playing_queued.is_some() && self.list_position.is_some())
+            || self.prev_index().is_some()
     }
 
     fn clear(&mut self, source: Option<SongsSource>) -> SongListModelPending {
         self.source = source;
         self.index = Default::default();
         self.list_position = None;
+        self.start = 0;
         self.songs.clear()
     }
 
@@ -98,32 +280,75 @@ impl PlaybackState {
     fn set_batch(&mut self, source: Option<SongsSource>, song_batch: SongBatch) -> bool {
         let ok = self.clear(source).and(|s| s.add(song_batch)).commit();
         self.index.resize(self.songs.len());
+        self.recount_source();
         ok
     }
 
     fn add_batch(&mut self, song_batch: SongBatch) -> bool {
         let ok = self.songs.add(song_batch).commit();
         self.index.resize(self.songs.len());
+        self.recount_source();
         ok
     }
 
+    // Returns whether it changed
+    fn set_start(&mut self, start: usize) -> bool {
+        if self.start == start {
+            return false;
+        }
+        self.start = start;
+        self.recount_source();
+        true
+    }
+
     // Replaces (!) the current playlist with a bunch of songs (not batched, not expected to grow)
     fn set_queue(&mut self, tracks: Vec<SongDescription>) {
         self.clear(None).and(|s| s.append(tracks)).commit();
         self.index.grow(self.songs.len());
+        self.recount_source();
     }
 
+    // Queued songs come after the current one, whatever the source is
     pub fn queue(&mut self, tracks: Vec<SongDescription>) {
-        self.source = None;
-        self.songs.append(tracks).commit();
-        self.index.grow(self.songs.len());
+        for track in tracks.iter() {
+            self.queued_summary.add(Some(track.duration));
+        }
+        self.queued.append(tracks).commit();
+    }
+
+    fn clear_queued(&mut self) -> bool {
+        if self.queued.len() == 0 {
+            return false;
+        }
+        self.queued_summary = Default::default();
+        self.queued.clear().commit()
+    }
+
+    fn remove_queued(&mut self, ids: &[String]) {
+        for id in ids {
+            if let Some(song) = self.queued.get(id) {
+                self.queued_summary
+                    .remove(Some(song.description().duration));
+            }
+        }
+        self.queued.remove(ids).commit();
     }
 
     pub fn dequeue(&mut self, ids: &[String]) {
-        let current_id = self.current_song_id();
+        self.remove_queued(ids);
+        let current_id = self
+            .list_position
+            .and_then(|position| Some(self.index(position)?.id));
+        let removed_before_start = ids
+            .iter()
+            .filter_map(|id| self.songs.find_index(id))
+            .filter(|&i| i < self.start)
+            .count();
+        self.start -= removed_before_start;
         self.songs.remove(ids).commit();
         self.list_position = current_id.and_then(|id| self.songs.find_index(&id));
         self.index.shrink(self.songs.len());
+        self.recount_source();
     }
 
     // Update the current playing track (identified by a position in the list) if we're swapping songs
@@ -136,10 +361,15 @@ impl PlaybackState {
                 i if i == other_index => index,
                 _ => position,
             })
-            .map(|p| usize::min(p, len - 1))
+            .map(|p| usize::min(p, len - 1));
+        self.recount_source();
     }
 
     pub fn move_down(&mut self, id: &str) -> Option<usize> {
+        if let Some(index) = self.queued.find_index(id) {
+            self.queued.move_down(index).commit();
+            return Some(index);
+        }
         let index = self.songs.find_index(id)?;
         self.songs.move_down(index).commit();
         self.swap_pos(index + 1, index);
@@ -147,6 +377,13 @@ impl PlaybackState {
     }
 
     pub fn move_up(&mut self, id: &str) -> Option<usize> {
+        if let Some(index) = self.queued.find_index(id) {
+            if index == 0 {
+                return None;
+            }
+            self.queued.move_up(index).commit();
+            return Some(index);
+        }
         let index = self.songs.find_index(id).filter(|&index| index > 0)?;
         self.songs.move_up(index).commit();
         self.swap_pos(index - 1, index);
@@ -158,37 +395,102 @@ impl PlaybackState {
             return false;
         }
 
-        let found_index = self.songs.find_index(id);
+        // Unplayable songs are skipped, as if the next one had been picked
+        let len = self.songs.len();
+        let found_index = self.songs.find_index(id).and_then(|index| {
+            (index..len).find(|&i| {
+                self.songs
+                    .index(i)
+                    .map(|song| song.description().is_playable())
+                    .unwrap_or(true)
+            })
+        });
 
         if let Some(index) = found_index {
             // If shufflings songs, we make sure the track we just picked is the first to come up
             if self.is_shuffled {
                 self.index.reset_picking_first(index);
                 self.play_index(0);
+                self.recount_source();
             } else {
                 self.play_index(index);
             }
             true
+        } else if let Some(song) = self.queued.get(id).map(|song| song.into_description()) {
+            self.remove_queued(std::slice::from_ref(&song.id));
+            self.play_queued(song);
+            true
         } else {
             false
         }
     }
 
+    // Songs taken out of "Next in queue" as they start change the queue too
+    fn with_queued_changes(
+        &self,
+        queued_before: usize,
+        mut events: Vec<PlaybackEvent>,
+    ) -> Vec<PlaybackEvent> {
+        if self.queued.len() != queued_before {
+            events.push(PlaybackEvent::PlaylistChanged);
+        }
+        events
+    }
+
+    // Playing a queued song leaves the position in the source as is
+    fn play_queued(&mut self, song: SongDescription) -> String {
+        self.is_playing = true;
+        self.seek_position.set(0, true);
+        let id = song.id.clone();
+        self.playing_queued = Some(song);
+        id
+    }
+
     fn stop(&mut self) {
-        self.list_position = None;
+        self.playing_queued = None;
+        self.set_list_position(None);
         self.is_playing = false;
         self.seek_position.set(0, false);
     }
 
     fn play_index(&mut self, index: usize) -> Option<String> {
+        self.playing_queued = None;
         self.is_playing = true;
-        self.list_position.replace(index);
+        self.pick_until(index);
+        self.set_list_position(Some(index));
         self.seek_position.set(0, true);
-        self.index.next_until(index + 1);
         self.current_song_id()
     }
 
+    // Generates the shuffled mappings up to the i-th one
+    fn pick_until(&mut self, i: usize) {
+        if self.smart_shuffle {
+            let songs = &self.songs;
+            self.index.next_weighted_until(i + 1, |i| {
+                songs
+                    .index(i)
+                    .map(|song| play_history::shuffle_weight(&song.description().id))
+                    .unwrap_or(1.0)
+            });
+        } else {
+            self.index.next_until(i + 1);
+        }
+    }
+
     fn play_next(&mut self) -> Option<String> {
+        if self.repeat == RepeatMode::Song {
+            if let Some(song) = self.playing_queued.clone() {
+                return Some(self.play_queued(song));
+            }
+        } else {
+            // Queued songs are taken out of the queue as they start, unplayable ones are just dropped
+            while let Some(song) = self.queued.index(0).map(|song| song.into_description()) {
+                self.remove_queued(std::slice::from_ref(&song.id));
+                if song.is_playable() {
+                    return Some(self.play_queued(song));
+                }
+            }
+        }
         self.next_index().and_then(|i| {
             self.seek_position.set(0, true);
             self.play_index(i)
@@ -199,14 +501,22 @@ impl PlaybackState {
         let len = self.songs.len();
         self.list_position.and_then(|p| match self.repeat {
             RepeatMode::Song => Some(p),
-            RepeatMode::Playlist if len != 0 => Some((p + 1) % len),
-            RepeatMode::None => Some(p + 1).filter(|&i| i < len),
+            RepeatMode::Playlist if len != 0 => (1..=len)
+                .map(|n| (p + n) % len)
+                .find(|&i| self.is_playable_at(i)),
+            RepeatMode::None => (p + 1..len).find(|&i| self.is_playable_at(i)),
             _ => None,
         })
     }
 
     fn play_prev(&mut self) -> Option<String> {
-        self.prev_index().and_then(|i| {
+        // Going back from a queued song means going back to where the source was left
+        let prev = if self.playing_queued.is_some() {
+            self.list_position
+        } else {
+            self.prev_index()
+        };
+        prev.and_then(|i| {
             // Only jump to the previous track if we aren't more than 2 seconds (2,000 ms) into the current track.
             // Otherwise, seek to the start of the current track.
             // (This replicates the behavior of official Spotify clients.)
@@ -224,14 +534,16 @@ impl PlaybackState {
         let len = self.songs.len();
         self.list_position.and_then(|p| match self.repeat {
             RepeatMode::Song => Some(p),
-            RepeatMode::Playlist if len != 0 => Some((if p == 0 { len } else { p }) - 1),
-            RepeatMode::None => Some(p).filter(|&i| i > 0).map(|i| i - 1),
+            RepeatMode::Playlist if len != 0 => (1..=len)
+                .map(|n| (p + len - n) % len)
+                .find(|&i| self.is_playable_at(i)),
+            RepeatMode::None => (0..p).rev().find(|&i| self.is_playable_at(i)),
             _ => None,
         })
     }
 
     fn toggle_play(&mut self) -> Option<bool> {
-        if self.list_position.is_some() {
+        if self.has_current_song() {
             self.is_playing = !self.is_playing;
 
             match self.is_playing {
@@ -249,6 +561,32 @@ impl PlaybackState {
         self.is_shuffled = shuffled;
         let old = self.list_position.replace(0).unwrap_or(0);
         self.index.reset_picking_first(old);
+        self.recount_source();
+    }
+
+    // Shuffles the source anew and starts it from a random track, among the ones loaded that can be played
+    fn play_shuffled(&mut self) -> Option<String> {
+        let was_shuffled = self.is_shuffled;
+        self.is_shuffled = true;
+        self.index.reset();
+        let first = (0..self.songs.len()).find(|&i| {
+            self.pick_until(i);
+            self.index(i).is_some() && self.is_playable_at(i)
+        });
+        let id = match first {
+            Some(first) => self.play_index(first),
+            None => {
+                self.is_shuffled = was_shuffled;
+                None
+            }
+        };
+        self.recount_source();
+        id
+    }
+
+    // Only changes how the next tracks get picked, what was already shuffled stays as is
+    pub fn set_smart_shuffle(&mut self, smart_shuffle: bool) {
+        self.smart_shuffle = smart_shuffle;
     }
 
     pub fn available_devices(&self) -> &Vec<ConnectDevice> {
@@ -267,12 +605,18 @@ impl Default for PlaybackState {
             current_device: Device::Local,
             index: LazyRandomIndex::default(),
             songs: SongListModel::new(50),
+            queued: SongListModel::new(50),
+            playing_queued: None,
             list_position: None,
+            start: 0,
             seek_position: PositionMillis::new(1.0),
             source: None,
             repeat: RepeatMode::None,
             is_playing: false,
             is_shuffled: false,
+            smart_shuffle: false,
+            queued_summary: Default::default(),
+            source_summary: Default::default(),
         }
     }
 }
@@ -288,17 +632,26 @@ pub enum PlaybackAction {
     ToggleRepeat,
     ToggleShuffle,
     Seek(u32),
+    // Relative to the current position, in seconds
+    SeekBy(i32),
     // I can't remember the diff betweek Seek and SyncSeek right now. Probably the source of the action
     SyncSeek(u32),
     Load(String),
-    LoadSongs(Vec<SongDescription>),
-    LoadPagedSongs(SongsSource, SongBatch),
+    // The songs, and the index of the first one to actually queue
+    LoadSongs(Vec<SongDescription>, usize),
+    LoadPagedSongs(SongsSource, SongBatch, usize),
+    // For batches loaded in the background, ignored if another source started playing in the meantime
+    AppendPagedSongs(SongsSource, SongBatch),
     SetVolume(f64),
     Next,
     Previous,
     Preload,
     Queue(Vec<SongDescription>),
     Dequeue(String),
+    // Empties "Next in queue", the source is left alone
+    ClearQueue,
+    // Turns shuffle on and starts the source from a random track
+    PlayShuffled,
     SwitchDevice(Device),
     SetAvailableDevices(Vec<ConnectDevice>),
 }
@@ -392,7 +745,8 @@ impl UpdatableState for PlaybackState {
                 vec![PlaybackEvent::ShuffleChanged(self.is_shuffled)]
             }
             PlaybackAction::Next => {
-                if let Some(id) = self.play_next() {
+                let queued = self.queued.len();
+                let events = if let Some(id) = self.play_next() {
                     vec![
                         PlaybackEvent::TrackChanged(id),
                         PlaybackEvent::PlaybackResumed,
@@ -400,7 +754,8 @@ impl UpdatableState for PlaybackState {
                 } else {
                     self.stop();
                     vec![PlaybackEvent::PlaybackStopped]
-                }
+                };
+                self.with_queued_changes(queued, events)
             }
             PlaybackAction::Stop => {
                 self.stop();
@@ -417,14 +772,36 @@ impl UpdatableState for PlaybackState {
                 }
             }
             PlaybackAction::Load(id) => {
-                if self.play(&id) {
+                let queued = self.queued.len();
+                // Unplayable songs are skipped, what ends up playing might not be the one asked for
+                let played = if self.play(&id) {
+                    self.current_song_id()
+                } else {
+                    None
+                };
+                let events = if let Some(played) = played {
                     vec![
-                        PlaybackEvent::TrackChanged(id),
+                        PlaybackEvent::TrackChanged(played),
                         PlaybackEvent::PlaybackResumed,
                     ]
                 } else {
                     vec![]
+                };
+                self.with_queued_changes(queued, events)
+            }
+            PlaybackAction::PlayShuffled => {
+                let was_shuffled = self.is_shuffled;
+                let Some(id) = self.play_shuffled() else {
+                    return vec![];
+                };
+                let mut events = vec![
+                    PlaybackEvent::TrackChanged(id),
+                    PlaybackEvent::PlaybackResumed,
+                ];
+                if !was_shuffled {
+                    events.push(PlaybackEvent::ShuffleChanged(true));
                 }
+                events
             }
             PlaybackAction::Preload => {
                 if let Some(id) = self.next_id() {
@@ -433,24 +810,36 @@ impl UpdatableState for PlaybackState {
                     vec![]
                 }
             }
-            PlaybackAction::LoadPagedSongs(source, batch)
+            PlaybackAction::LoadPagedSongs(source, batch, start)
                 if Some(&source) == self.source.as_ref() =>
             {
-                if self.add_batch(batch) {
+                let start_changed = self.set_start(start);
+                if self.add_batch(batch) || start_changed {
                     vec![PlaybackEvent::PlaylistChanged]
                 } else {
                     vec![]
                 }
             }
-            PlaybackAction::LoadPagedSongs(source, batch)
+            PlaybackAction::LoadPagedSongs(source, batch, start)
                 if Some(&source) != self.source.as_ref() =>
             {
                 debug!("new source: {:?}", &source);
                 self.set_batch(Some(source), batch);
+                self.set_start(start);
                 vec![PlaybackEvent::PlaylistChanged, PlaybackEvent::SourceChanged]
             }
-            PlaybackAction::LoadSongs(tracks) => {
-                self.set_queue(tracks);
+            PlaybackAction::AppendPagedSongs(source, batch)
+                if Some(&source) == self.source.as_ref() =>
+            {
+                if self.add_batch(batch) {
+                    vec![PlaybackEvent::PlaylistChanged]
+                } else {
+                    vec![]
+                }
+            }
+            PlaybackAction::AppendPagedSongs(_, _) => vec![],
+            PlaybackAction::LoadSongs(mut tracks, start) => {
+                self.set_queue(tracks.split_off(start.min(tracks.len())));
                 vec![PlaybackEvent::PlaylistChanged, PlaybackEvent::SourceChanged]
             }
             PlaybackAction::Queue(tracks) => {
@@ -461,10 +850,26 @@ impl UpdatableState for PlaybackState {
                 self.dequeue(&[id]);
                 vec![PlaybackEvent::PlaylistChanged]
             }
+            PlaybackAction::ClearQueue => {
+                if self.clear_queued() {
+                    vec![PlaybackEvent::PlaylistChanged]
+                } else {
+                    vec![]
+                }
+            }
             PlaybackAction::Seek(pos) => {
                 self.seek_position.set(pos as u64 * 1000, true);
                 vec![PlaybackEvent::TrackSeeked(pos)]
             }
+            PlaybackAction::SeekBy(offset) => {
+                let Some(duration) = self.current_song().map(|s| s.duration) else {
+                    return vec![];
+                };
+                let current = (self.seek_position.current() / 1000) as i64;
+                let pos = (current + offset as i64 * 1000).clamp(0, duration as i64) as u32;
+                self.seek_position.set(pos as u64 * 1000, self.is_playing());
+                vec![PlaybackEvent::TrackSeeked(pos)]
+            }
             PlaybackAction::SyncSeek(pos) => {
                 self.seek_position.set(pos as u64 * 1000, true);
                 vec![PlaybackEvent::SeekSynced(pos)]
@@ -533,23 +938,7 @@ impl PositionMillis {
 mod tests {
 
     use super::*;
-    use crate::app::models::AlbumRef;
-
-    fn song(id: &str) -> SongDescription {
-        SongDescription {
-            id: id.to_string(),
-            uri: "".to_string(),
-            title: "Title".to_string(),
-            artists: vec![],
-            album: AlbumRef {
-                id: "".to_string(),
-                name: "".to_string(),
-            },
-            duration: 1000,
-            art: None,
-            track_number: None,
-        }
-    }
+    use crate::app::models::fixtures::song;
 
     impl PlaybackState {
         fn current_position(&self) -> Option<usize> {
@@ -568,6 +957,10 @@ mod tests {
                 .map(|s| s.id.clone())
                 .collect()
         }
+
+        fn queued_ids(&self) -> Vec<String> {
+            self.queued_songs().map_collect(|s| s.id)
+        }
     }
 
     #[test]
@@ -583,7 +976,7 @@ mod tests {
     #[test]
     fn test_play_one() {
         let mut state = PlaybackState::default();
-        state.queue(vec![song("foo")]);
+        state.set_queue(vec![song("foo")]);
 
         state.play("foo");
         assert!(state.is_playing());
@@ -596,23 +989,138 @@ mod tests {
         assert!(!state.is_playing());
     }
 
+    #[test]
+    fn test_seek_by() {
+        let mut state = PlaybackState::default();
+        state.set_queue(vec![SongDescription {
+            duration: 60000,
+            ..song("foo")
+        }]);
+        state.play("foo");
+        state.toggle_play();
+
+        let seek_by = |state: &mut PlaybackState, offset| {
+            state.update_with(Cow::Owned(PlaybackAction::SeekBy(offset)))
+        };
+        assert!(matches!(
+            seek_by(&mut state, 10)[..],
+            [PlaybackEvent::TrackSeeked(10000)]
+        ));
+        assert!(matches!(
+            seek_by(&mut state, -20)[..],
+            [PlaybackEvent::TrackSeeked(0)]
+        ));
+        assert!(matches!(
+            seek_by(&mut state, 100)[..],
+            [PlaybackEvent::TrackSeeked(60000)]
+        ));
+    }
+
     #[test]
     fn test_queue() {
         let mut state = PlaybackState::default();
-        state.queue(vec![song("1"), song("2"), song("3")]);
+        state.set_queue(vec![song("1"), song("2"), song("3")]);
 
         assert_eq!(state.songs().len(), 3);
 
         state.play("2");
 
         state.queue(vec![song("4")]);
-        assert_eq!(state.songs().len(), 4);
+        assert_eq!(state.songs().len(), 3);
+        assert_eq!(state.queued_ids(), vec!["4"]);
+        assert_eq!(state.next_id(), Some("4".to_string()));
+    }
+
+    #[test]
+    fn test_play_queued() {
+        let mut state = PlaybackState::default();
+        state.set_queue(vec![song("1"), song("2"), song("3")]);
+        state.play("1");
+        state.queue(vec![song("a"), song("b")]);
+
+        state.play_next();
+        assert_eq!(state.current_song_id(), Some("a".to_string()));
+        assert_eq!(state.queued_ids(), vec!["b"]);
+        assert_eq!(state.current_position(), Some(0));
+
+        // Back to where the source was left
+        state.play_prev();
+        assert_eq!(state.current_song_id(), Some("1".to_string()));
+
+        state.play_next();
+        assert_eq!(state.current_song_id(), Some("b".to_string()));
+        assert!(state.queued_ids().is_empty());
+
+        state.play_next();
+        assert_eq!(state.current_song_id(), Some("2".to_string()));
+    }
+
+    #[test]
+    fn test_clear_queue() {
+        let mut state = PlaybackState::default();
+        state.set_queue(vec![song("1"), song("2")]);
+        state.play("1");
+        state.queue(vec![song("a"), song("b")]);
+
+        let events = state.update_with(Cow::Owned(PlaybackAction::ClearQueue));
+        assert_eq!(events.len(), 1);
+        assert!(state.queued_ids().is_empty());
+        assert_eq!(state.song_ids(), vec!["1", "2"]);
+        assert_eq!(state.next_id(), Some("2".to_string()));
+
+        let events = state.update_with(Cow::Owned(PlaybackAction::ClearQueue));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_next_song() {
+        let mut state = PlaybackState::default();
+        state.set_queue(vec![song("1"), song("2")]);
+        state.play("1");
+        assert_eq!(state.next_song().map(|s| s.id), Some("2".to_string()));
+
+        state.queue(vec![song("a")]);
+        assert_eq!(state.next_song().map(|s| s.id), Some("a".to_string()));
+
+        // The same track again isn't worth announcing
+        state.update_with(Cow::Owned(PlaybackAction::SetRepeatMode(RepeatMode::Song)));
+        assert!(state.next_song().is_none());
+    }
+
+    #[test]
+    fn test_remaining_songs() {
+        let mut state = PlaybackState::default();
+        state.set_queue(vec![song("1"), song("2"), song("3")]);
+        assert_eq!(state.remaining_songs(), None);
+
+        state.play("2");
+        assert_eq!(state.remaining_songs(), Some((1, Some(1000))));
+
+        state.queue(vec![song("a"), song("b")]);
+        assert_eq!(state.remaining_songs(), Some((3, Some(3000))));
+
+        state.play_prev();
+        assert_eq!(state.remaining_songs(), Some((4, Some(4000))));
+
+        state.dequeue(&["a".to_string()]);
+        assert_eq!(state.remaining_songs(), Some((3, Some(3000))));
+    }
+
+    #[test]
+    fn test_remaining_songs_shuffled() {
+        let mut state = PlaybackState::default();
+        state.set_queue(vec![song("1"), song("2"), song("3")]);
+        state.update_with(Cow::Owned(PlaybackAction::PlayShuffled));
+        assert_eq!(state.remaining_songs(), Some((2, Some(2000))));
+
+        state.play_next();
+        assert_eq!(state.remaining_songs(), Some((1, Some(1000))));
     }
 
     #[test]
     fn test_play_multiple() {
         let mut state = PlaybackState::default();
-        state.queue(vec![song("1"), song("2"), song("3")]);
+        state.set_queue(vec![song("1"), song("2"), song("3")]);
         assert_eq!(state.songs().len(), 3);
 
         state.play("2");
@@ -652,10 +1160,71 @@ mod tests {
         assert_eq!(state.current_song_id(), Some("1".to_string()));
     }
 
+    #[test]
+    fn test_skip_unplayable() {
+        let unplayable = |id| SongDescription {
+            restriction: Some(Restriction::Market),
+            ..song(id)
+        };
+        let mut state = PlaybackState::default();
+        state.set_queue(vec![song("1"), unplayable("2"), song("3"), unplayable("4")]);
+
+        let events = state.update_with(Cow::Owned(PlaybackAction::Load("2".to_string())));
+        assert!(matches!(
+            &events[..],
+            [PlaybackEvent::TrackChanged(id), PlaybackEvent::PlaybackResumed] if id == "3"
+        ));
+        assert_eq!(state.current_song_id(), Some("3".to_string()));
+        assert_eq!(state.prev_id(), Some("1".to_string()));
+        assert!(state.next_index().is_none());
+
+        state.play_prev();
+        assert_eq!(state.current_song_id(), Some("1".to_string()));
+        assert_eq!(state.next_id(), Some("3".to_string()));
+
+        let events = state.update_with(Cow::Owned(PlaybackAction::Load("4".to_string())));
+        assert!(events.is_empty());
+        assert_eq!(state.current_song_id(), Some("1".to_string()));
+
+        state.repeat = RepeatMode::Playlist;
+        assert_eq!(state.prev_id(), Some("3".to_string()));
+    }
+
+    #[test]
+    fn test_play_from_here() {
+        let mut state = PlaybackState::default();
+        let batch = SongBatch {
+            songs: vec![song("1"), song("2"), song("3"), song("4")],
+            batch: Batch {
+                offset: 0,
+                batch_size: 4,
+                total: 4,
+            },
+        };
+        state.update_with(Cow::Owned(PlaybackAction::LoadPagedSongs(
+            SongsSource::SavedTracks,
+            batch,
+            2,
+        )));
+        state.play("3");
+        assert!(state.prev_index().is_none());
+        assert_eq!(state.next_id(), Some("4".to_string()));
+
+        state.repeat = RepeatMode::Playlist;
+        state.play_next();
+        assert_eq!(state.next_id(), Some("3".to_string()));
+
+        state.update_with(Cow::Owned(PlaybackAction::LoadSongs(
+            vec![song("1"), song("2"), song("3")],
+            1,
+        )));
+        assert_eq!(state.song_ids(), vec!["2", "3"]);
+    }
+
     #[test]
     fn test_shuffle() {
         let mut state = PlaybackState::default();
-        state.queue(vec![song("1"), song("2"), song("3"), song("4")]);
+        state.set_queue(vec![song("1"), song("2"), song("3"), song("4")]);
 
         assert_eq!(state.songs().len(), 4);
 
@@ -684,10 +1253,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_play_shuffled() {
+        let mut state = PlaybackState::default();
+        state.set_queue(vec![song("1"), song("2"), song("3"), song("4")]);
+
+        let events = state.update_with(Cow::Owned(PlaybackAction::PlayShuffled));
+        assert!(state.is_shuffled());
+        assert!(state.is_playing());
+        assert_eq!(state.current_position(), Some(0));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, PlaybackEvent::ShuffleChanged(true))));
+
+        // Every track comes up once
+        let mut played = vec![state.current_song_id().unwrap()];
+        while let Some(id) = state.play_next() {
+            played.push(id);
+        }
+        played.sort();
+        assert_eq!(played, vec!["1", "2", "3", "4"]);
+    }
+
+    #[test]
+    fn test_play_shuffled_skips_unplayable() {
+        let unplayable = |id| SongDescription {
+            restriction: Some(Restriction::Market),
+            ..song(id)
+        };
+
+        // Whatever the shuffle, the only track that can be played comes up first
+        for _ in 0..10 {
+            let mut state = PlaybackState::default();
+            state.set_queue(vec![unplayable("1"), unplayable("2"), song("3")]);
+            state.update_with(Cow::Owned(PlaybackAction::PlayShuffled));
+            assert_eq!(state.current_song_id(), Some("3".to_string()));
+        }
+    }
+
     #[test]
     fn test_shuffle_queue() {
         let mut state = PlaybackState::default();
-        state.queue(vec![song("1"), song("2"), song("3")]);
+        state.set_queue(vec![song("1"), song("2"), song("3")]);
 
         state.set_shuffled(true);
         assert!(state.is_shuffled());
@@ -698,21 +1305,35 @@ mod tests {
         assert!(!state.is_shuffled());
 
         let ids = state.song_ids();
-        assert_eq!(
-            ids,
-            vec![
-                "1".to_string(),
-                "2".to_string(),
-                "3".to_string(),
-                "4".to_string()
-            ]
-        );
+        assert_eq!(ids, vec!["1".to_string(), "2".to_string(), "3".to_string()]);
+        assert_eq!(state.queued_ids(), vec!["4"]);
+    }
+
+    #[test]
+    fn test_set_shuffle_and_repeat() {
+        let mut state = PlaybackState::default();
+        state.set_queue(vec![song("1"), song("2"), song("3")]);
+
+        let events = state.update_with(Cow::Owned(PlaybackAction::SetShuffled(true)));
+        assert_eq!(events.len(), 1);
+        assert!(state.is_shuffled());
+
+        let events = state.update_with(Cow::Owned(PlaybackAction::SetShuffled(true)));
+        assert!(events.is_empty());
+        assert!(state.is_shuffled());
+
+        let events = state.update_with(Cow::Owned(PlaybackAction::SetRepeatMode(RepeatMode::Song)));
+        assert_eq!(events.len(), 1);
+        assert_eq!(state.repeat_mode(), RepeatMode::Song);
+
+        let events = state.update_with(Cow::Owned(PlaybackAction::SetRepeatMode(RepeatMode::Song)));
+        assert!(events.is_empty());
     }
 
     #[test]
     fn test_move() {
         let mut state = PlaybackState::default();
-        state.queue(vec![song("1"), song("2"), song("3")]);
+        state.set_queue(vec![song("1"), song("2"), song("3")]);
 
         state.play("2");
         assert!(state.is_playing());
@@ -743,7 +1364,7 @@ mod tests {
     #[test]
     fn test_dequeue_last() {
         let mut state = PlaybackState::default();
-        state.queue(vec![song("1"), song("2"), song("3")]);
+        state.set_queue(vec![song("1"), song("2"), song("3")]);
 
         state.play("3");
         assert!(state.is_playing());
@@ -755,7 +1376,7 @@ mod tests {
     #[test]
     fn test_dequeue_a_few_songs() {
         let mut state = PlaybackState::default();
-        state.queue(vec![
+        state.set_queue(vec![
             song("1"),
             song("2"),
             song("3"),
@@ -774,7 +1395,7 @@ mod tests {
     #[test]
     fn test_dequeue_all() {
         let mut state = PlaybackState::default();
-        state.queue(vec![song("3")]);
+        state.set_queue(vec![song("3")]);
 
         state.play("3");
         assert!(state.is_playing());
@@ -1,5 +1,5 @@
 use std::borrow::Cow;
-use std::cmp::PartialEq;
+use std::cmp::{PartialEq, Reverse};
 
 use super::{pagination::Pagination, BrowserAction, BrowserEvent, UpdatableState};
 use crate::app::models::*;
@@ -13,6 +13,7 @@ pub enum ScreenName {
     Artist(String),
     PlaylistDetails(String),
     User(String),
+    ShowDetails(String),
 }
 
 impl ScreenName {
@@ -24,6 +25,21 @@ impl ScreenName {
             Self::Artist(s) => Cow::Owned(format!("artist_{s}")),
             Self::PlaylistDetails(s) => Cow::Owned(format!("playlist_{s}")),
             Self::User(s) => Cow::Owned(format!("user_{s}")),
+            Self::ShowDetails(s) => Cow::Owned(format!("show_{s}")),
+        }
+    }
+
+    // The reverse of the above
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        match identifier.split_once('_') {
+            None if identifier == "home" => Some(Self::Home),
+            None if identifier == "search" => Some(Self::Search),
+            Some(("album", s)) => Some(Self::AlbumDetails(s.to_string())),
+            Some(("artist", s)) => Some(Self::Artist(s.to_string())),
+            Some(("playlist", s)) => Some(Self::PlaylistDetails(s.to_string())),
+            Some(("user", s)) => Some(Self::User(s.to_string())),
+            Some(("show", s)) => Some(Self::ShowDetails(s.to_string())),
+            _ => None,
         }
     }
 }
@@ -139,18 +155,37 @@ impl UpdatableState for PlaylistDetailsState {
                 self.songs.remove(&uris[..]).commit();
                 vec![BrowserEvent::PlaylistTracksRemoved(self.id.clone())]
             }
+            BrowserAction::InsertTracksInPlaylist(id, songs) if id == &self.id => {
+                self.songs.insert(songs.clone()).commit();
+                vec![BrowserEvent::PlaylistTracksInserted(self.id.clone())]
+            }
             _ => vec![],
         }
     }
 }
 
+// How the releases of an artist are ordered
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReleaseSort {
+    // As Spotify lists them
+    #[default]
+    Default,
+    NewestFirst,
+    OldestFirst,
+    MostPopular,
+}
+
 pub struct ArtistState {
     pub id: String,
     pub name: ScreenName,
     pub artist: Option<String>,
     pub next_page: Pagination<String>,
+    // Read the releases from here, they're sorted
     pub albums: ListStore<AlbumModel>,
     pub top_tracks: SongListModel,
+    pub release_sort: ReleaseSort,
+    // In the order they were loaded
+    releases: Vec<AlbumDescription>,
 }
 
 impl ArtistState {
@@ -162,8 +197,44 @@ impl ArtistState {
             next_page: Pagination::new(id, 20),
             albums: ListStore::new(),
             top_tracks: SongListModel::new(10),
+            release_sort: ReleaseSort::Default,
+            releases: vec![],
         }
     }
+
+    // Sorting only some of the releases would be misleading, they're shown as Spotify lists them until all are loaded
+    fn is_sorted(&self) -> bool {
+        self.release_sort != ReleaseSort::Default && self.next_page.next_offset.is_none()
+    }
+
+    // Releases without a date or popularity go last either way
+    fn sorted_releases(&self) -> Vec<&AlbumDescription> {
+        let mut releases: Vec<&AlbumDescription> = self.releases.iter().collect();
+        if !self.is_sorted() {
+            return releases;
+        }
+        match self.release_sort {
+            ReleaseSort::Default => {}
+            ReleaseSort::NewestFirst => releases.sort_by_key(|&a| Reverse(&a.release_date)),
+            ReleaseSort::OldestFirst => {
+                releases.sort_by(|a, b| match (&a.release_date, &b.release_date) {
+                    (Some(a), Some(b)) => a.cmp(b),
+                    (a, b) => b.is_some().cmp(&a.is_some()),
+                })
+            }
+            ReleaseSort::MostPopular => releases.sort_by_key(|&a| Reverse(a.popularity)),
+        }
+        releases
+    }
+
+    fn update_albums(&mut self) {
+        let albums: Vec<AlbumModel> = self
+            .sorted_releases()
+            .into_iter()
+            .map(|a| a.into())
+            .collect();
+        self.albums.replace_all(albums.into_iter());
+    }
 }
 
 impl UpdatableState for ArtistState {
@@ -180,9 +251,9 @@ impl UpdatableState for ArtistState {
                     mut top_tracks,
                 } = *details.clone();
                 self.artist = Some(name);
-                self.albums
-                    .replace_all(albums.into_iter().map(|a| a.into()));
-                self.next_page.reset_count(self.albums.len());
+                self.releases = albums;
+                self.update_albums();
+                self.next_page.reset_count(self.releases.len());
 
                 top_tracks.truncate(5);
                 self.top_tracks.append(top_tracks).commit();
@@ -190,8 +261,28 @@ impl UpdatableState for ArtistState {
                 vec![BrowserEvent::ArtistDetailsUpdated(id)]
             }
             BrowserAction::AppendArtistReleases(id, albums) if id == &self.id => {
+                // A page can come twice, when loading them all for sorting while scrolling down for instance
+                let new_albums: Vec<AlbumDescription> = albums
+                    .iter()
+                    .filter(|a| !self.releases.iter().any(|r| r.id == a.id))
+                    .cloned()
+                    .collect();
+                if new_albums.is_empty() && !albums.is_empty() {
+                    return vec![];
+                }
                 self.next_page.set_loaded_count(albums.len());
-                self.albums.extend(albums.iter().map(|a| a.into()));
+                self.releases.extend(new_albums.iter().cloned());
+                // Spotify's order is kept by just adding the new ones at the end, the list is sorted (once) when the last ones come
+                if self.is_sorted() {
+                    self.update_albums();
+                } else {
+                    self.albums.extend(new_albums.iter().map(|a| a.into()));
+                }
+                vec![BrowserEvent::ArtistDetailsUpdated(self.id.clone())]
+            }
+            BrowserAction::SetArtistReleaseSort(id, sort) if id == &self.id => {
+                self.release_sort = *sort;
+                self.update_albums();
                 vec![BrowserEvent::ArtistDetailsUpdated(self.id.clone())]
             }
             _ => vec![],
@@ -208,6 +299,7 @@ pub struct HomeState {
     pub next_playlists_page: Pagination<()>,
     pub playlists: ListStore<AlbumModel>,
     pub saved_tracks: SongListModel,
+    pub saved_episodes: SongListModel,
 }
 
 impl Default for HomeState {
@@ -220,6 +312,7 @@ impl Default for HomeState {
             next_playlists_page: Pagination::new((), 30),
             playlists: ListStore::new(),
             saved_tracks: SongListModel::new(50),
+            saved_episodes: SongListModel::new(50),
         }
     }
 }
@@ -321,6 +414,26 @@ impl UpdatableState for HomeState {
                 self.saved_tracks.remove(&tracks[..]).commit();
                 vec![BrowserEvent::SavedTracksUpdated]
             }
+            BrowserAction::AppendSavedEpisodes(song_batch) => {
+                if self.saved_episodes.add(*song_batch.clone()).commit() {
+                    vec![BrowserEvent::SavedEpisodesUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            BrowserAction::SetSavedEpisodes(song_batch) => {
+                let song_batch = *song_batch.clone();
+                if self
+                    .saved_episodes
+                    .clear()
+                    .and(|s| s.add(song_batch))
+                    .commit()
+                {
+                    vec![BrowserEvent::SavedEpisodesUpdated]
+                } else {
+                    vec![]
+                }
+            }
             _ => vec![],
         }
     }
@@ -364,13 +477,18 @@ impl UpdatableState for SearchState {
     }
 }
 
-// Screen when we click on the name of a playlist owner
+// Screen when we click on the name of a playlist owner (or on our own profile)
 pub struct UserState {
     pub id: String,
     pub name: ScreenName,
     pub user: Option<String>,
+    pub followers: Option<u32>,
+    pub following: Option<u32>,
     pub next_page: Pagination<String>,
+    // Public playlists only
     pub playlists: ListStore<AlbumModel>,
+    // Playlists created by the user, which they can make public or private on their own profile
+    pub owned_playlists: Vec<PlaylistDescription>,
 }
 
 impl UserState {
@@ -379,10 +497,53 @@ impl UserState {
             id: id.clone(),
             name: ScreenName::User(id.clone()),
             user: None,
+            followers: None,
+            following: None,
             next_page: Pagination::new(id, 30),
             playlists: ListStore::new(),
+            owned_playlists: vec![],
         }
     }
+
+    // We get private playlists as well when looking at our own profile
+    fn public_playlists(
+        playlists: &[PlaylistDescription],
+    ) -> impl Iterator<Item = AlbumModel> + '_ {
+        playlists
+            .iter()
+            .filter(|p| p.public != Some(false))
+            .map(|p| p.into())
+    }
+
+    fn owned_by_user<'a>(
+        &'a self,
+        playlists: &'a [PlaylistDescription],
+    ) -> impl Iterator<Item = PlaylistDescription> + 'a {
+        playlists
+            .iter()
+            .filter(move |p| p.owner.id == self.id && p.public.is_some())
+            .cloned()
+    }
+
+    fn set_playlist_public(&mut self, id: &str, public: bool) -> bool {
+        let Some(playlist) = self.owned_playlists.iter_mut().find(|p| p.id == id) else {
+            return false;
+        };
+        if playlist.public == Some(public) {
+            return false;
+        }
+        playlist.public = Some(public);
+        if public {
+            self.playlists
+                .extend(std::iter::once(AlbumModel::from(&*playlist)));
+        } else {
+            let position = self.playlists.iter().position(|p| p.uri() == id);
+            if let Some(position) = position {
+                self.playlists.remove(position as u32);
+            }
+        }
+        true
+    }
 }
 
 impl UpdatableState for UserState {
@@ -395,20 +556,81 @@ impl UpdatableState for UserState {
                 let UserDescription {
                     id,
                     name,
+                    followers,
                     playlists,
                 } = *user.clone();
                 self.user = Some(name);
+                self.followers = followers;
                 self.playlists
-                    .replace_all(playlists.iter().map(|p| p.into()));
-                self.next_page.reset_count(self.playlists.len());
+                    .replace_all(Self::public_playlists(&playlists));
+                self.owned_playlists = self.owned_by_user(&playlists).collect();
+                self.next_page.reset_count(playlists.len());
 
                 vec![BrowserEvent::UserDetailsUpdated(id)]
             }
             BrowserAction::AppendUserPlaylists(id, playlists) if id == &self.id => {
                 self.next_page.set_loaded_count(playlists.len());
-                self.playlists.extend(playlists.iter().map(|p| p.into()));
+                self.playlists.extend(Self::public_playlists(playlists));
+                let mut owned: Vec<_> = self.owned_by_user(playlists).collect();
+                self.owned_playlists.append(&mut owned);
+                vec![BrowserEvent::UserDetailsUpdated(self.id.clone())]
+            }
+            BrowserAction::SetUserFollowing(id, following) if id == &self.id => {
+                self.following = Some(*following);
                 vec![BrowserEvent::UserDetailsUpdated(self.id.clone())]
             }
+            BrowserAction::SetPlaylistPublic(id, public) => {
+                if self.set_playlist_public(id, *public) {
+                    vec![BrowserEvent::UserDetailsUpdated(self.id.clone())]
+                } else {
+                    vec![]
+                }
+            }
+            _ => vec![],
+        }
+    }
+}
+
+// A podcast and its episodes
+pub struct ShowDetailsState {
+    pub id: String,
+    pub name: ScreenName,
+    pub show: Option<ShowDescription>,
+    pub next_page: Pagination<String>,
+}
+
+impl ShowDetailsState {
+    pub fn new(id: String) -> Self {
+        Self {
+            id: id.clone(),
+            name: ScreenName::ShowDetails(id.clone()),
+            show: None,
+            next_page: Pagination::new(id, 50),
+        }
+    }
+}
+
+impl UpdatableState for ShowDetailsState {
+    type Action = BrowserAction;
+    type Event = BrowserEvent;
+
+    fn update_with(&mut self, action: Cow<Self::Action>) -> Vec<Self::Event> {
+        match action.as_ref() {
+            // Paging goes by the pages as Spotify sent them, missing episodes included
+            BrowserAction::SetShowDetails(show) if show.id == self.id => {
+                self.next_page.next_offset =
+                    show.episodes_batch.and_then(Batch::next).map(|b| b.offset);
+                self.show = Some(*show.clone());
+                vec![BrowserEvent::ShowDetailsLoaded(self.id.clone())]
+            }
+            BrowserAction::AppendShowEpisodes(id, batch) if id == &self.id => {
+                let Some(show) = self.show.as_mut() else {
+                    return vec![];
+                };
+                self.next_page.next_offset = batch.batch.next().map(|b| b.offset);
+                show.episodes.extend(batch.episodes.iter().cloned());
+                vec![BrowserEvent::ShowEpisodesAppended(self.id.clone())]
+            }
             _ => vec![],
         }
     }
@@ -441,7 +663,8 @@ mod tests {
             id: "".to_owned(),
             title: "".to_owned(),
             artists: vec![],
-            release_date: Some("1970-01-01".to_owned()),
+            release_date: ReleaseDate::parse("1970-01-01", None),
+            popularity: None,
             art: Some("".to_owned()),
             songs: SongBatch::empty(),
             is_liked: false,
@@ -468,4 +691,188 @@ mod tests {
         let next = &artist_state.next_page;
         assert_eq!(None, next.next_offset);
     }
+
+    #[test]
+    fn test_release_sort() {
+        let album =
+            |id: &str, release_date: Option<&str>, popularity: Option<u32>| AlbumDescription {
+                id: id.to_owned(),
+                title: "".to_owned(),
+                artists: vec![],
+                release_date: release_date.and_then(|d| ReleaseDate::parse(d, None)),
+                popularity,
+                art: None,
+                songs: SongBatch::empty(),
+                is_liked: false,
+            };
+        let id = "id".to_string();
+        let mut artist_state = ArtistState::new(id.clone());
+        artist_state.update_with(Cow::Owned(BrowserAction::SetArtistDetails(Box::new(
+            ArtistDescription {
+                id: id.clone(),
+                name: "Foo".to_owned(),
+                albums: vec![
+                    album("a", Some("2001-05-01"), Some(10)),
+                    album("b", None, Some(50)),
+                    album("c", Some("1999"), Some(80)),
+                ],
+                top_tracks: vec![],
+            },
+        ))));
+        let order = |state: &ArtistState| state.albums.iter().map(|a| a.uri()).collect::<Vec<_>>();
+
+        assert_eq!(order(&artist_state), vec!["a", "b", "c"]);
+
+        artist_state.update_with(Cow::Owned(BrowserAction::SetArtistReleaseSort(
+            id.clone(),
+            ReleaseSort::NewestFirst,
+        )));
+        assert_eq!(order(&artist_state), vec!["a", "c", "b"]);
+
+        artist_state.update_with(Cow::Owned(BrowserAction::SetArtistReleaseSort(
+            id.clone(),
+            ReleaseSort::OldestFirst,
+        )));
+        assert_eq!(order(&artist_state), vec!["c", "a", "b"]);
+
+        artist_state.update_with(Cow::Owned(BrowserAction::SetArtistReleaseSort(
+            id.clone(),
+            ReleaseSort::MostPopular,
+        )));
+        assert_eq!(order(&artist_state), vec!["c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_release_sort_waits_for_all_pages() {
+        let album = |popularity: u32| AlbumDescription {
+            id: popularity.to_string(),
+            title: "".to_owned(),
+            artists: vec![],
+            release_date: None,
+            popularity: Some(popularity),
+            art: None,
+            songs: SongBatch::empty(),
+            is_liked: false,
+        };
+        let id = "id".to_string();
+        let mut artist_state = ArtistState::new(id.clone());
+        artist_state.update_with(Cow::Owned(BrowserAction::SetArtistDetails(Box::new(
+            ArtistDescription {
+                id: id.clone(),
+                name: "Foo".to_owned(),
+                albums: (0..20).map(album).collect(),
+                top_tracks: vec![],
+            },
+        ))));
+        let first = |state: &ArtistState| state.albums.iter().next().map(|a| a.uri());
+
+        artist_state.update_with(Cow::Owned(BrowserAction::SetArtistReleaseSort(
+            id.clone(),
+            ReleaseSort::MostPopular,
+        )));
+        assert_eq!(first(&artist_state), Some("0".to_string()));
+
+        artist_state.update_with(Cow::Owned(BrowserAction::AppendArtistReleases(
+            id.clone(),
+            vec![album(50)],
+        )));
+        assert_eq!(first(&artist_state), Some("50".to_string()));
+
+        artist_state.update_with(Cow::Owned(BrowserAction::AppendArtistReleases(
+            id,
+            vec![album(50)],
+        )));
+        assert_eq!(artist_state.albums.len(), 21);
+    }
+
+    #[test]
+    fn test_show_episodes_appended() {
+        let episode = EpisodeDescription {
+            id: "episode".to_owned(),
+            uri: "spotify:episode:episode".to_owned(),
+            title: "".to_owned(),
+            description: "".to_owned(),
+            release_date: None,
+            duration: 0,
+            art: None,
+            resume_position: None,
+        };
+        let id = "id".to_string();
+        let mut show_state = ShowDetailsState::new(id.clone());
+        show_state.update_with(Cow::Owned(BrowserAction::SetShowDetails(Box::new(
+            ShowDescription {
+                id: id.clone(),
+                title: "Show".to_owned(),
+                publisher: "".to_owned(),
+                description: "".to_owned(),
+                art: None,
+                // One of them is missing
+                episodes: (0..49).map(|_| episode.clone()).collect(),
+                episodes_batch: Some(Batch {
+                    offset: 0,
+                    batch_size: 50,
+                    total: 51,
+                }),
+            },
+        ))));
+        assert_eq!(Some(50), show_state.next_page.next_offset);
+
+        show_state.update_with(Cow::Owned(BrowserAction::AppendShowEpisodes(
+            id,
+            Box::new(EpisodeBatch {
+                episodes: vec![episode],
+                batch: Batch {
+                    offset: 50,
+                    batch_size: 50,
+                    total: 51,
+                },
+            }),
+        )));
+        assert_eq!(show_state.show.as_ref().unwrap().episodes.len(), 50);
+        assert_eq!(None, show_state.next_page.next_offset);
+    }
+
+    #[test]
+    fn test_user_playlist_visibility() {
+        let playlist = |id: &str, public: bool| PlaylistDescription {
+            id: id.to_owned(),
+            title: "".to_owned(),
+            art: None,
+            songs: SongBatch::empty(),
+            owner: UserRef {
+                id: "me".to_owned(),
+                display_name: "Me".to_owned(),
+            },
+            public: Some(public),
+        };
+        let mut user_state = UserState::new("me".to_owned());
+        user_state.update_with(Cow::Owned(BrowserAction::SetUserDetails(Box::new(
+            UserDescription {
+                id: "me".to_owned(),
+                name: "Me".to_owned(),
+                followers: Some(1),
+                playlists: vec![playlist("public", true), playlist("private", false)],
+            },
+        ))));
+
+        assert_eq!(user_state.playlists.len(), 1);
+        assert_eq!(user_state.owned_playlists.len(), 2);
+
+        let events = user_state.update_with(Cow::Owned(BrowserAction::SetPlaylistPublic(
+            "private".to_owned(),
+            true,
+        )));
+        assert_eq!(
+            events,
+            vec![BrowserEvent::UserDetailsUpdated("me".to_owned())]
+        );
+        assert_eq!(user_state.playlists.len(), 2);
+
+        user_state.update_with(Cow::Owned(BrowserAction::SetPlaylistPublic(
+            "public".to_owned(),
+            false,
+        )));
+        assert_eq!(user_state.playlists.len(), 1);
+        assert_eq!(user_state.playlists.get(0).uri(), "private");
+    }
 }
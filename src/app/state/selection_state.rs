@@ -30,12 +30,15 @@ impl From<SelectionEvent> for AppEvent {
     }
 }
 
-#[derive(Debug, Clone)]
+// Where the selection was started from, so that it only shows up on that screen
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SelectionContext {
     ReadOnlyQueue,
     Queue,
-    Playlist,
+    PlaylistDetails(String),
     EditablePlaylist(String),
+    AlbumDetails(String),
+    ArtistDetails(String),
     SavedTracks,
     Default,
 }
@@ -99,6 +102,10 @@ impl SelectionState {
         self.selection_active
     }
 
+    pub fn is_selection_enabled_in(&self, context: &SelectionContext) -> bool {
+        self.selection_active && &self.context == context
+    }
+
     pub fn is_song_selected(&self, id: &str) -> bool {
         self.selected_songs_ids.contains(id)
     }
@@ -1,11 +1,19 @@
 use crate::{
     app::state::{AppAction, AppEvent, UpdatableState},
-    settings::SpotSettings,
+    settings::{save_inspector_paned_position, save_library_list_view, PinnedItem, SpotSettings},
 };
 
 #[derive(Clone, Debug)]
 pub enum SettingsAction {
     ChangeSettings,
+    SetOfflineMode(bool),
+    SetCarMode(bool),
+    SetGuestMode(bool),
+    // Pins the item to the sidebar, or unpins it if it already was
+    TogglePinned(PinnedItem),
+    // Whether the given user sees their saved albums as a list
+    SetLibraryListView(String, bool),
+    SetInspectorPanedPosition(i32),
 }
 
 impl From<SettingsAction> for AppAction {
@@ -14,9 +22,17 @@ impl From<SettingsAction> for AppAction {
     }
 }
 
+// Named like the other events (TrackChanged, PlaybackPaused...), even if that makes every variant end the same
+#[allow(clippy::enum_variant_names)]
 #[derive(Clone, Debug)]
 pub enum SettingsEvent {
     PlayerSettingsChanged,
+    // Any setting might have changed
+    SettingsChanged,
+    OfflineModeChanged(bool),
+    CarModeChanged(bool),
+    GuestModeChanged(bool),
+    PinnedChanged,
 }
 
 impl From<SettingsEvent> for AppEvent {
@@ -43,11 +59,52 @@ impl UpdatableState for SettingsState {
                 let player_settings_changed =
                     new_settings.player_settings != old_settings.player_settings;
                 self.settings = new_settings;
+                let mut events = vec![];
                 if player_settings_changed {
-                    vec![SettingsEvent::PlayerSettingsChanged.into()]
-                } else {
-                    vec![]
+                    events.push(SettingsEvent::PlayerSettingsChanged.into());
                 }
+                events.push(SettingsEvent::SettingsChanged.into());
+                events
+            }
+            SettingsAction::SetOfflineMode(offline) if offline != self.settings.offline_mode => {
+                self.settings.offline_mode = offline;
+                vec![SettingsEvent::OfflineModeChanged(offline).into()]
+            }
+            SettingsAction::SetOfflineMode(_) => vec![],
+            SettingsAction::SetCarMode(car_mode) if car_mode != self.settings.car_mode => {
+                self.settings.car_mode = car_mode;
+                vec![SettingsEvent::CarModeChanged(car_mode).into()]
+            }
+            SettingsAction::SetCarMode(_) => vec![],
+            SettingsAction::SetGuestMode(guest_mode) if guest_mode != self.settings.guest_mode => {
+                self.settings.guest_mode = guest_mode;
+                vec![SettingsEvent::GuestModeChanged(guest_mode).into()]
+            }
+            SettingsAction::SetGuestMode(_) => vec![],
+            SettingsAction::TogglePinned(item) => {
+                let pinned = &mut self.settings.pinned;
+                match pinned.iter().position(|p| p.uri == item.uri) {
+                    Some(i) => {
+                        pinned.remove(i);
+                    }
+                    None => pinned.push(item),
+                }
+                PinnedItem::save_all(pinned);
+                vec![SettingsEvent::PinnedChanged.into()]
+            }
+            SettingsAction::SetLibraryListView(user, list_view) => {
+                let users = &mut self.settings.library_list_view;
+                users.retain(|u| u != &user);
+                if list_view {
+                    users.push(user);
+                }
+                save_library_list_view(users);
+                vec![]
+            }
+            SettingsAction::SetInspectorPanedPosition(position) => {
+                self.settings.inspector_paned_position = position;
+                save_inspector_paned_position(position);
+                vec![]
             }
         }
     }
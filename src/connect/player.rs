@@ -86,7 +86,7 @@ impl ConnectPlayer {
 
     async fn apply_remote_state(&self, state: &ConnectPlayerState) {
         if let Some(songs) = self.get_queue_if_changed().await {
-            self.send_actions([PlaybackAction::LoadSongs(songs).into()]);
+            self.send_actions([PlaybackAction::LoadSongs(songs, 0).into()]);
         }
 
         let play_pause = if state.is_playing {
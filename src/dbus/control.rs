@@ -0,0 +1,165 @@
+#![allow(non_snake_case)]
+
+use std::sync::Arc;
+
+use futures::channel::mpsc::UnboundedSender;
+use zbus::dbus_interface;
+use zbus::fdo::{Error, Result};
+
+use crate::api::{SpotifyApiClient, SpotifyApiError};
+use crate::app::models::{SongBatch, SongDescription};
+use crate::app::state::{parse_spotify_uri, PlaybackAction};
+use crate::app::{AppAction, SongsSource};
+
+// How many results of each kind (albums, artists) Search returns
+const SEARCH_RESULTS_PER_KIND: usize = 10;
+const PAGE_SIZE: usize = 50;
+
+// A song as exposed over D-Bus: its URI, title and artists
+pub type QueueEntry = (String, String, String);
+
+pub fn queue_entry(song: &SongDescription) -> QueueEntry {
+    (song.uri.clone(), song.title.clone(), song.artists_name())
+}
+
+fn api_error(err: SpotifyApiError) -> Error {
+    Error::Failed(err.to_string())
+}
+
+// The dev.alextren.Spot interface, for scripts that need more than what MPRIS offers
+pub struct SpotControl {
+    api: Arc<dyn SpotifyApiClient + Send + Sync>,
+    sender: UnboundedSender<AppAction>,
+    // Kept up to date by the app, see MprisStateUpdate::SetQueue
+    queue: Vec<QueueEntry>,
+}
+
+impl SpotControl {
+    pub fn new(
+        api: Arc<dyn SpotifyApiClient + Send + Sync>,
+        sender: UnboundedSender<AppAction>,
+    ) -> Self {
+        Self {
+            api,
+            sender,
+            queue: vec![],
+        }
+    }
+
+    pub fn set_queue(&mut self, queue: Vec<QueueEntry>) {
+        self.queue = queue;
+    }
+
+    fn send(&self, actions: impl IntoIterator<Item = AppAction>) -> Result<()> {
+        for action in actions.into_iter() {
+            self.sender
+                .unbounded_send(action)
+                .map_err(|_| Error::Failed("Could not send action".to_string()))?;
+        }
+        Ok(())
+    }
+
+    // The first page of an album or a playlist, along with where it comes from
+    async fn first_page(&self, kind: &str, id: &str) -> Result<(SongsSource, SongBatch)> {
+        let (source, batch) = match kind {
+            "album" => (
+                SongsSource::Album(id.to_string()),
+                self.api.get_album_tracks(id, 0, PAGE_SIZE).await,
+            ),
+            "playlist" => (
+                SongsSource::Playlist(id.to_string()),
+                self.api.get_playlist_tracks(id, 0, PAGE_SIZE).await,
+            ),
+            _ => return Err(Error::InvalidArgs(format!("Unsupported URI kind: {kind}"))),
+        };
+        Ok((source, batch.map_err(api_error)?))
+    }
+
+    // All the songs of an album or a playlist
+    async fn all_songs(&self, kind: &str, id: &str) -> Result<Vec<SongDescription>> {
+        let mut songs = vec![];
+        let mut offset = 0;
+        loop {
+            let batch = match kind {
+                "album" => self.api.get_album_tracks(id, offset, PAGE_SIZE).await,
+                "playlist" => self.api.get_playlist_tracks(id, offset, PAGE_SIZE).await,
+                _ => return Err(Error::InvalidArgs(format!("Unsupported URI kind: {kind}"))),
+            }
+            .map_err(api_error)?;
+            songs.extend(batch.songs);
+            // Batches can hold fewer songs than asked for (local files are left out)
+            match batch.batch.next() {
+                Some(next) if next.offset > offset => offset = next.offset,
+                _ => return Ok(songs),
+            }
+        }
+    }
+}
+
+#[dbus_interface(interface = "dev.alextren.Spot")]
+impl SpotControl {
+    // Albums and artists matching the query, as (uri, name, description)
+    async fn search(&self, query: &str) -> Result<Vec<(String, String, String)>> {
+        let results = self
+            .api
+            .search(query, 0, SEARCH_RESULTS_PER_KIND)
+            .await
+            .map_err(api_error)?;
+        let artists = results.artists.into_iter().map(|artist| {
+            (
+                format!("spotify:artist:{}", artist.id),
+                artist.name,
+                String::new(),
+            )
+        });
+        let albums = results.albums.into_iter().map(|album| {
+            (
+                format!("spotify:album:{}", album.id),
+                album.title.clone(),
+                album.artists_name(),
+            )
+        });
+        Ok(artists.chain(albums).collect())
+    }
+
+    // Plays a track, or an album or playlist from its start
+    async fn play_uri(&self, uri: &str) -> Result<()> {
+        let (kind, id) = parse_spotify_uri(uri)
+            .ok_or_else(|| Error::InvalidArgs(format!("Not a Spotify URI: {uri}")))?;
+        if kind == "track" {
+            let song = self.api.get_track(id).await.map_err(api_error)?;
+            return self.send([
+                PlaybackAction::LoadSongs(vec![song], 0).into(),
+                PlaybackAction::Load(id.to_string()).into(),
+            ]);
+        }
+
+        let (source, batch) = self.first_page(kind, id).await?;
+        let first = batch
+            .songs
+            .first()
+            .map(|song| song.id.clone())
+            .ok_or_else(|| Error::Failed("Nothing to play".to_string()))?;
+        self.send([
+            PlaybackAction::LoadPagedSongs(source, batch, 0).into(),
+            PlaybackAction::Load(first).into(),
+        ])
+    }
+
+    // Adds a track, or all the tracks of an album or playlist, to the play queue
+    async fn queue_uri(&self, uri: &str) -> Result<()> {
+        let (kind, id) = parse_spotify_uri(uri)
+            .ok_or_else(|| Error::InvalidArgs(format!("Not a Spotify URI: {uri}")))?;
+        let songs = if kind == "track" {
+            vec![self.api.get_track(id).await.map_err(api_error)?]
+        } else {
+            self.all_songs(kind, id).await?
+        };
+        self.send([PlaybackAction::Queue(songs).into()])
+    }
+
+    // The loaded part of the play queue, as (uri, title, artists)
+    fn get_queue(&self) -> Vec<QueueEntry> {
+        self.queue.clone()
+    }
+}
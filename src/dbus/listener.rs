@@ -4,10 +4,11 @@ use std::rc::Rc;
 use crate::app::{
     components::EventListener,
     models::{RepeatMode, SongDescription},
-    state::PlaybackEvent,
+    state::{PlaybackEvent, SettingsEvent},
     AppEvent, AppModel,
 };
 
+use super::control::{queue_entry, QueueEntry};
 use super::types::{LoopStatus, PlaybackStatus, TrackMetadata};
 
 #[derive(Debug)]
@@ -19,6 +20,7 @@ pub enum MprisStateUpdate {
         has_next: bool,
     },
     SetPositionMs(u128),
+    Seeked(u128),
     SetLoopStatus {
         has_prev: bool,
         loop_status: LoopStatus,
@@ -26,6 +28,10 @@ pub enum MprisStateUpdate {
     },
     SetShuffled(bool),
     SetPlaying(PlaybackStatus),
+    // For the dev.alextren.Spot interface rather than MPRIS
+    SetQueue(Vec<QueueEntry>),
+    // Whether to show the tray icon, from the settings
+    SetTrayIcon(bool),
 }
 
 pub struct AppPlaybackStateListener {
@@ -39,31 +45,30 @@ impl AppPlaybackStateListener {
     }
 
     fn make_track_meta(&self) -> Option<TrackMetadata> {
+        let song = self.app_model.get_state().playback.current_song()?;
+        // Relinked songs are still described as the track that was asked for
+        let id = song.original_id().to_string();
         let SongDescription {
-            id,
             title,
             artists,
             album,
             duration,
             art,
             ..
-        } = self.app_model.get_state().playback.current_song()?;
+        } = song;
         Some(TrackMetadata {
             id: format!("/dev/alextren/Spot/Track/{id}"),
             length: 1000 * duration as u64,
             title,
-            album: album.name,
-            artist: artists.into_iter().map(|a| a.name).collect(),
-            art,
+            album: album.name.to_string(),
+            artist: artists.into_iter().map(|a| a.name.to_string()).collect(),
+            art: art.map(|art| art.to_string()),
         })
     }
 
     fn has_prev_next(&self) -> (bool, bool) {
         let state = self.app_model.get_state();
-        (
-            state.playback.prev_index().is_some(),
-            state.playback.next_index().is_some(),
-        )
+        (state.playback.has_prev(), state.playback.has_next())
     }
 
     fn loop_status(&self) -> LoopStatus {
@@ -107,11 +112,29 @@ impl AppPlaybackStateListener {
             PlaybackEvent::ShuffleChanged(shuffled) => {
                 Some(MprisStateUpdate::SetShuffled(*shuffled))
             }
-            PlaybackEvent::TrackSeeked(pos) | PlaybackEvent::SeekSynced(pos) => {
+            PlaybackEvent::TrackSeeked(pos) => {
+                let pos = 1000 * (*pos as u128);
+                Some(MprisStateUpdate::Seeked(pos))
+            }
+            PlaybackEvent::SeekSynced(pos) => {
                 let pos = 1000 * (*pos as u128);
                 Some(MprisStateUpdate::SetPositionMs(pos))
             }
             PlaybackEvent::VolumeSet(vol) => Some(MprisStateUpdate::SetVolume(*vol)),
+            PlaybackEvent::PlaylistChanged => {
+                let state = self.app_model.get_state();
+                let mut queue = state
+                    .playback
+                    .queued_songs()
+                    .map_collect(|song| queue_entry(&song));
+                queue.append(
+                    &mut state
+                        .playback
+                        .songs()
+                        .map_collect(|song| queue_entry(&song)),
+                );
+                Some(MprisStateUpdate::SetQueue(queue))
+            }
             _ => None,
         }
     }
@@ -119,12 +142,18 @@ impl AppPlaybackStateListener {
 
 impl EventListener for AppPlaybackStateListener {
     fn on_event(&mut self, event: &AppEvent) {
-        if let AppEvent::PlaybackEvent(event) = event {
-            if let Some(update) = self.update_for(event) {
-                self.sender
-                    .unbounded_send(update)
-                    .expect("Could not send event to DBUS server");
+        let update = match event {
+            AppEvent::PlaybackEvent(event) => self.update_for(event),
+            AppEvent::Started | AppEvent::SettingsEvent(SettingsEvent::SettingsChanged) => {
+                let tray_icon = self.app_model.get_state().settings.settings.tray_icon;
+                Some(MprisStateUpdate::SetTrayIcon(tray_icon))
             }
+            _ => None,
+        };
+        if let Some(update) = update {
+            self.sender
+                .unbounded_send(update)
+                .expect("Could not send event to DBUS server");
         }
     }
 }
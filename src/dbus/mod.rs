@@ -1,23 +1,42 @@
 use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
 use futures::StreamExt;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::thread;
 use zbus::Connection;
 
+use crate::app::loader::ImageLoader;
 use crate::app::{AppAction, AppModel};
 
 mod mpris;
 pub use mpris::*;
 
+mod search_provider;
+use search_provider::SpotSearchProvider;
+
+mod control;
+use control::SpotControl;
+
+mod tray;
+
+mod session_lock;
+
 mod types;
+use types::PlaybackStatus;
 
 mod listener;
 use listener::*;
 
+// Periodic position syncs that are off by more than this are reported as a seek
+const SEEK_DRIFT_TOLERANCE_MICROS: u128 = 2_000_000;
+
 #[tokio::main]
 async fn dbus_server(
     mpris: SpotMpris,
     player: SpotMprisPlayer,
+    search_provider: SpotSearchProvider,
+    control: SpotControl,
+    sender: UnboundedSender<AppAction>,
     receiver: UnboundedReceiver<MprisStateUpdate>,
 ) -> zbus::Result<()> {
     let connection = Connection::session().await?;
@@ -33,8 +52,73 @@ async fn dbus_server(
         .request_name("org.mpris.MediaPlayer2.Spot")
         .await?;
 
+    // See dev.alextren.Spot.search-provider.ini
+    connection
+        .object_server()
+        .at("/dev/alextren/Spot/SearchProvider", search_provider)
+        .await?;
+    // Not worth losing MPRIS over
+    if let Err(err) = connection
+        .request_name("dev.alextren.Spot.SearchProvider")
+        .await
+    {
+        warn!("Could not register the search provider: {}", err);
+    }
+
+    // The app id itself is already owned by GApplication
+    connection
+        .object_server()
+        .at("/dev/alextren/Spot", control)
+        .await?;
+    connection.request_name("dev.alextren.Spot.Control").await?;
+
+    tokio::task::spawn(session_lock::watch_session_lock(
+        connection.clone(),
+        sender.clone(),
+    ));
+
+    let image_loader = Arc::new(ImageLoader::new());
+
     receiver
         .for_each(|update| async {
+            if let MprisStateUpdate::SetTrayIcon(visible) = update {
+                let result = if visible {
+                    tray::show_tray(&connection, &sender).await
+                } else {
+                    tray::hide_tray(&connection).await
+                };
+                if let Err(err) = result {
+                    warn!("Could not update the tray icon: {}", err);
+                }
+                return;
+            }
+
+            if let MprisStateUpdate::SetPlaying(status) = &update {
+                let playing = *status == PlaybackStatus::Playing;
+                if let Err(err) = tray::set_tray_playing(&connection, playing).await {
+                    warn!("Could not update the tray menu: {}", err);
+                }
+            }
+
+            if let MprisStateUpdate::SetQueue(queue) = update {
+                if let Ok(control_ref) = connection
+                    .object_server()
+                    .interface::<_, SpotControl>("/dev/alextren/Spot")
+                    .await
+                {
+                    control_ref.get_mut().await.set_queue(queue);
+                }
+                return;
+            }
+
+            let remote_art = match &update {
+                MprisStateUpdate::SetCurrentTrack {
+                    current: Some(current),
+                    ..
+                } => current.art.clone().map(|url| (current.id.clone(), url)),
+                _ => None,
+            };
+
             if let Ok(player_ref) = connection
                 .object_server()
                 .interface::<_, SpotMprisPlayer>("/org/mpris/MediaPlayer2")
@@ -58,8 +142,18 @@ async fn dbus_server(
                         player.notify_current_track_changed(ctxt).await
                     }
                     MprisStateUpdate::SetPositionMs(position) => {
+                        // Only signal a seek if the position we were told about doesn't match what we expected
+                        let drift = player.state_mut().position().abs_diff(position);
                         player.state_mut().set_position(position);
-                        Ok(())
+                        if drift > SEEK_DRIFT_TOLERANCE_MICROS {
+                            SpotMprisPlayer::seeked(ctxt, position as i64).await
+                        } else {
+                            Ok(())
+                        }
+                    }
+                    MprisStateUpdate::Seeked(position) => {
+                        player.state_mut().set_position(position);
+                        SpotMprisPlayer::seeked(ctxt, position as i64).await
                     }
                     MprisStateUpdate::SetLoopStatus {
                         has_prev,
@@ -79,25 +173,73 @@ async fn dbus_server(
                         player.state_mut().set_playing(status);
                         player.playback_status_changed(ctxt).await
                     }
+                    // Handled above
+                    MprisStateUpdate::SetQueue(_) | MprisStateUpdate::SetTrayIcon(_) => Ok(()),
                 };
                 res.expect("Signal emission failed");
             }
+
+            if let Some((track_id, url)) = remote_art {
+                tokio::task::spawn(set_local_art(
+                    connection.clone(),
+                    Arc::clone(&image_loader),
+                    track_id,
+                    url,
+                ));
+            }
         })
         .await;
 
     Ok(())
 }
 
+// Some applets can't load remote images, so we point them to our cached copy of the cover instead.
+// The track is published with the remote cover first, so that downloading doesn't hold up other updates
+async fn set_local_art(
+    connection: Connection,
+    image_loader: Arc<ImageLoader>,
+    track_id: String,
+    url: String,
+) {
+    let Some(local_url) = image_loader
+        .load_remote_to_file(&url, "jpg")
+        .await
+        .and_then(|path| glib::filename_to_uri(path, None).ok())
+    else {
+        return;
+    };
+    let Ok(player_ref) = connection
+        .object_server()
+        .interface::<_, SpotMprisPlayer>("/org/mpris/MediaPlayer2")
+        .await
+    else {
+        return;
+    };
+    let mut player = player_ref.get_mut().await;
+    // The track might have changed while the cover was downloading
+    if player
+        .state_mut()
+        .set_current_track_art(&track_id, local_url.to_string())
+    {
+        let ctxt = player_ref.signal_context();
+        if let Err(err) = player.notify_current_track_changed(ctxt).await {
+            warn!("Could not update the track cover: {}", err);
+        }
+    }
+}
+
 pub fn start_dbus_server(
     app_model: Rc<AppModel>,
     sender: UnboundedSender<AppAction>,
 ) -> AppPlaybackStateListener {
     let mpris = SpotMpris::new(sender.clone());
-    let player = SpotMprisPlayer::new(sender);
+    let player = SpotMprisPlayer::new(sender.clone());
+    let search_provider = SpotSearchProvider::new(app_model.get_spotify(), sender.clone());
+    let control = SpotControl::new(app_model.get_spotify(), sender.clone());
 
-    let (sender, receiver) = unbounded();
+    let (state_sender, receiver) = unbounded();
 
-    thread::spawn(move || dbus_server(mpris, player, receiver));
+    thread::spawn(move || dbus_server(mpris, player, search_provider, control, sender, receiver));
 
-    AppPlaybackStateListener::new(app_model, sender)
+    AppPlaybackStateListener::new(app_model, state_sender)
 }
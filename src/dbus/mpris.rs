@@ -94,6 +94,7 @@ impl SpotMprisPlayer {
         let metadata = Value::from(self.metadata());
         let can_go_next = Value::from(self.can_go_next());
         let can_go_previous = Value::from(self.can_go_previous());
+        let can_seek = Value::from(self.can_seek());
 
         zbus::fdo::Properties::properties_changed(
             ctxt,
@@ -102,6 +103,7 @@ impl SpotMprisPlayer {
                 ("Metadata", &metadata),
                 ("CanGoNext", &can_go_next),
                 ("CanGoPrevious", &can_go_previous),
+                ("CanSeek", &can_seek),
             ]),
             &[],
         )
@@ -206,7 +208,8 @@ impl SpotMprisPlayer {
             .try_into()
             .map_err(|_| Error::Failed("Could not cast length (too large)".to_string()))?;
 
-        if Position > length {
+        // As per spec, positions outside of the track are ignored
+        if Position < 0 || Position > length {
             return Ok(());
         }
 
@@ -325,7 +328,7 @@ impl SpotMprisPlayer {
     #[dbus_interface(property)]
     pub fn set_shuffle(&self, value: bool) -> zbus::Result<()> {
         self.sender
-            .unbounded_send(PlaybackAction::ToggleShuffle.into())
+            .unbounded_send(PlaybackAction::SetShuffled(value).into())
             .map_err(|_| Error::Failed("Could not send action".to_string()))?;
         Ok(())
     }
@@ -0,0 +1,173 @@
+#![allow(non_snake_case)]
+#![allow(unused_variables)]
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures::channel::mpsc::UnboundedSender;
+use zbus::dbus_interface;
+use zbus::fdo::{Error, Result};
+use zvariant::{OwnedValue, Value};
+
+use crate::api::SpotifyApiClient;
+use crate::app::loader::ImageLoader;
+use crate::app::{AppAction, BrowserAction};
+
+// How many results of each kind (albums, artists) we offer to the shell
+const RESULTS_PER_KIND: usize = 5;
+
+#[derive(Clone, Debug)]
+struct ResultMeta {
+    name: String,
+    description: String,
+    art: Option<String>,
+}
+
+// Result identifiers are Spotify URIs, ie spotify:album:<id> or spotify:artist:<id>
+enum ResultId<'a> {
+    Album(&'a str),
+    Artist(&'a str),
+}
+
+impl<'a> ResultId<'a> {
+    fn parse(identifier: &'a str) -> Option<Self> {
+        let mut parts = identifier.split(':');
+        if parts.next()? != "spotify" {
+            return None;
+        }
+        match (parts.next()?, parts.next()?) {
+            ("album", id) => Some(Self::Album(id)),
+            ("artist", id) => Some(Self::Artist(id)),
+            _ => None,
+        }
+    }
+}
+
+// The org.gnome.Shell.SearchProvider2 interface, allowing searching Spotify from the Activities overview
+pub struct SpotSearchProvider {
+    api: Arc<dyn SpotifyApiClient + Send + Sync>,
+    sender: UnboundedSender<AppAction>,
+    image_loader: ImageLoader,
+    // What we know about the last results we returned, as the shell will ask for it right after
+    metas: HashMap<String, ResultMeta>,
+}
+
+impl SpotSearchProvider {
+    pub fn new(
+        api: Arc<dyn SpotifyApiClient + Send + Sync>,
+        sender: UnboundedSender<AppAction>,
+    ) -> Self {
+        Self {
+            api,
+            sender,
+            image_loader: ImageLoader::new(),
+            metas: HashMap::new(),
+        }
+    }
+
+    fn send(&self, actions: impl IntoIterator<Item = AppAction>) -> Result<()> {
+        for action in actions.into_iter() {
+            self.sender
+                .unbounded_send(action)
+                .map_err(|_| Error::Failed("Could not send action".to_string()))?;
+        }
+        Ok(())
+    }
+
+    async fn search(&mut self, terms: Vec<String>) -> Vec<String> {
+        let query = terms.join(" ");
+        if query.trim().is_empty() {
+            return vec![];
+        }
+
+        let Ok(results) = self.api.search(&query, 0, RESULTS_PER_KIND).await else {
+            return vec![];
+        };
+
+        self.metas.clear();
+        let mut ids = vec![];
+        for artist in results.artists.into_iter() {
+            let id = format!("spotify:artist:{}", artist.id);
+            self.metas.insert(
+                id.clone(),
+                ResultMeta {
+                    name: artist.name,
+                    description: String::new(),
+                    art: artist.photo,
+                },
+            );
+            ids.push(id);
+        }
+        for album in results.albums.into_iter() {
+            let id = format!("spotify:album:{}", album.id);
+            self.metas.insert(
+                id.clone(),
+                ResultMeta {
+                    description: album.artists_name(),
+                    name: album.title,
+                    art: album.art,
+                },
+            );
+            ids.push(id);
+        }
+        ids
+    }
+}
+
+#[dbus_interface(interface = "org.gnome.Shell.SearchProvider2")]
+impl SpotSearchProvider {
+    async fn get_initial_result_set(&mut self, terms: Vec<String>) -> Vec<String> {
+        self.search(terms).await
+    }
+
+    async fn get_subsearch_result_set(
+        &mut self,
+        previous_results: Vec<String>,
+        terms: Vec<String>,
+    ) -> Vec<String> {
+        // Results come from a remote search, we can't just filter the previous ones
+        self.search(terms).await
+    }
+
+    async fn get_result_metas(&self, identifiers: Vec<String>) -> Vec<HashMap<String, OwnedValue>> {
+        let mut result = vec![];
+        for identifier in identifiers.into_iter() {
+            let Some(meta) = self.metas.get(&identifier) else {
+                continue;
+            };
+
+            let mut map = HashMap::new();
+            map.insert("id".to_string(), Value::from(identifier.clone()).into());
+            map.insert("name".to_string(), Value::from(meta.name.clone()).into());
+            map.insert(
+                "description".to_string(),
+                Value::from(meta.description.clone()).into(),
+            );
+
+            // The shell accepts a serialized GIcon, which for a file icon is simply its path
+            if let Some(url) = meta.art.as_ref() {
+                let path = self.image_loader.load_remote_to_file(url, "jpg").await;
+                if let Some(path) = path.and_then(|p| p.to_str().map(|s| s.to_string())) {
+                    map.insert("gicon".to_string(), Value::from(path).into());
+                }
+            }
+
+            result.push(map);
+        }
+        result
+    }
+
+    fn activate_result(&self, identifier: &str, terms: Vec<String>, timestamp: u32) -> Result<()> {
+        let action = match ResultId::parse(identifier) {
+            Some(ResultId::Album(id)) => AppAction::ViewAlbum(id.to_string()),
+            Some(ResultId::Artist(id)) => AppAction::ViewArtist(id.to_string()),
+            None => return Err(Error::InvalidArgs("Unknown result".to_string())),
+        };
+        self.send([AppAction::Raise, action])
+    }
+
+    fn launch_search(&self, terms: Vec<String>, timestamp: u32) -> Result<()> {
+        let query = terms.join(" ");
+        self.send([AppAction::Raise, BrowserAction::Search(query).into()])
+    }
+}
@@ -0,0 +1,108 @@
+use futures::channel::mpsc::UnboundedSender;
+use futures::{FutureExt, Stream, StreamExt};
+use zbus::{dbus_proxy, Connection};
+use zvariant::OwnedObjectPath;
+
+use crate::app::AppAction;
+
+// Both GNOME's org.gnome.ScreenSaver and the org.freedesktop.ScreenSaver of most other desktops look like this
+#[dbus_proxy(interface = "org.freedesktop.ScreenSaver")]
+trait ScreenSaver {
+    #[dbus_proxy(signal)]
+    fn active_changed(&self, active: bool) -> zbus::Result<()>;
+}
+
+#[dbus_proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait LoginManager {
+    #[dbus_proxy(name = "GetSessionByPID")]
+    fn get_session_by_pid(&self, pid: u32) -> zbus::Result<OwnedObjectPath>;
+}
+
+#[dbus_proxy(
+    interface = "org.freedesktop.login1.Session",
+    default_service = "org.freedesktop.login1"
+)]
+trait LoginSession {
+    #[dbus_proxy(signal)]
+    fn lock(&self) -> zbus::Result<()>;
+
+    #[dbus_proxy(signal)]
+    fn unlock(&self) -> zbus::Result<()>;
+}
+
+async fn forward_locks(
+    locks: impl Stream<Item = bool>,
+    sender: &UnboundedSender<AppAction>,
+) -> zbus::Result<()> {
+    locks
+        .for_each(|locked| async move {
+            let _ = sender.unbounded_send(AppAction::SetSessionLocked(locked));
+        })
+        .await;
+    Ok(())
+}
+
+async fn watch_screensaver(
+    connection: &Connection,
+    name: &'static str,
+    path: &'static str,
+    sender: &UnboundedSender<AppAction>,
+) -> zbus::Result<()> {
+    let proxy = ScreenSaverProxy::builder(connection)
+        .destination(name)?
+        .path(path)?
+        .interface(name)?
+        .build()
+        .await?;
+    let changes = proxy
+        .receive_active_changed()
+        .await?
+        .filter_map(|signal| async move { signal.args().ok().map(|args| args.active) });
+    forward_locks(changes, sender).await
+}
+
+// Lock and Unlock are what loginctl lock-session and friends send, on the system bus
+async fn watch_login_session(sender: &UnboundedSender<AppAction>) -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let session_path = LoginManagerProxy::new(&connection)
+        .await?
+        .get_session_by_pid(std::process::id())
+        .await?;
+    let session = LoginSessionProxy::builder(&connection)
+        .path(session_path)?
+        .build()
+        .await?;
+    let locks = session.receive_lock().await?.map(|_| true);
+    let unlocks = session.receive_unlock().await?.map(|_| false);
+    forward_locks(futures::stream::select(locks, unlocks), sender).await
+}
+
+// None of these are guaranteed to be around (or reachable from a sandbox), so failures are only logged
+pub async fn watch_session_lock(connection: Connection, sender: UnboundedSender<AppAction>) {
+    let log_error = |result: zbus::Result<()>| {
+        if let Err(err) = result {
+            debug!("Could not watch for session locks: {}", err);
+        }
+    };
+    futures::join!(
+        watch_screensaver(
+            &connection,
+            "org.gnome.ScreenSaver",
+            "/org/gnome/ScreenSaver",
+            &sender
+        )
+        .map(log_error),
+        watch_screensaver(
+            &connection,
+            "org.freedesktop.ScreenSaver",
+            "/org/freedesktop/ScreenSaver",
+            &sender
+        )
+        .map(log_error),
+        watch_login_session(&sender).map(log_error),
+    );
+}
@@ -0,0 +1,308 @@
+#![allow(non_snake_case)]
+#![allow(unused_variables)]
+
+use std::collections::HashMap;
+
+use futures::channel::mpsc::UnboundedSender;
+use gettextrs::gettext;
+use zbus::fdo::{Error, Result};
+use zbus::{dbus_interface, Connection, SignalContext};
+use zvariant::{ObjectPath, OwnedValue, Value};
+
+use crate::app::state::PlaybackAction;
+use crate::app::AppAction;
+
+const ITEM_PATH: &str = "/StatusNotifierItem";
+const MENU_PATH: &str = "/MenuBar";
+
+// Ids of the menu entries, 0 being the (invisible) root
+const SHOW_ITEM: i32 = 1;
+const SEPARATOR_ITEM: i32 = 2;
+const TOGGLE_PLAY_ITEM: i32 = 3;
+const PREVIOUS_ITEM: i32 = 4;
+const NEXT_ITEM: i32 = 5;
+
+// An entry of the menu: its id, its properties and its children
+type MenuLayout = (i32, HashMap<String, OwnedValue>, Vec<OwnedValue>);
+
+fn send(sender: &UnboundedSender<AppAction>, action: AppAction) -> Result<()> {
+    sender
+        .unbounded_send(action)
+        .map_err(|_| Error::Failed("Could not send action".to_string()))
+}
+
+// Watchers tell items apart by their bus name, see the StatusNotifierItem spec
+fn bus_name() -> String {
+    format!("org.kde.StatusNotifierItem-{}-1", std::process::id())
+}
+
+// The icon itself: clicking it brings the window back, middle-clicking it toggles playback
+pub struct SpotTrayItem {
+    sender: UnboundedSender<AppAction>,
+}
+
+impl SpotTrayItem {
+    pub fn new(sender: UnboundedSender<AppAction>) -> Self {
+        Self { sender }
+    }
+}
+
+#[dbus_interface(interface = "org.kde.StatusNotifierItem")]
+impl SpotTrayItem {
+    fn activate(&self, x: i32, y: i32) -> Result<()> {
+        send(&self.sender, AppAction::Raise)
+    }
+
+    fn secondary_activate(&self, x: i32, y: i32) -> Result<()> {
+        send(&self.sender, PlaybackAction::TogglePlay.into())
+    }
+
+    // Hosts show the menu exported below on their own
+    fn context_menu(&self, x: i32, y: i32) {}
+
+    fn scroll(&self, delta: i32, orientation: &str) {}
+
+    #[dbus_interface(property)]
+    fn category(&self) -> &'static str {
+        "ApplicationStatus"
+    }
+
+    #[dbus_interface(property)]
+    fn id(&self) -> &'static str {
+        "spot"
+    }
+
+    #[dbus_interface(property)]
+    fn title(&self) -> &'static str {
+        "Spot"
+    }
+
+    #[dbus_interface(property)]
+    fn status(&self) -> &'static str {
+        "Active"
+    }
+
+    #[dbus_interface(property)]
+    fn icon_name(&self) -> &'static str {
+        "dev.alextren.Spot"
+    }
+
+    #[dbus_interface(property)]
+    fn item_is_menu(&self) -> bool {
+        false
+    }
+
+    #[dbus_interface(property)]
+    fn menu(&self) -> ObjectPath<'static> {
+        ObjectPath::from_static_str_unchecked(MENU_PATH)
+    }
+}
+
+// The menu of the icon, exported with the com.canonical.dbusmenu protocol
+pub struct SpotTrayMenu {
+    sender: UnboundedSender<AppAction>,
+    playing: bool,
+    // Bumped whenever an entry changes, so that hosts know to fetch the menu again
+    revision: u32,
+}
+
+impl SpotTrayMenu {
+    pub fn new(sender: UnboundedSender<AppAction>) -> Self {
+        Self {
+            sender,
+            playing: false,
+            revision: 0,
+        }
+    }
+
+    // Whether the menu changed
+    fn set_playing(&mut self, playing: bool) -> bool {
+        if self.playing == playing {
+            return false;
+        }
+        self.playing = playing;
+        self.revision += 1;
+        true
+    }
+
+    fn item_properties(&self, id: i32) -> HashMap<String, OwnedValue> {
+        let label = match id {
+            // translators: Entry of the tray icon menu that shows the main window
+            SHOW_ITEM => gettext("Show Spot"),
+            // translators: Entry of the tray icon menu that pauses playback
+            TOGGLE_PLAY_ITEM if self.playing => gettext("Pause"),
+            // translators: Entry of the tray icon menu that resumes playback
+            TOGGLE_PLAY_ITEM => gettext("Play"),
+            // translators: Entry of the tray icon menu that goes back to the previous track
+            PREVIOUS_ITEM => gettext("Previous"),
+            // translators: Entry of the tray icon menu that skips to the next track
+            NEXT_ITEM => gettext("Next"),
+            SEPARATOR_ITEM => {
+                return HashMap::from([("type".to_string(), Value::from("separator").into())])
+            }
+            _ => return HashMap::new(),
+        };
+        HashMap::from([("label".to_string(), Value::from(label).into())])
+    }
+
+    fn layout(&self) -> MenuLayout {
+        let children = [
+            SHOW_ITEM,
+            SEPARATOR_ITEM,
+            TOGGLE_PLAY_ITEM,
+            PREVIOUS_ITEM,
+            NEXT_ITEM,
+        ]
+        .iter()
+        .map(|&id| Value::from((id, self.item_properties(id), Vec::<OwnedValue>::new())).into())
+        .collect();
+        let root = HashMap::from([(
+            "children-display".to_string(),
+            Value::from("submenu").into(),
+        )]);
+        (0, root, children)
+    }
+}
+
+#[dbus_interface(interface = "com.canonical.dbusmenu")]
+impl SpotTrayMenu {
+    // The menu is flat and small, so all of it is sent whatever was asked for
+    fn get_layout(
+        &self,
+        parent_id: i32,
+        recursion_depth: i32,
+        property_names: Vec<String>,
+    ) -> (u32, MenuLayout) {
+        (self.revision, self.layout())
+    }
+
+    fn get_group_properties(
+        &self,
+        ids: Vec<i32>,
+        property_names: Vec<String>,
+    ) -> Vec<(i32, HashMap<String, OwnedValue>)> {
+        ids.into_iter()
+            .map(|id| (id, self.item_properties(id)))
+            .collect()
+    }
+
+    fn get_property(&self, id: i32, name: String) -> Result<OwnedValue> {
+        self.item_properties(id)
+            .remove(&name)
+            .ok_or_else(|| Error::InvalidArgs(format!("No property {name} on item {id}")))
+    }
+
+    fn event(&self, id: i32, event_id: String, data: OwnedValue, timestamp: u32) -> Result<()> {
+        if event_id != "clicked" {
+            return Ok(());
+        }
+        let action = match id {
+            SHOW_ITEM => AppAction::Raise,
+            TOGGLE_PLAY_ITEM => PlaybackAction::TogglePlay.into(),
+            PREVIOUS_ITEM => PlaybackAction::Previous.into(),
+            NEXT_ITEM => PlaybackAction::Next.into(),
+            _ => return Ok(()),
+        };
+        send(&self.sender, action)
+    }
+
+    fn event_group(&self, events: Vec<(i32, String, OwnedValue, u32)>) -> Result<Vec<i32>> {
+        for (id, event_id, data, timestamp) in events {
+            self.event(id, event_id, data, timestamp)?;
+        }
+        Ok(vec![])
+    }
+
+    fn about_to_show(&self, id: i32) -> bool {
+        false
+    }
+
+    fn about_to_show_group(&self, ids: Vec<i32>) -> (Vec<i32>, Vec<i32>) {
+        (vec![], vec![])
+    }
+
+    #[dbus_interface(signal)]
+    async fn layout_updated(
+        ctxt: &SignalContext<'_>,
+        revision: u32,
+        parent: i32,
+    ) -> zbus::Result<()>;
+
+    #[dbus_interface(property)]
+    fn version(&self) -> u32 {
+        3
+    }
+
+    #[dbus_interface(property)]
+    fn text_direction(&self) -> &'static str {
+        "ltr"
+    }
+
+    #[dbus_interface(property)]
+    fn status(&self) -> &'static str {
+        "normal"
+    }
+
+    #[dbus_interface(property)]
+    fn icon_theme_path(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+// Desktops without a StatusNotifierWatcher (stock GNOME for instance) just won't show anything
+pub async fn show_tray(
+    connection: &Connection,
+    sender: &UnboundedSender<AppAction>,
+) -> zbus::Result<()> {
+    let server = connection.object_server();
+    // Already shown
+    if !server
+        .at(ITEM_PATH, SpotTrayItem::new(sender.clone()))
+        .await?
+    {
+        return Ok(());
+    }
+    server
+        .at(MENU_PATH, SpotTrayMenu::new(sender.clone()))
+        .await?;
+
+    let name = bus_name();
+    connection.request_name(name.as_str()).await?;
+    connection
+        .call_method(
+            Some("org.kde.StatusNotifierWatcher"),
+            "/StatusNotifierWatcher",
+            Some("org.kde.StatusNotifierWatcher"),
+            "RegisterStatusNotifierItem",
+            &(name,),
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn hide_tray(connection: &Connection) -> zbus::Result<()> {
+    let server = connection.object_server();
+    if !server.remove::<SpotTrayItem, _>(ITEM_PATH).await? {
+        return Ok(());
+    }
+    server.remove::<SpotTrayMenu, _>(MENU_PATH).await?;
+    // Watchers drop items whose bus name goes away
+    connection.release_name(bus_name().as_str()).await?;
+    Ok(())
+}
+
+pub async fn set_tray_playing(connection: &Connection, playing: bool) -> zbus::Result<()> {
+    let Ok(menu_ref) = connection
+        .object_server()
+        .interface::<_, SpotTrayMenu>(MENU_PATH)
+        .await
+    else {
+        // The icon isn't shown
+        return Ok(());
+    };
+    let mut menu = menu_ref.get_mut().await;
+    if menu.set_playing(playing) {
+        SpotTrayMenu::layout_updated(menu_ref.signal_context(), menu.revision, 0).await?;
+    }
+    Ok(())
+}
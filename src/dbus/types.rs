@@ -213,6 +213,17 @@ impl MprisState {
         self.position.set(0, playing);
     }
 
+    // Returns false if the track with that id isn't the current one anymore
+    pub fn set_current_track_art(&mut self, id: &str, art: String) -> bool {
+        match self.metadata.as_mut().filter(|track| track.id == id) {
+            Some(track) => {
+                track.art = Some(art);
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn position(&self) -> u128 {
         self.position.current()
     }
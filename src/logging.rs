@@ -0,0 +1,85 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+// How many lines we keep around for the log viewer
+const MAX_LINES: usize = 1000;
+
+#[derive(Clone, Debug)]
+pub struct LogLine {
+    pub time: SystemTime,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+static CAPTURING: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    static ref RECENT_LINES: Mutex<VecDeque<LogLine>> =
+        Mutex::new(VecDeque::with_capacity(MAX_LINES));
+}
+
+// Forwards everything to env_logger as usual, but also keeps the most recent lines in memory when capturing
+struct SpotLogger {
+    inner: env_logger::Logger,
+    capture: bool,
+}
+
+impl Log for SpotLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.capture || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.inner.matches(record) {
+            self.inner.log(record);
+        }
+
+        if self.capture {
+            let mut lines = RECENT_LINES.lock().unwrap();
+            if lines.len() >= MAX_LINES {
+                lines.pop_front();
+            }
+            lines.push_back(LogLine {
+                time: SystemTime::now(),
+                level: record.level(),
+                target: record.target().to_string(),
+                message: record.args().to_string(),
+            });
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+pub fn init(capture: bool) {
+    let inner = env_logger::Builder::from_default_env().build();
+    let max_level = if capture {
+        LevelFilter::Debug.max(inner.filter())
+    } else {
+        inner.filter()
+    };
+    log::set_boxed_logger(Box::new(SpotLogger { inner, capture }))
+        .expect("Could not setup logging");
+    log::set_max_level(max_level);
+    CAPTURING.store(capture, Ordering::Relaxed);
+}
+
+pub fn is_capturing() -> bool {
+    CAPTURING.load(Ordering::Relaxed)
+}
+
+pub fn recent_lines(max_level: Level) -> Vec<LogLine> {
+    RECENT_LINES
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|line| line.level <= max_level)
+        .cloned()
+        .collect()
+}
@@ -18,22 +18,60 @@ mod app;
 mod config;
 mod connect;
 mod dbus;
+mod logging;
 mod player;
+mod remote;
+mod scrobbling;
 mod settings;
 
-use crate::app::components::expose_custom_widgets;
+use crate::app::components::{expose_custom_widgets, ShareWindow};
 use crate::app::dispatch::{spawn_task_handler, DispatchLoop};
 use crate::app::{state::PlaybackAction, App, AppAction, BrowserAction};
 
 fn main() {
+    app::credentials::load_secrets();
     let settings = settings::SpotSettings::new_from_gsettings().unwrap_or_default();
+    if let Some(cache_dir) = settings.cache_dir.clone() {
+        api::cache::set_cache_root(cache_dir);
+    }
+    api::cache::set_cache_account(app::credentials::current_account().as_deref());
+    api::set_proxy(settings.proxy.url());
     setup_gtk(&settings);
 
     // Looks like there's a side effect to declaring widgets that allows them to be referenced them in ui/blueprint files
     // so here goes!
     expose_custom_widgets();
 
-    let gtk_app = gtk::Application::new(Some(config::APPID), ApplicationFlags::HANDLES_OPEN);
+    // Runs as a service (without a window) to be controlled through MPRIS or Spotify Connect, until activated
+    let headless =
+        std::env::args().any(|arg| arg == "--headless" || arg == "--gapplication-service");
+    let flags = if headless {
+        ApplicationFlags::HANDLES_OPEN | ApplicationFlags::IS_SERVICE
+    } else {
+        ApplicationFlags::HANDLES_OPEN
+    };
+    let gtk_app = gtk::Application::new(Some(config::APPID), flags);
+    // Handled in setup_gtk, but GApplication needs to know about it
+    gtk_app.add_main_option(
+        "debug",
+        glib::Char::from(b'd'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        "Keep recent logs to show them in the app",
+        None,
+    );
+    // Handled above, same as --gapplication-service
+    gtk_app.add_main_option(
+        "headless",
+        glib::Char::from(b'\0'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        "Start without a window, for playback controlled through MPRIS or Spotify Connect",
+        None,
+    );
+    add_remote_options(&gtk_app);
+    gtk_app.connect_handle_local_options(handle_remote_options);
+
     let builder = gtk::Builder::from_resource("/dev/alextren/Spot/window.ui");
     let window: libadwaita::ApplicationWindow = builder.object("window").unwrap();
 
@@ -58,31 +96,61 @@ fn main() {
         builder,
         sender.clone(),
         spawn_task_handler(&context),
+        headless,
     );
     context.spawn_local(app.attach(dispatch_loop));
 
+    // Services aren't activated on startup, so this is where they start instead
+    if headless {
+        let sender_clone = sender.clone();
+        gtk_app.connect_startup(move |gtk_app| {
+            debug!("starting headless");
+            // Without a window, nothing else would keep the app running (quitting still works)
+            std::mem::forget(gtk_app.hold());
+            sender_clone.unbounded_send(AppAction::Start).unwrap();
+        });
+    }
+
     let sender_clone = sender.clone();
     gtk_app.connect_activate(move |gtk_app| {
         debug!("activate");
         if let Some(existing_window) = gtk_app.active_window() {
             existing_window.present();
         } else {
-            // Only send the Start action if we've just created the window
             window.set_application(Some(gtk_app));
             gtk_app.add_window(&window);
-            sender_clone.unbounded_send(AppAction::Start).unwrap();
+            // Only send the Start action if we've just created the window, headless instances already started
+            let action = if headless {
+                AppAction::Raise
+            } else {
+                AppAction::Start
+            };
+            sender_clone.unbounded_send(action).unwrap();
         }
     });
 
     gtk_app.connect_open(move |gtk_app, targets, _| {
         gtk_app.activate();
 
-        // There should only be one target because %u is used in desktop file
-        let target = &targets[0];
-        let uri = target.uri().to_string();
-        let action = AppAction::OpenURI(uri)
-            .unwrap_or_else(|| AppAction::ShowNotification(gettext("Failed to open link!")));
-        sender.unbounded_send(action).unwrap();
+        // One target from the desktop file (%u), but possibly several from the command line
+        for target in targets {
+            // Local files can only be library backups, see the library export
+            if let Some(path) = target.path() {
+                let (content_type, _) = gio::content_type_guess(Some(&path), &[]);
+                let action = if is_library_backup_type(&content_type) {
+                    AppAction::ImportLibrary(path)
+                } else {
+                    // translators: Shown when a file opened with the app isn't a library backup
+                    AppAction::ShowNotification(gettext("This file isn't a library backup"))
+                };
+                sender.unbounded_send(action).unwrap();
+                continue;
+            }
+            let uri = target.uri().to_string();
+            let action = AppAction::OpenURI(uri)
+                .unwrap_or_else(|| AppAction::ShowNotification(gettext("Failed to open link!")));
+            sender.unbounded_send(action).unwrap();
+        }
     });
 
     context.invoke_local(move || {
@@ -92,9 +160,109 @@ fn main() {
     std::process::exit(0);
 }
 
+// The types listed in the desktop file, that the library export writes
+fn is_library_backup_type(content_type: &str) -> bool {
+    gio::content_type_is_a(content_type, "application/json")
+        || gio::content_type_is_a(content_type, "text/csv")
+}
+
+// Flags that control the instance that's already running, mapped to the app action they trigger
+const REMOTE_ACTIONS: [(&str, &str, &str); 3] = [
+    (
+        "play-pause",
+        "toggle_playback",
+        "Toggle playback in the running instance",
+    ),
+    (
+        "next",
+        "player_next",
+        "Skip to the next track in the running instance",
+    ),
+    (
+        "prev",
+        "player_prev",
+        "Go back to the previous track in the running instance",
+    ),
+];
+
+fn add_remote_options(app: &gtk::Application) {
+    for (option, _, description) in REMOTE_ACTIONS {
+        app.add_main_option(
+            option,
+            glib::Char::from(b'\0'),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::None,
+            description,
+            None,
+        );
+    }
+    app.add_main_option(
+        "seek",
+        glib::Char::from(b'\0'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Seek to a position, or by an offset if prefixed with + or - (in seconds)",
+        Some("[+|-]SECONDS"),
+    );
+    app.add_main_option(
+        "query",
+        glib::Char::from(b'\0'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        "Print what the running instance is playing",
+        None,
+    );
+}
+
+// Forwards the flags above to the primary instance, then exits; anything else starts the app as usual
+fn handle_remote_options(app: &gtk::Application, options: &glib::VariantDict) -> i32 {
+    let actions: Vec<(&str, Option<glib::Variant>)> = REMOTE_ACTIONS
+        .iter()
+        .filter(|(option, _, _)| options.contains(option))
+        .map(|(_, action, _)| (*action, None))
+        .chain(
+            options
+                .lookup::<String>("seek")
+                .ok()
+                .flatten()
+                .map(|seek| ("seek", Some(seek.to_variant()))),
+        )
+        .collect();
+    let query = options.contains("query");
+    if actions.is_empty() && !query {
+        return -1;
+    }
+
+    if let Err(err) = app.register(gio::Cancellable::NONE) {
+        eprintln!("Could not reach Spot: {err}");
+        return 1;
+    }
+    if !app.is_remote() {
+        eprintln!("Spot is not running");
+        return 1;
+    }
+
+    for (action, parameter) in actions {
+        app.activate_action(action, parameter.as_ref());
+    }
+    if query {
+        let status = app
+            .action_state("query")
+            .and_then(|state| state.get::<String>())
+            .unwrap_or_default();
+        println!("{status}");
+    }
+    // Make sure the actions were sent before exiting
+    if let Some(connection) = app.dbus_connection() {
+        let _ = connection.flush_sync(gio::Cancellable::NONE);
+    }
+    0
+}
+
 fn setup_gtk(settings: &settings::SpotSettings) {
-    // Setup logging
-    env_logger::init();
+    // Setup logging, keeping recent lines around for the log viewer if asked to
+    let debug = settings.debug_logs || std::env::args().any(|arg| arg == "--debug");
+    logging::init(debug);
 
     // Setup translations
     textdomain("spot")
@@ -180,6 +348,39 @@ fn register_actions(app: &gtk::Application, sender: UnboundedSender<AppAction>)
         sender.clone(),
     ));
 
+    app.add_action(&make_action("undo", AppAction::Undo, sender.clone()));
+
+    // Takes a Spotify URI, so that menus and buttons can share anything
+    let share = SimpleAction::new("share", Some(glib::VariantTy::STRING));
+    share.connect_activate(clone!(@weak app => move |_, uri| {
+        let window = uri.and_then(|uri| uri.str()).and_then(ShareWindow::for_uri);
+        if let Some(window) = window {
+            window.set_transient_for(app.active_window().as_ref());
+            window.present();
+        }
+    }));
+    app.add_action(&share);
+
+    app.add_action(&{
+        let action = SimpleAction::new("seek", Some(glib::VariantTy::STRING));
+        action.connect_activate(clone!(@strong sender => move |_, position| {
+            let position = position.and_then(|s| s.str()).unwrap_or_default();
+            // Either relative ("+10", "-10") or absolute ("42")
+            let action = if position.starts_with(['+', '-']) {
+                position.parse().ok().map(PlaybackAction::SeekBy)
+            } else {
+                position
+                    .parse::<u32>()
+                    .ok()
+                    .map(|seconds| PlaybackAction::Seek(seconds.saturating_mul(1000)))
+            };
+            if let Some(action) = action {
+                sender.unbounded_send(action.into()).unwrap();
+            }
+        }));
+        action
+    });
+
     app.add_action(&{
         let action = SimpleAction::new("open_playlist", Some(glib::VariantTy::STRING));
         action.set_enabled(true);
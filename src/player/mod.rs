@@ -14,10 +14,21 @@ pub use player::*;
 
 #[derive(Debug, Clone)]
 pub enum Command {
-    PasswordLogin { username: String, password: String },
-    TokenLogin { username: String, token: String },
+    PasswordLogin {
+        username: String,
+        password: String,
+    },
+    TokenLogin {
+        username: String,
+        token: String,
+    },
     Logout,
-    PlayerLoad { track: SpotifyId, resume: bool },
+    PlayerLoad {
+        track: SpotifyId,
+        resume: bool,
+        // In milliseconds
+        position: u32,
+    },
     PlayerResume,
     PlayerPause,
     PlayerStop,
@@ -102,6 +113,20 @@ impl SpotifyPlayerDelegate for AppPlayerDelegate {
             .unbounded_send(PlaybackAction::Preload.into())
             .unwrap();
     }
+
+    fn connection_lost(&self) {
+        self.sender
+            .borrow_mut()
+            .unbounded_send(LoginAction::SetConnectionLost.into())
+            .unwrap();
+    }
+
+    fn connection_restored(&self) {
+        self.sender
+            .borrow_mut()
+            .unbounded_send(LoginAction::SetConnectionRestored.into())
+            .unwrap();
+    }
 }
 
 #[tokio::main]
@@ -15,7 +15,6 @@ use librespot::playback::audio_backend;
 use librespot::playback::config::{AudioFormat, Bitrate, PlayerConfig, VolumeCtrl};
 use librespot::playback::player::{Player, PlayerEvent, PlayerEventChannel};
 
-use std::cell::RefCell;
 use std::env;
 use std::error::Error;
 use std::fmt;
@@ -23,8 +22,9 @@ use std::rc::Rc;
 use std::time::{Duration, SystemTime};
 
 use super::Command;
+use crate::api::cache::librespot_cache_root;
 use crate::app::credentials;
-use crate::settings::SpotSettings;
+use crate::settings::{ProxyKind, ProxySettings, SpotSettings};
 
 #[derive(Debug)]
 pub enum SpotifyError {
@@ -57,6 +57,8 @@ pub trait SpotifyPlayerDelegate {
     fn report_error(&self, error: SpotifyError);
     fn notify_playback_state(&self, position: u32);
     fn preload_next_track(&self);
+    fn connection_lost(&self);
+    fn connection_restored(&self);
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -72,6 +74,9 @@ pub struct SpotifyPlayerSettings {
     pub backend: AudioBackend,
     pub gapless: bool,
     pub ap_port: Option<u16>,
+    // Size limit of the audio cache in bytes (applied when creating a session)
+    pub audio_cache_size: Option<u64>,
+    pub proxy: ProxySettings,
 }
 
 impl Default for SpotifyPlayerSettings {
@@ -81,15 +86,23 @@ impl Default for SpotifyPlayerSettings {
             gapless: true,
             backend: AudioBackend::PulseAudio,
             ap_port: None,
+            audio_cache_size: None,
+            proxy: Default::default(),
         }
     }
 }
 
+// How often we check that our session is still alive, to reconnect if it isn't
+const SESSION_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
 pub struct SpotifyPlayer {
     settings: SpotifyPlayerSettings,
     player: Option<Player>,
     mixer: Option<Box<dyn Mixer>>,
     session: Option<Session>,
+    // Reusable credentials handed out by Spotify when logging in, used to reconnect
+    credentials: Option<Credentials>,
+    connection_lost: bool,
     delegate: Rc<dyn SpotifyPlayerDelegate>,
 }
 
@@ -100,6 +113,8 @@ impl SpotifyPlayer {
             mixer: None,
             player: None,
             session: None,
+            credentials: None,
+            connection_lost: false,
             delegate,
         }
     }
@@ -140,11 +155,15 @@ impl SpotifyPlayer {
                     .seek(position);
                 Ok(())
             }
-            Command::PlayerLoad { track, resume } => {
+            Command::PlayerLoad {
+                track,
+                resume,
+                position,
+            } => {
                 self.player
                     .as_mut()
                     .ok_or(SpotifyError::PlayerNotReady)?
-                    .load(track, resume, 0);
+                    .load(track, resume, position);
                 Ok(())
             }
             Command::PlayerPreload(track) => {
@@ -155,6 +174,8 @@ impl SpotifyPlayer {
                 Ok(())
             }
             Command::RefreshToken => {
+                // The token might have expired because we lost the connection
+                self.check_session().await;
                 let session = self.session.as_ref().ok_or(SpotifyError::PlayerNotReady)?;
                 let (token, token_expiry_time) = get_access_token_and_expiry_time(session).await?;
                 self.delegate.refresh_successful(token, token_expiry_time);
@@ -166,11 +187,14 @@ impl SpotifyPlayer {
                     .ok_or(SpotifyError::PlayerNotReady)?
                     .shutdown();
                 let _ = self.player.take();
+                self.credentials = None;
+                self.connection_lost = false;
                 Ok(())
             }
             Command::PasswordLogin { username, password } => {
                 let credentials = Credentials::with_password(username, password.clone());
-                let new_session = create_session(&credentials, self.settings.ap_port).await?;
+                let (new_session, reusable_credentials) =
+                    create_session(&credentials, &self.settings).await?;
                 let (token, token_expiry_time) =
                     get_access_token_and_expiry_time(&new_session).await?;
                 let credentials = credentials::Credentials {
@@ -179,6 +203,7 @@ impl SpotifyPlayer {
                     token,
                     token_expiry_time: Some(token_expiry_time),
                     country: new_session.country(),
+                    refresh_token: None,
                 };
                 self.delegate.password_login_successful(credentials);
 
@@ -186,6 +211,7 @@ impl SpotifyPlayer {
                 tokio::task::spawn_local(player_setup_delegate(channel, Rc::clone(&self.delegate)));
                 self.player.replace(new_player);
                 self.session.replace(new_session);
+                self.credentials.replace(reusable_credentials);
 
                 Ok(())
             }
@@ -195,7 +221,8 @@ impl SpotifyPlayer {
                     auth_type: AuthenticationType::AUTHENTICATION_SPOTIFY_TOKEN,
                     auth_data: token.clone().into_bytes(),
                 };
-                let new_session = create_session(&credentials, self.settings.ap_port).await?;
+                let (new_session, reusable_credentials) =
+                    create_session(&credentials, &self.settings).await?;
                 self.delegate
                     .token_login_successful(new_session.username(), token);
 
@@ -203,6 +230,7 @@ impl SpotifyPlayer {
                 tokio::task::spawn_local(player_setup_delegate(channel, Rc::clone(&self.delegate)));
                 self.player.replace(new_player);
                 self.session.replace(new_session);
+                self.credentials.replace(reusable_credentials);
 
                 Ok(())
             }
@@ -263,24 +291,69 @@ impl SpotifyPlayer {
         })
     }
 
-    pub async fn start(self, receiver: UnboundedReceiver<Command>) -> Result<(), ()> {
-        let _self = RefCell::new(self);
-        receiver
-            .for_each(|action| async {
-                let mut _self = _self.borrow_mut();
-                match _self.handle(action).await {
-                    Ok(_) => {}
-                    Err(err) => _self.delegate.report_error(err),
+    // The session dies when the connection is lost (network issues, suspend...), in which case we keep trying to log in again
+    async fn check_session(&mut self) {
+        let is_invalid = self
+            .session
+            .as_ref()
+            .map(|s| s.is_invalid())
+            .unwrap_or(false);
+        if !is_invalid {
+            return;
+        }
+        if !self.connection_lost {
+            warn!("Session lost, reconnecting...");
+            self.connection_lost = true;
+            self.delegate.connection_lost();
+        }
+
+        let Some(credentials) = self.credentials.clone() else {
+            return;
+        };
+        match create_session(&credentials, &self.settings).await {
+            Ok((new_session, reusable_credentials)) => {
+                info!("Session restored");
+                let (new_player, channel) = self.create_player(new_session.clone());
+                tokio::task::spawn_local(player_setup_delegate(channel, Rc::clone(&self.delegate)));
+                self.player.replace(new_player);
+                self.session.replace(new_session.clone());
+                self.credentials.replace(reusable_credentials);
+                self.connection_lost = false;
+                self.delegate.connection_restored();
+
+                // The Web API token is likely to have expired as well
+                if let Ok((token, token_expiry_time)) =
+                    get_access_token_and_expiry_time(&new_session).await
+                {
+                    self.delegate.refresh_successful(token, token_expiry_time);
                 }
-            })
-            .await;
+            }
+            Err(err) => debug!("Could not reconnect: {}", err),
+        }
+    }
+
+    pub async fn start(mut self, mut receiver: UnboundedReceiver<Command>) -> Result<(), ()> {
+        let mut session_check = tokio::time::interval(SESSION_CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                action = receiver.next() => {
+                    let Some(action) = action else {
+                        break;
+                    };
+                    if let Err(err) = self.handle(action).await {
+                        self.delegate.report_error(err);
+                    }
+                }
+                _ = session_check.tick() => self.check_session().await,
+            }
+        }
         Ok(())
     }
 }
 
 const CLIENT_ID: &str = "782ae96ea60f4cdf986a766049607005";
 
-const SCOPES: &str = "user-read-private,\
+pub const SCOPES: &str = "user-read-private,\
 playlist-read-private,\
 playlist-read-collaborative,\
 user-library-read,\
@@ -288,6 +361,8 @@ user-library-modify,\
 user-top-read,\
 user-read-recently-played,\
 user-read-playback-state,\
+user-read-playback-position,\
+user-follow-read,\
 playlist-modify-public,\
 playlist-modify-private,\
 user-modify-playback-state,\
@@ -306,25 +381,41 @@ async fn get_access_token_and_expiry_time(
     Ok((token.access_token, expiry_time))
 }
 
+// librespot can only tunnel its connection through HTTP proxies
+fn session_proxy(proxy: &ProxySettings) -> Option<url::Url> {
+    match proxy.kind {
+        ProxyKind::Http => proxy.url()?.parse().ok(),
+        ProxyKind::Socks5 => {
+            warn!("SOCKS proxies are not supported for the player connection, connecting directly");
+            None
+        }
+        ProxyKind::None => None,
+    }
+}
+
 async fn create_session_with_port(
     credentials: &Credentials,
+    settings: &SpotifyPlayerSettings,
     ap_port: Option<u16>,
-) -> Result<Session, SpotifyError> {
+) -> Result<(Session, Credentials), SpotifyError> {
     let session_config = SessionConfig {
         ap_port,
+        proxy: session_proxy(&settings.proxy),
         ..Default::default()
     };
-    let root = glib::user_cache_dir().join("spot").join("librespot");
+    let root = librespot_cache_root();
+    // We keep credentials ourselves (see app::credentials), librespot used to store a plain text copy
+    let _ = std::fs::remove_file(root.join("credentials").join("credentials.json"));
     let cache = Cache::new(
-        Some(root.join("credentials")),
+        None,
         Some(root.join("volume")),
         Some(root.join("audio")),
-        None,
+        settings.audio_cache_size,
     )
     .map_err(|e| dbg!(e))
     .ok();
     match Session::connect(session_config, credentials.clone(), cache, true).await {
-        Ok(r) => Ok(r.0),
+        Ok(r) => Ok(r),
         Err(SessionError::IoError(_)) => Err(SpotifyError::TechnicalError),
         Err(SessionError::AuthenticationError(err)) => {
             warn!("Login failure: {}", err);
@@ -335,15 +426,15 @@ async fn create_session_with_port(
 
 async fn create_session(
     credentials: &Credentials,
-    ap_port: Option<u16>,
-) -> Result<Session, SpotifyError> {
-    match ap_port {
-        Some(_) => create_session_with_port(credentials, ap_port).await,
+    settings: &SpotifyPlayerSettings,
+) -> Result<(Session, Credentials), SpotifyError> {
+    match settings.ap_port {
+        Some(_) => create_session_with_port(credentials, settings, settings.ap_port).await,
         None => {
             let mut ports_to_try = KNOWN_AP_PORTS.iter();
             loop {
                 if let Some(next_port) = ports_to_try.next() {
-                    let res = create_session_with_port(credentials, *next_port).await;
+                    let res = create_session_with_port(credentials, settings, *next_port).await;
                     match res {
                         Err(SpotifyError::TechnicalError) => continue,
                         _ => break res,
@@ -362,7 +453,11 @@ async fn player_setup_delegate(
 ) {
     while let Some(event) = channel.recv().await {
         match event {
-            PlayerEvent::EndOfTrack { .. } => {
+            PlayerEvent::EndOfTrack { track_id, .. } => {
+                // The whole file went through librespot's audio cache by now
+                if let Ok(id) = track_id.to_base62() {
+                    tokio::task::spawn_local(crate::app::offline_tracks::record_cached(id));
+                }
                 delegate.end_of_track_reached();
             }
             PlayerEvent::Playing { position_ms, .. } => {
@@ -0,0 +1,88 @@
+use futures::channel::mpsc::UnboundedSender;
+use std::rc::Rc;
+
+use crate::app::{
+    components::EventListener,
+    state::{PlaybackEvent, SettingsEvent},
+    AppEvent, AppModel,
+};
+use crate::settings::RemoteControlSettings;
+
+use super::server::{remote_track, RemoteTrack};
+
+#[derive(Debug)]
+pub enum RemoteStateUpdate {
+    // None stops the server
+    Settings(Option<RemoteControlSettings>),
+    Playing(bool),
+    CurrentTrack(Option<RemoteTrack>),
+    Queue(Vec<RemoteTrack>),
+}
+
+// Keeps the server up to date with what is playing, since it can't read the app state from its thread
+pub struct RemoteStateListener {
+    app_model: Rc<AppModel>,
+    sender: UnboundedSender<RemoteStateUpdate>,
+}
+
+impl RemoteStateListener {
+    pub fn new(app_model: Rc<AppModel>, sender: UnboundedSender<RemoteStateUpdate>) -> Self {
+        Self { app_model, sender }
+    }
+
+    fn update_for(&self, event: &PlaybackEvent) -> Option<RemoteStateUpdate> {
+        let state = self.app_model.get_state();
+        match event {
+            PlaybackEvent::PlaybackPaused
+            | PlaybackEvent::PlaybackResumed
+            | PlaybackEvent::PlaybackStopped => {
+                Some(RemoteStateUpdate::Playing(state.playback.is_playing()))
+            }
+            PlaybackEvent::TrackChanged(_) => {
+                let current = state
+                    .playback
+                    .current_song()
+                    .map(|song| remote_track(&song));
+                Some(RemoteStateUpdate::CurrentTrack(current))
+            }
+            PlaybackEvent::PlaylistChanged => {
+                let mut queue = state
+                    .playback
+                    .queued_songs()
+                    .map_collect(|song| remote_track(&song));
+                queue.append(
+                    &mut state
+                        .playback
+                        .songs()
+                        .map_collect(|song| remote_track(&song)),
+                );
+                Some(RemoteStateUpdate::Queue(queue))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl EventListener for RemoteStateListener {
+    fn on_event(&mut self, event: &AppEvent) {
+        let update = match event {
+            AppEvent::PlaybackEvent(event) => self.update_for(event),
+            AppEvent::Started | AppEvent::SettingsEvent(SettingsEvent::SettingsChanged) => {
+                let settings = self
+                    .app_model
+                    .get_state()
+                    .settings
+                    .settings
+                    .remote_control
+                    .clone();
+                Some(RemoteStateUpdate::Settings(settings))
+            }
+            _ => None,
+        };
+        if let Some(update) = update {
+            self.sender
+                .unbounded_send(update)
+                .expect("Could not send event to the remote control server");
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use async_std::net::TcpListener;
+use async_std::task::{self, JoinHandle};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::StreamExt;
+use rand::{distributions::Alphanumeric, Rng};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::api::SpotifyApiClient;
+use crate::app::{AppAction, AppModel};
+use crate::settings::RemoteControlSettings;
+
+mod listener;
+pub use listener::*;
+
+mod server;
+use server::{serve, RemoteState, ServerContext};
+
+// Only reachable from this computer, for home automation setups and the like
+const HOST: &str = "127.0.0.1";
+const TOKEN_LENGTH: usize = 32;
+
+// Suggested to the user when enabling the server, so that requests never go unauthenticated
+pub fn new_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+async fn remote_server(
+    api: Arc<dyn SpotifyApiClient + Send + Sync>,
+    sender: UnboundedSender<AppAction>,
+    mut receiver: UnboundedReceiver<RemoteStateUpdate>,
+) {
+    let state = Arc::new(Mutex::new(RemoteState::default()));
+    let mut running: Option<(RemoteControlSettings, JoinHandle<()>)> = None;
+
+    while let Some(update) = receiver.next().await {
+        let settings = match update {
+            RemoteStateUpdate::Settings(settings) => settings,
+            update => {
+                state.lock().unwrap().apply(update);
+                continue;
+            }
+        };
+
+        if running.as_ref().map(|(current, _)| current) == settings.as_ref() {
+            continue;
+        }
+        if let Some((_, handle)) = running.take() {
+            handle.cancel().await;
+        }
+        let Some(settings) = settings else {
+            continue;
+        };
+
+        match TcpListener::bind((HOST, settings.port)).await {
+            Ok(listener) => {
+                let context = Arc::new(ServerContext {
+                    api: Arc::clone(&api),
+                    sender: sender.clone(),
+                    token: settings.token.clone(),
+                    state: Arc::clone(&state),
+                });
+                running = Some((settings, task::spawn(serve(listener, context))));
+            }
+            Err(err) => warn!("Could not start the remote control server: {}", err),
+        }
+    }
+}
+
+pub fn start_remote_server(
+    app_model: Rc<AppModel>,
+    sender: UnboundedSender<AppAction>,
+) -> RemoteStateListener {
+    let api = app_model.get_spotify();
+    let (state_sender, receiver) = unbounded();
+
+    thread::spawn(move || task::block_on(remote_server(api, sender, receiver)));
+
+    RemoteStateListener::new(app_model, state_sender)
+}
@@ -0,0 +1,281 @@
+use async_std::net::{TcpListener, TcpStream};
+use async_std::prelude::*;
+use async_std::task;
+use futures::channel::mpsc::UnboundedSender;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::api::SpotifyApiClient;
+use crate::app::models::{SearchResults, SongDescription};
+use crate::app::state::{parse_spotify_uri, PlaybackAction};
+use crate::app::AppAction;
+
+use super::RemoteStateUpdate;
+
+// How many results of each kind (albums, artists) /search returns
+const SEARCH_RESULTS_PER_KIND: usize = 10;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RemoteTrack {
+    uri: String,
+    title: String,
+    artists: String,
+}
+
+pub fn remote_track(song: &SongDescription) -> RemoteTrack {
+    RemoteTrack {
+        uri: song.uri.clone(),
+        title: song.title.clone(),
+        artists: song.artists_name(),
+    }
+}
+
+// What the app last told us, served as is by /status and /queue
+#[derive(Default, Serialize)]
+pub struct RemoteState {
+    playing: bool,
+    track: Option<RemoteTrack>,
+    #[serde(skip)]
+    queue: Vec<RemoteTrack>,
+}
+
+impl RemoteState {
+    pub fn apply(&mut self, update: RemoteStateUpdate) {
+        match update {
+            RemoteStateUpdate::Playing(playing) => self.playing = playing,
+            RemoteStateUpdate::CurrentTrack(track) => self.track = track,
+            RemoteStateUpdate::Queue(queue) => self.queue = queue,
+            RemoteStateUpdate::Settings(_) => {}
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RemoteArtist {
+    uri: String,
+    name: String,
+}
+
+#[derive(Serialize)]
+struct RemoteAlbum {
+    uri: String,
+    title: String,
+    artists: String,
+}
+
+#[derive(Serialize)]
+struct RemoteSearchResults {
+    artists: Vec<RemoteArtist>,
+    albums: Vec<RemoteAlbum>,
+}
+
+impl From<SearchResults> for RemoteSearchResults {
+    fn from(results: SearchResults) -> Self {
+        let artists = results
+            .artists
+            .into_iter()
+            .map(|artist| RemoteArtist {
+                uri: format!("spotify:artist:{}", artist.id),
+                name: artist.name,
+            })
+            .collect();
+        let albums = results
+            .albums
+            .into_iter()
+            .map(|album| RemoteAlbum {
+                uri: format!("spotify:album:{}", album.id),
+                artists: album.artists_name(),
+                title: album.title,
+            })
+            .collect();
+        Self { artists, albums }
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    params: HashMap<String, String>,
+    bearer: Option<String>,
+}
+
+impl Request {
+    // ie POST /queue?uri=spotify:track:... HTTP/1.1, followed by the headers
+    fn parse(raw: &str) -> Option<Self> {
+        let mut lines = raw.lines();
+        let mut request_line = lines.next()?.split(' ');
+        let method = request_line.next()?.to_string();
+        let target = request_line.next()?;
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let params = form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        let bearer = lines.take_while(|line| !line.is_empty()).find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            if !name.trim().eq_ignore_ascii_case("authorization") {
+                return None;
+            }
+            value.trim().strip_prefix("Bearer ").map(str::to_string)
+        });
+        Some(Self {
+            method,
+            path: path.to_string(),
+            params,
+            bearer,
+        })
+    }
+
+    // The token can also be passed as a parameter, for clients that can't set headers
+    fn is_authorized(&self, token: &str) -> bool {
+        let given = self
+            .bearer
+            .as_deref()
+            .or_else(|| self.params.get("token").map(String::as_str));
+        given == Some(token)
+    }
+
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .get(name)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+}
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn json(value: &impl Serialize) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self { status: 200, body },
+            Err(err) => Self::error(500, &err.to_string()),
+        }
+    }
+
+    fn no_content() -> Self {
+        Self {
+            status: 204,
+            body: String::new(),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        let body = serde_json::json!({ "error": message }).to_string();
+        Self { status, body }
+    }
+
+    fn to_http(&self) -> String {
+        let reason = match self.status {
+            200 => "OK",
+            204 => "No Content",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            502 => "Bad Gateway",
+            _ => "Internal Server Error",
+        };
+        format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            reason,
+            self.body.len(),
+            self.body
+        )
+    }
+}
+
+pub struct ServerContext {
+    pub api: Arc<dyn SpotifyApiClient + Send + Sync>,
+    pub sender: UnboundedSender<AppAction>,
+    pub token: String,
+    pub state: Arc<Mutex<RemoteState>>,
+}
+
+impl ServerContext {
+    fn send(&self, action: AppAction) -> Response {
+        match self.sender.unbounded_send(action) {
+            Ok(_) => Response::no_content(),
+            Err(_) => Response::error(500, "Could not send action"),
+        }
+    }
+
+    // The guard isn't held across an await that way, keeping requests Send
+    fn status(&self) -> Response {
+        Response::json(&*self.state.lock().unwrap())
+    }
+
+    fn queue(&self) -> Response {
+        Response::json(&self.state.lock().unwrap().queue)
+    }
+
+    async fn respond(&self, request: Request) -> Response {
+        if !request.is_authorized(&self.token) {
+            return Response::error(401, "Missing or invalid token");
+        }
+        let action = match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/status") => return self.status(),
+            ("GET", "/queue") => return self.queue(),
+            ("GET", "/search") => return self.search(request.param("q")).await,
+            ("POST", "/queue") => return self.add_to_queue(request.param("uri")).await,
+            ("POST", "/play-pause") => PlaybackAction::TogglePlay,
+            ("POST", "/play") => PlaybackAction::Play,
+            ("POST", "/pause") => PlaybackAction::Pause,
+            ("POST", "/next") => PlaybackAction::Next,
+            ("POST", "/previous") => PlaybackAction::Previous,
+            _ => return Response::error(404, "Unknown endpoint"),
+        };
+        self.send(action.into())
+    }
+
+    async fn search(&self, query: Option<&str>) -> Response {
+        let Some(query) = query else {
+            return Response::error(400, "Missing query (q)");
+        };
+        match self.api.search(query, 0, SEARCH_RESULTS_PER_KIND).await {
+            Ok(results) => Response::json(&RemoteSearchResults::from(results)),
+            Err(err) => Response::error(502, &err.to_string()),
+        }
+    }
+
+    async fn add_to_queue(&self, uri: Option<&str>) -> Response {
+        let Some(("track", id)) = uri.and_then(parse_spotify_uri) else {
+            return Response::error(400, "Expected the URI of a track (uri)");
+        };
+        match self.api.get_track(id).await {
+            Ok(song) => self.send(PlaybackAction::Queue(vec![song]).into()),
+            Err(err) => Response::error(502, &err.to_string()),
+        }
+    }
+}
+
+// Requests are small and answered right away, so one read is enough (as for the OAuth redirect)
+async fn handle_connection(mut stream: TcpStream, context: &ServerContext) -> std::io::Result<()> {
+    let mut buffer = [0u8; 4096];
+    let len = stream.read(&mut buffer).await?;
+    let request = String::from_utf8_lossy(&buffer[..len]).into_owned();
+
+    let response = match Request::parse(&request) {
+        Some(request) => context.respond(request).await,
+        None => Response::error(400, "Malformed request"),
+    };
+    stream.write_all(response.to_http().as_bytes()).await
+}
+
+pub async fn serve(listener: TcpListener, context: Arc<ServerContext>) {
+    let mut incoming = listener.incoming();
+    while let Some(stream) = incoming.next().await {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let context = Arc::clone(&context);
+        task::spawn(async move {
+            if let Err(err) = handle_connection(stream, &context).await {
+                debug!("Remote control request failed: {}", err);
+            }
+        });
+    }
+}
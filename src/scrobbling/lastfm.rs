@@ -0,0 +1,171 @@
+use form_urlencoded::Serializer;
+use futures::future::BoxFuture;
+use gio::prelude::SettingsExt;
+use isahc::{AsyncReadResponseExt, HttpClient, Request};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use super::{ScrobbleError, ScrobbleResult, ScrobbledTrack, Scrobbler};
+use crate::app::credentials::{self, Secret};
+
+const API_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+const AUTH_URL: &str = "https://www.last.fm/api/auth/";
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct SessionResponse {
+    session: LastfmSession,
+}
+
+#[derive(Deserialize)]
+pub struct LastfmSession {
+    pub name: String,
+    pub key: String,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: u32,
+    message: String,
+}
+
+// See https://www.last.fm/api/desktopauth for how we get a session key
+pub struct LastfmClient {
+    api_key: String,
+    api_secret: String,
+    session_key: Option<String>,
+    client: HttpClient,
+}
+
+impl LastfmClient {
+    pub fn new(api_key: String, api_secret: String, session_key: Option<String>) -> Self {
+        Self {
+            api_key,
+            api_secret,
+            session_key,
+            client: crate::api::http_client(),
+        }
+    }
+
+    // Only if enabled and connected to an account
+    pub fn from_settings(settings: &gio::Settings) -> Option<Self> {
+        if !settings.boolean("lastfm-enabled") {
+            return None;
+        }
+        let non_empty =
+            |key: &str| Some(settings.string(key).to_string()).filter(|s| !s.is_empty());
+        // The secret and the session key are kept in the keyring
+        let non_empty_secret = |secret| Some(credentials::secret(secret)).filter(|s| !s.is_empty());
+        Some(Self::new(
+            non_empty("lastfm-api-key")?,
+            non_empty_secret(Secret::LastfmApi)?,
+            Some(non_empty_secret(Secret::LastfmSessionKey)?),
+        ))
+    }
+
+    // Every call is signed with the md5 of its (sorted) parameters followed by the secret
+    fn sign(&self, params: &[(&str, String)]) -> String {
+        let mut sorted = params.to_vec();
+        sorted.sort_by(|a, b| a.0.cmp(b.0));
+        let mut payload: String = sorted.iter().map(|(k, v)| format!("{k}{v}")).collect();
+        payload.push_str(&self.api_secret);
+        glib::compute_checksum_for_data(glib::ChecksumType::Md5, payload.as_bytes())
+            .map(|s| s.to_string())
+            .unwrap_or_default()
+    }
+
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        mut params: Vec<(&str, String)>,
+    ) -> ScrobbleResult<T> {
+        params.push(("method", method.to_string()));
+        params.push(("api_key", self.api_key.clone()));
+        if let Some(session_key) = &self.session_key {
+            params.push(("sk", session_key.clone()));
+        }
+        let signature = self.sign(&params);
+        let body = Serializer::new(String::new())
+            .extend_pairs(params.iter().map(|(k, v)| (*k, v.as_str())))
+            .append_pair("api_sig", &signature)
+            // Not part of the signature
+            .append_pair("format", "json")
+            .finish();
+
+        let request = Request::post(API_URL)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(body)
+            .unwrap();
+        let mut response = self.client.send_async(request).await?;
+        let status = response.status();
+        let text = response.text().await?;
+
+        if let Ok(error) = serde_json::from_str::<ErrorResponse>(&text) {
+            return Err(ScrobbleError::ServiceError(error.error, error.message));
+        }
+        if !status.is_success() {
+            return Err(ScrobbleError::BadStatus(status.as_u16(), text));
+        }
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    pub async fn get_token(&self) -> ScrobbleResult<String> {
+        let response: TokenResponse = self.call("auth.getToken", vec![]).await?;
+        Ok(response.token)
+    }
+
+    // Where the user authorizes the token we got
+    pub fn auth_url(&self, token: &str) -> String {
+        format!("{AUTH_URL}?api_key={}&token={token}", self.api_key)
+    }
+
+    // Fails until the user has authorized the token
+    pub async fn get_session(&self, token: &str) -> ScrobbleResult<LastfmSession> {
+        let response: SessionResponse = self
+            .call("auth.getSession", vec![("token", token.to_string())])
+            .await?;
+        Ok(response.session)
+    }
+
+    fn track_params(track: &ScrobbledTrack) -> Vec<(&'static str, String)> {
+        vec![
+            ("artist", track.artist.clone()),
+            ("track", track.title.clone()),
+            ("album", track.album.clone()),
+            ("duration", track.duration.as_secs().to_string()),
+        ]
+    }
+}
+
+impl Scrobbler for LastfmClient {
+    fn name(&self) -> &'static str {
+        "Last.fm"
+    }
+
+    fn now_playing(&self, track: ScrobbledTrack) -> BoxFuture<'_, ScrobbleResult<()>> {
+        Box::pin(async move {
+            let params = Self::track_params(&track);
+            self.call::<serde_json::Value>("track.updateNowPlaying", params)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn scrobble(
+        &self,
+        track: ScrobbledTrack,
+        started_at: u64,
+    ) -> BoxFuture<'_, ScrobbleResult<()>> {
+        Box::pin(async move {
+            let mut params = Self::track_params(&track);
+            params.push(("timestamp", started_at.to_string()));
+            self.call::<serde_json::Value>("track.scrobble", params)
+                .await?;
+            Ok(())
+        })
+    }
+}
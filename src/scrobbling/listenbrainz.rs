@@ -0,0 +1,116 @@
+use futures::future::BoxFuture;
+use gio::prelude::SettingsExt;
+use isahc::{AsyncReadResponseExt, HttpClient, Request};
+use serde::Serialize;
+
+use super::{ScrobbleError, ScrobbleResult, ScrobbledTrack, Scrobbler};
+use crate::app::credentials::{self, Secret};
+
+const SUBMIT_URL: &str = "https://api.listenbrainz.org/1/submit-listens";
+
+#[derive(Serialize)]
+struct AdditionalInfo {
+    duration_ms: u128,
+    submission_client: &'static str,
+}
+
+#[derive(Serialize)]
+struct TrackMetadata<'a> {
+    artist_name: &'a str,
+    track_name: &'a str,
+    release_name: &'a str,
+    additional_info: AdditionalInfo,
+}
+
+#[derive(Serialize)]
+struct Listen<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    listened_at: Option<u64>,
+    track_metadata: TrackMetadata<'a>,
+}
+
+#[derive(Serialize)]
+struct Submission<'a> {
+    listen_type: &'static str,
+    payload: [Listen<'a>; 1],
+}
+
+// See https://listenbrainz.readthedocs.io/en/latest/users/api/core.html, a user token is all we need
+pub struct ListenbrainzClient {
+    token: String,
+    client: HttpClient,
+}
+
+impl ListenbrainzClient {
+    pub fn new(token: String) -> Self {
+        Self {
+            token,
+            client: crate::api::http_client(),
+        }
+    }
+
+    // Only if enabled and given a token
+    pub fn from_settings(settings: &gio::Settings) -> Option<Self> {
+        if !settings.boolean("listenbrainz-enabled") {
+            return None;
+        }
+        // Kept in the keyring
+        let token = credentials::secret(Secret::ListenbrainzToken);
+        Some(Self::new(token)).filter(|client| !client.token.is_empty())
+    }
+
+    async fn submit(
+        &self,
+        listen_type: &'static str,
+        track: &ScrobbledTrack,
+        listened_at: Option<u64>,
+    ) -> ScrobbleResult<()> {
+        let submission = Submission {
+            listen_type,
+            payload: [Listen {
+                listened_at,
+                track_metadata: TrackMetadata {
+                    artist_name: &track.artist,
+                    track_name: &track.title,
+                    release_name: &track.album,
+                    additional_info: AdditionalInfo {
+                        duration_ms: track.duration.as_millis(),
+                        submission_client: "Spot",
+                    },
+                },
+            }],
+        };
+        let body = serde_json::to_string(&submission)?;
+
+        let request = Request::post(SUBMIT_URL)
+            .header("Authorization", format!("Token {}", self.token))
+            .header("Content-Type", "application/json")
+            .body(body)
+            .unwrap();
+        let mut response = self.client.send_async(request).await?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await?;
+            return Err(ScrobbleError::BadStatus(status.as_u16(), text));
+        }
+        Ok(())
+    }
+}
+
+impl Scrobbler for ListenbrainzClient {
+    fn name(&self) -> &'static str {
+        "ListenBrainz"
+    }
+
+    fn now_playing(&self, track: ScrobbledTrack) -> BoxFuture<'_, ScrobbleResult<()>> {
+        Box::pin(async move { self.submit("playing_now", &track, None).await })
+    }
+
+    fn scrobble(
+        &self,
+        track: ScrobbledTrack,
+        started_at: u64,
+    ) -> BoxFuture<'_, ScrobbleResult<()>> {
+        Box::pin(async move { self.submit("single", &track, Some(started_at)).await })
+    }
+}
@@ -0,0 +1,119 @@
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+mod lastfm;
+pub use lastfm::LastfmClient;
+
+mod listenbrainz;
+pub use listenbrainz::ListenbrainzClient;
+
+mod queue;
+pub use queue::{scrobble_or_enqueue, submit_pending};
+
+const SETTINGS: &str = "dev.alextren.Spot";
+
+// Tracks shorter than that are never scrobbled
+const MIN_DURATION: Duration = Duration::from_secs(30);
+// Long tracks are scrobbled after that long, even if not halfway through
+const MAX_PLAYED: Duration = Duration::from_secs(4 * 60);
+
+// What scrobbling services need to know about a track
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScrobbledTrack {
+    pub artist: String,
+    pub title: String,
+    pub album: String,
+    pub duration: Duration,
+}
+
+#[derive(Error, Debug)]
+pub enum ScrobbleError {
+    #[error("Request failed ({0}): {1}")]
+    BadStatus(u16, String),
+    #[error("Service error ({0}): {1}")]
+    ServiceError(u32, String),
+    #[error(transparent)]
+    ClientError(#[from] isahc::Error),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    ParseError(#[from] serde_json::Error),
+}
+
+impl ScrobbleError {
+    // Whether trying again later might work (no network, service down...)
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::BadStatus(status, _) => *status == 429 || *status >= 500,
+            // Last.fm's "service offline" and "temporarily unavailable"
+            Self::ServiceError(code, _) => *code == 11 || *code == 16,
+            Self::ClientError(_) | Self::IoError(_) => true,
+            Self::ParseError(_) => false,
+        }
+    }
+}
+
+pub type ScrobbleResult<T> = Result<T, ScrobbleError>;
+
+// A service keeping track of what the user listens to.
+// Adding one takes an implementation of this trait, and an entry in BACKENDS.
+// Scrobbles that fail are queued and submitted again later, matching queued listens by name.
+pub trait Scrobbler: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn now_playing(&self, track: ScrobbledTrack) -> BoxFuture<'_, ScrobbleResult<()>>;
+
+    // started_at is the unix timestamp of when the track started playing
+    fn scrobble(&self, track: ScrobbledTrack, started_at: u64)
+        -> BoxFuture<'_, ScrobbleResult<()>>;
+}
+
+// The usual rule: more than 30 seconds long, and played for half its duration or 4 minutes
+pub fn should_scrobble(duration: Duration, played: Duration) -> bool {
+    duration > MIN_DURATION && played >= Duration::min(duration / 2, MAX_PLAYED)
+}
+
+type Backend = fn(&gio::Settings) -> Option<Arc<dyn Scrobbler>>;
+
+// Every supported service, built from the preferences if set up and enabled
+const BACKENDS: &[Backend] = &[
+    |settings| Some(Arc::new(LastfmClient::from_settings(settings)?)),
+    |settings| Some(Arc::new(ListenbrainzClient::from_settings(settings)?)),
+];
+
+// The scrobblers that were set up and enabled in the preferences
+pub fn enabled_scrobblers() -> Vec<Arc<dyn Scrobbler>> {
+    let settings = gio::Settings::new(SETTINGS);
+    BACKENDS
+        .iter()
+        .filter_map(|backend| backend(&settings))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_scrobble() {
+        let secs = Duration::from_secs;
+
+        assert!(!should_scrobble(secs(20), secs(20)));
+        assert!(!should_scrobble(secs(180), secs(60)));
+        assert!(should_scrobble(secs(180), secs(90)));
+        assert!(!should_scrobble(secs(600), secs(200)));
+        assert!(should_scrobble(secs(600), secs(240)));
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        assert!(ScrobbleError::BadStatus(503, String::new()).is_retryable());
+        assert!(ScrobbleError::BadStatus(429, String::new()).is_retryable());
+        assert!(!ScrobbleError::BadStatus(401, String::new()).is_retryable());
+        assert!(ScrobbleError::ServiceError(16, String::new()).is_retryable());
+        assert!(!ScrobbleError::ServiceError(9, String::new()).is_retryable());
+    }
+}
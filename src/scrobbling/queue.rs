@@ -0,0 +1,146 @@
+use async_std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::{ScrobbledTrack, Scrobbler};
+
+// Past that, the oldest listens are dropped
+const MAX_PENDING: usize = 1000;
+
+lazy_static! {
+    // Only one task at a time gets to touch the file
+    static ref QUEUE_LOCK: Mutex<()> = Mutex::new(());
+    // And only one submits what's in there, so that the same listens aren't submitted twice
+    static ref FLUSH_LOCK: Mutex<()> = Mutex::new(());
+}
+
+// A listen that could not be submitted yet
+#[derive(Serialize, Deserialize)]
+struct PendingScrobble {
+    // The name of the scrobbler
+    service: String,
+    track: ScrobbledTrack,
+    started_at: u64,
+}
+
+impl PendingScrobble {
+    fn is_same(&self, other: &Self) -> bool {
+        self.service == other.service
+            && self.started_at == other.started_at
+            && self.track.title == other.track.title
+    }
+}
+
+fn queue_path() -> PathBuf {
+    glib::user_data_dir().join("spot").join("scrobbles.json")
+}
+
+async fn read_queue() -> Vec<PendingScrobble> {
+    async_std::fs::read(queue_path())
+        .await
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+async fn write_queue(queue: &[PendingScrobble]) {
+    let path = queue_path();
+    let result = if queue.is_empty() {
+        async_std::fs::remove_file(&path)
+            .await
+            .or_else(|err| match err.kind() {
+                std::io::ErrorKind::NotFound => Ok(()),
+                _ => Err(err),
+            })
+    } else {
+        match serde_json::to_vec(queue) {
+            Ok(content) => {
+                async {
+                    async_std::fs::create_dir_all(path.parent().unwrap()).await?;
+                    async_std::fs::write(&path, content).await
+                }
+                .await
+            }
+            Err(err) => Err(err.into()),
+        }
+    };
+    if let Err(err) = result {
+        warn!("Could not save pending scrobbles: {}", err);
+    }
+}
+
+async fn enqueue(pending: Vec<PendingScrobble>) {
+    if pending.is_empty() {
+        return;
+    }
+    let _lock = QUEUE_LOCK.lock().await;
+    let mut queue = read_queue().await;
+    queue.extend(pending);
+    let excess = queue.len().saturating_sub(MAX_PENDING);
+    queue.drain(..excess);
+    write_queue(&queue).await;
+}
+
+// Once it went through (or never will), and not before, so that nothing is lost if Spot quits in the meantime
+async fn dequeue(done: &PendingScrobble) {
+    let _lock = QUEUE_LOCK.lock().await;
+    let mut queue = read_queue().await;
+    queue.retain(|pending| !pending.is_same(done));
+    write_queue(&queue).await;
+}
+
+// Submits a listen, keeping it for later if the service can't be reached
+pub async fn scrobble_or_enqueue(
+    scrobbler: Arc<dyn Scrobbler>,
+    track: ScrobbledTrack,
+    started_at: u64,
+) -> bool {
+    match scrobbler.scrobble(track.clone(), started_at).await {
+        Ok(_) => true,
+        Err(err) if err.is_retryable() => {
+            info!("Will retry scrobbling to {}: {}", scrobbler.name(), err);
+            enqueue(vec![PendingScrobble {
+                service: scrobbler.name().to_string(),
+                track,
+                started_at,
+            }])
+            .await;
+            false
+        }
+        Err(err) => {
+            warn!("Could not scrobble to {}: {}", scrobbler.name(), err);
+            false
+        }
+    }
+}
+
+// Submits the listens that were recorded while offline; those for services that are now disabled are kept
+pub async fn submit_pending(scrobblers: Vec<Arc<dyn Scrobbler>>) {
+    let _flushing = FLUSH_LOCK.lock().await;
+    let queue = {
+        let _lock = QUEUE_LOCK.lock().await;
+        read_queue().await
+    };
+    // No need to insist with a service that can't be reached
+    let mut unreachable: Vec<&'static str> = vec![];
+    for pending in queue {
+        let scrobbler = scrobblers
+            .iter()
+            .find(|s| s.name() == pending.service && !unreachable.contains(&s.name()));
+        let Some(scrobbler) = scrobbler else {
+            continue;
+        };
+        match scrobbler
+            .scrobble(pending.track.clone(), pending.started_at)
+            .await
+        {
+            Ok(_) => dequeue(&pending).await,
+            Err(err) if err.is_retryable() => unreachable.push(scrobbler.name()),
+            Err(err) => {
+                warn!("Dropping pending scrobble to {}: {}", pending.service, err);
+                dequeue(&pending).await;
+            }
+        }
+    }
+}